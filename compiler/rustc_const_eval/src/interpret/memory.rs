@@ -807,6 +807,41 @@ pub fn get_alloc_extra_mut<'a>(
         interp_ok((&mut alloc.extra, machine))
     }
 
+    /// Accounts for native code having written to the given allocation, through the address of
+    /// its bytes. Afterwards, all its bytes are initialized and every non-null, pointer-sized
+    /// chunk at a pointer-aligned address has the provenance `prov`, since native code might have
+    /// stored a pointer there. This only makes sense for machines where `OFFSET_IS_ADDR`.
+    /// The caller is responsible for calling the access hooks!
+    pub fn mark_native_write(&mut self, id: AllocId, prov: M::Provenance) -> InterpResult<'tcx> {
+        assert!(M::Provenance::OFFSET_IS_ADDR);
+        let tcx = self.tcx;
+        let ptr_size = tcx.data_layout.pointer_size;
+        let (alloc, _machine) = self.get_alloc_raw_mut(id)?;
+        let size = alloc.size();
+        // Getting the bytes for an overwrite marks them as initialized and removes their
+        // provenance; the contents are left as native code wrote them.
+        alloc
+            .get_bytes_unchecked_for_overwrite(&tcx, alloc_range(Size::ZERO, size))
+            .map_err(|e| e.to_interp_error(id))?;
+        let addr = u64::try_from(alloc.get_bytes_unchecked_raw().addr()).unwrap();
+        let mut offset = Size::from_bytes(addr.next_multiple_of(ptr_size.bytes()) - addr);
+        while offset + ptr_size <= size {
+            let range = alloc_range(offset, ptr_size);
+            let bits = alloc
+                .read_scalar(&tcx, range, /* read_provenance */ false)
+                .map_err(|e| e.to_interp_error(id))?
+                .to_target_usize(&tcx)?;
+            if bits != 0 {
+                let ptr = Pointer::new(prov, Size::from_bytes(bits));
+                alloc
+                    .write_scalar(&tcx, range, Scalar::from_pointer(ptr, &tcx))
+                    .map_err(|e| e.to_interp_error(id))?;
+            }
+            offset += ptr_size;
+        }
+        interp_ok(())
+    }
+
     /// Check whether an allocation is live. This is faster than calling
     /// [`InterpCx::get_alloc_info`] if all you need to check is whether the kind is
     /// [`AllocKind::Dead`] because it doesn't have to look up the type and layout of statics.
@@ -25,7 +25,7 @@
 use std::fs::{self, File};
 use std::io::{self, IsTerminal, Read, Write};
 use std::panic::{self, PanicHookInfo, catch_unwind};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
@@ -49,16 +49,19 @@
 use rustc_lint::unerased_lint_store;
 use rustc_metadata::creader::MetadataLoader;
 use rustc_metadata::locator;
+use rustc_middle::ty::TyCtxt;
 use rustc_parse::{new_parser_from_file, new_parser_from_source_str, unwrap_or_emit_fatal};
 use rustc_session::config::{
-    CG_OPTIONS, ErrorOutputType, Input, OutFileName, OutputType, UnstableOptions, Z_OPTIONS,
-    nightly_options,
+    CG_OPTIONS, ErrorOutputType, Input, OutFileName, OutputType, SwitchWithOptPath,
+    UnstableOptions, Z_OPTIONS, nightly_options,
 };
 use rustc_session::getopts::{self, Matches};
 use rustc_session::lint::{Lint, LintId};
 use rustc_session::output::collect_crate_types;
 use rustc_session::{EarlyDiagCtxt, Session, config, filesearch};
+use rustc_smir::rustc_internal::export::write_smir_json;
 use rustc_span::FileName;
+use rustc_span::def_id::LOCAL_CRATE;
 use rustc_span::source_map::FileLoader;
 use rustc_target::json::ToJson;
 use rustc_target::spec::{Target, TargetTriple};
@@ -99,8 +102,9 @@ pub(super) fn install() {}
 }
 
 use crate::session_diagnostics::{
-    RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch, RLinkRustcVersionMismatch,
-    RLinkWrongFileType, RlinkCorruptFile, RlinkNotAFile, RlinkUnableToRead,
+    CouldntEmitStableMir, RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch,
+    RLinkRustcVersionMismatch, RLinkWrongFileType, RlinkCorruptFile, RlinkNotAFile,
+    RlinkUnableToRead,
 };
 
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
@@ -452,6 +456,10 @@ fn run_compiler(
                 return early_exit();
             }
 
+            if let SwitchWithOptPath::Enabled(ref dir) = sess.opts.unstable_opts.emit_smir {
+                queries.global_ctxt()?.enter(|tcx| emit_smir(tcx, dir.as_deref()));
+            }
+
             queries.global_ctxt()?.enter(|tcx| {
                 Ok(Some(Linker::codegen_and_build_linker(tcx, &*compiler.codegen_backend)?))
             })
@@ -472,6 +480,23 @@ fn run_compiler(
     })
 }
 
+/// Writes the stable MIR of the crate to `<crate>.smir.json` in `dir`, or next to the other
+/// outputs if no directory was given.
+fn emit_smir(tcx: TyCtxt<'_>, dir: Option<&Path>) {
+    let path = match dir {
+        Some(dir) => dir.join(format!("{}.smir.json", tcx.crate_name(LOCAL_CRATE))),
+        None => tcx.output_filenames(()).with_extension("smir.json"),
+    };
+    let res = dir.map_or(Ok(()), fs::create_dir_all).and_then(|()| {
+        let mut file = io::BufWriter::new(File::create(&path)?);
+        write_smir_json(tcx, &mut file)?;
+        file.flush()
+    });
+    if let Err(error) = res {
+        tcx.dcx().emit_fatal(CouldntEmitStableMir { path: &path, error });
+    }
+}
+
 // Extract output directory and file from matches.
 fn make_output(matches: &getopts::Matches) -> (Option<PathBuf>, Option<OutFileName>) {
     let odir = matches.opt_str("out-dir").map(|o| PathBuf::from(&o));
@@ -32,6 +32,13 @@ pub(crate) struct RLinkRustcVersionMismatch<'a> {
 #[diag(driver_impl_rlink_no_a_file)]
 pub(crate) struct RlinkNotAFile;
 
+#[derive(Diagnostic)]
+#[diag(driver_impl_couldnt_emit_stable_mir)]
+pub(crate) struct CouldntEmitStableMir<'a> {
+    pub path: &'a std::path::Path,
+    pub error: std::io::Error,
+}
+
 #[derive(Diagnostic)]
 #[diag(driver_impl_rlink_corrupt_file)]
 pub(crate) struct RlinkCorruptFile<'a> {
@@ -696,6 +696,7 @@ macro_rules! untracked {
     untracked!(dump_mono_stats, SwitchWithOptPath::Enabled(Some("mono-items-dir/".into())));
     untracked!(dump_mono_stats_format, DumpMonoStatsFormat::Json);
     untracked!(dylib_lto, true);
+    untracked!(emit_smir, SwitchWithOptPath::Enabled(Some("smir-dir/".into())));
     untracked!(emit_stack_sizes, true);
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
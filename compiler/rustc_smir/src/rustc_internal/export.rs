@@ -0,0 +1,24 @@
+use std::io;
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_middle::ty::TyCtxt;
+use stable_mir::export::ExportedCrate;
+
+use super::{run, stable};
+
+/// Writes the bodies of all mono items of the local crate, and the data they refer to, as JSON.
+///
+/// See [`stable_mir::export`] for the format.
+pub fn write_smir_json<'tcx, W: io::Write>(tcx: TyCtxt<'tcx>, w: &mut W) -> io::Result<()> {
+    let (_, units) = tcx.collect_and_partition_mono_items(());
+    // An item can be part of several codegen units, e.g. if it is inlined.
+    let items = units
+        .iter()
+        .flat_map(|unit| unit.items_in_deterministic_order(tcx))
+        .map(|(item, _)| item)
+        .collect::<FxIndexSet<_>>();
+    let krate = run(tcx, || ExportedCrate::collect(items.into_iter().map(stable)))
+        .map_err(io::Error::other)?;
+    serde_json::to_writer(&mut *w, &krate)?;
+    Ok(())
+}
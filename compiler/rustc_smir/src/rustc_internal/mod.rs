@@ -23,6 +23,7 @@
 use crate::rustc_smir::context::TablesWrapper;
 use crate::rustc_smir::{Stable, Tables};
 
+pub mod export;
 mod internal;
 pub mod pretty;
 
@@ -0,0 +1,291 @@
+//! A self-contained snapshot of the mono items of a crate, which can be stored and analyzed
+//! outside of the compiler.
+//!
+//! Most of the data model only holds indices (e.g. [`Ty`], [`DefId`] or [`Span`]) that are
+//! resolved by the compiler on demand. An [`ExportedCrate`] resolves all indices that are reachable
+//! from the bodies of its items upfront, so that it stays meaningful once the compiler is gone.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::abi::LayoutShape;
+use crate::mir::alloc::{AllocId, GlobalAlloc};
+use crate::mir::mono::{Instance, MonoItem};
+use crate::mir::visit::Location;
+use crate::mir::{Body, MirVisitor};
+use crate::target::MachineInfo;
+use crate::ty::{
+    AdtDef, AdtKind, Allocation, ConstantKind, ExistentialPredicate, GenericArgs, LineInfo,
+    MirConst, RigidTy, Span, TraitDecl, TraitDef, Ty, TyConst, TyConstKind, TyKind,
+};
+use crate::visitor::{Visitable, Visitor};
+use crate::{CrateDef, DefId, Filename, Symbol};
+
+/// The version of the format of [`ExportedCrate`].
+///
+/// This is bumped whenever the serialized representation changes, so that consumers can reject
+/// files they do not understand.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The mono items of a crate together with everything needed to interpret them.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportedCrate {
+    /// The [`FORMAT_VERSION`] this was exported with.
+    pub version: u32,
+    /// The name of the crate.
+    pub name: Symbol,
+    /// The target the crate was compiled for.
+    pub machine_info: MachineInfo,
+    /// The mono items in the order they were given to [`ExportedCrate::collect`].
+    pub items: Vec<ExportedItem>,
+    /// Every type that is referenced by the items, including the types nested in them.
+    pub types: Vec<(Ty, TypeInfo)>,
+    /// Every ADT whose type is in `types`.
+    pub adts: Vec<(AdtDef, AdtInfo)>,
+    /// Every trait that is referenced by a trait object type in `types`.
+    pub traits: Vec<(TraitDef, TraitDecl)>,
+    /// Every global allocation that is reachable from the constants of the items.
+    pub allocs: Vec<(AllocId, GlobalAlloc)>,
+    /// The name of every definition referenced by a type in `types`.
+    pub def_names: Vec<(DefId, Symbol)>,
+    /// The location of every span in the bodies of the items.
+    pub spans: Vec<(Span, SpanInfo)>,
+}
+
+/// A mono item and its body.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportedItem {
+    /// The symbol name of the item, or `None` for global assembly.
+    pub symbol_name: Option<Symbol>,
+    /// The fully qualified name of the item, or `None` for global assembly.
+    pub name: Option<Symbol>,
+    pub mono_item: MonoItem,
+    /// The body of the item, if it has one.
+    pub body: Option<Body>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TypeInfo {
+    pub kind: TyKind,
+    /// The layout of the type, or `None` if it does not have one (e.g. because it is generic).
+    pub layout: Option<LayoutShape>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AdtInfo {
+    pub name: Symbol,
+    pub kind: AdtKind,
+    /// The name and fields of each variant. Structs and unions have a single variant.
+    pub variants: Vec<(Symbol, Vec<FieldInfo>)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FieldInfo {
+    pub name: Symbol,
+    /// The type of the field, in terms of the generic parameters of the ADT.
+    pub ty: Ty,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpanInfo {
+    pub filename: Filename,
+    pub lines: LineInfo,
+}
+
+impl ExportedCrate {
+    /// Collects the bodies of `items` of the local crate, and all the data they refer to.
+    pub fn collect(items: impl IntoIterator<Item = MonoItem>) -> ExportedCrate {
+        let mut collector = Collector::default();
+        let items = items
+            .into_iter()
+            .map(|mono_item| {
+                let instance = match &mono_item {
+                    MonoItem::Fn(instance) => Some(*instance),
+                    MonoItem::Static(def) => Some(Instance::from(*def)),
+                    MonoItem::GlobalAsm(_) => None,
+                };
+                let body = instance.and_then(|instance| instance.body());
+                if let Some(body) = &body {
+                    collector.visit_body(body);
+                }
+                ExportedItem {
+                    symbol_name: instance.map(|instance| instance.mangled_name()),
+                    name: instance.map(|instance| instance.name()),
+                    mono_item,
+                    body,
+                }
+            })
+            .collect();
+        let Collector { types, adts, traits, allocs, def_names, spans, .. } = collector;
+        ExportedCrate {
+            version: FORMAT_VERSION,
+            name: crate::local_crate().name,
+            machine_info: MachineInfo::target(),
+            items,
+            types,
+            adts,
+            traits,
+            allocs,
+            def_names,
+            spans,
+        }
+    }
+}
+
+/// Records everything that is reachable from the bodies it visits.
+#[derive(Default)]
+struct Collector {
+    seen_types: HashSet<Ty>,
+    seen_adts: HashSet<AdtDef>,
+    seen_traits: HashSet<TraitDef>,
+    seen_allocs: HashSet<AllocId>,
+    seen_defs: HashSet<DefId>,
+    seen_spans: HashSet<Span>,
+    types: Vec<(Ty, TypeInfo)>,
+    adts: Vec<(AdtDef, AdtInfo)>,
+    traits: Vec<(TraitDef, TraitDecl)>,
+    allocs: Vec<(AllocId, GlobalAlloc)>,
+    def_names: Vec<(DefId, Symbol)>,
+    spans: Vec<(Span, SpanInfo)>,
+}
+
+impl Collector {
+    fn add_ty(&mut self, ty: Ty) {
+        let _ = ty.visit(self);
+    }
+
+    fn add_def(&mut self, def: impl CrateDef) {
+        let def_id = def.def_id();
+        if self.seen_defs.insert(def_id) {
+            self.def_names.push((def_id, def.name()));
+        }
+    }
+
+    fn add_adt(&mut self, adt: AdtDef) {
+        if !self.seen_adts.insert(adt) {
+            return;
+        }
+        let variants = adt
+            .variants_iter()
+            .map(|variant| {
+                let fields = variant
+                    .fields()
+                    .into_iter()
+                    .map(|field| FieldInfo { ty: field.ty(), name: field.name })
+                    .collect::<Vec<_>>();
+                (variant.name(), fields)
+            })
+            .collect::<Vec<_>>();
+        for (_, fields) in &variants {
+            for field in fields {
+                self.add_ty(field.ty);
+            }
+        }
+        self.adts.push((adt, AdtInfo { name: adt.name(), kind: adt.kind(), variants }));
+    }
+
+    fn add_trait(&mut self, def: TraitDef) {
+        if self.seen_traits.insert(def) {
+            self.traits.push((def, TraitDef::declaration(&def)));
+        }
+    }
+
+    /// Records the allocations that the pointers in `alloc` point to.
+    fn add_allocation(&mut self, alloc: &Allocation) {
+        for (_, prov) in &alloc.provenance.ptrs {
+            self.add_alloc(prov.0);
+        }
+    }
+
+    fn add_alloc(&mut self, id: AllocId) {
+        if !self.seen_allocs.insert(id) {
+            return;
+        }
+        let global = GlobalAlloc::from(id);
+        match &global {
+            GlobalAlloc::Function(instance) => self.add_ty(instance.ty()),
+            GlobalAlloc::VTable(ty, trait_ref) => {
+                self.add_ty(*ty);
+                if let Some(trait_ref) = trait_ref {
+                    self.add_trait(trait_ref.value.def_id);
+                }
+            }
+            GlobalAlloc::Static(def) => {
+                self.add_def(*def);
+                self.add_ty(def.ty());
+            }
+            GlobalAlloc::Memory(alloc) => self.add_allocation(alloc),
+        }
+        self.allocs.push((id, global));
+    }
+}
+
+impl Visitor for Collector {
+    type Break = ();
+
+    fn visit_ty(&mut self, ty: &Ty) -> ControlFlow<()> {
+        if !self.seen_types.insert(*ty) {
+            return ControlFlow::Continue(());
+        }
+        let kind = ty.kind();
+        match &kind {
+            TyKind::RigidTy(RigidTy::Adt(def, _)) => self.add_adt(*def),
+            TyKind::RigidTy(RigidTy::Foreign(def)) => self.add_def(*def),
+            TyKind::RigidTy(RigidTy::FnDef(def, _)) => self.add_def(*def),
+            TyKind::RigidTy(RigidTy::Closure(def, _)) => self.add_def(*def),
+            TyKind::RigidTy(RigidTy::Coroutine(def, _, _)) => self.add_def(*def),
+            TyKind::RigidTy(RigidTy::Dynamic(predicates, _, _)) => {
+                for predicate in predicates {
+                    match &predicate.value {
+                        ExistentialPredicate::Trait(trait_ref) => self.add_trait(trait_ref.def_id),
+                        ExistentialPredicate::Projection(projection) => {
+                            self.add_trait(projection.def_id)
+                        }
+                        ExistentialPredicate::AutoTrait(def) => self.add_trait(*def),
+                    }
+                }
+            }
+            _ => {}
+        }
+        let layout = ty.layout().ok().map(|layout| layout.shape());
+        self.types.push((*ty, TypeInfo { kind, layout }));
+        ty.super_visit(self)
+    }
+
+    fn visit_const(&mut self, c: &TyConst) -> ControlFlow<()> {
+        if let TyConstKind::Value(_, alloc) = c.kind() {
+            self.add_allocation(alloc);
+        }
+        c.super_visit(self)
+    }
+}
+
+impl MirVisitor for Collector {
+    fn visit_span(&mut self, span: &Span) {
+        if self.seen_spans.insert(*span) {
+            let info = SpanInfo { filename: span.get_filename(), lines: span.get_lines() };
+            self.spans.push((*span, info));
+        }
+    }
+
+    fn visit_ty(&mut self, ty: &Ty, _location: Location) {
+        self.add_ty(*ty);
+    }
+
+    fn visit_mir_const(&mut self, constant: &MirConst, _location: Location) {
+        if let ConstantKind::Allocated(alloc) = constant.kind() {
+            self.add_allocation(alloc);
+        }
+        let _ = constant.visit(self);
+    }
+
+    fn visit_ty_const(&mut self, constant: &TyConst, _location: Location) {
+        let _ = constant.visit(self);
+    }
+
+    fn visit_args(&mut self, args: &GenericArgs, _location: Location) {
+        let _ = args.visit(self);
+    }
+}
@@ -20,7 +20,7 @@
 use std::fmt::Debug;
 use std::{fmt, io};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::compiler_interface::with;
 pub use crate::crate_def::{CrateDef, CrateDefType, DefId};
@@ -34,6 +34,7 @@
 pub mod compiler_interface;
 #[macro_use]
 pub mod error;
+pub mod export;
 pub mod mir;
 pub mod target;
 pub mod ty;
@@ -74,7 +75,7 @@ fn to_index(&self) -> usize {
 pub type ImplTraitDecls = Vec<ImplDef>;
 
 /// Holds information about a crate.
-#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Crate {
     pub id: CrateNum,
     pub name: Symbol,
@@ -98,7 +99,7 @@ pub fn trait_impls(&self) -> ImplTraitDecls {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum ItemKind {
     Fn,
     Static,
@@ -106,7 +107,7 @@ pub enum ItemKind {
     Ctor(CtorKind),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum CtorKind {
     Const,
     Fn,
@@ -116,7 +117,7 @@ pub enum CtorKind {
 
 crate_def_with_ty! {
     /// Holds information about an item in a crate.
-    #[derive(Serialize)]
+    #[derive(Serialize, Deserialize)]
     pub CrateItem;
 }
 
@@ -189,7 +190,7 @@ pub fn all_trait_impls() -> ImplTraitDecls {
 }
 
 /// A type that provides internal information but that can still be used for debug purpose.
-#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Opaque(String);
 
 impl std::fmt::Display for Opaque {
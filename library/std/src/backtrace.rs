@@ -60,6 +60,7 @@
 
 #![stable(feature = "backtrace", since = "1.65.0")]
 
+mod offline;
 #[cfg(test)]
 mod tests;
 
@@ -80,22 +81,25 @@
 // of an address to a human readable name for as long as possible. When
 // `Backtrace::create` is called to capture a backtrace it doesn't actually
 // perform any symbol resolution, but rather we lazily resolve symbols only just
-// before they're needed for printing. This way we can make capturing a
-// backtrace and throwing it away much cheaper, but actually printing a
-// backtrace is still basically the same cost.
+// before they're needed for printing, or when the symbols of a frame are
+// requested. This way we can make capturing a backtrace and throwing it away
+// much cheaper, but actually printing a backtrace is still basically the same
+// cost.
 //
 // This strategy comes at the cost of some synchronization required inside of a
 // `Backtrace`, but that's a relatively small price to pay relative to capturing
-// a backtrace or actually symbolizing it.
+// a backtrace or actually symbolizing it. Symbols are only ever resolved while
+// holding the global backtrace lock, which also guarantees that two threads
+// never wait on each other to resolve different frames of the same backtrace.
 
 use crate::backtrace_rs::{self, BytesOrWideString};
 use crate::ffi::c_void;
-use crate::panic::UnwindSafe;
-use crate::sync::LazyLock;
+use crate::path::{Path, PathBuf};
+use crate::sync::OnceLock;
 use crate::sync::atomic::AtomicU8;
 use crate::sync::atomic::Ordering::Relaxed;
-use crate::sys::backtrace::{lock, output_filename, set_image_base};
-use crate::{env, fmt};
+use crate::sys::backtrace::{lock, module_base_address, output_filename, set_image_base};
+use crate::{env, fmt, io};
 
 /// A captured OS thread stack backtrace.
 ///
@@ -132,7 +136,7 @@ pub enum BacktraceStatus {
 enum Inner {
     Unsupported,
     Disabled,
-    Captured(LazyLock<Capture, LazyResolve>),
+    Captured(Capture),
 }
 
 struct Capture {
@@ -146,10 +150,14 @@ fn _assert<T: Send + Sync>() {}
 }
 
 /// A single frame of a backtrace.
+///
+/// The addresses of a frame are known as soon as the backtrace is captured,
+/// while its symbols are only resolved from the debug information of the
+/// program when they are first requested, see [`BacktraceFrame::symbols`].
 #[unstable(feature = "backtrace_frames", issue = "79676")]
 pub struct BacktraceFrame {
     frame: RawFrame,
-    symbols: Vec<BacktraceSymbol>,
+    symbols: OnceLock<Vec<BacktraceSymbol>>,
 }
 
 #[derive(Debug)]
@@ -159,7 +167,15 @@ enum RawFrame {
     Fake,
 }
 
-struct BacktraceSymbol {
+/// A symbol of a [`BacktraceFrame`].
+///
+/// A frame has several symbols when functions were inlined into the function
+/// of the frame, in which case the innermost function comes first.
+///
+/// All of the fields of a symbol are optional, as they depend on the debug
+/// information available for the program.
+#[unstable(feature = "backtrace_frames", issue = "79676")]
+pub struct BacktraceSymbol {
     name: Option<Vec<u8>>,
     filename: Option<BytesOrWide>,
     lineno: Option<u32>,
@@ -177,8 +193,9 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let capture = match &self.inner {
             Inner::Unsupported => return fmt.write_str("<unsupported>"),
             Inner::Disabled => return fmt.write_str("<disabled>"),
-            Inner::Captured(c) => &**c,
+            Inner::Captured(c) => c,
         };
+        capture.resolve();
 
         let frames = &capture.frames[capture.actual_start..];
 
@@ -191,7 +208,7 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
                 continue;
             }
 
-            dbg.entries(&frame.symbols);
+            dbg.entries(frame.symbols());
         }
 
         dbg.finish()
@@ -202,11 +219,12 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
 impl fmt::Debug for BacktraceFrame {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut dbg = fmt.debug_list();
-        dbg.entries(&self.symbols);
+        dbg.entries(self.symbols());
         dbg.finish()
     }
 }
 
+#[unstable(feature = "backtrace_frames", issue = "79676")]
 impl fmt::Debug for BacktraceSymbol {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         // FIXME: improve formatting: https://github.com/rust-lang/rust/issues/65280
@@ -331,7 +349,7 @@ fn create(ip: usize) -> Backtrace {
             backtrace_rs::trace_unsynchronized(|frame| {
                 frames.push(BacktraceFrame {
                     frame: RawFrame::Actual(frame.clone()),
-                    symbols: Vec::new(),
+                    symbols: OnceLock::new(),
                 });
                 if frame.symbol_address().addr() == ip && actual_start.is_none() {
                     actual_start = Some(frames.len());
@@ -346,10 +364,7 @@ fn create(ip: usize) -> Backtrace {
         let inner = if frames.is_empty() {
             Inner::Unsupported
         } else {
-            Inner::Captured(LazyLock::new(lazy_resolve(Capture {
-                actual_start: actual_start.unwrap_or(0),
-                frames,
-            })))
+            Inner::Captured(Capture { actual_start: actual_start.unwrap_or(0), frames })
         };
 
         Backtrace { inner }
@@ -371,11 +386,29 @@ pub fn status(&self) -> BacktraceStatus {
 
 impl<'a> Backtrace {
     /// Returns an iterator over the backtrace frames.
+    ///
+    /// This doesn't resolve the symbols of the frames, which is done when they
+    /// are first requested, or by [`Backtrace::resolve`].
     #[must_use]
     #[unstable(feature = "backtrace_frames", issue = "79676")]
     pub fn frames(&'a self) -> &'a [BacktraceFrame] {
         if let Inner::Captured(c) = &self.inner { &c.frames } else { &[] }
     }
+
+    /// Resolves the symbols of every frame of this backtrace, if they haven't
+    /// been already.
+    ///
+    /// Symbols are otherwise resolved when the backtrace is printed, or when
+    /// the symbols of a frame are requested. Resolving the whole backtrace at
+    /// once is cheaper than resolving its frames one by one, and makes sure
+    /// that the debug information is read while the libraries of the program
+    /// are still loaded.
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn resolve(&self) {
+        if let Inner::Captured(c) = &self.inner {
+            c.resolve();
+        }
+    }
 }
 
 #[stable(feature = "backtrace", since = "1.65.0")]
@@ -384,8 +417,9 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let capture = match &self.inner {
             Inner::Unsupported => return fmt.write_str("unsupported backtrace"),
             Inner::Disabled => return fmt.write_str("disabled backtrace"),
-            Inner::Captured(c) => &**c,
+            Inner::Captured(c) => c,
         };
+        capture.resolve();
 
         let full = fmt.alternate();
         let (frames, style) = if full {
@@ -406,10 +440,11 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut f = backtrace_rs::BacktraceFmt::new(fmt, style, &mut print_path);
         f.add_context()?;
         for frame in frames {
-            if frame.symbols.is_empty() {
+            let symbols = frame.symbols();
+            if symbols.is_empty() {
                 f.frame().print_raw(frame.frame.ip(), None, None, None)?;
             } else {
-                for symbol in frame.symbols.iter() {
+                for symbol in symbols {
                     f.frame().print_raw_with_column(
                         frame.frame.ip(),
                         symbol.name.as_ref().map(|b| backtrace_rs::SymbolName::new(b)),
@@ -428,43 +463,165 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
-mod helper {
-    use super::*;
-    pub(super) type LazyResolve = impl (FnOnce() -> Capture) + Send + Sync + UnwindSafe;
-
-    pub(super) fn lazy_resolve(mut capture: Capture) -> LazyResolve {
-        move || {
-            // Use the global backtrace lock to synchronize this as it's a
-            // requirement of the `backtrace` crate, and then actually resolve
-            // everything.
-            let _lock = lock();
-            for frame in capture.frames.iter_mut() {
-                let symbols = &mut frame.symbols;
-                let frame = match &frame.frame {
-                    RawFrame::Actual(frame) => frame,
-                    #[cfg(test)]
-                    RawFrame::Fake => unimplemented!(),
-                };
-                unsafe {
-                    backtrace_rs::resolve_frame_unsynchronized(frame, |symbol| {
-                        symbols.push(BacktraceSymbol {
-                            name: symbol.name().map(|m| m.as_bytes().to_vec()),
-                            filename: symbol.filename_raw().map(|b| match b {
-                                BytesOrWideString::Bytes(b) => BytesOrWide::Bytes(b.to_owned()),
-                                BytesOrWideString::Wide(b) => BytesOrWide::Wide(b.to_owned()),
-                            }),
-                            lineno: symbol.lineno(),
-                            colno: symbol.colno(),
-                        });
-                    });
-                }
-            }
+impl Capture {
+    fn resolve(&self) {
+        if self.frames.iter().all(|frame| frame.symbols.get().is_some()) {
+            return;
+        }
+        // Use the global backtrace lock to synchronize this as it's a
+        // requirement of the `backtrace` crate, and then actually resolve
+        // everything.
+        let _lock = lock();
+        for frame in &self.frames {
+            frame.symbols.get_or_init(|| unsafe { frame.frame.resolve_unsynchronized() });
+        }
+    }
+}
+
+impl BacktraceFrame {
+    /// Returns the instruction pointer of this frame.
+    ///
+    /// This is normally the address of the next instruction to be executed in
+    /// the frame, such as the return address of a call.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn ip(&self) -> *mut c_void {
+        self.frame.ip()
+    }
+
+    /// Returns the starting address of the function of this frame.
+    ///
+    /// On some platforms this is only an approximation, which may be the
+    /// [instruction pointer](BacktraceFrame::ip) itself.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn symbol_address(&self) -> *mut c_void {
+        self.frame.symbol_address()
+    }
 
-            capture
+    /// Returns the address at which the executable or library containing this
+    /// frame is loaded, if it is known.
+    ///
+    /// The difference between the [instruction pointer] and this base address
+    /// doesn't depend on where the module was loaded, which makes it possible
+    /// to symbolize the frame offline, against a copy of the module and its
+    /// debug information, see [`BacktraceFrame::resolve_with`].
+    ///
+    /// [instruction pointer]: BacktraceFrame::ip
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn module_base_address(&self) -> Option<*mut c_void> {
+        self.frame.module_base_address()
+    }
+
+    /// Returns the symbols of this frame, resolving them if they haven't been
+    /// already.
+    ///
+    /// The list is empty if no symbol could be found for the frame.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn symbols(&self) -> &[BacktraceSymbol] {
+        if let Some(symbols) = self.symbols.get() {
+            return symbols;
+        }
+        let _lock = lock();
+        self.symbols.get_or_init(|| unsafe { self.frame.resolve_unsynchronized() })
+    }
+
+    /// Resolves the symbols of this frame against the executable or library
+    /// at `path`, rather than against the modules loaded in the current
+    /// process.
+    ///
+    /// The file must be a copy of the module containing this frame, or the
+    /// separate debug information of that module, like the output of
+    /// `objcopy --only-keep-debug` or the DWARF file of a `.dSYM` bundle. The
+    /// symbols are looked up in its DWARF debug information, or in its symbol
+    /// table if it has none. The file is read again on every call, and the
+    /// symbols returned by [`BacktraceFrame::symbols`] are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the file can't be read or isn't an
+    /// object file, if the [module base address] of the frame is unknown, or
+    /// on platforms where resolving against a file isn't supported, like
+    /// Windows MSVC targets, whose debug information is kept in PDB files.
+    ///
+    /// [module base address]: BacktraceFrame::module_base_address
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn resolve_with<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<BacktraceSymbol>> {
+        let Some(module_base) = self.module_base_address() else {
+            return Err(io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "the module base address of the frame is unknown",
+            ));
+        };
+        offline::resolve(path.as_ref(), self.ip(), module_base)
+    }
+}
+
+impl BacktraceSymbol {
+    /// Returns the demangled name of the function of this symbol.
+    ///
+    /// The hash which is part of the names of Rust symbols is omitted.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn name(&self) -> Option<String> {
+        self.name.as_ref().map(|b| format!("{:#}", backtrace_rs::SymbolName::new(b)))
+    }
+
+    /// Returns the name of the function of this symbol as found in the
+    /// binary, before demangling.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn raw_name(&self) -> Option<&[u8]> {
+        self.name.as_deref()
+    }
+
+    /// Returns the path of the source file of this symbol.
+    ///
+    /// The path is the one recorded in the debug information when the program
+    /// was compiled, which may be relative.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn filename(&self) -> Option<PathBuf> {
+        self.filename.as_ref().map(BytesOrWide::to_path_buf)
+    }
+
+    /// Returns the line number of this symbol in its source file.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn lineno(&self) -> Option<u32> {
+        self.lineno
+    }
+
+    /// Returns the column number of this symbol in its source file.
+    #[must_use]
+    #[unstable(feature = "backtrace_frames", issue = "79676")]
+    pub fn colno(&self) -> Option<u32> {
+        self.colno
+    }
+}
+
+impl BytesOrWide {
+    fn to_path_buf(&self) -> PathBuf {
+        match self {
+            #[cfg(unix)]
+            BytesOrWide::Bytes(bytes) => {
+                use crate::os::unix::prelude::*;
+                crate::ffi::OsStr::from_bytes(bytes).into()
+            }
+            #[cfg(not(unix))]
+            BytesOrWide::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned().into(),
+            #[cfg(windows)]
+            BytesOrWide::Wide(wide) => {
+                use crate::os::windows::prelude::*;
+                crate::ffi::OsString::from_wide(wide).into()
+            }
+            #[cfg(not(windows))]
+            BytesOrWide::Wide(wide) => String::from_utf16_lossy(wide).into(),
         }
     }
 }
-use helper::*;
 
 impl RawFrame {
     fn ip(&self) -> *mut c_void {
@@ -474,4 +631,50 @@ fn ip(&self) -> *mut c_void {
             RawFrame::Fake => crate::ptr::without_provenance_mut(1),
         }
     }
+
+    fn symbol_address(&self) -> *mut c_void {
+        match self {
+            RawFrame::Actual(frame) => frame.symbol_address(),
+            #[cfg(test)]
+            RawFrame::Fake => crate::ptr::without_provenance_mut(1),
+        }
+    }
+
+    fn module_base_address(&self) -> Option<*mut c_void> {
+        match self {
+            RawFrame::Actual(frame) => {
+                frame.module_base_address().or_else(|| module_base_address(frame.ip()))
+            }
+            #[cfg(test)]
+            RawFrame::Fake => None,
+        }
+    }
+
+    /// Resolves the symbols of this frame.
+    ///
+    /// # Safety
+    ///
+    /// The global backtrace lock must be held.
+    unsafe fn resolve_unsynchronized(&self) -> Vec<BacktraceSymbol> {
+        let frame = match self {
+            RawFrame::Actual(frame) => frame,
+            #[cfg(test)]
+            RawFrame::Fake => unimplemented!(),
+        };
+        let mut symbols = Vec::new();
+        unsafe {
+            backtrace_rs::resolve_frame_unsynchronized(frame, |symbol| {
+                symbols.push(BacktraceSymbol {
+                    name: symbol.name().map(|m| m.as_bytes().to_vec()),
+                    filename: symbol.filename_raw().map(|b| match b {
+                        BytesOrWideString::Bytes(b) => BytesOrWide::Bytes(b.to_owned()),
+                        BytesOrWideString::Wide(b) => BytesOrWide::Wide(b.to_owned()),
+                    }),
+                    lineno: symbol.lineno(),
+                    colno: symbol.colno(),
+                });
+            });
+        }
+        symbols
+    }
 }
@@ -0,0 +1,105 @@
+//! Resolution of backtrace frames against an object file, rather than against the modules loaded
+//! in the current process. See [`BacktraceFrame::resolve_with`](super::BacktraceFrame).
+
+use super::{BacktraceSymbol, BytesOrWide};
+use crate::ffi::c_void;
+use crate::io;
+use crate::path::Path;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        feature = "backtrace",
+        not(all(windows, target_env = "msvc", not(target_vendor = "uwp"))),
+    ))] {
+        use addr2line::gimli;
+        use object::{Object, ObjectSection, ObjectSegment};
+
+        use crate::borrow::Cow;
+        use crate::fs;
+
+        /// Resolves the symbols at `ip`, in a module loaded at `module_base`, from the copy of
+        /// that module at `path`.
+        pub(super) fn resolve(
+            path: &Path,
+            ip: *mut c_void,
+            module_base: *mut c_void,
+        ) -> io::Result<Vec<BacktraceSymbol>> {
+            let data = fs::read(path)?;
+            let object = object::File::parse(&*data).map_err(|_| invalid_object())?;
+
+            // The instruction pointer is usually a return address, which may already belong to
+            // the next line or function, so the call instruction before it is looked up instead,
+            // like when resolving against the loaded modules.
+            let offset = (ip.addr() as u64).wrapping_sub(module_base.addr() as u64);
+            let offset = offset.saturating_sub(1);
+            // The module base is where the start of the file is loaded.
+            let file_base = object
+                .segments()
+                .find(|segment| matches!(segment.file_range(), (0, size) if size != 0))
+                .map_or_else(|| object.relative_address_base(), |segment| segment.address());
+            let address = file_base.wrapping_add(offset);
+
+            let endian = if object.is_little_endian() {
+                gimli::RunTimeEndian::Little
+            } else {
+                gimli::RunTimeEndian::Big
+            };
+            let dwarf = gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+                let data = match object.section_by_name(id.name()).map(|s| s.uncompressed_data()) {
+                    Some(Ok(Cow::Borrowed(data))) => data,
+                    // Missing sections are empty. Compressed ones aren't supported.
+                    _ => &[],
+                };
+                Ok(gimli::EndianSlice::new(data, endian))
+            })
+            .map_err(|_| invalid_object())?;
+            let context = addr2line::Context::from_dwarf(dwarf).map_err(|_| invalid_object())?;
+
+            let mut symbols = Vec::new();
+            if let Ok(mut frames) = context.find_frames(address).skip_all_loads() {
+                while let Ok(Some(frame)) = frames.next() {
+                    let location = frame.location.as_ref();
+                    symbols.push(BacktraceSymbol {
+                        name: frame
+                            .function
+                            .as_ref()
+                            .and_then(|function| function.raw_name().ok())
+                            .map(|name| name.as_bytes().to_vec()),
+                        filename: location
+                            .and_then(|location| location.file)
+                            .map(|file| BytesOrWide::Bytes(file.as_bytes().to_vec())),
+                        lineno: location.and_then(|location| location.line),
+                        colno: location.and_then(|location| location.column),
+                    });
+                }
+            }
+            // Without debug information, the symbol table still has the name of the function.
+            if symbols.is_empty() {
+                if let Some(symbol) = object.symbol_map().get(address) {
+                    symbols.push(BacktraceSymbol {
+                        name: Some(symbol.name().as_bytes().to_vec()),
+                        filename: None,
+                        lineno: None,
+                        colno: None,
+                    });
+                }
+            }
+            Ok(symbols)
+        }
+
+        fn invalid_object() -> io::Error {
+            io::const_io_error!(io::ErrorKind::InvalidData, "not a valid object file")
+        }
+    } else {
+        pub(super) fn resolve(
+            _path: &Path,
+            _ip: *mut c_void,
+            _module_base: *mut c_void,
+        ) -> io::Result<Vec<BacktraceSymbol>> {
+            Err(io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "resolving backtraces against an object file is not supported on this platform",
+            ))
+        }
+    }
+}
@@ -1,29 +1,29 @@
 use super::*;
-use crate::panic::RefUnwindSafe;
+use crate::panic::{RefUnwindSafe, UnwindSafe};
 
 fn generate_fake_frames() -> Vec<BacktraceFrame> {
     vec![
         BacktraceFrame {
             frame: RawFrame::Fake,
-            symbols: vec![BacktraceSymbol {
+            symbols: OnceLock::from(vec![BacktraceSymbol {
                 name: Some(b"std::backtrace::Backtrace::create".to_vec()),
                 filename: Some(BytesOrWide::Bytes(b"rust/backtrace.rs".to_vec())),
                 lineno: Some(100),
                 colno: None,
-            }],
+            }]),
         },
         BacktraceFrame {
             frame: RawFrame::Fake,
-            symbols: vec![BacktraceSymbol {
+            symbols: OnceLock::from(vec![BacktraceSymbol {
                 name: Some(b"__rust_maybe_catch_panic".to_vec()),
                 filename: None,
                 lineno: None,
                 colno: None,
-            }],
+            }]),
         },
         BacktraceFrame {
             frame: RawFrame::Fake,
-            symbols: vec![
+            symbols: OnceLock::from(vec![
                 BacktraceSymbol {
                     name: Some(b"std::rt::lang_start_internal".to_vec()),
                     filename: Some(BytesOrWide::Bytes(b"rust/rt.rs".to_vec())),
@@ -36,7 +36,7 @@ fn generate_fake_frames() -> Vec<BacktraceFrame> {
                     lineno: Some(400),
                     colno: None,
                 },
-            ],
+            ]),
         },
     ]
 }
@@ -44,10 +44,7 @@ fn generate_fake_frames() -> Vec<BacktraceFrame> {
 #[test]
 fn test_debug() {
     let backtrace = Backtrace {
-        inner: Inner::Captured(LazyLock::preinit(Capture {
-            actual_start: 1,
-            frames: generate_fake_frames(),
-        })),
+        inner: Inner::Captured(Capture { actual_start: 1, frames: generate_fake_frames() }),
     };
 
     #[rustfmt::skip]
@@ -66,10 +63,7 @@ fn test_debug() {
 #[test]
 fn test_frames() {
     let backtrace = Backtrace {
-        inner: Inner::Captured(LazyLock::preinit(Capture {
-            actual_start: 1,
-            frames: generate_fake_frames(),
-        })),
+        inner: Inner::Captured(Capture { actual_start: 1, frames: generate_fake_frames() }),
     };
 
     let frames = backtrace.frames();
@@ -98,3 +92,80 @@ fn backtrace_unwind_safe() {
     fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
     assert_unwind_safe::<Backtrace>();
 }
+
+#[test]
+fn test_symbols() {
+    let frames = generate_fake_frames();
+    assert_eq!(frames[0].module_base_address(), None);
+
+    let symbols = frames[2].symbols();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0].name().as_deref(), Some("std::rt::lang_start_internal"));
+    assert_eq!(symbols[0].raw_name(), Some(&b"std::rt::lang_start_internal"[..]));
+    assert_eq!(symbols[0].filename(), Some(PathBuf::from("rust/rt.rs")));
+    assert_eq!((symbols[0].lineno(), symbols[0].colno()), (Some(300), Some(5)));
+    assert_eq!((symbols[1].lineno(), symbols[1].colno()), (Some(400), None));
+
+    let symbols = frames[1].symbols();
+    assert_eq!(symbols[0].filename(), None);
+    assert_eq!(symbols[0].lineno(), None);
+}
+
+#[test]
+fn test_demangled_name() {
+    let symbol = BacktraceSymbol {
+        name: Some(b"_ZN3std2rt10lang_start17h0123456789abcdefE".to_vec()),
+        filename: None,
+        lineno: None,
+        colno: None,
+    };
+    assert_eq!(symbol.name().as_deref(), Some("std::rt::lang_start"));
+    assert_eq!(symbol.raw_name(), Some(&b"_ZN3std2rt10lang_start17h0123456789abcdefE"[..]));
+}
+
+#[test]
+#[cfg_attr(any(target_os = "wasi", miri), ignore)]
+fn resolve_captured() {
+    let backtrace = Backtrace::force_capture();
+    if backtrace.status() != BacktraceStatus::Captured {
+        return;
+    }
+    let frames = backtrace.frames();
+    assert!(frames.iter().all(|frame| frame.symbols.get().is_none()));
+    assert!(frames.iter().any(|frame| !frame.ip().is_null()));
+
+    backtrace.resolve();
+    assert!(frames.iter().all(|frame| frame.symbols.get().is_some()));
+}
+
+#[test]
+#[cfg_attr(any(target_os = "wasi", miri), ignore)]
+fn resolve_with_current_exe() {
+    #[inline(never)]
+    fn capture() -> Backtrace {
+        Backtrace::force_capture()
+    }
+
+    let backtrace = capture();
+    if backtrace.status() != BacktraceStatus::Captured {
+        return;
+    }
+    let is_test = |symbol: &BacktraceSymbol| {
+        symbol.name().is_some_and(|name| name.contains("resolve_with_current_exe"))
+    };
+    let Some(frame) = backtrace.frames().iter().find(|frame| frame.symbols().iter().any(is_test))
+    else {
+        return;
+    };
+
+    // The test executable is the module of its own frames.
+    let exe = crate::env::current_exe().unwrap();
+    let symbols = match frame.resolve_with(&exe) {
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => return,
+        result => result.unwrap(),
+    };
+    assert!(symbols.iter().any(is_test));
+
+    let err = frame.resolve_with(exe.with_file_name("missing-executable")).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
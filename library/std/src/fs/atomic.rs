@@ -0,0 +1,342 @@
+//! Atomic replacement of files, see [`write_atomic`] and [`AtomicWriteOptions`].
+
+use super::{File, OpenOptions};
+use crate::ffi::OsString;
+use crate::io::{self, IoSlice, Write};
+use crate::path::{Path, PathBuf};
+use crate::sys::fs as fs_imp;
+use crate::sys::random::hashmap_random_keys;
+use crate::sys_common::ignore_notfound;
+
+/// The number of names tried for the temporary file before giving up.
+const TEMP_ATTEMPTS: u32 = 16;
+
+/// Writes a slice as the entire contents of a file, replacing it atomically.
+///
+/// Unlike [`write`](super::write), which truncates the file before writing to it, the contents
+/// are first written to a temporary file in the same directory, which is flushed to disk and
+/// then renamed over `path`. Readers of `path` see either its previous contents or the new ones
+/// in full, even if the process or the system crashes in the meantime.
+///
+/// The permissions of the file at `path`, if it exists, are carried over to the new file. Use
+/// [`AtomicWriteOptions`] for more control.
+///
+/// # Errors
+///
+/// This function will return an error if the temporary file can't be created, written to or
+/// renamed. The file at `path` is left untouched in that case.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_write_atomic)]
+/// use std::fs;
+///
+/// fn main() -> std::io::Result<()> {
+///     fs::write_atomic("config.toml", "answer = 42\n")?;
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "fs_write_atomic", issue = "none")]
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    fn inner(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut file = AtomicWriteOptions::new().open(path)?;
+        file.write_all(contents)?;
+        file.finish()
+    }
+    inner(path.as_ref(), contents.as_ref())
+}
+
+/// Options for replacing a file atomically.
+///
+/// [`open`] creates a temporary file next to the file being replaced, and returns an
+/// [`AtomicFile`] handle to it. Once the new contents have been written to the handle,
+/// [`AtomicFile::finish`] flushes them to disk and renames the temporary file over the target.
+/// Dropping the handle without finishing it removes the temporary file and leaves the target
+/// untouched.
+///
+/// [`open`]: AtomicWriteOptions::open
+///
+/// # Platform-specific behavior
+///
+/// On Linux, `renameat2` is used to implement [`create_new`] and [`backup`] without any window
+/// during which the target is missing or could be overwritten, where the kernel and the
+/// filesystem support it. Elsewhere [`create_new`] relies on hard links, and [`backup`] creates
+/// a hard link to the previous file before replacing it.
+///
+/// After the rename, the directory containing the file is flushed to disk on Unix. On other
+/// platforms the rename is left for the system to flush.
+///
+/// [`create_new`]: AtomicWriteOptions::create_new
+/// [`backup`]: AtomicWriteOptions::backup
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_write_atomic)]
+/// use std::fs::AtomicWriteOptions;
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut file = AtomicWriteOptions::new().backup("config.toml.bak").open("config.toml")?;
+///     writeln!(file, "answer = 42")?;
+///     file.finish()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[unstable(feature = "fs_write_atomic", issue = "none")]
+pub struct AtomicWriteOptions {
+    preserve_permissions: bool,
+    create_new: bool,
+    backup: Option<PathBuf>,
+}
+
+impl AtomicWriteOptions {
+    /// Creates a blank new set of options.
+    ///
+    /// The permissions of the file being replaced are preserved, and the file is created if it
+    /// doesn't exist.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    #[must_use]
+    pub fn new() -> Self {
+        AtomicWriteOptions { preserve_permissions: true, create_new: false, backup: None }
+    }
+
+    /// Sets whether the permissions of the file being replaced are given to the new file.
+    ///
+    /// Otherwise the new file gets the permissions of a newly created file. Either way, the
+    /// owner of the new file is the current user.
+    ///
+    /// Defaults to `true`.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn preserve_permissions(&mut self, preserve: bool) -> &mut Self {
+        self.preserve_permissions = preserve;
+        self
+    }
+
+    /// Sets whether [`AtomicFile::finish`] fails instead of replacing a file which already
+    /// exists, in the manner of [`OpenOptions::create_new`].
+    ///
+    /// The check is done atomically when the new file is moved into place, so there is no
+    /// window during which another process could create the file in between.
+    ///
+    /// Defaults to `false`.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Keeps the previous contents of the file at `path` when the file is replaced.
+    ///
+    /// A file already at `path` is replaced. `path` must be on the same filesystem as the file
+    /// being replaced. Nothing is written to `path` when there was no previous file, or with
+    /// [`create_new`](AtomicWriteOptions::create_new).
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn backup<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.backup = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Creates the temporary file which replaces the file at `path` once it is finished.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` has no file name, if the temporary file
+    /// can't be created in the directory containing `path`, or if the permissions of an
+    /// existing file at `path` can't be read or carried over.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<AtomicFile> {
+        self._open(path.as_ref())
+    }
+
+    fn _open(&self, path: &Path) -> io::Result<AtomicFile> {
+        let Some(file_name) = path.file_name() else {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "path to replace has no file name",
+            ));
+        };
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let mut attempts = 0;
+        let (file, temp) = loop {
+            // Hidden on Unix, and unlikely to collide with the name of another file.
+            let mut temp_name = OsString::from(".");
+            temp_name.push(file_name);
+            temp_name.push(format!(".{:016x}.tmp", hashmap_random_keys().0));
+            let temp = dir.join(temp_name);
+            match OpenOptions::new().write(true).create_new(true).open(&temp) {
+                Ok(file) => break (file, temp),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    attempts += 1;
+                    if attempts == TEMP_ATTEMPTS {
+                        return Err(err);
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        };
+        let file = AtomicFile {
+            file: Some(file),
+            temp,
+            path: path.to_path_buf(),
+            dir: dir.to_path_buf(),
+            options: self.clone(),
+        };
+
+        if self.preserve_permissions && !self.create_new {
+            match super::metadata(path) {
+                Ok(metadata) => file.as_file().set_permissions(metadata.permissions())?,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(file)
+    }
+}
+
+#[unstable(feature = "fs_write_atomic", issue = "none")]
+impl Default for AtomicWriteOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A temporary file which atomically replaces another one when it is finished.
+///
+/// This struct is created by [`AtomicWriteOptions::open`]. See its documentation for more.
+#[derive(Debug)]
+#[unstable(feature = "fs_write_atomic", issue = "none")]
+pub struct AtomicFile {
+    /// The temporary file, until it is finished.
+    file: Option<File>,
+    temp: PathBuf,
+    path: PathBuf,
+    dir: PathBuf,
+    options: AtomicWriteOptions,
+}
+
+impl AtomicFile {
+    /// Returns the temporary file, for example to seek in it or to query its metadata.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn as_file(&self) -> &File {
+        self.file.as_ref().unwrap()
+    }
+
+    /// Returns the temporary file, mutably.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn as_file_mut(&mut self) -> &mut File {
+        self.file.as_mut().unwrap()
+    }
+
+    /// Flushes the new contents to disk, and moves them into place.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the contents can't be flushed or the temporary
+    /// file can't be renamed, in which case the temporary file is removed and the file being
+    /// replaced is left untouched. With [`AtomicWriteOptions::create_new`], an error of kind
+    /// [`io::ErrorKind::AlreadyExists`] is returned if the file exists.
+    ///
+    /// An error is also returned if the directory containing the file can't be flushed to
+    /// disk after the rename. The file has been replaced in that case, but the replacement may
+    /// not survive a crash.
+    ///
+    /// With [`AtomicWriteOptions::backup`], the file may be replaced before its previous
+    /// contents can be moved to the backup. If that move fails, the previous contents are left
+    /// in the temporary file, and the error message gives its path.
+    #[unstable(feature = "fs_write_atomic", issue = "none")]
+    pub fn finish(mut self) -> io::Result<()> {
+        let file = self.file.take().unwrap();
+        let mut replaced = false;
+        let result = self.commit(file, &mut replaced);
+        // Once the file has been replaced, the temporary file holds its previous contents.
+        if result.is_err() && !replaced {
+            let _ = super::remove_file(&self.temp);
+        }
+        result
+    }
+
+    /// Moves the new contents into place. `replaced` is set once the file has been replaced.
+    fn commit(&self, file: File, replaced: &mut bool) -> io::Result<()> {
+        file.sync_all()?;
+        // Windows doesn't rename open files.
+        drop(file);
+        if self.options.create_new {
+            fs_imp::rename_noreplace(&self.temp, &self.path)?;
+        } else if let Some(backup) = &self.options.backup {
+            self.replace_with_backup(backup, replaced)?;
+        } else {
+            super::rename(&self.temp, &self.path)?;
+        }
+        fs_imp::sync_dir(&self.dir)
+    }
+
+    fn replace_with_backup(&self, backup: &Path, replaced: &mut bool) -> io::Result<()> {
+        match fs_imp::rename_exchange(&self.temp, &self.path) {
+            // The temporary file now holds the previous contents.
+            Ok(()) => {
+                *replaced = true;
+                return super::rename(&self.temp, backup).map_err(|err| {
+                    io::Error::new(
+                        err.kind(),
+                        format!(
+                            "the file was replaced, but its previous contents couldn't be moved \
+                             to the backup and were left in {}: {err}",
+                            self.temp.display(),
+                        ),
+                    )
+                });
+            }
+            // There is nothing to back up.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return super::rename(&self.temp, &self.path);
+            }
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => {}
+            Err(err) => return Err(err),
+        }
+        ignore_notfound(super::remove_file(backup))?;
+        match super::hard_link(&self.path, backup) {
+            // There is nothing to back up, as opposed to nowhere to put the backup.
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !super::exists(&self.path)? => {}
+            result => result?,
+        }
+        super::rename(&self.temp, &self.path)
+    }
+}
+
+#[unstable(feature = "fs_write_atomic", issue = "none")]
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.as_file_mut().write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.as_file_mut().write_vectored(bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.as_file().is_write_vectored()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.as_file_mut().flush()
+    }
+}
+
+#[unstable(feature = "fs_write_atomic", issue = "none")]
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // Not finished: leave the file being replaced untouched.
+        if let Some(file) = self.file.take() {
+            drop(file);
+            let _ = super::remove_file(&self.temp);
+        }
+    }
+}
@@ -235,6 +235,146 @@ fn file_test_io_seek_shakedown() {
     check!(fs::remove_file(filename));
 }
 
+#[test]
+#[cfg(any(
+    windows,
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_vendor = "apple",
+))]
+fn file_lock_multiple_shared() {
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_lock_multiple_shared_test.txt");
+    let f1 = check!(File::create(filename));
+    let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+    // Check that we can acquire concurrent shared locks
+    check!(f1.lock_shared());
+    check!(f2.lock_shared());
+    check!(f1.unlock());
+    check!(f2.unlock());
+    assert!(check!(f1.try_lock_shared()));
+    assert!(check!(f2.try_lock_shared()));
+}
+
+#[test]
+#[cfg(any(
+    windows,
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_vendor = "apple",
+))]
+fn file_lock_blocking() {
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_lock_blocking_test.txt");
+    let f1 = check!(File::create(filename));
+    let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+    // Check that shared locks block exclusive locks
+    check!(f1.lock_shared());
+    assert!(!check!(f2.try_lock()));
+    check!(f1.unlock());
+
+    // Check that exclusive locks block shared locks
+    check!(f1.lock());
+    assert!(!check!(f2.try_lock_shared()));
+}
+
+#[test]
+#[cfg(any(
+    windows,
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_vendor = "apple",
+))]
+fn file_lock_drop() {
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_lock_drop_test.txt");
+    let f1 = check!(File::create(filename));
+    let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+    // Check that locks are released when the File is dropped
+    check!(f1.lock_shared());
+    assert!(!check!(f2.try_lock()));
+    drop(f1);
+    assert!(check!(f2.try_lock()));
+}
+
+#[test]
+#[cfg(any(
+    windows,
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_vendor = "apple",
+))]
+fn file_lock_dup() {
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_lock_dup_test.txt");
+    let f1 = check!(File::create(filename));
+    let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+    // Check that locks are not dropped if the File has been cloned
+    check!(f1.lock_shared());
+    assert!(!check!(f2.try_lock()));
+    let cloned = check!(f1.try_clone());
+    drop(f1);
+    assert!(!check!(f2.try_lock()));
+    drop(cloned)
+}
+
+#[test]
+#[cfg(windows)]
+fn file_lock_double_unlock() {
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_lock_double_unlock_test.txt");
+    let f1 = check!(File::create(filename));
+    let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+    // On Windows a file handle may acquire both a shared and exclusive lock.
+    // Check that both are released by unlock()
+    check!(f1.lock());
+    check!(f1.lock_shared());
+    assert!(!check!(f2.try_lock()));
+    check!(f1.unlock());
+    assert!(check!(f2.try_lock()));
+}
+
+#[test]
+#[cfg(not(any(
+    windows,
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_vendor = "apple",
+)))]
+fn file_lock_unsupported() {
+    let tmpdir = tmpdir();
+    let filename = &tmpdir.join("file_lock_unsupported_test.txt");
+    let f = check!(File::create(filename));
+
+    assert_eq!(f.lock().unwrap_err().kind(), ErrorKind::Unsupported);
+    assert_eq!(f.try_lock_shared().unwrap_err().kind(), ErrorKind::Unsupported);
+}
+
 #[test]
 fn file_test_io_eof() {
     let tmpdir = tmpdir();
@@ -688,6 +828,349 @@ fn recursive_rmdir_toctou() {
     }
 }
 
+/// Walks `root`, returning the paths relative to it along with the depths of the entries.
+fn walk_dir_paths(walk: fs::WalkDir, root: &Path) -> Vec<(String, usize)> {
+    walk.into_iter()
+        .map(|entry| {
+            let entry = check!(entry);
+            let path = entry.path().strip_prefix(root).unwrap();
+            (path.to_str().unwrap().replace('\\', "/"), entry.depth())
+        })
+        .collect()
+}
+
+#[test]
+fn walk_dir_depth_first() {
+    let tmpdir = tmpdir();
+    let root = tmpdir.join("root");
+    check!(fs::create_dir_all(root.join("a/b")));
+    check!(fs::create_dir_all(root.join("c")));
+    check!(File::create(root.join("a/b/f1")));
+    check!(File::create(root.join("a/f2")));
+    check!(File::create(root.join("f3")));
+
+    let walk = fs::walk_dir(&root).sort_by_file_name();
+    assert_eq!(
+        walk_dir_paths(walk, &root),
+        [
+            ("".to_string(), 0),
+            ("a".to_string(), 1),
+            ("a/b".to_string(), 2),
+            ("a/b/f1".to_string(), 3),
+            ("a/f2".to_string(), 2),
+            ("c".to_string(), 1),
+            ("f3".to_string(), 1),
+        ]
+    );
+
+    let walk = fs::walk_dir(&root).sort_by(|a, b| b.file_name().cmp(a.file_name()));
+    let paths: Vec<_> = walk_dir_paths(walk, &root).into_iter().map(|(path, _)| path).collect();
+    assert_eq!(paths, ["", "f3", "c", "a", "a/f2", "a/b", "a/b/f1"]);
+
+    for entry in fs::walk_dir(&root) {
+        let entry = check!(entry);
+        let metadata = check!(entry.metadata());
+        assert_eq!(metadata.is_dir(), entry.file_type().is_dir());
+        assert!(!entry.path_is_symlink());
+    }
+}
+
+#[test]
+fn walk_dir_min_max_depth() {
+    let tmpdir = tmpdir();
+    let root = tmpdir.join("root");
+    check!(fs::create_dir_all(root.join("a/b/c")));
+    check!(File::create(root.join("a/b/c/f")));
+
+    let walk = fs::walk_dir(&root).min_depth(1).max_depth(2);
+    assert_eq!(walk_dir_paths(walk, &root), [("a".to_string(), 1), ("a/b".to_string(), 2)]);
+
+    let walk = fs::walk_dir(&root).min_depth(3);
+    assert_eq!(walk_dir_paths(walk, &root), [("a/b/c".to_string(), 3), ("a/b/c/f".to_string(), 4)]);
+
+    let walk = fs::walk_dir(&root).max_depth(0);
+    assert_eq!(walk_dir_paths(walk, &root), [("".to_string(), 0)]);
+}
+
+#[test]
+fn walk_dir_skip_current_dir() {
+    let tmpdir = tmpdir();
+    let root = tmpdir.join("root");
+    check!(fs::create_dir_all(root.join("a")));
+    check!(fs::create_dir_all(root.join("b")));
+    check!(fs::create_dir_all(root.join("c")));
+    check!(File::create(root.join("a/f0")));
+    check!(File::create(root.join("b/f1")));
+    check!(File::create(root.join("b/f2")));
+    check!(File::create(root.join("c/f3")));
+
+    let mut walk = fs::walk_dir(&root).sort_by_file_name().into_iter();
+    let mut paths = Vec::new();
+    while let Some(entry) = walk.next() {
+        let entry = check!(entry);
+        let path = entry.path().strip_prefix(&root).unwrap().to_str().unwrap().replace('\\', "/");
+        // Skips the contents of a directory, and the rest of a directory from one of its files.
+        if entry.file_name() == "a" || entry.file_name() == "f1" {
+            walk.skip_current_dir();
+        }
+        paths.push(path);
+    }
+    assert_eq!(paths, ["", "a", "b", "b/f1", "c", "c/f3"]);
+}
+
+#[test]
+fn walk_dir_skip_current_dir_at_max_depth() {
+    let tmpdir = tmpdir();
+    let root = tmpdir.join("root");
+    check!(fs::create_dir_all(root.join("a/b")));
+    check!(fs::create_dir_all(root.join("c")));
+
+    // Directories at the maximum depth aren't visited, so skipping them doesn't skip their
+    // siblings.
+    let mut walk = fs::walk_dir(&root).sort_by_file_name().max_depth(1).into_iter();
+    let mut paths = Vec::new();
+    while let Some(entry) = walk.next() {
+        let entry = check!(entry);
+        if entry.depth() == 1 {
+            walk.skip_current_dir();
+        }
+        paths.push(entry.file_name().to_str().unwrap().to_string());
+    }
+    assert_eq!(paths, ["root", "a", "c"]);
+}
+
+#[test]
+fn walk_dir_not_found() {
+    let tmpdir = tmpdir();
+    let mut walk = fs::walk_dir(tmpdir.join("missing")).into_iter();
+    assert_eq!(walk.next().unwrap().unwrap_err().kind(), ErrorKind::NotFound);
+    assert!(walk.next().is_none());
+}
+
+#[test]
+fn walk_dir_of_file() {
+    let tmpdir = tmpdir();
+    let file = tmpdir.join("file");
+    check!(File::create(&file));
+    let walk = fs::walk_dir(&file);
+    assert_eq!(walk_dir_paths(walk, &file), [("".to_string(), 0)]);
+}
+
+#[test]
+fn walk_dir_symlinks() {
+    let tmpdir = tmpdir();
+    if !got_symlink_permission(&tmpdir) {
+        return;
+    };
+    let root = tmpdir.join("root");
+    let outside = tmpdir.join("outside");
+    check!(fs::create_dir_all(&root));
+    check!(fs::create_dir_all(&outside));
+    check!(File::create(outside.join("f")));
+    check!(symlink_dir(&outside, root.join("link")));
+    check!(symlink_file(tmpdir.join("missing"), root.join("broken")));
+
+    // Links are yielded, but not followed.
+    let mut entries: Vec<_> =
+        fs::walk_dir(&root).min_depth(1).into_iter().map(|e| check!(e)).collect();
+    entries.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|entry| entry.path_is_symlink()));
+    assert!(entries.iter().all(|entry| entry.file_type().is_symlink()));
+    assert!(check!(entries[1].metadata()).file_type().is_symlink());
+
+    // Following links visits their targets, and yields broken links as links.
+    let walk = fs::walk_dir(&root).sort_by_file_name().follow_links(true);
+    let entries: Vec<_> = walk.into_iter().map(|e| check!(e)).collect();
+    let paths: Vec<_> = entries.iter().map(|e| e.path().strip_prefix(&root).unwrap()).collect();
+    assert_eq!(paths, [Path::new(""), Path::new("broken"), Path::new("link"), Path::new("link/f")]);
+    assert!(entries[1].file_type().is_symlink());
+    assert!(entries[2].path_is_symlink());
+    assert!(entries[2].file_type().is_dir());
+    assert!(check!(entries[2].metadata()).is_dir());
+}
+
+#[test]
+fn walk_dir_symlink_loop() {
+    let tmpdir = tmpdir();
+    if !got_symlink_permission(&tmpdir) {
+        return;
+    };
+    let root = tmpdir.join("root");
+    check!(fs::create_dir_all(root.join("a")));
+    check!(symlink_dir(&root, root.join("a/up")));
+
+    // Without following links, the loop is never entered.
+    assert_eq!(fs::walk_dir(&root).into_iter().map(|e| check!(e)).count(), 3);
+
+    let mut errors = 0;
+    let mut entries = 0;
+    for entry in fs::walk_dir(&root).follow_links(true) {
+        match entry {
+            Ok(_) => entries += 1,
+            Err(err) => {
+                assert_eq!(err.kind(), ErrorKind::FilesystemLoop);
+                errors += 1;
+            }
+        }
+    }
+    assert_eq!((entries, errors), (3, 1));
+}
+
+#[test]
+fn walk_dir_skip_current_dir_after_error() {
+    let tmpdir = tmpdir();
+    if !got_symlink_permission(&tmpdir) {
+        return;
+    };
+    let root = tmpdir.join("root");
+    check!(fs::create_dir_all(root.join("a")));
+    check!(fs::create_dir_all(root.join("b")));
+    check!(symlink_dir(&root, root.join("a/up")));
+    check!(File::create(root.join("a/z")));
+
+    // Skipping a directory which couldn't be opened doesn't skip its siblings.
+    let mut walk = fs::walk_dir(&root).sort_by_file_name().follow_links(true).into_iter();
+    let mut paths = Vec::new();
+    while let Some(entry) = walk.next() {
+        match entry {
+            Ok(entry) => {
+                let path = entry.path().strip_prefix(&root).unwrap();
+                paths.push(path.to_str().unwrap().replace('\\', "/"));
+            }
+            Err(err) => {
+                assert_eq!(err.kind(), ErrorKind::FilesystemLoop);
+                walk.skip_current_dir();
+                paths.push("error".to_string());
+            }
+        }
+    }
+    assert_eq!(paths, ["", "a", "a/up", "error", "a/z", "b"]);
+}
+
+/// Returns the names of the files in `dir`, sorted.
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<_> = check!(fs::read_dir(dir))
+        .map(|entry| check!(entry).file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn write_atomic_replaces_file() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    check!(fs::write_atomic(&path, "first"));
+    assert_eq!(check!(fs::read_to_string(&path)), "first");
+    check!(fs::write_atomic(&path, "second"));
+    assert_eq!(check!(fs::read_to_string(&path)), "second");
+    assert_eq!(file_names(tmpdir.path()), ["file"]);
+}
+
+#[test]
+#[cfg(unix)]
+fn write_atomic_preserves_permissions() {
+    use crate::os::unix::fs::PermissionsExt;
+
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    check!(fs::write(&path, "first"));
+    check!(fs::set_permissions(&path, fs::Permissions::from_mode(0o750)));
+    check!(fs::write_atomic(&path, "second"));
+    assert_eq!(check!(fs::metadata(&path)).permissions().mode() & 0o777, 0o750);
+
+    // New files are never created executable.
+    let mut file = check!(fs::AtomicWriteOptions::new().preserve_permissions(false).open(&path));
+    assert_eq!(check!(file.as_file().metadata()).permissions().mode() & 0o111, 0);
+    check!(file.write_all(b"third"));
+    check!(file.finish());
+}
+
+#[test]
+fn atomic_file_drop_discards() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    check!(fs::write(&path, "first"));
+    let mut file = check!(fs::AtomicWriteOptions::new().open(&path));
+    check!(file.write_all(b"second"));
+    assert_eq!(file_names(tmpdir.path()).len(), 2);
+    drop(file);
+    assert_eq!(check!(fs::read_to_string(&path)), "first");
+    assert_eq!(file_names(tmpdir.path()), ["file"]);
+}
+
+#[test]
+fn atomic_file_create_new() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    let mut options = fs::AtomicWriteOptions::new();
+    options.create_new(true);
+
+    let mut file = check!(options.open(&path));
+    check!(file.write_all(b"first"));
+    check!(file.finish());
+    assert_eq!(check!(fs::read_to_string(&path)), "first");
+
+    let mut file = check!(options.open(&path));
+    check!(file.write_all(b"second"));
+    assert_eq!(file.finish().unwrap_err().kind(), ErrorKind::AlreadyExists);
+    assert_eq!(check!(fs::read_to_string(&path)), "first");
+    assert_eq!(file_names(tmpdir.path()), ["file"]);
+}
+
+#[test]
+fn atomic_file_backup() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    let backup = tmpdir.join("file.bak");
+    let mut options = fs::AtomicWriteOptions::new();
+    options.backup(&backup);
+
+    // There is nothing to back up yet.
+    let mut file = check!(options.open(&path));
+    check!(file.write_all(b"first"));
+    check!(file.finish());
+    assert_eq!(file_names(tmpdir.path()), ["file"]);
+
+    for contents in ["second", "third"] {
+        let mut file = check!(options.open(&path));
+        check!(file.write_all(contents.as_bytes()));
+        check!(file.finish());
+    }
+    assert_eq!(check!(fs::read_to_string(&path)), "third");
+    assert_eq!(check!(fs::read_to_string(&backup)), "second");
+    assert_eq!(file_names(tmpdir.path()), ["file", "file.bak"]);
+}
+
+#[test]
+fn atomic_file_backup_fails() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    check!(fs::write(&path, "first"));
+    let mut options = fs::AtomicWriteOptions::new();
+    // The backup can't be moved into a directory which doesn't exist.
+    options.backup(tmpdir.join("missing").join("file.bak"));
+
+    let mut file = check!(options.open(&path));
+    check!(file.write_all(b"second"));
+    assert!(file.finish().is_err());
+    // Whether or not the file was replaced before the backup failed, its previous contents are
+    // still around.
+    let contents: Vec<_> = file_names(tmpdir.path())
+        .into_iter()
+        .map(|name| check!(fs::read_to_string(tmpdir.join(name))))
+        .collect();
+    assert!(contents.iter().any(|contents| contents == "first"), "{contents:?}");
+}
+
+#[test]
+fn atomic_file_no_file_name() {
+    let tmpdir = tmpdir();
+    let err = fs::AtomicWriteOptions::new().open(tmpdir.join("..")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
 #[test]
 fn unicode_path_is_dir() {
     assert!(Path::new(".").is_dir());
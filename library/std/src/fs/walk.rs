@@ -0,0 +1,449 @@
+//! Recursive directory traversal, see [`walk_dir`].
+
+use super::{FileType, Metadata};
+use crate::cmp::Ordering;
+use crate::ffi::OsStr;
+use crate::path::{Path, PathBuf};
+use crate::sync::{Arc, Weak};
+use crate::sys::fs::WalkDirHandle;
+use crate::{fmt, io, vec};
+
+/// Returns a builder for an iterator over the entries of a directory and, recursively, of all of
+/// its subdirectories.
+///
+/// The directory tree is walked depth-first: every directory is yielded right before its
+/// contents, starting with the directory at `path` itself at depth 0. The builder returned can
+/// limit the depth of the walk, sort the entries of each directory and choose whether symbolic
+/// links are followed; the iterator it produces can skip the contents of a directory with
+/// [`WalkDirIter::skip_current_dir`].
+///
+/// By default symbolic links are not followed: they are yielded as entries of their own, and
+/// the directories they point to are not visited. A symbolic link at `path` itself is always
+/// followed.
+///
+/// # Platform-specific behavior
+///
+/// On Unix platforms other than Redox, ESP-IDF, Horizon, Vita, QNX Neutrino, VxWorks and L4Re,
+/// every directory is opened, listed and queried through a file descriptor of its parent using
+/// `openat`, `fdopendir` and `fstatat`. This makes the walk immune to a directory being renamed
+/// or replaced with a symbolic link while it is being visited: it never escapes the tree it
+/// started in unless told to follow links. Elsewhere, entries are looked up by their full path.
+/// Note that this [may change in the future][changes].
+///
+/// [changes]: io#platform-specific-behavior
+///
+/// # Errors
+///
+/// Errors are yielded by the iterator rather than returned by this function, and don't end the
+/// walk: the iterator moves on to the next entry. An error is yielded when:
+///
+/// * The `path` doesn't exist, or the process lacks permissions to view it.
+/// * A directory can't be opened or listed. Its siblings are still visited.
+/// * The file type of an entry can't be queried. The error takes the place of the entry, and the
+///   other entries of its directory are still visited.
+/// * Following symbolic links leads to a directory which is being visited already. The error is
+///   of kind [`io::ErrorKind::FilesystemLoop`], and the directory is not visited again.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(fs_walk_dir)]
+/// use std::{fs, io};
+///
+/// fn main() -> io::Result<()> {
+///     let mut walk = fs::walk_dir("src").sort_by_file_name().into_iter();
+///     while let Some(entry) = walk.next() {
+///         let entry = entry?;
+///         if entry.file_name() == "target" {
+///             walk.skip_current_dir();
+///             continue;
+///         }
+///         println!("{}", entry.path().display());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[doc(alias = "walk", alias = "nftw", alias = "fts_read")]
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub fn walk_dir<P: AsRef<Path>>(path: P) -> WalkDir {
+    WalkDir {
+        root: path.as_ref().to_path_buf(),
+        follow_links: false,
+        min_depth: 0,
+        max_depth: usize::MAX,
+        sort: None,
+    }
+}
+
+type SortFn = dyn FnMut(&WalkDirEntry, &WalkDirEntry) -> Ordering + Send + Sync;
+
+/// A builder for a recursive walk over a directory tree.
+///
+/// This struct is created by the [`walk_dir`] function. See its documentation for more.
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub struct WalkDir {
+    root: PathBuf,
+    follow_links: bool,
+    min_depth: usize,
+    max_depth: usize,
+    sort: Option<Box<SortFn>>,
+}
+
+impl WalkDir {
+    /// Sets whether symbolic links are followed.
+    ///
+    /// When set, a symbolic link is yielded with the file type of its target, and the contents
+    /// of the directories it points to are visited. A link whose target can't be read is
+    /// yielded as a symbolic link. Links which lead back to a directory which is being visited
+    /// already produce an error instead of an endless walk.
+    ///
+    /// Defaults to `false`.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Sets the minimum depth of the entries yielded.
+    ///
+    /// Entries at a lower depth are still walked through, but not yielded. The root of the walk
+    /// has depth 0, and its entries depth 1.
+    ///
+    /// Defaults to 0.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Sets the maximum depth of the entries yielded.
+    ///
+    /// Directories at this depth are yielded, but their contents aren't visited. The root of the
+    /// walk has depth 0, so a maximum depth of 1 yields the root and its entries like
+    /// [`read_dir`](super::read_dir) does.
+    ///
+    /// Defaults to [`usize::MAX`].
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sorts the entries of every directory with `compare` before yielding them.
+    ///
+    /// Without sorting, the entries of a directory are yielded in the order the platform lists
+    /// them, which is not guaranteed.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn sort_by<F>(mut self, compare: F) -> Self
+    where
+        F: FnMut(&WalkDirEntry, &WalkDirEntry) -> Ordering + Send + Sync + 'static,
+    {
+        self.sort = Some(Box::new(compare));
+        self
+    }
+
+    /// Sorts the entries of every directory by their file name.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn sort_by_file_name(self) -> Self {
+        self.sort_by(|a, b| a.file_name().cmp(b.file_name()))
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl IntoIterator for WalkDir {
+    type Item = io::Result<WalkDirEntry>;
+    type IntoIter = WalkDirIter;
+
+    fn into_iter(self) -> WalkDirIter {
+        let WalkDir { root, follow_links, min_depth, max_depth, sort } = self;
+        WalkDirIter {
+            root: Some(root),
+            follow_links,
+            min_depth,
+            max_depth,
+            sort,
+            stack: Vec::new(),
+            next_dir: None,
+            no_contents: false,
+        }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Debug for WalkDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDir")
+            .field("root", &self.root)
+            .field("follow_links", &self.follow_links)
+            .field("min_depth", &self.min_depth)
+            .field("max_depth", &self.max_depth)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An iterator over the entries of a directory tree.
+///
+/// This struct is created by calling [`into_iter`](IntoIterator::into_iter) on a [`WalkDir`].
+/// See the documentation of [`walk_dir`] for more.
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub struct WalkDirIter {
+    /// The root of the walk, until it has been yielded.
+    root: Option<PathBuf>,
+    follow_links: bool,
+    min_depth: usize,
+    max_depth: usize,
+    sort: Option<Box<SortFn>>,
+    /// The directories being visited, from the root down to the innermost one.
+    stack: Vec<OpenDir>,
+    /// The directory walked through last, to visit once its entry has been yielded.
+    next_dir: Option<PendingDir>,
+    /// Whether the item yielded last is a directory whose contents aren't visited anyway,
+    /// because it is at the maximum depth or couldn't be opened.
+    no_contents: bool,
+}
+
+/// A directory whose entries are being yielded.
+struct OpenDir {
+    handle: Arc<WalkDirHandle>,
+    entries: vec::IntoIter<io::Result<WalkDirEntry>>,
+}
+
+/// A directory which hasn't been opened yet. Unless it is the root of the walk, it is an entry
+/// of the innermost directory being visited.
+struct PendingDir {
+    path: PathBuf,
+    depth: usize,
+}
+
+impl WalkDirIter {
+    /// Skips the contents of the current directory.
+    ///
+    /// If the entry yielded last is a directory, its contents won't be visited. Otherwise the
+    /// remaining entries of the directory containing it are skipped. If the item yielded last is
+    /// the error of a directory which couldn't be opened, nothing is skipped: there are no
+    /// contents to skip, and the siblings of that directory are still visited.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn skip_current_dir(&mut self) {
+        if self.next_dir.take().is_none() && !self.no_contents {
+            self.stack.pop();
+        }
+    }
+
+    fn root_entry(&self, path: PathBuf) -> io::Result<WalkDirEntry> {
+        let is_symlink = super::symlink_metadata(&path)?.file_type().is_symlink();
+        let file_type = super::metadata(&path)?.file_type();
+        Ok(WalkDirEntry {
+            path,
+            depth: 0,
+            file_type,
+            is_symlink,
+            follow_links: self.follow_links,
+            parent: Weak::new(),
+        })
+    }
+
+    /// Opens `dir` and pushes its entries onto the stack.
+    fn open_dir(&mut self, dir: PendingDir) -> io::Result<()> {
+        let handle = match self.stack.last() {
+            None => WalkDirHandle::open_root(&dir.path, self.follow_links)?,
+            Some(parent) => {
+                let name = dir.path.file_name().unwrap_or_default();
+                parent.handle.open_child(name, self.follow_links)?
+            }
+        };
+        if self.follow_links && self.stack.iter().any(|open| open.handle.is_same_dir(&handle)) {
+            return Err(io::const_io_error!(
+                io::ErrorKind::FilesystemLoop,
+                "symbolic link loop in directory tree",
+            ));
+        }
+        let handle = Arc::new(handle);
+        let mut entries = Vec::new();
+        // Entries whose file type can't be queried are replaced with their error, yielded
+        // before the other entries.
+        let mut errors = Vec::new();
+        for (name, file_type) in handle.entries()? {
+            let file_type = match file_type {
+                Some(file_type) => file_type,
+                None => match handle.child_metadata(&name, false) {
+                    Ok(metadata) => metadata.file_type(),
+                    // The entry has been removed since the directory was listed.
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                    Err(err) => {
+                        errors.push(Err(err));
+                        continue;
+                    }
+                },
+            };
+            let is_symlink = file_type.is_symlink();
+            // Links which can't be followed are yielded as links.
+            let file_type = if is_symlink && self.follow_links {
+                handle
+                    .child_metadata(&name, true)
+                    .map_or(file_type, |metadata| metadata.file_type())
+            } else {
+                file_type
+            };
+            entries.push(WalkDirEntry {
+                path: dir.path.join(&name),
+                depth: dir.depth + 1,
+                file_type,
+                is_symlink,
+                follow_links: self.follow_links,
+                parent: Arc::downgrade(&handle),
+            });
+        }
+        if let Some(sort) = &mut self.sort {
+            entries.sort_by(|a, b| sort(a, b));
+        }
+        errors.extend(entries.into_iter().map(Ok));
+        self.stack.push(OpenDir { handle, entries: errors.into_iter() });
+        Ok(())
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl Iterator for WalkDirIter {
+    type Item = io::Result<WalkDirEntry>;
+
+    fn next(&mut self) -> Option<io::Result<WalkDirEntry>> {
+        if let Some(root) = self.root.take() {
+            let entry = match self.root_entry(root) {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+            if entry.file_type.is_dir() && self.max_depth > 0 {
+                self.next_dir = Some(PendingDir { path: entry.path.clone(), depth: 0 });
+            }
+            if self.min_depth == 0 {
+                self.no_contents = entry.file_type.is_dir() && self.max_depth == 0;
+                return Some(Ok(entry));
+            }
+        }
+        loop {
+            if let Some(dir) = self.next_dir.take() {
+                if let Err(err) = self.open_dir(dir) {
+                    self.no_contents = true;
+                    return Some(Err(err));
+                }
+            }
+            let entry = match self.stack.last_mut()?.entries.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(err)) => {
+                    self.no_contents = false;
+                    return Some(Err(err));
+                }
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            if entry.file_type.is_dir() && entry.depth < self.max_depth {
+                self.next_dir = Some(PendingDir { path: entry.path.clone(), depth: entry.depth });
+            }
+            if entry.depth >= self.min_depth {
+                self.no_contents = entry.file_type.is_dir() && entry.depth >= self.max_depth;
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Debug for WalkDirIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDirIter")
+            .field("follow_links", &self.follow_links)
+            .field("min_depth", &self.min_depth)
+            .field("max_depth", &self.max_depth)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An entry yielded by a [`WalkDirIter`].
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+pub struct WalkDirEntry {
+    path: PathBuf,
+    depth: usize,
+    file_type: FileType,
+    is_symlink: bool,
+    follow_links: bool,
+    /// The directory containing this entry, while it is being visited. Entries don't keep their
+    /// directory open, as collecting them could exhaust the available file descriptors.
+    parent: Weak<WalkDirHandle>,
+}
+
+impl WalkDirEntry {
+    /// Returns the path to this entry: the path the walk started from, joined with the names
+    /// of the directories leading to this entry and with its own name.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path to this entry, consuming it.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn into_path(self) -> PathBuf {
+        self.path
+    }
+
+    /// Returns the file name of this entry.
+    ///
+    /// For the root of the walk, this is the last component of its path, or the whole path if
+    /// it doesn't end with a normal component, like `.` or `/`.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn file_name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+
+    /// Returns the depth of this entry, which is 0 for the root of the walk, 1 for its entries,
+    /// and so on.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the file type of this entry.
+    ///
+    /// When following symbolic links, this is the file type of the target of a link.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns whether this entry is a symbolic link, whether or not it was followed.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn path_is_symlink(&self) -> bool {
+        self.is_symlink
+    }
+
+    /// Queries the metadata of this entry.
+    ///
+    /// When following symbolic links, this is the metadata of the target of a link. While the
+    /// walk is still visiting the directory containing the entry, the metadata is looked up
+    /// relative to that directory, in the same way as the rest of the walk. Otherwise it is
+    /// looked up by path.
+    #[unstable(feature = "fs_walk_dir", issue = "none")]
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        if self.depth == 0 {
+            return super::metadata(&self.path);
+        }
+        let follow_links = self.follow_links && !self.file_type.is_symlink();
+        match self.parent.upgrade() {
+            Some(parent) => parent.child_metadata(self.file_name(), follow_links),
+            None if follow_links => super::metadata(&self.path),
+            None => super::symlink_metadata(&self.path),
+        }
+    }
+}
+
+#[unstable(feature = "fs_walk_dir", issue = "none")]
+impl fmt::Debug for WalkDirEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDirEntry")
+            .field("path", &self.path)
+            .field("depth", &self.depth)
+            .field("file_type", &self.file_type)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,162 @@
+//! Process-level capture of the standard output and error streams.
+
+#[cfg(test)]
+mod tests;
+
+use crate::fmt;
+use crate::fs::File;
+use crate::io::{self, Read, Write};
+use crate::sys::stdio_redirect as imp;
+use crate::thread::{self, JoinHandle};
+
+/// A guard that redirects the standard output or standard error stream of the
+/// whole process while it is alive.
+///
+/// Unlike the thread-local capture used by the test harness, which only sees
+/// text written through [`print!`] and [`eprint!`] on the capturing thread, an
+/// `OutputCapture` replaces the underlying file descriptor (using `dup2` on
+/// Unix) or standard handle (using `SetStdHandle` on Windows). It therefore
+/// also sees output from other threads, from [`io::stdout`] and
+/// [`io::stderr`], and from foreign code that writes to the stream directly.
+///
+/// The stream is restored when the capture is [finished](OutputCapture::finish)
+/// or dropped.
+///
+/// # Caveats
+///
+/// * The redirection is global. Captures of the same stream nest, but must be
+///   finished or dropped in the reverse order in which they were created.
+/// * Text written through [`print!`] while the test harness captures output
+///   on the current thread goes to the harness, not to this capture.
+/// * On Windows only the standard handle is replaced: output written through
+///   the file descriptors of the C runtime is not captured.
+/// * A child process spawned during the capture inherits the redirected
+///   stream. When capturing into memory, [`finish`](OutputCapture::finish)
+///   waits until every such child has closed it.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(stdio_capture)]
+/// use std::io::OutputCapture;
+///
+/// let capture = OutputCapture::stdout()?;
+/// std::thread::spawn(|| println!("hello from another thread")).join().unwrap();
+/// let output = capture.finish()?;
+/// assert_eq!(output, b"hello from another thread\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[unstable(feature = "stdio_capture", issue = "none")]
+pub struct OutputCapture {
+    stderr: bool,
+    redirect: Option<imp::Redirect>,
+    reader: Option<JoinHandle<io::Result<Vec<u8>>>>,
+}
+
+impl OutputCapture {
+    /// Starts capturing the standard output of the process into memory.
+    ///
+    /// The captured bytes are returned by [`finish`](OutputCapture::finish).
+    #[unstable(feature = "stdio_capture", issue = "none")]
+    pub fn stdout() -> io::Result<OutputCapture> {
+        OutputCapture::to_memory(false)
+    }
+
+    /// Starts capturing the standard error of the process into memory.
+    ///
+    /// The captured bytes are returned by [`finish`](OutputCapture::finish).
+    #[unstable(feature = "stdio_capture", issue = "none")]
+    pub fn stderr() -> io::Result<OutputCapture> {
+        OutputCapture::to_memory(true)
+    }
+
+    /// Starts redirecting the standard output of the process into `file`.
+    ///
+    /// The stream is redirected to a duplicate of the file descriptor or
+    /// handle of `file`, which is closed when the stream is restored. The file
+    /// itself does not need to outlive the capture.
+    #[unstable(feature = "stdio_capture", issue = "none")]
+    pub fn stdout_to_file(file: &File) -> io::Result<OutputCapture> {
+        OutputCapture::to_file(false, file)
+    }
+
+    /// Starts redirecting the standard error of the process into `file`.
+    ///
+    /// The stream is redirected to a duplicate of the file descriptor or
+    /// handle of `file`, which is closed when the stream is restored. The file
+    /// itself does not need to outlive the capture.
+    #[unstable(feature = "stdio_capture", issue = "none")]
+    pub fn stderr_to_file(file: &File) -> io::Result<OutputCapture> {
+        OutputCapture::to_file(true, file)
+    }
+
+    fn to_file(stderr: bool, file: &File) -> io::Result<OutputCapture> {
+        flush(stderr);
+        let redirect = imp::Redirect::new(stderr, file)?;
+        Ok(OutputCapture { stderr, redirect: Some(redirect), reader: None })
+    }
+
+    fn to_memory(stderr: bool) -> io::Result<OutputCapture> {
+        let (mut pipe, writer) = imp::pipe()?;
+        let reader = thread::Builder::new().name("output capture".to_owned()).spawn(move || {
+            let mut buf = Vec::new();
+            pipe.read_to_end(&mut buf)?;
+            Ok(buf)
+        })?;
+        // The redirected stream uses its own duplicate of the write end, so
+        // ours is closed when `writer` goes out of scope and the reader sees
+        // the end of the stream once the redirection is undone.
+        let mut capture = OutputCapture::to_file(stderr, &writer)?;
+        capture.reader = Some(reader);
+        Ok(capture)
+    }
+
+    /// Restores the captured stream and returns everything written to it
+    /// since the capture started.
+    ///
+    /// Buffered data in [`io::stdout`] is flushed first, so it ends up in the
+    /// capture. When capturing into a file the returned vector is empty.
+    #[unstable(feature = "stdio_capture", issue = "none")]
+    pub fn finish(mut self) -> io::Result<Vec<u8>> {
+        self.restore()?;
+        match self.reader.take() {
+            Some(reader) => reader.join().unwrap_or_else(|e| crate::panic::resume_unwind(e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        match self.redirect.take() {
+            Some(redirect) => {
+                flush(self.stderr);
+                redirect.restore()
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Flushes the buffer in front of the stream about to be redirected, so that
+/// data written before the switch ends up on the right side of it.
+fn flush(stderr: bool) {
+    let _ = if stderr { io::stderr().flush() } else { io::stdout().flush() };
+}
+
+#[unstable(feature = "stdio_capture", issue = "none")]
+impl Drop for OutputCapture {
+    fn drop(&mut self) {
+        // A reader thread that is still running exits on its own once all
+        // copies of the write end are closed.
+        let _ = self.restore();
+    }
+}
+
+#[unstable(feature = "stdio_capture", issue = "none")]
+impl fmt::Debug for OutputCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutputCapture")
+            .field("stream", &if self.stderr { "stderr" } else { "stdout" })
+            .field("in_memory", &self.reader.is_some())
+            .finish_non_exhaustive()
+    }
+}
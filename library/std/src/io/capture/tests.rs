@@ -0,0 +1,89 @@
+use super::OutputCapture;
+use crate::fs::{self, File};
+use crate::io::Write;
+use crate::sync::{Mutex, MutexGuard, PoisonError};
+use crate::sys_common::io::test::tmpdir;
+use crate::thread;
+
+// Other tests may write to the process streams concurrently, so these only
+// check that our own output shows up in the capture.
+
+/// Captures must be finished in the reverse order they were started in, which
+/// tests running in parallel wouldn't do, so they take turns.
+fn lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[test]
+#[cfg_attr(any(miri, not(any(unix, windows))), ignore)]
+fn capture_stdout_from_other_thread() {
+    let _lock = lock();
+    let capture = OutputCapture::stdout().unwrap();
+    thread::spawn(|| {
+        let mut out = crate::io::stdout();
+        out.write_all(b"<captured from another thread>\n").unwrap();
+        out.flush().unwrap();
+    })
+    .join()
+    .unwrap();
+    let output = String::from_utf8_lossy(&capture.finish().unwrap()).into_owned();
+    assert!(output.contains("<captured from another thread>"), "{output:?}");
+}
+
+#[test]
+#[cfg_attr(any(miri, not(any(unix, windows))), ignore)]
+fn capture_stderr() {
+    let _lock = lock();
+    let capture = OutputCapture::stderr().unwrap();
+    crate::io::stderr().write_all(b"<captured stderr>\n").unwrap();
+    let output = String::from_utf8_lossy(&capture.finish().unwrap()).into_owned();
+    assert!(output.contains("<captured stderr>"), "{output:?}");
+}
+
+#[test]
+#[cfg_attr(any(miri, not(any(unix, windows))), ignore)]
+fn capture_stdout_to_file() {
+    let _lock = lock();
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("stdout.txt");
+    let file = File::create(&path).unwrap();
+    let capture = OutputCapture::stdout_to_file(&file).unwrap();
+    drop(file);
+    // Buffered output is flushed when the capture finishes.
+    write!(crate::io::stdout(), "<captured into a file>").unwrap();
+    assert!(capture.finish().unwrap().is_empty());
+    let output = fs::read_to_string(&path).unwrap();
+    assert!(output.contains("<captured into a file>"), "{output:?}");
+}
+
+#[test]
+#[cfg_attr(any(miri, not(any(unix, windows))), ignore)]
+fn drop_restores_stream() {
+    let _lock = lock();
+    let outer = OutputCapture::stdout().unwrap();
+    drop(OutputCapture::stdout().unwrap());
+    crate::io::stdout().write_all(b"<after inner capture>\n").unwrap();
+    let output = String::from_utf8_lossy(&outer.finish().unwrap()).into_owned();
+    assert!(output.contains("<after inner capture>"), "{output:?}");
+}
+
+#[test]
+#[cfg(windows)]
+#[cfg_attr(miri, ignore)]
+fn capture_stderr_to_closed_file() {
+    let _lock = lock();
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("stderr.txt");
+    let file = File::create(&path).unwrap();
+    let capture = OutputCapture::stderr_to_file(&file).unwrap();
+    // The standard handle is a duplicate of the handle of the file, so it stays valid after the
+    // file is closed, until the capture finishes.
+    drop(file);
+    crate::io::stderr().write_all(b"<written after closing the file>\n").unwrap();
+    assert!(capture.finish().unwrap().is_empty());
+    crate::io::stderr().write_all(b"<written after the capture>\n").unwrap();
+    let output = fs::read_to_string(&path).unwrap();
+    assert!(output.contains("<written after closing the file>"), "{output:?}");
+    assert!(!output.contains("<written after the capture>"), "{output:?}");
+}
@@ -305,6 +305,8 @@
 
 #[stable(feature = "bufwriter_into_parts", since = "1.56.0")]
 pub use self::buffered::WriterPanicked;
+#[unstable(feature = "stdio_capture", issue = "none")]
+pub use self::capture::OutputCapture;
 #[unstable(feature = "raw_os_error_ty", issue = "107792")]
 pub use self::error::RawOsError;
 #[stable(feature = "is_terminal", since = "1.70.0")]
@@ -330,6 +332,7 @@
 use crate::{cmp, fmt, slice, str, sys};
 
 mod buffered;
+mod capture;
 pub(crate) mod copy;
 mod cursor;
 mod error;
@@ -268,7 +268,10 @@
 //
 // Features:
 #![cfg_attr(not(bootstrap), feature(autodiff))]
-#![cfg_attr(test, feature(internal_output_capture, print_internals, update_panic_count, rt))]
+#![cfg_attr(
+    test,
+    feature(internal_output_capture, print_internals, update_panic_count, rt, mpmc_select)
+)]
 #![cfg_attr(
     all(target_vendor = "fortanix", target_env = "sgx"),
     feature(slice_index_methods, coerce_unsized, sgx_platform)
@@ -322,7 +325,6 @@
 #![feature(stmt_expr_attributes)]
 #![feature(thread_local)]
 #![feature(try_blocks)]
-#![feature(type_alias_impl_trait)]
 // tidy-alphabetical-end
 //
 // Library features (core):
@@ -7,6 +7,8 @@
 //!
 //! * [`TcpListener`] and [`TcpStream`] provide functionality for communication over TCP
 //! * [`UdpSocket`] provides functionality for communication over UDP
+//! * [`TcpSocket`] and [`UdpSocketBuilder`] allow setting socket options before a socket is
+//!   bound or connected
 //! * [`IpAddr`] represents IP addresses of either IPv4 or IPv6; [`Ipv4Addr`] and
 //!   [`Ipv6Addr`] are respectively IPv4 and IPv6 addresses
 //! * [`SocketAddr`] represents socket addresses of either IPv4 or IPv6; [`SocketAddrV4`]
@@ -26,6 +28,8 @@
 
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::ip_addr::{IpAddr, Ipv4Addr, Ipv6Addr, Ipv6MulticastScope};
+#[unstable(feature = "net_socket_builder", issue = "none")]
+pub use self::socket::{TcpSocket, UdpSocketBuilder};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::socket_addr::{SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
 #[unstable(feature = "tcplistener_into_incoming", issue = "88373")]
@@ -37,6 +41,7 @@
 use crate::io::{self, ErrorKind};
 
 mod ip_addr;
+mod socket;
 mod socket_addr;
 mod tcp;
 #[cfg(test)]
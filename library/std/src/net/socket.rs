@@ -0,0 +1,455 @@
+//! Sockets which are configured before they are bound or connected, see [`TcpSocket`] and
+//! [`UdpSocketBuilder`].
+
+#[cfg(all(
+    test,
+    not(any(
+        target_os = "emscripten",
+        all(target_os = "wasi", target_env = "p1"),
+        target_os = "xous"
+    ))
+))]
+mod tests;
+
+use crate::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use crate::sys_common::{FromInner, net as net_imp};
+use crate::time::Duration;
+use crate::{fmt, io};
+
+/// A TCP socket which has not been connected or turned into a listener yet.
+///
+/// Some socket options only have an effect, or can only be set, before the socket is bound to
+/// an address: whether the address may be reused, the size of the buffers, or whether an IPv6
+/// socket also accepts IPv4 traffic. A `TcpSocket` is created unbound so that these options can
+/// be set first, and is then turned into a [`TcpListener`] with [`listen`] or into a
+/// [`TcpStream`] with [`connect`]. Binding it before connecting selects the local address of
+/// the connection.
+///
+/// Unlike [`TcpListener::bind`], no option is set on the socket unless it is asked for. In
+/// particular `SO_REUSEADDR` is off until [`set_reuseaddr`] is called.
+///
+/// The socket is closed when the value is dropped.
+///
+/// [`listen`]: TcpSocket::listen
+/// [`connect`]: TcpSocket::connect
+/// [`set_reuseaddr`]: TcpSocket::set_reuseaddr
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(net_socket_builder)]
+/// use std::net::TcpSocket;
+///
+/// fn main() -> std::io::Result<()> {
+///     let socket = TcpSocket::new_v6()?;
+///     socket.set_only_v6(false)?;
+///     socket.set_reuseaddr(true)?;
+///     socket.bind("[::]:8080".parse().unwrap())?;
+///     let listener = socket.listen(1024)?;
+///
+///     for stream in listener.incoming() {
+///         let _stream = stream?;
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "net_socket_builder", issue = "none")]
+pub struct TcpSocket(net_imp::TcpSocket);
+
+impl TcpSocket {
+    /// Creates a new IPv4 TCP socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn new_v4() -> io::Result<TcpSocket> {
+        net_imp::TcpSocket::new(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))).map(TcpSocket)
+    }
+
+    /// Creates a new IPv6 TCP socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn new_v6() -> io::Result<TcpSocket> {
+        net_imp::TcpSocket::new(&SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))).map(TcpSocket)
+    }
+
+    /// Binds the socket to the local address `addr`.
+    ///
+    /// A port of 0 requests that the OS assigns a port to the socket, which can be queried
+    /// with [`TcpSocket::local_addr`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `addr` is not of the address family of the
+    /// socket, or if it is not available.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<()> {
+        self.0.bind(&addr)
+    }
+
+    /// Opens a TCP connection to the remote host at `addr`.
+    ///
+    /// If the socket has not been bound, the OS picks the local address of the connection.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn connect(self, addr: SocketAddr) -> io::Result<TcpStream> {
+        self.0.connect(&addr).map(TcpStream::from_inner)
+    }
+
+    /// Opens a TCP connection to the remote host at `addr`, with a timeout.
+    ///
+    /// See [`TcpStream::connect_timeout`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn connect_timeout(self, addr: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+        self.0.connect_timeout(&addr, timeout).map(TcpStream::from_inner)
+    }
+
+    /// Starts listening for incoming connections, turning the socket into a [`TcpListener`].
+    ///
+    /// `backlog` is the maximum number of pending connections which have not been accepted
+    /// yet. The OS may cap it, or treat it as a hint. The socket should be bound first: the OS
+    /// picks an address otherwise.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        self.0.listen(backlog).map(TcpListener::from_inner)
+    }
+
+    /// Returns the local address that this socket is bound to.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.socket_addr()
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// On Unix, this allows binding to an address which is still held by a previous socket in
+    /// the `TIME_WAIT` state, so that a server can be restarted right away.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Windows, this allows binding to an address which is in active use by another socket,
+    /// including one belonging to another process.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.0.set_reuseaddr(reuseaddr)
+    }
+
+    /// Gets the value of the `SO_REUSEADDR` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.0.reuseaddr()
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// This allows several sockets to be bound to the same address, if all of them set the
+    /// option. On Linux, incoming connections are then spread between the listeners.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This option is supported on Linux, Android, Fuchsia, the BSDs and Apple platforms. An
+    /// error of kind [`io::ErrorKind::Unsupported`] is returned elsewhere.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        self.0.set_reuseport(reuseport)
+    }
+
+    /// Gets the value of the `SO_REUSEPORT` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.0.reuseport()
+    }
+
+    /// Sets the value of the `IPV6_V6ONLY` option on this socket.
+    ///
+    /// If this is set to `true` then the socket is restricted to sending and receiving IPv6
+    /// packets only. Otherwise, an IPv6 socket bound to the unspecified address also accepts
+    /// IPv4 traffic. The default depends on the platform.
+    ///
+    /// This option can only be set on IPv6 sockets, before they are bound.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.0.set_only_v6(only_v6)
+    }
+
+    /// Gets the value of the `IPV6_V6ONLY` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.0.only_v6()
+    }
+
+    /// Sets the size of the send buffer of this socket, the `SO_SNDBUF` option.
+    ///
+    /// The OS may round the size or cap it. On Linux, the size reported by
+    /// [`send_buffer_size`](TcpSocket::send_buffer_size) is double the size which was set, to
+    /// account for bookkeeping overhead.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.0.set_send_buffer_size(size)
+    }
+
+    /// Gets the size of the send buffer of this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.0.send_buffer_size()
+    }
+
+    /// Sets the size of the receive buffer of this socket, the `SO_RCVBUF` option.
+    ///
+    /// The size of the receive buffer bounds the TCP window advertised to the peer, and should
+    /// be set before connecting or listening to take effect. See
+    /// [`set_send_buffer_size`](TcpSocket::set_send_buffer_size) for how the OS treats the size.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.0.set_recv_buffer_size(size)
+    }
+
+    /// Gets the size of the receive buffer of this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.0.recv_buffer_size()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// See [`TcpStream::set_nodelay`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.0.set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.0.nodelay()
+    }
+
+    /// Sets the value of the `SO_KEEPALIVE` option on this socket.
+    ///
+    /// With keepalive enabled, probes are sent over a connection which has been idle for a
+    /// while, and the connection is closed if the peer stops answering them. The timings of the
+    /// probes are set with [`set_keepalive_time`], [`set_keepalive_interval`] and
+    /// [`set_keepalive_retries`], or left to the OS defaults.
+    ///
+    /// On most platforms, the connections accepted by a listener inherit this option.
+    ///
+    /// [`set_keepalive_time`]: TcpSocket::set_keepalive_time
+    /// [`set_keepalive_interval`]: TcpSocket::set_keepalive_interval
+    /// [`set_keepalive_retries`]: TcpSocket::set_keepalive_retries
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.0.set_keepalive(keepalive)
+    }
+
+    /// Gets the value of the `SO_KEEPALIVE` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn keepalive(&self) -> io::Result<bool> {
+        self.0.keepalive()
+    }
+
+    /// Sets how long a connection stays idle before the first keepalive probe is sent, the
+    /// `TCP_KEEPIDLE` option.
+    ///
+    /// The time is rounded up to whole seconds.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// The keepalive timings are supported on Linux, Android, Fuchsia, FreeBSD, NetBSD,
+    /// DragonFly BSD, Apple platforms and Windows 10 version 1709 and later. An error of kind
+    /// [`io::ErrorKind::Unsupported`] is returned on other platforms.
+    ///
+    /// # Errors
+    ///
+    /// An error of kind [`io::ErrorKind::InvalidInput`] is returned if `time` is zero.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_keepalive_time(&self, time: Duration) -> io::Result<()> {
+        self.0.set_keepalive_time(time)
+    }
+
+    /// Gets how long a connection stays idle before the first keepalive probe is sent.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn keepalive_time(&self) -> io::Result<Duration> {
+        self.0.keepalive_time()
+    }
+
+    /// Sets the time between two keepalive probes, the `TCP_KEEPINTVL` option.
+    ///
+    /// The interval is rounded up to whole seconds. See
+    /// [`set_keepalive_time`](TcpSocket::set_keepalive_time) for the supported platforms.
+    ///
+    /// # Errors
+    ///
+    /// An error of kind [`io::ErrorKind::InvalidInput`] is returned if `interval` is zero.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_keepalive_interval(&self, interval: Duration) -> io::Result<()> {
+        self.0.set_keepalive_interval(interval)
+    }
+
+    /// Gets the time between two keepalive probes.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        self.0.keepalive_interval()
+    }
+
+    /// Sets how many keepalive probes may go unanswered before the connection is closed, the
+    /// `TCP_KEEPCNT` option.
+    ///
+    /// See [`set_keepalive_time`](TcpSocket::set_keepalive_time) for the supported platforms.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_keepalive_retries(&self, retries: u32) -> io::Result<()> {
+        self.0.set_keepalive_retries(retries)
+    }
+
+    /// Gets how many keepalive probes may go unanswered before the connection is closed.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        self.0.keepalive_retries()
+    }
+}
+
+#[unstable(feature = "net_socket_builder", issue = "none")]
+impl fmt::Debug for TcpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A UDP socket which has not been bound yet.
+///
+/// Like [`TcpSocket`], this allows setting the options which only have an effect before the
+/// socket is bound, and is then turned into a [`UdpSocket`] with [`bind`].
+///
+/// [`bind`]: UdpSocketBuilder::bind
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(net_socket_builder)]
+/// use std::net::UdpSocketBuilder;
+///
+/// fn main() -> std::io::Result<()> {
+///     let builder = UdpSocketBuilder::new_v4()?;
+///     builder.set_reuseaddr(true)?;
+///     builder.set_recv_buffer_size(1 << 20)?;
+///     let socket = builder.bind("0.0.0.0:5353".parse().unwrap())?;
+///
+///     let mut buf = [0; 1500];
+///     let (len, src) = socket.recv_from(&mut buf)?;
+///     socket.send_to(&buf[..len], src)?;
+///     Ok(())
+/// }
+/// ```
+#[unstable(feature = "net_socket_builder", issue = "none")]
+pub struct UdpSocketBuilder(net_imp::UdpSocketBuilder);
+
+impl UdpSocketBuilder {
+    /// Creates a new IPv4 UDP socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn new_v4() -> io::Result<UdpSocketBuilder> {
+        net_imp::UdpSocketBuilder::new(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+            .map(UdpSocketBuilder)
+    }
+
+    /// Creates a new IPv6 UDP socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn new_v6() -> io::Result<UdpSocketBuilder> {
+        net_imp::UdpSocketBuilder::new(&SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)))
+            .map(UdpSocketBuilder)
+    }
+
+    /// Binds the socket to the local address `addr`, turning it into a [`UdpSocket`].
+    ///
+    /// A port of 0 requests that the OS assigns a port to the socket, which can be queried
+    /// with [`UdpSocket::local_addr`].
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn bind(self, addr: SocketAddr) -> io::Result<UdpSocket> {
+        self.0.bind(&addr).map(UdpSocket::from_inner)
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// See [`TcpSocket::set_reuseaddr`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.0.set_reuseaddr(reuseaddr)
+    }
+
+    /// Gets the value of the `SO_REUSEADDR` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.0.reuseaddr()
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// See [`TcpSocket::set_reuseport`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        self.0.set_reuseport(reuseport)
+    }
+
+    /// Gets the value of the `SO_REUSEPORT` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.0.reuseport()
+    }
+
+    /// Sets the value of the `IPV6_V6ONLY` option on this socket.
+    ///
+    /// See [`TcpSocket::set_only_v6`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.0.set_only_v6(only_v6)
+    }
+
+    /// Gets the value of the `IPV6_V6ONLY` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.0.only_v6()
+    }
+
+    /// Sets the size of the send buffer of this socket, the `SO_SNDBUF` option.
+    ///
+    /// See [`TcpSocket::set_send_buffer_size`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.0.set_send_buffer_size(size)
+    }
+
+    /// Gets the size of the send buffer of this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.0.send_buffer_size()
+    }
+
+    /// Sets the size of the receive buffer of this socket, the `SO_RCVBUF` option.
+    ///
+    /// Datagrams which arrive while the receive buffer is full are dropped.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.0.set_recv_buffer_size(size)
+    }
+
+    /// Gets the size of the receive buffer of this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.0.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option on this socket.
+    ///
+    /// See [`UdpSocket::set_broadcast`] for more.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.0.set_broadcast(broadcast)
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option on this socket.
+    #[unstable(feature = "net_socket_builder", issue = "none")]
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.0.broadcast()
+    }
+}
+
+#[unstable(feature = "net_socket_builder", issue = "none")]
+impl fmt::Debug for UdpSocketBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
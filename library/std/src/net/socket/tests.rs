@@ -0,0 +1,118 @@
+use crate::io::prelude::*;
+use crate::net::test::{next_test_ip4, next_test_ip6, sa4};
+use crate::net::*;
+use crate::time::Duration;
+
+macro_rules! t {
+    ($e:expr) => {
+        match $e {
+            Ok(t) => t,
+            Err(e) => panic!("received error for `{}`: {}", stringify!($e), e),
+        }
+    };
+}
+
+#[test]
+fn listen_and_connect() {
+    let addr = next_test_ip4();
+    let socket = t!(TcpSocket::new_v4());
+    t!(socket.set_reuseaddr(true));
+    assert!(t!(socket.reuseaddr()));
+    t!(socket.bind(addr));
+    assert_eq!(t!(socket.local_addr()), addr);
+    let listener = t!(socket.listen(16));
+
+    let mut stream = t!(t!(TcpSocket::new_v4()).connect(addr));
+    t!(stream.write_all(b"hello"));
+    let (mut accepted, peer) = t!(listener.accept());
+    assert_eq!(peer, t!(stream.local_addr()));
+    let mut buf = [0; 5];
+    t!(accepted.read_exact(&mut buf));
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn bind_before_connect() {
+    let listener = t!(TcpListener::bind(next_test_ip4()));
+    let socket = t!(TcpSocket::new_v4());
+    t!(socket.bind(sa4(Ipv4Addr::LOCALHOST, 0)));
+    let local = t!(socket.local_addr());
+    assert_ne!(local.port(), 0);
+
+    let stream = t!(socket.connect_timeout(t!(listener.local_addr()), Duration::from_secs(10)));
+    assert_eq!(t!(stream.local_addr()), local);
+    let (_, peer) = t!(listener.accept());
+    assert_eq!(peer, local);
+}
+
+#[test]
+fn only_v6() {
+    let socket = t!(TcpSocket::new_v6());
+    t!(socket.set_only_v6(true));
+    assert!(t!(socket.only_v6()));
+    t!(socket.bind(next_test_ip6()));
+    t!(socket.listen(1));
+}
+
+#[test]
+fn buffer_sizes() {
+    let socket = t!(TcpSocket::new_v4());
+    t!(socket.set_send_buffer_size(32 * 1024));
+    t!(socket.set_recv_buffer_size(32 * 1024));
+    assert!(t!(socket.send_buffer_size()) >= 32 * 1024);
+    assert!(t!(socket.recv_buffer_size()) >= 32 * 1024);
+}
+
+#[test]
+fn keepalive() {
+    let socket = t!(TcpSocket::new_v4());
+    t!(socket.set_keepalive(true));
+    assert!(t!(socket.keepalive()));
+    t!(socket.set_keepalive(false));
+    assert!(!t!(socket.keepalive()));
+
+    let err = socket.set_keepalive_time(Duration::ZERO).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+fn keepalive_timings() {
+    let socket = t!(TcpSocket::new_v4());
+    t!(socket.set_keepalive_time(Duration::from_secs(60)));
+    assert_eq!(t!(socket.keepalive_time()), Duration::from_secs(60));
+    // Rounded up to whole seconds.
+    t!(socket.set_keepalive_interval(Duration::from_millis(1500)));
+    assert_eq!(t!(socket.keepalive_interval()), Duration::from_secs(2));
+    t!(socket.set_keepalive_retries(5));
+    assert_eq!(t!(socket.keepalive_retries()), 5);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+fn reuseport() {
+    let addr = next_test_ip4();
+    let sockets = [(); 2].map(|()| {
+        let builder = t!(UdpSocketBuilder::new_v4());
+        t!(builder.set_reuseport(true));
+        assert!(t!(builder.reuseport()));
+        t!(builder.bind(addr))
+    });
+    assert_eq!(t!(sockets[0].local_addr()), t!(sockets[1].local_addr()));
+}
+
+#[test]
+fn udp_builder() {
+    let builder = t!(UdpSocketBuilder::new_v4());
+    t!(builder.set_broadcast(true));
+    assert!(t!(builder.broadcast()));
+    t!(builder.set_recv_buffer_size(32 * 1024));
+    let server = t!(builder.bind(next_test_ip4()));
+    assert!(t!(server.broadcast()));
+
+    let client = t!(t!(UdpSocketBuilder::new_v4()).bind(next_test_ip4()));
+    t!(client.send_to(&[1, 2, 3], t!(server.local_addr())));
+    let mut buf = [0; 3];
+    let (len, src) = t!(server.recv_from(&mut buf));
+    assert_eq!((len, src, buf), (3, t!(client.local_addr()), [1, 2, 3]));
+}
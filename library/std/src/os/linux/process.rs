@@ -11,6 +11,7 @@
 #[cfg(not(doc))]
 use crate::sys::{fd::FileDesc, linux::pidfd::PidFd as InnerPidFd};
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::time::Duration;
 
 #[cfg(doc)]
 struct InnerPidFd;
@@ -84,6 +85,15 @@ pub fn wait(&self) -> Result<ExitStatus> {
     pub fn try_wait(&self) -> Result<Option<ExitStatus>> {
         Ok(self.inner.try_wait()?.map(FromInner::from_inner))
     }
+
+    /// Waits for the child to exit for at most `timeout`, returning the status that it exited
+    /// with, or `None` if it's still running.
+    ///
+    /// Like [`try_wait`](PidFd::try_wait), this method will return an Error
+    /// if the child has already been reaped.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<Option<ExitStatus>> {
+        Ok(self.inner.wait_timeout(timeout)?.map(FromInner::from_inner))
+    }
 }
 
 impl AsInner<InnerPidFd> for PidFd {
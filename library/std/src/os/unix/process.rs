@@ -197,6 +197,35 @@ fn arg0<S>(&mut self, arg: S) -> &mut process::Command
     /// ```
     #[stable(feature = "process_set_process_group", since = "1.64.0")]
     fn process_group(&mut self, pgroup: i32) -> &mut process::Command;
+
+    /// Sets whether the child process is started in a new session. Equivalent
+    /// to a `setsid` call in the child process.
+    ///
+    /// The child becomes the leader of the new session, and of a new process
+    /// group whose ID is its process ID. It has no controlling terminal, so it
+    /// doesn't receive signals from the terminal of its parent.
+    ///
+    /// Spawning the child fails if this is combined with
+    /// [`process_group`](CommandExt::process_group), since the leader of a
+    /// session can't change its process group.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_setsid)]
+    /// use std::process::Command;
+    /// use std::os::unix::process::CommandExt;
+    ///
+    /// Command::new("sleep")
+    ///     .arg("10")
+    ///     .setsid(true)
+    ///     .spawn()?
+    ///     .wait()?;
+    /// #
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[unstable(feature = "process_setsid", issue = "none")]
+    fn setsid(&mut self, setsid: bool) -> &mut process::Command;
 }
 
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -242,6 +271,89 @@ fn process_group(&mut self, pgroup: i32) -> &mut process::Command {
         self.as_inner_mut().pgroup(pgroup);
         self
     }
+
+    fn setsid(&mut self, setsid: bool) -> &mut process::Command {
+        self.as_inner_mut().setsid(setsid);
+        self
+    }
+}
+
+/// Unix-specific extensions to [`process::Child`].
+///
+/// This trait is sealed: it cannot be implemented outside the standard library.
+/// This is so that future additional methods are not breaking changes.
+#[unstable(feature = "unix_child_signal", issue = "none")]
+pub trait ChildExt: Sealed {
+    /// Sends the signal `signal` to the child process.
+    ///
+    /// Like [`Child::kill`], this does nothing if the child has already been
+    /// waited on, since its process ID may have been reused.
+    ///
+    /// [`Child::kill`]: process::Child::kill
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_child_signal)]
+    /// use std::process::Command;
+    /// use std::os::unix::process::ChildExt;
+    ///
+    /// let mut child = Command::new("sleep").arg("10").spawn()?;
+    /// child.send_signal(15)?; // SIGTERM
+    /// child.wait()?;
+    /// #
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[unstable(feature = "unix_child_signal", issue = "none")]
+    fn send_signal(&self, signal: i32) -> io::Result<()>;
+
+    /// Sends the signal `signal` to every process in the process group led by
+    /// the child. Equivalent to a `killpg` call.
+    ///
+    /// The child must have been made the leader of its own process group, with
+    /// [`process_group(0)`] or [`setsid`]. Otherwise, an error is returned, or
+    /// the signal is sent to an unrelated process group.
+    ///
+    /// Processes in the group can outlive the child, so the signal is sent even
+    /// if the child has already been waited on. An error of kind
+    /// [`NotFound`] is returned if no process is left in the group.
+    ///
+    /// [`process_group(0)`]: CommandExt::process_group
+    /// [`setsid`]: CommandExt::setsid
+    /// [`NotFound`]: io::ErrorKind::NotFound
+    ///
+    /// # Examples
+    ///
+    /// Killing a shell along with all the processes it started:
+    ///
+    /// ```no_run
+    /// #![feature(unix_child_signal)]
+    /// use std::process::Command;
+    /// use std::os::unix::process::{ChildExt, CommandExt};
+    ///
+    /// let mut child = Command::new("sh")
+    ///     .arg("-c")
+    ///     .arg("sleep 100 & sleep 100")
+    ///     .process_group(0)
+    ///     .spawn()?;
+    /// child.send_signal_group(9)?; // SIGKILL
+    /// child.wait()?;
+    /// #
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[unstable(feature = "unix_child_signal", issue = "none")]
+    fn send_signal_group(&self, signal: i32) -> io::Result<()>;
+}
+
+#[unstable(feature = "unix_child_signal", issue = "none")]
+impl ChildExt for process::Child {
+    fn send_signal(&self, signal: i32) -> io::Result<()> {
+        self.as_inner().send_signal(signal)
+    }
+
+    fn send_signal_group(&self, signal: i32) -> io::Result<()> {
+        self.as_inner().send_signal_group(signal)
+    }
 }
 
 /// Unix-specific extensions to [`process::ExitStatus`] and
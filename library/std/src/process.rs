@@ -148,6 +148,7 @@
 #![stable(feature = "process", since = "1.0.0")]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+mod pipeline;
 #[cfg(all(
     test,
     not(any(
@@ -159,6 +160,8 @@
 ))]
 mod tests;
 
+#[unstable(feature = "process_pipeline", issue = "none")]
+pub use self::pipeline::{Pipeline, PipelineChild, PipelineOutput, PipelineStatus};
 use crate::convert::Infallible;
 use crate::ffi::OsStr;
 use crate::io::prelude::*;
@@ -170,6 +173,7 @@
 #[stable(feature = "command_access", since = "1.57.0")]
 pub use crate::sys_common::process::CommandEnvs;
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::time::Duration;
 use crate::{fmt, fs, str};
 
 /// Representation of a running or exited child process.
@@ -2196,6 +2200,54 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         Ok(self.handle.try_wait()?.map(ExitStatus))
     }
 
+    /// Waits for the child to exit for at most `timeout`, returning the status
+    /// that it exited with.
+    ///
+    /// If the child has exited, then `Ok(Some(status))` is returned, and on
+    /// Unix the process ID is reaped. If the child is still running once the
+    /// timeout elapsed, then `Ok(None)` is returned. If an error occurs, then
+    /// that error is returned.
+    ///
+    /// Like [`try_wait`], this function will not attempt to drop stdin, so
+    /// that the child can still be given input after a timeout.
+    ///
+    /// [`try_wait`]: Child::try_wait
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Linux, this waits on a pidfd for the child, which is opened if none
+    /// was created with `CommandExt::create_pidfd`. On other Unix platforms,
+    /// and on Linux kernels without pidfd support, the child is polled with
+    /// increasing sleeps in between, so its exit may be noticed a few
+    /// milliseconds late. `SIGCHLD` is never used, so its handler and
+    /// disposition are left to the application.
+    ///
+    /// On Windows, this waits on the process handle.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(child_wait_timeout)]
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let mut child = Command::new("sleep").arg("60").spawn().unwrap();
+    ///
+    /// match child.wait_timeout(Duration::from_secs(1)) {
+    ///     Ok(Some(status)) => println!("exited with: {status}"),
+    ///     Ok(None) => {
+    ///         println!("timed out, killing the child");
+    ///         child.kill().unwrap();
+    ///         child.wait().unwrap();
+    ///     }
+    ///     Err(e) => println!("error attempting to wait: {e}"),
+    /// }
+    /// ```
+    #[unstable(feature = "child_wait_timeout", issue = "none")]
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        Ok(self.handle.wait_timeout(timeout)?.map(ExitStatus))
+    }
+
     /// Simultaneously waits for the child to exit and collect all remaining
     /// output on the stdout/stderr handles, returning an `Output`
     /// instance.
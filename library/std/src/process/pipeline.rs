@@ -0,0 +1,355 @@
+//! Chains of processes connected by pipes, see [`Pipeline`].
+
+use super::{Child, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use crate::ffi::OsStr;
+use crate::io::{self, Read};
+use crate::pipe::pipe;
+use crate::{fmt, str};
+
+/// A chain of commands, each of which has its standard output connected to
+/// the standard input of the next one, like `cmd1 | cmd2 | cmd3` in a shell.
+///
+/// The standard input of the first command and the standard output of the
+/// last one are configured on the commands themselves, with
+/// [`Command::stdin`] and [`Command::stdout`]. The standard output of every
+/// other command, and the standard input of every command but the first, are
+/// replaced by the pipes connecting them.
+///
+/// Unlike connecting the commands by hand, the processes which were already
+/// spawned are killed and waited for if spawning a later command fails, so
+/// no process is left behind.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(process_pipeline)]
+/// use std::process::Pipeline;
+///
+/// let mut pipeline = Pipeline::new();
+/// pipeline.command("ls");
+/// pipeline.command("sort").arg("-r");
+/// pipeline.command("head").arg("-n3");
+/// let output = pipeline.output()?;
+/// assert!(output.status.success());
+/// println!("{}", String::from_utf8_lossy(&output.stdout));
+/// # std::io::Result::Ok(())
+/// ```
+#[derive(Debug, Default)]
+#[unstable(feature = "process_pipeline", issue = "none")]
+pub struct Pipeline {
+    commands: Vec<Command>,
+}
+
+impl Pipeline {
+    /// Creates a new pipeline without any commands.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    #[must_use]
+    pub fn new() -> Pipeline {
+        Pipeline { commands: Vec::new() }
+    }
+
+    /// Appends a command to the pipeline, which reads the output of the
+    /// previous command, if any.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn pipe(&mut self, command: Command) -> &mut Pipeline {
+        self.commands.push(command);
+        self
+    }
+
+    /// Appends a new command for launching the program at path `program` to
+    /// the pipeline, returning it to be configured.
+    ///
+    /// This is a shorthand for [`pipe`](Pipeline::pipe) with
+    /// [`Command::new`].
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn command<S: AsRef<OsStr>>(&mut self, program: S) -> &mut Command {
+        self.commands.push(Command::new(program));
+        self.commands.last_mut().unwrap()
+    }
+
+    /// Returns the commands of the pipeline, in order.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Returns the commands of the pipeline mutably, for example to configure
+    /// the standard input of the first command.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn commands_mut(&mut self) -> &mut [Command] {
+        &mut self.commands
+    }
+
+    /// Spawns every command of the pipeline, returning a handle to the
+    /// processes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the pipeline has no commands, if
+    /// the pipes can't be created, or if any of the commands fails to spawn.
+    /// The processes which were already spawned are killed and waited for in
+    /// that case.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn spawn(&mut self) -> io::Result<PipelineChild> {
+        if self.commands.is_empty() {
+            return Err(io::const_io_error!(
+                io::ErrorKind::InvalidInput,
+                "pipeline has no commands",
+            ));
+        }
+
+        let mut children = Vec::with_capacity(self.commands.len());
+        if let Err(err) = self.spawn_stages(&mut children) {
+            for mut child in children {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            return Err(err);
+        }
+
+        let stdin = children.first_mut().unwrap().stdin.take();
+        let stdout = children.last_mut().unwrap().stdout.take();
+        Ok(PipelineChild { children, stdin, stdout })
+    }
+
+    fn spawn_stages(&mut self, children: &mut Vec<Child>) -> io::Result<()> {
+        let last = self.commands.len() - 1;
+        let mut stdin = None;
+        for (i, command) in self.commands.iter_mut().enumerate() {
+            let (next_stdin, stdout) = if i < last {
+                let (reader, writer) = pipe()?;
+                (Some(reader), Some(writer))
+            } else {
+                (None, None)
+            };
+            if let Some(stdin) = stdin.take() {
+                command.stdin(stdin);
+            }
+            if let Some(stdout) = stdout {
+                command.stdout(stdout);
+            }
+
+            let child = command.spawn();
+            // Only the processes on either end may keep the pipe open, or the
+            // next process would never see the end of its input.
+            if i > 0 {
+                command.stdin(Stdio::null());
+            }
+            if i < last {
+                command.stdout(Stdio::null());
+            }
+            children.push(child?);
+            stdin = next_stdin;
+        }
+        Ok(())
+    }
+
+    /// Spawns every command of the pipeline and waits for all of them to
+    /// finish, returning their exit statuses.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_pipeline)]
+    /// use std::process::{Command, Pipeline};
+    ///
+    /// let mut cat = Command::new("cat");
+    /// cat.arg("missing.txt");
+    /// let status = Pipeline::new().pipe(cat).pipe(Command::new("wc")).status()?;
+    /// if let Some(stage) = status.failed_stage() {
+    ///     println!("command {stage} failed: {}", status.statuses()[stage]);
+    /// }
+    /// # std::io::Result::Ok(())
+    /// ```
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn status(&mut self) -> io::Result<PipelineStatus> {
+        self.spawn()?.wait()
+    }
+
+    /// Spawns every command of the pipeline and waits for all of them to
+    /// finish, collecting the standard output of the last command.
+    ///
+    /// The standard output of the last command is replaced by a pipe. Unlike
+    /// with [`Command::output`], the standard input of the first command is
+    /// inherited from the parent unless it is configured.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn output(&mut self) -> io::Result<PipelineOutput> {
+        if let Some(last) = self.commands.last_mut() {
+            last.stdout(Stdio::piped());
+        }
+        let mut child = self.spawn()?;
+        let mut stdout = Vec::new();
+        let read = child.stdout.take().unwrap().read_to_end(&mut stdout);
+        let status = child.wait()?;
+        read?;
+        Ok(PipelineOutput { status, stdout })
+    }
+}
+
+/// The processes of a running [`Pipeline`].
+///
+/// This structure is created by [`Pipeline::spawn`]. Like a [`Child`], the
+/// processes are not killed or waited for when it is dropped.
+#[derive(Debug)]
+#[unstable(feature = "process_pipeline", issue = "none")]
+pub struct PipelineChild {
+    children: Vec<Child>,
+
+    /// The handle for writing to the standard input of the first process, if
+    /// it has been captured.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub stdin: Option<ChildStdin>,
+
+    /// The handle for reading from the standard output of the last process,
+    /// if it has been captured.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub stdout: Option<ChildStdout>,
+}
+
+impl PipelineChild {
+    /// Returns the processes of the pipeline, in order.
+    ///
+    /// The captured standard input of the first process and standard output
+    /// of the last one are held by [`stdin`](PipelineChild::stdin) and
+    /// [`stdout`](PipelineChild::stdout) instead.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn children(&self) -> &[Child] {
+        &self.children
+    }
+
+    /// Returns the processes of the pipeline mutably, for example to read the
+    /// captured standard error of one of them.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn children_mut(&mut self) -> &mut [Child] {
+        &mut self.children
+    }
+
+    /// Forces every process of the pipeline to exit, as with [`Child::kill`].
+    ///
+    /// # Errors
+    ///
+    /// Every process is killed even if killing one of them fails, in which
+    /// case the first error is returned.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn kill(&mut self) -> io::Result<()> {
+        let mut result = Ok(());
+        for child in &mut self.children {
+            let res = child.kill();
+            if result.is_ok() {
+                result = res;
+            }
+        }
+        result
+    }
+
+    /// Waits for every process of the pipeline to exit, returning their exit
+    /// statuses.
+    ///
+    /// The handle to the standard input of the first process, if any, is
+    /// closed before waiting, as with [`Child::wait`].
+    ///
+    /// # Errors
+    ///
+    /// Every process is waited for even if waiting for one of them fails, in
+    /// which case the first error is returned.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn wait(&mut self) -> io::Result<PipelineStatus> {
+        drop(self.stdin.take());
+        let mut statuses = Vec::with_capacity(self.children.len());
+        let mut error = None;
+        for child in &mut self.children {
+            match child.wait() {
+                Ok(status) => statuses.push(status),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(PipelineStatus { statuses }),
+        }
+    }
+}
+
+/// The exit statuses of the processes of a finished [`Pipeline`].
+///
+/// The status of the pipeline as a whole is the status of the last process
+/// which failed, or of the last process if all of them succeeded, like with
+/// `set -o pipefail` in a shell.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[unstable(feature = "process_pipeline", issue = "none")]
+pub struct PipelineStatus {
+    statuses: Vec<ExitStatus>,
+}
+
+impl PipelineStatus {
+    /// Returns the exit status of every process, in order.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn statuses(&self) -> &[ExitStatus] {
+        &self.statuses
+    }
+
+    /// Was termination successful for every process?
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn success(&self) -> bool {
+        self.statuses.iter().all(ExitStatus::success)
+    }
+
+    /// Returns the index of the last process which failed, if any.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn failed_stage(&self) -> Option<usize> {
+        self.statuses.iter().rposition(|status| !status.success())
+    }
+
+    /// Returns the status of the pipeline as a whole: the status of the last
+    /// process which failed, or of the last process if all of them succeeded.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn status(&self) -> ExitStatus {
+        self.statuses[self.failed_stage().unwrap_or(self.statuses.len() - 1)]
+    }
+
+    /// Returns the status of the last process, ignoring the others like a
+    /// shell does without `set -o pipefail`.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub fn last(&self) -> ExitStatus {
+        *self.statuses.last().unwrap()
+    }
+}
+
+#[unstable(feature = "process_pipeline", issue = "none")]
+impl fmt::Display for PipelineStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.status(), f)
+    }
+}
+
+/// The output of a finished [`Pipeline`].
+///
+/// This is returned by [`Pipeline::output`].
+#[derive(PartialEq, Eq, Clone)]
+#[unstable(feature = "process_pipeline", issue = "none")]
+pub struct PipelineOutput {
+    /// The exit statuses of the processes.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub status: PipelineStatus,
+    /// The data that the last process wrote to stdout.
+    #[unstable(feature = "process_pipeline", issue = "none")]
+    pub stdout: Vec<u8>,
+}
+
+#[unstable(feature = "process_pipeline", issue = "none")]
+impl fmt::Debug for PipelineOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stdout_utf8 = str::from_utf8(&self.stdout);
+        let stdout_debug: &dyn fmt::Debug = match stdout_utf8 {
+            Ok(ref str) => str,
+            Err(_) => &self.stdout,
+        };
+
+        f.debug_struct("PipelineOutput")
+            .field("status", &self.status)
+            .field("stdout", stdout_debug)
+            .finish()
+    }
+}
@@ -1,8 +1,9 @@
-use super::{Command, Output, Stdio};
+use super::{Command, Output, Pipeline, Stdio};
 use crate::io::prelude::*;
 use crate::io::{BorrowedBuf, ErrorKind};
 use crate::mem::MaybeUninit;
 use crate::str;
+use crate::time::Duration;
 
 fn known_command() -> Command {
     if cfg!(windows) { Command::new("help") } else { Command::new("echo") }
@@ -68,6 +69,86 @@ fn signal_reported_right() {
     }
 }
 
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn wait_timeout() {
+    let mut p = shell_cmd().arg("-c").arg("read a").stdin(Stdio::piped()).spawn().unwrap();
+    assert!(p.wait_timeout(Duration::ZERO).unwrap().is_none());
+    assert!(p.wait_timeout(Duration::from_millis(10)).unwrap().is_none());
+
+    // `read` fails once stdin is closed.
+    drop(p.stdin.take());
+    let status = p.wait_timeout(Duration::from_secs(60)).unwrap().unwrap();
+    assert_eq!(status.code(), Some(1));
+    assert_eq!(p.wait_timeout(Duration::ZERO).unwrap(), Some(status));
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn pipeline_output() {
+    let mut echo = shell_cmd();
+    echo.arg("-c").arg("echo foobar");
+    let mut pipeline = Pipeline::new();
+    pipeline.pipe(echo).pipe(Command::new("tr"));
+    pipeline.commands_mut()[1].arg("a-z").arg("A-Z");
+    pipeline.command("cat");
+    let output = pipeline.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.status.statuses().len(), 3);
+    assert_eq!(output.stdout, b"FOOBAR\n");
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn pipeline_stdin() {
+    let mut pipeline = Pipeline::new();
+    pipeline.command("cat").stdin(Stdio::piped());
+    pipeline.command("tr").arg("a-z").arg("A-Z").stdout(Stdio::piped());
+
+    let mut child = pipeline.spawn().unwrap();
+    assert_eq!(child.children().len(), 2);
+    child.stdin.take().unwrap().write_all(b"foobar").unwrap();
+    let mut out = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut out).unwrap();
+    assert!(child.wait().unwrap().success());
+    assert_eq!(out, "FOOBAR");
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn pipeline_pipefail() {
+    let mut pipeline = Pipeline::new();
+    for code in [3, 4, 0] {
+        let mut cmd = shell_cmd();
+        cmd.arg("-c").arg(format!("exit {code}"));
+        pipeline.pipe(cmd);
+    }
+    let status = pipeline.status().unwrap();
+    assert!(!status.success());
+    assert_eq!(status.failed_stage(), Some(1));
+    assert_eq!(status.status().code(), Some(4));
+    assert_eq!(status.last().code(), Some(0));
+    let codes: Vec<_> = status.statuses().iter().map(|s| s.code()).collect();
+    assert_eq!(codes, [Some(3), Some(4), Some(0)]);
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn pipeline_spawn_failure() {
+    assert_eq!(Pipeline::new().spawn().unwrap_err().kind(), ErrorKind::InvalidInput);
+
+    // `cat` keeps waiting for input unless the pipeline kills it.
+    let mut pipeline = Pipeline::new();
+    pipeline.command("cat").stdin(Stdio::piped());
+    pipeline.command("if-this-is-a-binary-then-the-world-has-ended");
+    assert!(pipeline.spawn().is_err());
+}
+
 pub fn run_output(mut cmd: Command) -> String {
     let p = cmd.spawn();
     assert!(p.is_ok());
@@ -155,6 +155,9 @@
 //!   writer at a time. In some cases, this can be more efficient than
 //!   a mutex.
 //!
+//! - [`Semaphore`]: Limits the number of threads which can access a
+//!   resource at the same time, by handing out a fixed number of permits.
+//!
 //! [`Arc`]: crate::sync::Arc
 //! [`Barrier`]: crate::sync::Barrier
 //! [`Condvar`]: crate::sync::Condvar
@@ -164,6 +167,7 @@
 //! [`Once`]: crate::sync::Once
 //! [`OnceLock`]: crate::sync::OnceLock
 //! [`RwLock`]: crate::sync::RwLock
+//! [`Semaphore`]: crate::sync::Semaphore
 
 #![stable(feature = "rust1", since = "1.0.0")]
 
@@ -198,6 +202,8 @@
 pub use self::rwlock::{MappedRwLockReadGuard, MappedRwLockWriteGuard};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[unstable(feature = "sync_semaphore", issue = "none")]
+pub use self::semaphore::{Semaphore, SemaphorePermit};
 
 #[unstable(feature = "mpmc_channel", issue = "126840")]
 pub mod mpmc;
@@ -212,3 +218,4 @@
 mod poison;
 mod reentrant_lock;
 mod rwlock;
+mod semaphore;
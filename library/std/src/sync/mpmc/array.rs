@@ -10,7 +10,7 @@
 
 use super::context::Context;
 use super::error::*;
-use super::select::{Operation, Selected, Token};
+use super::select::{Operation, SelectHandle, Selected, Token};
 use super::utils::{Backoff, CachePadded};
 use super::waker::SyncWaker;
 use crate::cell::UnsafeCell;
@@ -536,6 +536,16 @@ unsafe fn discard_all_messages(&self, tail: usize) {
         }
     }
 
+    /// Returns a receiver handle to the channel.
+    pub(crate) fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub(crate) fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
     /// Returns `true` if the channel is disconnected.
     pub(crate) fn is_disconnected(&self) -> bool {
         self.tail.load(Ordering::SeqCst) & self.mark_bit != 0
@@ -565,3 +575,47 @@ pub(crate) fn is_full(&self) -> bool {
         head.wrapping_add(self.one_lap) == tail & !self.mark_bit
     }
 }
+
+/// Receiver handle to a channel.
+pub(crate) struct Receiver<'a, T>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub(crate) struct Sender<'a, T>(&'a Channel<T>);
+
+impl<T> SelectHandle for Receiver<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+}
+
+impl<T> SelectHandle for Sender<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.senders.register(oper, cx);
+        !self.0.is_full() || self.0.is_disconnected()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.senders.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+}
@@ -2,7 +2,7 @@
 
 use super::context::Context;
 use super::error::*;
-use super::select::{Operation, Selected, Token};
+use super::select::{Operation, SelectHandle, Selected, Token};
 use super::utils::{Backoff, CachePadded};
 use super::waker::SyncWaker;
 use crate::cell::UnsafeCell;
@@ -599,6 +599,16 @@ fn discard_all_messages(&self) {
         self.head.index.store(head, Ordering::Release);
     }
 
+    /// Returns a receiver handle to the channel.
+    pub(crate) fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub(crate) fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
     /// Returns `true` if the channel is disconnected.
     pub(crate) fn is_disconnected(&self) -> bool {
         self.tail.index.load(Ordering::SeqCst) & MARK_BIT != 0
@@ -654,3 +664,45 @@ fn drop(&mut self) {
         }
     }
 }
+
+/// Receiver handle to a channel.
+pub(crate) struct Receiver<'a, T>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub(crate) struct Sender<'a, T>(&'a Channel<T>);
+
+impl<T> SelectHandle for Receiver<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.receivers.register(oper, cx);
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+}
+
+impl<T> SelectHandle for Sender<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        // An unbounded channel is always ready for sending.
+        true
+    }
+
+    fn unregister(&self, _oper: Operation) {}
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+}
@@ -147,6 +147,9 @@
 mod zero;
 
 pub use error::*;
+pub(crate) use select::sealed;
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub use select::{Select, SelectReceiver, SelectSender, SelectedOperation, select};
 
 use crate::fmt;
 use crate::panic::{RefUnwindSafe, UnwindSafe};
@@ -1,3 +1,13 @@
+//! Waiting on several channel operations at once, see [`Select`].
+
+use super::context::Context;
+use super::error::*;
+use super::utils;
+use super::{Receiver, ReceiverFlavor, Sender, SenderFlavor};
+use crate::marker::PhantomData;
+use crate::time::{Duration, Instant};
+use crate::{fmt, mem, ptr, thread};
+
 /// Temporary data that gets initialized during a blocking operation, and is consumed by
 /// `read` or `write`.
 ///
@@ -69,3 +79,733 @@ fn into(self) -> usize {
         }
     }
 }
+
+/// The timeout of a select operation.
+#[derive(Debug, Clone, Copy)]
+enum Timeout {
+    /// No blocking.
+    Now,
+
+    /// Block forever.
+    Never,
+
+    /// Block until the deadline.
+    At(Instant),
+}
+
+/// A channel operation which can take part in a select operation.
+pub(crate) trait SelectHandle {
+    /// Attempts to select the operation and returns `true` on success.
+    fn try_select(&self, token: &mut Token) -> bool;
+
+    /// Registers a select operation.
+    ///
+    /// Returns `true` if the operation is ready or the channel is disconnected, in which case
+    /// the caller should try selecting it instead of blocking.
+    fn register(&self, oper: Operation, cx: &Context) -> bool;
+
+    /// Unregisters a select operation.
+    fn unregister(&self, oper: Operation);
+
+    /// Attempts to select the operation the current thread was woken up by and returns `true`
+    /// on success.
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool;
+}
+
+impl<T> SelectHandle for Sender<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().try_select(token),
+            SenderFlavor::List(chan) => chan.sender().try_select(token),
+            SenderFlavor::Zero(chan) => chan.sender().try_select(token),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::List(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Zero(chan) => chan.sender().register(oper, cx),
+        }
+    }
+
+    fn unregister(&self, oper: Operation) {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().unregister(oper),
+            SenderFlavor::List(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Zero(chan) => chan.sender().unregister(oper),
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::List(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Zero(chan) => chan.sender().accept(token, cx),
+        }
+    }
+}
+
+impl<T> SelectHandle for Receiver<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::List(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Zero(chan) => chan.receiver().try_select(token),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::List(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Zero(chan) => chan.receiver().register(oper, cx),
+        }
+    }
+
+    fn unregister(&self, oper: Operation) {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::List(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Zero(chan) => chan.receiver().unregister(oper),
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::List(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Zero(chan) => chan.receiver().accept(token, cx),
+        }
+    }
+}
+
+/// Writes a message into the channel of a selected send operation.
+unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Result<(), T> {
+    unsafe {
+        match &s.flavor {
+            SenderFlavor::Array(chan) => chan.write(token, msg),
+            SenderFlavor::List(chan) => chan.write(token, msg),
+            SenderFlavor::Zero(chan) => chan.write(token, msg),
+        }
+    }
+}
+
+/// Reads a message from the channel of a selected receive operation.
+unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()> {
+    unsafe {
+        match &r.flavor {
+            ReceiverFlavor::Array(chan) => chan.read(token),
+            ReceiverFlavor::List(chan) => chan.read(token),
+            ReceiverFlavor::Zero(chan) => chan.read(token),
+        }
+    }
+}
+
+/// Selects one of the operations, waiting until `timeout` if none of them is ready.
+///
+/// Returns the token of the selected operation along with its index and channel pointer.
+fn run_select(
+    handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    timeout: Timeout,
+) -> Option<(Token, usize, *const u8)> {
+    if handles.is_empty() {
+        // Wait until the timeout and return.
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {
+                utils::sleep_until(None);
+                unreachable!();
+            }
+            Timeout::At(when) => {
+                utils::sleep_until(Some(when));
+                return None;
+            }
+        }
+    }
+
+    // Shuffle the operations for fairness.
+    utils::shuffle(handles);
+
+    // Create a token, which serves as a temporary variable that gets initialized in this function
+    // and is later used by a call to `read` or `write` that completes the selected operation.
+    let mut token = Token::default();
+
+    // Try selecting one of the operations without blocking.
+    for &(handle, i, ptr) in handles.iter() {
+        if handle.try_select(&mut token) {
+            return Some((token, i, ptr));
+        }
+    }
+
+    loop {
+        // Prepare for blocking.
+        let res = Context::with(|cx| {
+            let mut sel = Selected::Waiting;
+            let mut registered_count = 0;
+            let mut index_ready = None;
+
+            if let Timeout::Now = timeout {
+                cx.try_select(Selected::Aborted).unwrap();
+            }
+
+            // Register all operations.
+            for (handle, i, _) in handles.iter_mut() {
+                registered_count += 1;
+
+                // If registration returns `true`, that means the operation has just become ready.
+                if handle.register(Operation::hook::<&dyn SelectHandle>(handle), cx) {
+                    // Try aborting select.
+                    sel = match cx.try_select(Selected::Aborted) {
+                        Ok(()) => {
+                            index_ready = Some(*i);
+                            Selected::Aborted
+                        }
+                        Err(s) => s,
+                    };
+                    break;
+                }
+
+                // If another thread has already selected one of the operations, stop registration.
+                sel = cx.selected();
+                if sel != Selected::Waiting {
+                    break;
+                }
+            }
+
+            if sel == Selected::Waiting {
+                let deadline = match timeout {
+                    Timeout::Now => unreachable!(),
+                    Timeout::Never => None,
+                    Timeout::At(when) => Some(when),
+                };
+
+                // Block the current thread.
+                sel = cx.wait_until(deadline);
+            }
+
+            // Unregister all registered operations.
+            for (handle, _, _) in handles.iter_mut().take(registered_count) {
+                handle.unregister(Operation::hook::<&dyn SelectHandle>(handle));
+            }
+
+            match sel {
+                Selected::Waiting => unreachable!(),
+                Selected::Aborted => {
+                    // If an operation became ready during registration, try selecting it.
+                    if let Some(index_ready) = index_ready {
+                        for &(handle, i, ptr) in handles.iter() {
+                            if i == index_ready && handle.try_select(&mut token) {
+                                return Some((i, ptr));
+                            }
+                        }
+                    }
+                }
+                Selected::Disconnected => {}
+                Selected::Operation(_) => {
+                    // Find the selected operation.
+                    for (handle, i, ptr) in handles.iter_mut() {
+                        // Is this the selected operation?
+                        if sel == Selected::Operation(Operation::hook::<&dyn SelectHandle>(handle))
+                        {
+                            // Try selecting this operation.
+                            if handle.accept(&mut token, cx) {
+                                return Some((*i, *ptr));
+                            }
+                        }
+                    }
+                }
+            }
+
+            None
+        });
+
+        // Return if an operation was selected.
+        if let Some((i, ptr)) = res {
+            return Some((token, i, ptr));
+        }
+
+        // Try selecting one of the operations without blocking.
+        for &(handle, i, ptr) in handles.iter() {
+            if handle.try_select(&mut token) {
+                return Some((token, i, ptr));
+            }
+        }
+
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {}
+            Timeout::At(when) => {
+                if Instant::now() >= when {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+pub(crate) mod sealed {
+    use super::super::{Receiver, Sender};
+
+    /// Gives access to the channel behind the sending half of an `mpmc` or `mpsc` channel.
+    pub trait AsSender<T> {
+        /// Returns the `mpmc` sender.
+        fn as_mpmc_sender(&self) -> &Sender<T>;
+    }
+
+    /// Gives access to the channel behind the receiving half of an `mpmc` or `mpsc` channel.
+    pub trait AsReceiver<T> {
+        /// Returns the `mpmc` receiver.
+        fn as_mpmc_receiver(&self) -> &Receiver<T>;
+    }
+}
+
+/// The sending half of a channel, which can be added to a [`Select`].
+///
+/// This trait is sealed. It is implemented by the senders of [`mpmc`](crate::sync::mpmc) and
+/// [`mpsc`](crate::sync::mpsc) channels.
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub trait SelectSender<T>: sealed::AsSender<T> {}
+
+/// The receiving half of a channel, which can be added to a [`Select`].
+///
+/// This trait is sealed. It is implemented by the receivers of [`mpmc`](crate::sync::mpmc) and
+/// [`mpsc`](crate::sync::mpsc) channels.
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub trait SelectReceiver<T>: sealed::AsReceiver<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> sealed::AsSender<T> for Sender<T> {
+    fn as_mpmc_sender(&self) -> &Sender<T> {
+        self
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectSender<T> for Sender<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> sealed::AsReceiver<T> for Receiver<T> {
+    fn as_mpmc_receiver(&self) -> &Receiver<T> {
+        self
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectReceiver<T> for Receiver<T> {}
+
+/// Waits on several send and receive operations at once.
+///
+/// Operations are added with [`send`] and [`recv`], which return the index of the operation.
+/// [`select`] then blocks until one of them is ready, and returns it as a [`SelectedOperation`],
+/// which must be completed with [`SelectedOperation::send`] or [`SelectedOperation::recv`]
+/// passing the same sender or receiver that was added. If several operations are ready at the
+/// same time, one of them is chosen at random.
+///
+/// An operation is ready when it can complete without blocking, including when it would fail
+/// because its channel is disconnected. A receive operation on a disconnected channel is thus
+/// selected repeatedly, and should be [`remove`]d once it's been seen to fail.
+///
+/// The [`select!`] macro wraps this type for a fixed set of operations.
+///
+/// [`send`]: Select::send
+/// [`recv`]: Select::recv
+/// [`select`]: Select::select
+/// [`remove`]: Select::remove
+/// [`select!`]: crate::sync::mpmc::select
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_select)]
+///
+/// use std::sync::mpsc::{Select, channel};
+/// use std::thread;
+///
+/// let (tx1, rx1) = channel();
+/// let (tx2, rx2) = channel::<i32>();
+///
+/// thread::spawn(move || tx1.send(10).unwrap());
+/// thread::spawn(move || drop(tx2));
+///
+/// let mut sel = Select::new();
+/// let oper1 = sel.recv(&rx1);
+/// let oper2 = sel.recv(&rx2);
+///
+/// let mut received = None;
+/// while received.is_none() {
+///     let oper = sel.select();
+///     match oper.index() {
+///         i if i == oper1 => received = Some(oper.recv(&rx1).unwrap()),
+///         i if i == oper2 => {
+///             // `tx2` is gone, don't wait on `rx2` anymore.
+///             assert!(oper.recv(&rx2).is_err());
+///             sel.remove(oper2);
+///         }
+///         _ => unreachable!(),
+///     }
+/// }
+/// assert_eq!(received, Some(10));
+/// ```
+#[derive(Clone)]
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub struct Select<'a> {
+    /// The added operations, with their index and the address of their sender or receiver.
+    handles: Vec<(&'a dyn SelectHandle, usize, *const u8)>,
+
+    /// The index of the next added operation.
+    next_index: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of operations.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    #[must_use]
+    pub fn new() -> Select<'a> {
+        Select { handles: Vec::with_capacity(4), next_index: 0 }
+    }
+
+    /// Adds a send operation, and returns its index.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn send<T: 'a, S: SelectSender<T>>(&mut self, s: &'a S) -> usize {
+        let s = s.as_mpmc_sender();
+        let index = self.next_index;
+        let ptr = ptr::from_ref(s).cast::<u8>();
+        self.handles.push((s, index, ptr));
+        self.next_index += 1;
+        index
+    }
+
+    /// Adds a receive operation, and returns its index.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn recv<T: 'a, R: SelectReceiver<T>>(&mut self, r: &'a R) -> usize {
+        let r = r.as_mpmc_receiver();
+        let index = self.next_index;
+        let ptr = ptr::from_ref(r).cast::<u8>();
+        self.handles.push((r, index, ptr));
+        self.next_index += 1;
+        index
+    }
+
+    /// Removes a previously added operation.
+    ///
+    /// The indices of the other operations are unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't the index of an operation which was added and not removed yet.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn remove(&mut self, index: usize) {
+        let i = self
+            .handles
+            .iter()
+            .position(|&(_, i, _)| i == index)
+            .expect("no operation with this index");
+        self.handles.swap_remove(i);
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TrySelectError`] if none of the operations is ready.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        self.run(Timeout::Now).ok_or(TrySelectError)
+    }
+
+    /// Blocks until one of the operations is ready, and selects it.
+    ///
+    /// If no operations were added, this blocks forever.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        self.run(Timeout::Never).unwrap()
+    }
+
+    /// Blocks until one of the operations is ready or `timeout` elapses, and selects the ready
+    /// operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SelectTimeoutError`] if none of the operations became ready in time.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.select_deadline(deadline),
+            None => Ok(self.select()),
+        }
+    }
+
+    /// Blocks until one of the operations is ready or `deadline` is reached, and selects the
+    /// ready operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SelectTimeoutError`] if none of the operations became ready in time.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn select_deadline(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        self.run(Timeout::At(deadline)).ok_or(SelectTimeoutError)
+    }
+
+    fn run(&mut self, timeout: Timeout) -> Option<SelectedOperation<'a>> {
+        let (token, index, ptr) = run_select(&mut self.handles, timeout)?;
+        Some(SelectedOperation { token, index, ptr, _marker: PhantomData })
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<'a> Default for Select<'a> {
+    fn default() -> Select<'a> {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Select { .. }")
+    }
+}
+
+/// An operation chosen by a [`Select`], which must be completed.
+///
+/// The operation is completed by calling [`send`] or [`recv`] with the sender or receiver it
+/// was added with.
+///
+/// [`send`]: SelectedOperation::send
+/// [`recv`]: SelectedOperation::recv
+///
+/// # Panics
+///
+/// Dropping a `SelectedOperation` without completing it panics, since the channel may have
+/// been reserved for it. The channel may be left unusable if a `SelectedOperation` is dropped
+/// while unwinding from another panic.
+#[must_use = "the selected operation must be completed with `send` or `recv`"]
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub struct SelectedOperation<'a> {
+    /// The token used to complete the operation.
+    token: Token,
+
+    /// The index of the operation.
+    index: usize,
+
+    /// The address of the sender or receiver of the operation.
+    ptr: *const u8,
+
+    /// Ties the operation to the lifetime of its `Select`.
+    _marker: PhantomData<&'a ()>,
+}
+
+impl SelectedOperation<'_> {
+    /// Returns the index of the operation, as returned when it was added to the [`Select`].
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the send operation.
+    ///
+    /// This doesn't block: the channel is ready for the message.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message back in a [`SendError`] if the channel is disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `s` isn't the sender of the selected operation.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn send<T, S: SelectSender<T>>(mut self, s: &S, msg: T) -> Result<(), SendError<T>> {
+        let s = s.as_mpmc_sender();
+        assert!(
+            ptr::eq(ptr::from_ref(s).cast::<u8>(), self.ptr),
+            "passed a sender that wasn't selected",
+        );
+        let res = unsafe { write(s, &mut self.token, msg) };
+        mem::forget(self);
+        res.map_err(SendError)
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// This doesn't block, except for waiting on a sender which is in the middle of handing over
+    /// its message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] if the channel is empty and disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r` isn't the receiver of the selected operation.
+    #[unstable(feature = "mpmc_select", issue = "none")]
+    pub fn recv<T, R: SelectReceiver<T>>(mut self, r: &R) -> Result<T, RecvError> {
+        let r = r.as_mpmc_receiver();
+        assert!(
+            ptr::eq(ptr::from_ref(r).cast::<u8>(), self.ptr),
+            "passed a receiver that wasn't selected",
+        );
+        let res = unsafe { read(r, &mut self.token) };
+        mem::forget(self);
+        res.map_err(|_| RecvError)
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl fmt::Debug for SelectedOperation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectedOperation").field("index", &self.index).finish_non_exhaustive()
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl Drop for SelectedOperation<'_> {
+    fn drop(&mut self) {
+        // Don't abort if the operation is being dropped because of another panic.
+        if !thread::panicking() {
+            panic!("dropped `SelectedOperation` without completing the operation");
+        }
+    }
+}
+
+/// Waits on several send and receive operations at once, and runs the arm of the one which
+/// completes.
+///
+/// Each operation is written as an arm:
+///
+/// * `recv(r) -> res => body` receives from `r`, binding the result to the irrefutable pattern
+///   `res` as a `Result<T, RecvError>`.
+/// * `send(s, msg) -> res => body` sends `msg` to `s`, binding the result to `res` as a
+///   `Result<(), SendError<T>>`. `msg` is only evaluated if the operation is selected.
+///
+/// Without a `default` arm, the macro blocks until one of the operations completes. With
+/// `default => body`, `body` is run instead of blocking if none of the operations is ready, and
+/// with `default(timeout) => body` it is run once `timeout` elapses.
+///
+/// As with [`Select`], an operation on a disconnected channel is ready, and is picked among the
+/// ready operations at random. The result then holds an error.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_select)]
+///
+/// use std::sync::mpsc::{channel, select};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (jobs_tx, jobs_rx) = channel();
+/// let (_stop_tx, stop_rx) = channel::<()>();
+///
+/// thread::spawn(move || jobs_tx.send(42).unwrap());
+///
+/// select! {
+///     recv(jobs_rx) -> job => assert_eq!(job, Ok(42)),
+///     recv(stop_rx) -> _ => panic!("stopped"),
+///     default(Duration::from_secs(10)) => panic!("timed out"),
+/// }
+/// ```
+#[unstable(feature = "mpmc_select", issue = "none")]
+#[allow_internal_unstable(mpmc_channel, mpmc_select)]
+pub macro select {
+    // Parse an arm into `(kind (args..) ..)`, then its body.
+    (@parse $arms:tt $default:tt recv($r:expr) -> $res:pat => $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@body $arms $default (recv ($r) ($res)) $($rest)*)
+    },
+    (@parse $arms:tt $default:tt send($s:expr, $msg:expr) -> $res:pat => $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@body $arms $default (send ($s) ($msg) ($res)) $($rest)*)
+    },
+    (@parse $arms:tt $default:tt default($timeout:expr) => $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@body $arms $default (default ($timeout)) $($rest)*)
+    },
+    (@parse $arms:tt $default:tt default => $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@body $arms $default (default ()) $($rest)*)
+    },
+    (@parse [$($arms:tt)*] [$($default:tt)*]) => {{
+        let mut sel = $crate::sync::mpmc::Select::new();
+        match $crate::sync::mpmc::select!(@dispatch sel [$($default)*] $($arms)*) {
+            $crate::result::Result::Ok(value) => value,
+            $crate::result::Result::Err(_) => $crate::unreachable!(),
+        }
+    }},
+    (@parse $arms:tt $default:tt $($rest:tt)+) => {
+        $crate::compile_error!("expected `recv(..)`, `send(..)` or `default` arm in `select!`")
+    },
+
+    // A block body may be followed by a comma, other bodies must be unless they're last.
+    (@body $arms:tt $default:tt ($($arm:tt)*) $body:block, $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@push $arms $default ($($arm)* ($body)) $($rest)*)
+    },
+    (@body $arms:tt $default:tt ($($arm:tt)*) $body:block $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@push $arms $default ($($arm)* ($body)) $($rest)*)
+    },
+    (@body $arms:tt $default:tt ($($arm:tt)*) $body:expr, $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@push $arms $default ($($arm)* ($body)) $($rest)*)
+    },
+    (@body $arms:tt $default:tt ($($arm:tt)*) $body:expr) => {
+        $crate::sync::mpmc::select!(@push $arms $default ($($arm)* ($body)))
+    },
+
+    // Add the parsed arm to the operations, or make it the `default` arm.
+    (@push $arms:tt [] (default $($arm:tt)*) $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@parse $arms [(default $($arm)*)] $($rest)*)
+    },
+    (@push $arms:tt $default:tt (default $($arm:tt)*) $($rest:tt)*) => {
+        $crate::compile_error!("more than one `default` arm in `select!`")
+    },
+    (@push [$($arms:tt)*] $default:tt $arm:tt $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@parse [$($arms)* $arm] $default $($rest)*)
+    },
+
+    // Add each operation to the `Select`, from the first arm to the last, and evaluate to
+    // `Ok(body)` once the selected one is completed, or to `Err(oper)` if it belongs to an
+    // earlier arm.
+    (@dispatch $sel:ident $default:tt (recv ($r:expr) ($res:pat) ($body:tt)) $($arms:tt)*) => {{
+        let chan = &$r;
+        let index = $sel.recv(chan);
+        match $crate::sync::mpmc::select!(@dispatch $sel $default $($arms)*) {
+            $crate::result::Result::Err(oper) if oper.index() == index => {
+                let $res = oper.recv(chan);
+                $crate::result::Result::Ok($body)
+            }
+            res => res,
+        }
+    }},
+    (@dispatch $sel:ident $default:tt
+        (send ($s:expr) ($msg:expr) ($res:pat) ($body:tt)) $($arms:tt)*) => {{
+        let chan = &$s;
+        let index = $sel.send(chan);
+        match $crate::sync::mpmc::select!(@dispatch $sel $default $($arms)*) {
+            $crate::result::Result::Err(oper) if oper.index() == index => {
+                let $res = oper.send(chan, $msg);
+                $crate::result::Result::Ok($body)
+            }
+            res => res,
+        }
+    }},
+    (@dispatch $sel:ident []) => {
+        $crate::result::Result::Err($sel.select())
+    },
+    (@dispatch $sel:ident [(default () ($body:tt))]) => {
+        match $sel.try_select() {
+            $crate::result::Result::Ok(oper) => $crate::result::Result::Err(oper),
+            $crate::result::Result::Err(_) => $crate::result::Result::Ok($body),
+        }
+    },
+    (@dispatch $sel:ident [(default ($timeout:expr) ($body:tt))]) => {
+        match $sel.select_timeout($timeout) {
+            $crate::result::Result::Ok(oper) => $crate::result::Result::Err(oper),
+            $crate::result::Result::Err(_) => $crate::result::Result::Ok($body),
+        }
+    },
+
+    ($($tokens:tt)*) => {
+        $crate::sync::mpmc::select!(@parse [] [] $($tokens)*)
+    },
+}
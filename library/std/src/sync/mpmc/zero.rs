@@ -4,7 +4,7 @@
 
 use super::context::Context;
 use super::error::*;
-use super::select::{Operation, Selected, Token};
+use super::select::{Operation, SelectHandle, Selected, Token};
 use super::utils::Backoff;
 use super::waker::Waker;
 use crate::cell::UnsafeCell;
@@ -47,6 +47,15 @@ fn empty_on_stack() -> Packet<T> {
         Packet { on_stack: true, ready: AtomicBool::new(false), msg: UnsafeCell::new(None) }
     }
 
+    /// Creates an empty packet on the heap.
+    fn empty_on_heap() -> Box<Packet<T>> {
+        Box::new(Packet {
+            on_stack: false,
+            ready: AtomicBool::new(false),
+            msg: UnsafeCell::new(None),
+        })
+    }
+
     /// Creates a packet on the stack, containing a message.
     fn message_on_stack(msg: T) -> Packet<T> {
         Packet { on_stack: true, ready: AtomicBool::new(false), msg: UnsafeCell::new(Some(msg)) }
@@ -95,6 +104,32 @@ pub(crate) fn new() -> Self {
         }
     }
 
+    /// Returns a receiver handle to the channel.
+    pub(crate) fn receiver(&self) -> Receiver<'_, T> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub(crate) fn sender(&self) -> Sender<'_, T> {
+        Sender(self)
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    fn start_send(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        // If there's a waiting receiver, pair up with it.
+        if let Some(operation) = inner.receivers.try_select() {
+            token.zero.0 = operation.packet;
+            true
+        } else if inner.is_disconnected {
+            token.zero.0 = ptr::null_mut();
+            true
+        } else {
+            false
+        }
+    }
+
     /// Writes a message into the packet.
     pub(crate) unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
         // If there is no packet, the channel is disconnected.
@@ -110,6 +145,22 @@ pub(crate) unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
         Ok(())
     }
 
+    /// Attempts to pair up with a sender.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        // If there's a waiting sender, pair up with it.
+        if let Some(operation) = inner.senders.try_select() {
+            token.zero.0 = operation.packet;
+            true
+        } else if inner.is_disconnected {
+            token.zero.0 = ptr::null_mut();
+            true
+        } else {
+            false
+        }
+    }
+
     /// Reads a message from the packet.
     pub(crate) unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
         // If there is no packet, the channel is disconnected.
@@ -315,3 +366,67 @@ pub(crate) fn is_full(&self) -> bool {
         true
     }
 }
+
+/// Receiver handle to a channel.
+pub(crate) struct Receiver<'a, T>(&'a Channel<T>);
+
+/// Sender handle to a channel.
+pub(crate) struct Sender<'a, T>(&'a Channel<T>);
+
+impl<T> SelectHandle for Receiver<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        // The packet is filled in by the sender we pair up with, and destroyed by `read`.
+        let packet = Box::into_raw(Packet::<T>::empty_on_heap());
+
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.receivers.register_with_packet(oper, packet.cast::<()>(), cx);
+        inner.senders.notify();
+        inner.senders.can_select() || inner.is_disconnected
+    }
+
+    fn unregister(&self, oper: Operation) {
+        if let Some(operation) = self.0.inner.lock().unwrap().receivers.unregister(oper) {
+            unsafe {
+                drop(Box::from_raw(operation.packet.cast::<Packet<T>>()));
+            }
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        token.zero.0 = cx.wait_packet();
+        true
+    }
+}
+
+impl<T> SelectHandle for Sender<'_, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        // The packet is filled in by `write`, and destroyed by the receiver we pair up with.
+        let packet = Box::into_raw(Packet::<T>::empty_on_heap());
+
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.senders.register_with_packet(oper, packet.cast::<()>(), cx);
+        inner.receivers.notify();
+        inner.receivers.can_select() || inner.is_disconnected
+    }
+
+    fn unregister(&self, oper: Operation) {
+        if let Some(operation) = self.0.inner.lock().unwrap().senders.unregister(oper) {
+            unsafe {
+                drop(Box::from_raw(operation.packet.cast::<Packet<T>>()));
+            }
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        token.zero.0 = cx.wait_packet();
+        true
+    }
+}
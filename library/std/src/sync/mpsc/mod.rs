@@ -143,12 +143,21 @@
 #[cfg(all(test, not(any(target_os = "emscripten", target_os = "wasi"))))]
 mod sync_tests;
 
+#[cfg(all(test, not(any(target_os = "emscripten", target_os = "wasi"))))]
+mod select_tests;
+
 // MPSC channels are built as a wrapper around MPMC channels, which
 // were ported from the `crossbeam-channel` crate. MPMC channels are
 // not exposed publicly, but if you are curious about the implementation,
 // that's where everything is.
 
 use crate::sync::mpmc;
+use crate::sync::mpmc::sealed::{AsReceiver, AsSender};
+#[unstable(feature = "mpmc_select", issue = "none")]
+pub use crate::sync::mpmc::{
+    Select, SelectReceiver, SelectSender, SelectTimeoutError, SelectedOperation, TrySelectError,
+    select,
+};
 use crate::time::{Duration, Instant};
 use crate::{error, fmt};
 
@@ -1243,3 +1252,33 @@ fn from(err: RecvError) -> RecvTimeoutError {
         }
     }
 }
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> AsSender<T> for Sender<T> {
+    fn as_mpmc_sender(&self) -> &mpmc::Sender<T> {
+        &self.inner
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectSender<T> for Sender<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> AsSender<T> for SyncSender<T> {
+    fn as_mpmc_sender(&self) -> &mpmc::Sender<T> {
+        &self.inner
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectSender<T> for SyncSender<T> {}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> AsReceiver<T> for Receiver<T> {
+    fn as_mpmc_receiver(&self) -> &mpmc::Receiver<T> {
+        &self.inner
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "none")]
+impl<T> SelectReceiver<T> for Receiver<T> {}
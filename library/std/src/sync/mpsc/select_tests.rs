@@ -0,0 +1,280 @@
+use super::*;
+use crate::thread;
+
+#[test]
+fn smoke() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<i32>();
+    tx2.send(2).unwrap();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx1);
+    let oper2 = sel.recv(&rx2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&rx2), Ok(2));
+
+    tx1.send(1).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx1), Ok(1));
+}
+
+#[test]
+fn try_select() {
+    let (tx, rx) = channel::<i32>();
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx);
+    assert_eq!(sel.try_select().unwrap_err(), TrySelectError);
+
+    tx.send(1).unwrap();
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx), Ok(1));
+}
+
+#[test]
+fn select_timeout() {
+    let (_tx, rx) = channel::<i32>();
+    let (stx, _srx) = sync_channel::<i32>(0);
+    let mut sel = Select::new();
+    sel.recv(&rx);
+    sel.send(&stx);
+    assert_eq!(sel.select_timeout(Duration::from_millis(1)).unwrap_err(), SelectTimeoutError);
+    assert_eq!(
+        sel.select_deadline(Instant::now() + Duration::from_millis(1)).unwrap_err(),
+        SelectTimeoutError
+    );
+}
+
+#[test]
+fn empty() {
+    let mut sel = Select::new();
+    assert!(sel.try_select().is_err());
+    assert!(sel.select_timeout(Duration::from_millis(1)).is_err());
+}
+
+#[test]
+fn disconnected() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = sync_channel::<i32>(1);
+    drop(tx1);
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&rx1);
+    sel.recv(&rx2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&rx1), Err(RecvError));
+
+    sel.remove(oper1);
+    tx2.send(2).unwrap();
+    assert_eq!(sel.select().recv(&rx2), Ok(2));
+}
+
+#[test]
+fn send() {
+    let (tx1, rx1) = sync_channel::<i32>(1);
+    let (tx2, rx2) = channel::<i32>();
+    tx1.send(0).unwrap();
+
+    let mut sel = Select::new();
+    sel.send(&tx1);
+    let oper2 = sel.send(&tx2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    oper.send(&tx2, 2).unwrap();
+    assert_eq!(rx2.recv(), Ok(2));
+
+    drop(rx1);
+    drop(rx2);
+    let oper = sel.select();
+    if oper.index() == oper2 {
+        assert_eq!(oper.send(&tx2, 2), Err(SendError(2)));
+    } else {
+        assert_eq!(oper.send(&tx1, 1), Err(SendError(1)));
+    }
+}
+
+#[test]
+fn zero_capacity() {
+    let (tx1, rx1) = sync_channel::<i32>(0);
+    let (tx2, rx2) = sync_channel::<i32>(0);
+
+    thread::scope(|s| {
+        s.spawn(move || {
+            tx1.send(1).unwrap();
+            assert_eq!(rx2.recv(), Ok(2));
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&rx1);
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&rx1), Ok(1));
+
+        let mut sel = Select::new();
+        let oper2 = sel.send(&tx2);
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.send(&tx2, 2), Ok(()));
+    });
+}
+
+#[test]
+fn zero_capacity_both_selecting() {
+    let (tx, rx) = sync_channel::<i32>(0);
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            let mut sel = Select::new();
+            sel.send(&tx);
+            sel.select().send(&tx, 1).unwrap();
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&rx);
+        assert_eq!(sel.select().recv(&rx), Ok(1));
+    });
+}
+
+#[test]
+fn mpmc_channels() {
+    let (tx, rx) = mpmc::sync_channel::<i32>(1);
+    let mut sel = Select::new();
+    let send = sel.send(&tx);
+    let recv = sel.recv(&rx);
+
+    let oper = sel.select();
+    assert_eq!(oper.index(), send);
+    oper.send(&tx, 1).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), recv);
+    assert_eq!(oper.recv(&rx), Ok(1));
+}
+
+#[test]
+fn stress() {
+    const AMT: i32 = 10000;
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = sync_channel::<i32>(0);
+    let (tx3, rx3) = sync_channel::<i32>(4);
+
+    thread::scope(|s| {
+        s.spawn(move || (0..AMT).for_each(|i| tx1.send(i).unwrap()));
+        s.spawn(move || (0..AMT).for_each(|i| tx2.send(i).unwrap()));
+        s.spawn(move || (0..AMT).for_each(|i| tx3.send(i).unwrap()));
+
+        let mut next = [0; 3];
+        let mut sel = Select::new();
+        let receivers = [&rx1, &rx2, &rx3];
+        let opers = receivers.map(|rx| sel.recv(rx));
+        let mut live = 3;
+        while live > 0 {
+            let oper = sel.select();
+            let i = opers.iter().position(|&o| o == oper.index()).unwrap();
+            match oper.recv(receivers[i]) {
+                Ok(msg) => {
+                    assert_eq!(msg, next[i]);
+                    next[i] += 1;
+                }
+                Err(RecvError) => {
+                    sel.remove(opers[i]);
+                    live -= 1;
+                }
+            }
+        }
+        assert_eq!(next, [AMT; 3]);
+    });
+}
+
+#[test]
+#[should_panic = "passed a receiver that wasn't selected"]
+fn wrong_receiver() {
+    let (tx1, rx1) = channel::<i32>();
+    let (_tx2, rx2) = channel::<i32>();
+    tx1.send(1).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&rx1);
+    let _ = sel.select().recv(&rx2);
+}
+
+#[test]
+#[should_panic = "dropped `SelectedOperation` without completing the operation"]
+fn dropped_operation() {
+    let (tx, rx) = channel::<i32>();
+    tx.send(1).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&rx);
+    drop(sel.select());
+}
+
+#[test]
+fn macro_recv() {
+    let (tx1, rx1) = channel::<i32>();
+    let (_tx2, rx2) = channel::<i32>();
+    tx1.send(1).unwrap();
+
+    let res = select! {
+        recv(rx1) -> msg => msg.unwrap() + 1,
+        recv(rx2) -> _ => unreachable!(),
+    };
+    assert_eq!(res, 2);
+}
+
+#[test]
+fn macro_send() {
+    let (tx1, _rx1) = sync_channel::<i32>(0);
+    let (tx2, rx2) = sync_channel::<i32>(1);
+
+    select! {
+        send(tx1, 1) -> _ => unreachable!(),
+        send(tx2, 2) -> res => {
+            assert_eq!(res, Ok(()));
+        }
+    }
+    assert_eq!(rx2.try_recv(), Ok(2));
+}
+
+#[test]
+fn macro_default() {
+    let (tx, rx) = channel::<i32>();
+
+    let ready = select! {
+        recv(rx) -> _ => true,
+        default => false,
+    };
+    assert!(!ready);
+
+    let ready = select! {
+        recv(rx) -> _ => true,
+        default(Duration::from_millis(1)) => false,
+    };
+    assert!(!ready);
+
+    tx.send(1).unwrap();
+    let msg = select! {
+        recv(rx) -> msg => msg,
+        default => unreachable!(),
+    };
+    assert_eq!(msg, Ok(1));
+
+    let timed_out = select! {
+        default(Duration::from_millis(1)) => true,
+    };
+    assert!(timed_out);
+}
+
+#[test]
+fn macro_hygiene() {
+    let (tx, rx) = channel::<i32>();
+    let (sel, index, oper) = (1, 2, 3);
+    tx.send(sel).unwrap();
+
+    let res = select! {
+        recv(rx) -> chan => chan.unwrap() + index + oper,
+    };
+    assert_eq!(res, 6);
+}
@@ -9,6 +9,7 @@
 use crate::ptr::NonNull;
 use crate::sync::{LockResult, TryLockError, TryLockResult, poison};
 use crate::sys::sync as sys;
+use crate::time::Duration;
 
 /// A mutual exclusion primitive useful for protecting shared data
 ///
@@ -369,6 +370,58 @@ pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
         }
     }
 
+    /// Attempts to acquire this lock, blocking the current thread for at most
+    /// `timeout`.
+    ///
+    /// If the lock could not be acquired before the timeout elapsed, then
+    /// [`Err`] is returned. Otherwise, an RAII guard is returned. The lock will
+    /// be unlocked when the guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// If another user of this mutex panicked while holding the mutex, then
+    /// this call will return the [`Poisoned`] error if the mutex would
+    /// otherwise be acquired.
+    ///
+    /// If the mutex is still locked when the timeout elapses, then this call
+    /// will return the [`WouldBlock`] error.
+    ///
+    /// [`Poisoned`]: TryLockError::Poisoned
+    /// [`WouldBlock`]: TryLockError::WouldBlock
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On platforms where the mutex is not implemented with futexes, the lock
+    /// is polled with increasing sleeps in between, so the thread may not be
+    /// woken up immediately when the lock becomes available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(lock_timeout)]
+    /// use std::sync::{Mutex, TryLockError};
+    /// use std::time::Duration;
+    ///
+    /// let mutex = Mutex::new(0);
+    /// let guard = mutex.lock().unwrap();
+    /// assert!(matches!(
+    ///     mutex.try_lock_for(Duration::from_millis(10)),
+    ///     Err(TryLockError::WouldBlock),
+    /// ));
+    /// drop(guard);
+    /// *mutex.try_lock_for(Duration::from_millis(10)).unwrap() = 10;
+    /// ```
+    #[unstable(feature = "lock_timeout", issue = "none")]
+    pub fn try_lock_for(&self, timeout: Duration) -> TryLockResult<MutexGuard<'_, T>> {
+        unsafe {
+            if self.inner.try_lock_for(timeout) {
+                Ok(MutexGuard::new(self)?)
+            } else {
+                Err(TryLockError::WouldBlock)
+            }
+        }
+    }
+
     /// Determines whether the mutex is poisoned.
     ///
     /// If another thread is active, the mutex can still become poisoned at any
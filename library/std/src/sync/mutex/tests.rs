@@ -2,6 +2,7 @@
 use crate::sync::mpsc::channel;
 use crate::sync::{Arc, Condvar, MappedMutexGuard, Mutex, MutexGuard, TryLockError};
 use crate::thread;
+use crate::time::Duration;
 
 struct Packet<T>(Arc<(Mutex<T>, Condvar)>);
 
@@ -57,6 +58,29 @@ fn try_lock() {
     *m.try_lock().unwrap() = ();
 }
 
+#[test]
+fn try_lock_for() {
+    let m = Mutex::new(());
+    let guard = m.lock().unwrap();
+    assert!(matches!(m.try_lock_for(Duration::from_millis(10)), Err(TryLockError::WouldBlock)));
+    drop(guard);
+    *m.try_lock_for(Duration::from_millis(10)).unwrap() = ();
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn try_lock_for_contended() {
+    let m = Mutex::new(0);
+    thread::scope(|s| {
+        let guard = m.lock().unwrap();
+        let waiter = s.spawn(|| *m.try_lock_for(Duration::from_secs(60)).unwrap() += 1);
+        thread::sleep(Duration::from_millis(10));
+        drop(guard);
+        waiter.join().unwrap();
+    });
+    assert_eq!(*m.lock().unwrap(), 1);
+}
+
 #[test]
 fn test_into_inner() {
     let m = Mutex::new(NonCopy(10));
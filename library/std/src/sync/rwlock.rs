@@ -9,6 +9,7 @@
 use crate::ptr::NonNull;
 use crate::sync::{LockResult, TryLockError, TryLockResult, poison};
 use crate::sys::sync as sys;
+use crate::time::Duration;
 
 /// A reader-writer lock
 ///
@@ -324,6 +325,56 @@ pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
         }
     }
 
+    /// Attempts to acquire this `RwLock` with shared read access, blocking the
+    /// current thread for at most `timeout`.
+    ///
+    /// If the access could not be granted before the timeout elapsed, then
+    /// `Err` is returned. Otherwise, an RAII guard is returned which will
+    /// release the shared access when it is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the [`Poisoned`] error if the `RwLock` is
+    /// poisoned. `Poisoned` will only be returned if the lock would have
+    /// otherwise been acquired.
+    ///
+    /// This function will return the [`WouldBlock`] error if the `RwLock` is
+    /// still locked exclusively when the timeout elapses.
+    ///
+    /// [`Poisoned`]: TryLockError::Poisoned
+    /// [`WouldBlock`]: TryLockError::WouldBlock
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On platforms where the lock is not implemented with futexes, it is
+    /// polled with increasing sleeps in between, so the thread may not be
+    /// woken up immediately when the lock becomes available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(lock_timeout)]
+    /// use std::sync::RwLock;
+    /// use std::time::Duration;
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let n = lock.write().unwrap();
+    /// assert!(lock.try_read_for(Duration::from_millis(10)).is_err());
+    /// drop(n);
+    /// assert_eq!(*lock.try_read_for(Duration::from_millis(10)).unwrap(), 1);
+    /// ```
+    #[unstable(feature = "lock_timeout", issue = "none")]
+    pub fn try_read_for(&self, timeout: Duration) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        unsafe {
+            if self.inner.try_read_for(timeout) {
+                Ok(RwLockReadGuard::new(self)?)
+            } else {
+                Err(TryLockError::WouldBlock)
+            }
+        }
+    }
+
     /// Locks this `RwLock` with exclusive write access, blocking the current
     /// thread until it can be acquired.
     ///
@@ -413,6 +464,56 @@ pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
         }
     }
 
+    /// Attempts to lock this `RwLock` with exclusive write access, blocking the
+    /// current thread for at most `timeout`.
+    ///
+    /// If the lock could not be acquired before the timeout elapsed, then
+    /// `Err` is returned. Otherwise, an RAII guard is returned which will
+    /// release the lock when it is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the [`Poisoned`] error if the `RwLock` is
+    /// poisoned. `Poisoned` will only be returned if the lock would have
+    /// otherwise been acquired.
+    ///
+    /// This function will return the [`WouldBlock`] error if the `RwLock` is
+    /// still locked when the timeout elapses.
+    ///
+    /// [`Poisoned`]: TryLockError::Poisoned
+    /// [`WouldBlock`]: TryLockError::WouldBlock
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On platforms where the lock is not implemented with futexes, it is
+    /// polled with increasing sleeps in between, so the thread may not be
+    /// woken up immediately when the lock becomes available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(lock_timeout)]
+    /// use std::sync::RwLock;
+    /// use std::time::Duration;
+    ///
+    /// let lock = RwLock::new(1);
+    ///
+    /// let n = lock.read().unwrap();
+    /// assert!(lock.try_write_for(Duration::from_millis(10)).is_err());
+    /// drop(n);
+    /// *lock.try_write_for(Duration::from_millis(10)).unwrap() = 2;
+    /// ```
+    #[unstable(feature = "lock_timeout", issue = "none")]
+    pub fn try_write_for(&self, timeout: Duration) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        unsafe {
+            if self.inner.try_write_for(timeout) {
+                Ok(RwLockWriteGuard::new(self)?)
+            } else {
+                Err(TryLockError::WouldBlock)
+            }
+        }
+    }
+
     /// Determines whether the lock is poisoned.
     ///
     /// If another thread is active, the lock can still become poisoned at any
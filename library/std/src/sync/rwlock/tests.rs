@@ -7,6 +7,7 @@
     TryLockError,
 };
 use crate::thread;
+use crate::time::Duration;
 
 #[derive(Eq, PartialEq, Debug)]
 struct NonCopy(i32);
@@ -255,6 +256,39 @@ fn test_rwlock_try_write() {
     drop(mapped_read_guard);
 }
 
+#[test]
+fn test_rwlock_try_read_write_for() {
+    let lock = RwLock::new(0isize);
+    let timeout = Duration::from_millis(10);
+
+    let read_guard = lock.read().unwrap();
+    assert!(lock.try_read_for(timeout).is_ok());
+    assert!(matches!(lock.try_write_for(timeout), Err(TryLockError::WouldBlock)));
+    drop(read_guard);
+
+    let write_guard = lock.write().unwrap();
+    assert!(matches!(lock.try_read_for(timeout), Err(TryLockError::WouldBlock)));
+    assert!(matches!(lock.try_write_for(timeout), Err(TryLockError::WouldBlock)));
+    drop(write_guard);
+
+    *lock.try_write_for(timeout).unwrap() = 1;
+    assert_eq!(*lock.try_read_for(timeout).unwrap(), 1);
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn test_rwlock_try_write_for_contended() {
+    let lock = RwLock::new(0);
+    thread::scope(|s| {
+        let read_guard = lock.read().unwrap();
+        let writer = s.spawn(|| *lock.try_write_for(Duration::from_secs(60)).unwrap() += 1);
+        thread::sleep(Duration::from_millis(10));
+        drop(read_guard);
+        writer.join().unwrap();
+    });
+    assert_eq!(*lock.try_read_for(Duration::ZERO).unwrap(), 1);
+}
+
 #[test]
 fn test_into_inner() {
     let m = RwLock::new(NonCopy(10));
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod tests;
+
+use crate::fmt;
+use crate::sys::sync as sys;
+use crate::time::Duration;
+
+/// A counting semaphore, which limits the number of threads that can access a
+/// resource at the same time.
+///
+/// A semaphore holds a number of permits. [`acquire`] takes one of them,
+/// blocking the current thread until one is available, and returns a
+/// [`SemaphorePermit`] which gives it back to the semaphore when it is
+/// dropped.
+///
+/// Unlike a [`Mutex`], a semaphore doesn't protect any data by itself, and
+/// permits can be added or taken away at any time with [`release`] and
+/// [`SemaphorePermit::forget`].
+///
+/// [`acquire`]: Semaphore::acquire
+/// [`release`]: Semaphore::release
+/// [`Mutex`]: crate::sync::Mutex
+///
+/// # Examples
+///
+/// ```
+/// #![feature(sync_semaphore)]
+/// use std::sync::Semaphore;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::thread;
+///
+/// // At most two threads are busy at the same time.
+/// let semaphore = Semaphore::new(2);
+/// let busy = AtomicUsize::new(0);
+///
+/// thread::scope(|s| {
+///     for _ in 0..8 {
+///         s.spawn(|| {
+///             let _permit = semaphore.acquire();
+///             assert!(busy.fetch_add(1, Ordering::Relaxed) < 2);
+///             busy.fetch_sub(1, Ordering::Relaxed);
+///         });
+///     }
+/// });
+/// ```
+#[unstable(feature = "sync_semaphore", issue = "none")]
+pub struct Semaphore {
+    inner: sys::Semaphore,
+}
+
+/// An RAII permit taken from a [`Semaphore`].
+///
+/// The permit is given back to the semaphore when this structure is dropped,
+/// unless it is [forgotten].
+///
+/// This structure is created by the [`acquire`], [`try_acquire`] and
+/// [`acquire_timeout`] methods on [`Semaphore`].
+///
+/// [forgotten]: SemaphorePermit::forget
+/// [`acquire`]: Semaphore::acquire
+/// [`try_acquire`]: Semaphore::try_acquire
+/// [`acquire_timeout`]: Semaphore::acquire_timeout
+#[must_use = "if unused the permit will immediately be released"]
+#[clippy::has_significant_drop]
+#[unstable(feature = "sync_semaphore", issue = "none")]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl Semaphore {
+    /// The largest number of permits a semaphore can hold.
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub const MAX_PERMITS: usize = sys::SEMAPHORE_MAX_PERMITS;
+
+    /// Creates a new semaphore holding `permits` permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` is greater than [`Semaphore::MAX_PERMITS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(sync_semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(3);
+    /// assert_eq!(semaphore.available_permits(), 3);
+    /// ```
+    #[inline]
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub const fn new(permits: usize) -> Semaphore {
+        Semaphore { inner: sys::Semaphore::new(permits) }
+    }
+
+    /// Takes a permit, blocking the current thread until one is available.
+    ///
+    /// The permit is given back when the returned [`SemaphorePermit`] is
+    /// dropped.
+    ///
+    /// This function does not provide any guarantees with respect to the
+    /// order in which waiting threads get a permit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(sync_semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(1);
+    /// let permit = semaphore.acquire();
+    /// assert_eq!(semaphore.available_permits(), 0);
+    /// drop(permit);
+    /// assert_eq!(semaphore.available_permits(), 1);
+    /// ```
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        self.inner.acquire();
+        SemaphorePermit { semaphore: self }
+    }
+
+    /// Attempts to take a permit without blocking.
+    ///
+    /// Returns `None` if no permit is available at this time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(sync_semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(1);
+    /// let permit = semaphore.try_acquire().unwrap();
+    /// assert!(semaphore.try_acquire().is_none());
+    /// drop(permit);
+    /// assert!(semaphore.try_acquire().is_some());
+    /// ```
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.inner.try_acquire().then(|| SemaphorePermit { semaphore: self })
+    }
+
+    /// Takes a permit, blocking the current thread for at most `timeout`.
+    ///
+    /// Returns `None` if no permit became available before the timeout
+    /// elapsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(sync_semaphore)]
+    /// use std::sync::Semaphore;
+    /// use std::time::Duration;
+    ///
+    /// let semaphore = Semaphore::new(0);
+    /// assert!(semaphore.acquire_timeout(Duration::from_millis(10)).is_none());
+    /// ```
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub fn acquire_timeout(&self, timeout: Duration) -> Option<SemaphorePermit<'_>> {
+        self.inner.acquire_timeout(timeout).then(|| SemaphorePermit { semaphore: self })
+    }
+
+    /// Adds `permits` permits to the semaphore, waking up threads waiting for
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the semaphore would hold more than [`Semaphore::MAX_PERMITS`]
+    /// permits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(sync_semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(0);
+    /// semaphore.release(2);
+    /// assert_eq!(semaphore.available_permits(), 2);
+    /// ```
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub fn release(&self, permits: usize) {
+        self.inner.release(permits)
+    }
+
+    /// Returns the number of permits which are currently available.
+    ///
+    /// Other threads may take or release permits at any time, so the result
+    /// should not be trusted for program correctness without additional
+    /// synchronization.
+    #[inline]
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub fn available_permits(&self) -> usize {
+        self.inner.available_permits()
+    }
+}
+
+#[unstable(feature = "sync_semaphore", issue = "none")]
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore").field("available_permits", &self.available_permits()).finish()
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Keeps the permit taken, instead of giving it back to the semaphore.
+    ///
+    /// This permanently reduces the number of permits held by the semaphore,
+    /// until more are added with [`Semaphore::release`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(sync_semaphore)]
+    /// use std::sync::Semaphore;
+    ///
+    /// let semaphore = Semaphore::new(2);
+    /// semaphore.acquire().forget();
+    /// assert_eq!(semaphore.available_permits(), 1);
+    /// ```
+    #[unstable(feature = "sync_semaphore", issue = "none")]
+    pub fn forget(self) {
+        crate::mem::forget(self)
+    }
+}
+
+#[unstable(feature = "sync_semaphore", issue = "none")]
+impl Drop for SemaphorePermit<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.semaphore.inner.release(1);
+    }
+}
+
+#[unstable(feature = "sync_semaphore", issue = "none")]
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit").finish_non_exhaustive()
+    }
+}
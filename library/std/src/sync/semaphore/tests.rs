@@ -0,0 +1,83 @@
+use crate::sync::Semaphore;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+#[test]
+fn smoke() {
+    let semaphore = Semaphore::new(2);
+    let a = semaphore.acquire();
+    let b = semaphore.try_acquire().unwrap();
+    assert!(semaphore.try_acquire().is_none());
+    drop(a);
+    assert_eq!(semaphore.available_permits(), 1);
+    drop(b);
+    assert_eq!(semaphore.available_permits(), 2);
+}
+
+#[test]
+fn release_and_forget() {
+    let semaphore = Semaphore::new(0);
+    assert!(semaphore.try_acquire().is_none());
+    semaphore.release(3);
+    assert_eq!(semaphore.available_permits(), 3);
+    semaphore.acquire().forget();
+    semaphore.try_acquire().unwrap().forget();
+    assert_eq!(semaphore.available_permits(), 1);
+}
+
+#[test]
+#[should_panic]
+fn release_too_many() {
+    let semaphore = Semaphore::new(Semaphore::MAX_PERMITS);
+    semaphore.release(1);
+}
+
+#[test]
+fn acquire_timeout() {
+    let semaphore = Semaphore::new(0);
+    let start = Instant::now();
+    assert!(semaphore.acquire_timeout(Duration::from_millis(10)).is_none());
+    assert!(start.elapsed() >= Duration::from_millis(10));
+
+    semaphore.release(1);
+    assert!(semaphore.acquire_timeout(Duration::from_millis(10)).is_some());
+    assert!(semaphore.acquire_timeout(Duration::MAX).is_some());
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn wakes_waiters() {
+    let semaphore = Semaphore::new(0);
+    thread::scope(|s| {
+        let waiters: Vec<_> = (0..4).map(|_| s.spawn(|| semaphore.acquire().forget())).collect();
+        let timed =
+            s.spawn(|| semaphore.acquire_timeout(Duration::from_secs(60)).map(|p| p.forget()));
+        semaphore.release(5);
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert!(timed.join().unwrap().is_some());
+    });
+    assert_eq!(semaphore.available_permits(), 0);
+}
+
+#[test]
+#[cfg_attr(any(target_os = "emscripten", target_os = "wasi"), ignore)] // no threads
+fn limits_concurrency() {
+    const N: usize = 3;
+    let semaphore = Semaphore::new(N);
+    let busy = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    let _permit = semaphore.acquire();
+                    assert!(busy.fetch_add(1, Ordering::Relaxed) < N);
+                    busy.fetch_sub(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    assert_eq!(semaphore.available_permits(), N);
+}
@@ -3,6 +3,7 @@
 
 use crate::backtrace_rs::{self, BacktraceFmt, BytesOrWideString, PrintFmt};
 use crate::borrow::Cow;
+use crate::ffi::c_void;
 use crate::io::prelude::*;
 use crate::path::{self, Path, PathBuf};
 use crate::sync::{Mutex, MutexGuard, PoisonError};
@@ -214,6 +215,47 @@ pub fn output_filename(
     fmt::Display::fmt(&file.display(), fmt)
 }
 
+/// Returns the address at which the module containing `ip` is loaded, on the platforms where
+/// the unwinder doesn't report it.
+#[cfg(all(
+    any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly",
+        target_vendor = "apple",
+    ),
+    not(miri),
+))]
+pub fn module_base_address(ip: *mut c_void) -> Option<*mut c_void> {
+    let mut info = crate::mem::MaybeUninit::<libc::Dl_info>::uninit();
+    // SAFETY: `dladdr` only writes to `info`, which is only read if it succeeded.
+    if unsafe { libc::dladdr(ip.cast_const(), info.as_mut_ptr()) } == 0 {
+        return None;
+    }
+    // SAFETY: `dladdr` succeeded, so it initialized `info`.
+    let base = unsafe { info.assume_init() }.dli_fbase;
+    (!base.is_null()).then_some(base)
+}
+
+#[cfg(not(all(
+    any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly",
+        target_vendor = "apple",
+    ),
+    not(miri),
+)))]
+pub fn module_base_address(_ip: *mut c_void) -> Option<*mut c_void> {
+    None
+}
+
 #[cfg(all(target_vendor = "fortanix", target_env = "sgx"))]
 pub fn set_image_base() {
     let image_base = crate::os::fortanix_sgx::mem::image_base();
@@ -15,6 +15,7 @@
 pub mod os_str;
 pub mod path;
 pub mod random;
+pub mod stdio_redirect;
 pub mod sync;
 pub mod thread_local;
 
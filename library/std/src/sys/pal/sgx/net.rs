@@ -447,6 +447,186 @@ fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+pub struct TcpSocket(!);
+
+impl TcpSocket {
+    pub fn new(_: &SocketAddr) -> io::Result<TcpSocket> {
+        unsupported()
+    }
+
+    pub fn bind(&self, _: &SocketAddr) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn connect(self, _: &SocketAddr) -> io::Result<TcpStream> {
+        self.0
+    }
+
+    pub fn connect_timeout(self, _: &SocketAddr, _: Duration) -> io::Result<TcpStream> {
+        self.0
+    }
+
+    pub fn listen(self, _: u32) -> io::Result<TcpListener> {
+        self.0
+    }
+
+    pub fn socket_addr(&self) -> io::Result<SocketAddr> {
+        self.0
+    }
+
+    pub fn set_reuseaddr(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_reuseport(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_only_v6(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_send_buffer_size(&self, _: usize) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.0
+    }
+
+    pub fn set_recv_buffer_size(&self, _: usize) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.0
+    }
+
+    pub fn set_nodelay(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_keepalive(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn keepalive(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_keepalive_time(&self, _: Duration) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn keepalive_time(&self) -> io::Result<Duration> {
+        self.0
+    }
+
+    pub fn set_keepalive_interval(&self, _: Duration) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        self.0
+    }
+
+    pub fn set_keepalive_retries(&self, _: u32) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        self.0
+    }
+}
+
+impl fmt::Debug for TcpSocket {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+    }
+}
+
+pub struct UdpSocketBuilder(!);
+
+impl UdpSocketBuilder {
+    pub fn new(_: &SocketAddr) -> io::Result<UdpSocketBuilder> {
+        unsupported()
+    }
+
+    pub fn bind(self, _: &SocketAddr) -> io::Result<UdpSocket> {
+        self.0
+    }
+
+    pub fn set_reuseaddr(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_reuseport(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_only_v6(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.0
+    }
+
+    pub fn set_send_buffer_size(&self, _: usize) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.0
+    }
+
+    pub fn set_recv_buffer_size(&self, _: usize) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.0
+    }
+
+    pub fn set_broadcast(&self, _: bool) -> io::Result<()> {
+        self.0
+    }
+
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.0
+    }
+}
+
+impl fmt::Debug for UdpSocketBuilder {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+    }
+}
+
 #[derive(Debug)]
 pub struct NonIpSockAddr {
     host: String,
@@ -9,7 +9,9 @@
 use crate::sync::Arc;
 use crate::sys::time::SystemTime;
 use crate::sys::unsupported;
-pub use crate::sys_common::fs::exists;
+pub use crate::sys_common::fs::{
+    WalkDirHandle, exists, rename_exchange, rename_noreplace, sync_dir,
+};
 use crate::sys_common::ignore_notfound;
 
 /// A file descriptor.
@@ -350,6 +352,26 @@ pub fn datasync(&self) -> io::Result<()> {
         self.flush()
     }
 
+    pub fn lock(&self) -> io::Result<()> {
+        unsupported()
+    }
+
+    pub fn lock_shared(&self) -> io::Result<()> {
+        unsupported()
+    }
+
+    pub fn try_lock(&self) -> io::Result<bool> {
+        unsupported()
+    }
+
+    pub fn try_lock_shared(&self) -> io::Result<bool> {
+        unsupported()
+    }
+
+    pub fn unlock(&self) -> io::Result<()> {
+        unsupported()
+    }
+
     pub fn truncate(&self, _size: u64) -> io::Result<()> {
         unsupported()
     }
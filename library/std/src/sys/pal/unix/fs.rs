@@ -85,7 +85,7 @@
 use crate::sys::common::small_c_string::run_path_with_cstr;
 use crate::sys::fd::FileDesc;
 use crate::sys::time::SystemTime;
-#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[cfg(target_os = "linux")]
 use crate::sys::weak::syscall;
 #[cfg(target_os = "android")]
 use crate::sys::weak::weak;
@@ -1254,6 +1254,153 @@ unsafe fn os_datasync(fd: c_int) -> c_int {
         }
     }
 
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    ))]
+    pub fn lock(&self) -> io::Result<()> {
+        cvt_r(|| unsafe { libc::flock(self.as_raw_fd(), libc::LOCK_EX) })?;
+        return Ok(());
+    }
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    )))]
+    pub fn lock(&self) -> io::Result<()> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "lock() not supported"))
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    ))]
+    pub fn lock_shared(&self) -> io::Result<()> {
+        cvt_r(|| unsafe { libc::flock(self.as_raw_fd(), libc::LOCK_SH) })?;
+        return Ok(());
+    }
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    )))]
+    pub fn lock_shared(&self) -> io::Result<()> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "lock_shared() not supported"))
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    ))]
+    pub fn try_lock(&self) -> io::Result<bool> {
+        let result = cvt(unsafe { libc::flock(self.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) });
+        if let Err(ref err) = result {
+            if err.kind() == io::ErrorKind::WouldBlock {
+                return Ok(false);
+            }
+        }
+        result?;
+        return Ok(true);
+    }
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    )))]
+    pub fn try_lock(&self) -> io::Result<bool> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "try_lock() not supported"))
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    ))]
+    pub fn try_lock_shared(&self) -> io::Result<bool> {
+        let result = cvt(unsafe { libc::flock(self.as_raw_fd(), libc::LOCK_SH | libc::LOCK_NB) });
+        if let Err(ref err) = result {
+            if err.kind() == io::ErrorKind::WouldBlock {
+                return Ok(false);
+            }
+        }
+        result?;
+        return Ok(true);
+    }
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    )))]
+    pub fn try_lock_shared(&self) -> io::Result<bool> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "try_lock_shared() not supported"))
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    ))]
+    pub fn unlock(&self) -> io::Result<()> {
+        cvt(unsafe { libc::flock(self.as_raw_fd(), libc::LOCK_UN) })?;
+        return Ok(());
+    }
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_vendor = "apple",
+    )))]
+    pub fn unlock(&self) -> io::Result<()> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "unlock() not supported"))
+    }
+
     pub fn truncate(&self, size: u64) -> io::Result<()> {
         let size: off64_t =
             size.try_into().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
@@ -1693,6 +1840,68 @@ pub fn rename(old: &Path, new: &Path) -> io::Result<()> {
     })
 }
 
+/// Renames `old` to `new`, failing if `new` already exists.
+pub fn rename_noreplace(old: &Path, new: &Path) -> io::Result<()> {
+    run_path_with_cstr(old, &|old| {
+        run_path_with_cstr(new, &|new| {
+            #[cfg(target_os = "linux")]
+            if let Some(result) = try_renameat2(old, new, libc::RENAME_NOREPLACE) {
+                return result;
+            }
+            // Creating a hard link fails if the destination exists.
+            cvt(unsafe { libc::link(old.as_ptr(), new.as_ptr()) })?;
+            cvt(unsafe { libc::unlink(old.as_ptr()) }).map(|_| ())
+        })
+    })
+}
+
+/// Atomically exchanges `old` and `new`, which must both exist.
+pub fn rename_exchange(old: &Path, new: &Path) -> io::Result<()> {
+    run_path_with_cstr(old, &|old| {
+        run_path_with_cstr(new, &|new| {
+            #[cfg(target_os = "linux")]
+            if let Some(result) = try_renameat2(old, new, libc::RENAME_EXCHANGE) {
+                return result;
+            }
+            let _ = (old, new);
+            Err(io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "exchanging files is not supported on this platform",
+            ))
+        })
+    })
+}
+
+/// Calls `renameat2`, or returns `None` if the kernel or the filesystem doesn't support it.
+#[cfg(target_os = "linux")]
+fn try_renameat2(old: &CStr, new: &CStr, flags: libc::c_uint) -> Option<io::Result<()>> {
+    syscall! {
+        fn renameat2(
+            olddirfd: c_int,
+            oldpath: *const libc::c_char,
+            newdirfd: c_int,
+            newpath: *const libc::c_char,
+            flags: libc::c_uint
+        ) -> c_int
+    }
+
+    match cvt(unsafe {
+        renameat2(libc::AT_FDCWD, old.as_ptr(), libc::AT_FDCWD, new.as_ptr(), flags)
+    }) {
+        Ok(_) => Some(Ok(())),
+        // Older kernels lack the syscall, and not all filesystems support the flags.
+        Err(err) if matches!(err.raw_os_error(), Some(libc::ENOSYS | libc::EINVAL)) => None,
+        Err(err) => Some(Err(err)),
+    }
+}
+
+/// Flushes the entries of the directory at `p`, such as a file renamed into it, to disk.
+pub fn sync_dir(p: &Path) -> io::Result<()> {
+    let mut opts = OpenOptions::new();
+    opts.read(true);
+    File::open(p, &opts)?.fsync()
+}
+
 pub fn set_perm(p: &Path, perm: FilePermissions) -> io::Result<()> {
     run_path_with_cstr(p, &|p| cvt_r(|| unsafe { libc::chmod(p.as_ptr(), perm.mode) }).map(|_| ()))
 }
@@ -2162,3 +2371,189 @@ pub fn remove_dir_all(p: &Path) -> io::Result<()> {
         remove_dir_all_modern(p)
     }
 }
+
+pub use walk_dir_impl::WalkDirHandle;
+
+// Fallback for REDOX, ESP-ID, Horizon, Vita, Vxworks, L4Re and Miri
+#[cfg(any(
+    target_os = "redox",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita",
+    target_os = "nto",
+    target_os = "vxworks",
+    target_os = "l4re",
+    miri
+))]
+mod walk_dir_impl {
+    pub use crate::sys_common::fs::WalkDirHandle;
+}
+
+// Modern implementation using openat(), fstatat() and fdopendir(), which resolves every entry
+// relative to an open descriptor of its parent directory so that the walk cannot be redirected
+// by a concurrent rename or by a directory being swapped for a symlink.
+#[cfg(not(any(
+    target_os = "redox",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita",
+    target_os = "nto",
+    target_os = "vxworks",
+    target_os = "l4re",
+    miri
+)))]
+mod walk_dir_impl {
+    #[cfg(not(any(
+        all(target_os = "linux", not(target_env = "musl")),
+        target_os = "emscripten",
+        target_os = "hurd"
+    )))]
+    use libc::fstatat as fstatat64;
+    #[cfg(any(
+        all(target_os = "linux", not(target_env = "musl")),
+        target_os = "emscripten",
+        target_os = "hurd"
+    ))]
+    use libc::fstatat64;
+    #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+    use libc::{fdopendir, openat};
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    use libc::{fdopendir, openat64 as openat};
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    use super::try_statx;
+    use super::{Dir, DirEntry, File, FileAttr, FileType, InnerReadDir, ReadDir, stat64};
+    use crate::ffi::{CStr, OsStr, OsString};
+    use crate::os::unix::io::{AsFd, AsRawFd, FromRawFd, IntoRawFd, RawFd};
+    use crate::path::{Path, PathBuf};
+    use crate::sys::common::small_c_string::run_path_with_cstr;
+    use crate::sys::fd::FileDesc;
+    use crate::sys::{cvt, cvt_r};
+    use crate::sys_common::{AsInner, FromInner};
+    use crate::{fs, io, mem};
+
+    /// An open directory visited by `fs::walk_dir`.
+    pub struct WalkDirHandle {
+        dir: File,
+        dev: u64,
+        ino: u64,
+    }
+
+    fn openat_dironly(
+        parent_fd: Option<RawFd>,
+        p: &CStr,
+        follow_links: bool,
+    ) -> io::Result<WalkDirHandle> {
+        let mut flags = libc::O_CLOEXEC | libc::O_RDONLY | libc::O_DIRECTORY;
+        if !follow_links {
+            flags |= libc::O_NOFOLLOW;
+        }
+        let fd =
+            cvt_r(|| unsafe { openat(parent_fd.unwrap_or(libc::AT_FDCWD), p.as_ptr(), flags) })?;
+        let dir = File::from_inner(unsafe { FileDesc::from_raw_fd(fd) });
+        let stat = dir.file_attr()?;
+        let stat = stat.as_inner();
+        Ok(WalkDirHandle { dev: stat.st_dev as u64, ino: stat.st_ino as u64, dir })
+    }
+
+    #[cfg(any(
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+        target_os = "vxworks",
+        target_os = "aix",
+    ))]
+    fn file_type(_ent: &DirEntry) -> Option<FileType> {
+        None
+    }
+
+    #[cfg(not(any(
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+        target_os = "vxworks",
+        target_os = "aix",
+    )))]
+    fn file_type(ent: &DirEntry) -> Option<FileType> {
+        // Unlike `DirEntry::file_type`, never fall back to `lstat`: the entries read here don't
+        // know the path of their directory.
+        let mode = match ent.entry.d_type {
+            libc::DT_CHR => libc::S_IFCHR,
+            libc::DT_FIFO => libc::S_IFIFO,
+            libc::DT_LNK => libc::S_IFLNK,
+            libc::DT_REG => libc::S_IFREG,
+            libc::DT_SOCK => libc::S_IFSOCK,
+            libc::DT_DIR => libc::S_IFDIR,
+            libc::DT_BLK => libc::S_IFBLK,
+            _ => return None,
+        };
+        Some(FileType { mode })
+    }
+
+    impl WalkDirHandle {
+        /// Opens the directory the walk starts from. A symlink at `path` itself is always
+        /// followed.
+        pub fn open_root(path: &Path, _follow_links: bool) -> io::Result<WalkDirHandle> {
+            run_path_with_cstr(path, &|p| openat_dironly(None, p, true))
+        }
+
+        /// Opens the subdirectory `name` of this directory, failing if it is a symlink unless
+        /// `follow_links` is set.
+        pub fn open_child(&self, name: &OsStr, follow_links: bool) -> io::Result<WalkDirHandle> {
+            run_path_with_cstr(name.as_ref(), &|p| {
+                openat_dironly(Some(self.dir.as_raw_fd()), p, follow_links)
+            })
+        }
+
+        /// Reads the names of the entries of this directory, along with their file types when
+        /// the platform provides them without an extra call.
+        pub fn entries(&self) -> io::Result<Vec<(OsString, Option<fs::FileType>)>> {
+            // The directory stream takes ownership of the descriptor it is created from, and
+            // `self.dir` must stay usable for `openat` and `fstatat`, so hand it a duplicate.
+            let fd = self.dir.as_fd().try_clone_to_owned()?;
+            let ptr = unsafe { fdopendir(fd.as_raw_fd()) };
+            if ptr.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let dirp = Dir(ptr);
+            // file descriptor is automatically closed by libc::closedir() now, so give up ownership
+            let _ = fd.into_raw_fd();
+            // a valid root is not needed because we do not call any functions involving the full path
+            // of the `DirEntry`s.
+            let inner = InnerReadDir { dirp, root: PathBuf::new() };
+            ReadDir::new(inner)
+                .map(|ent| {
+                    let ent = ent?;
+                    Ok((ent.file_name(), file_type(&ent).map(fs::FileType::from_inner)))
+                })
+                .collect()
+        }
+
+        /// Queries the metadata of the entry `name` of this directory.
+        pub fn child_metadata(&self, name: &OsStr, follow_links: bool) -> io::Result<fs::Metadata> {
+            let fd = self.dir.as_raw_fd();
+            let flags = if follow_links { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+            run_path_with_cstr(name.as_ref(), &|name| {
+                cfg_has_statx! {
+                    if let Some(ret) = unsafe { try_statx(
+                        fd,
+                        name.as_ptr(),
+                        flags | libc::AT_STATX_SYNC_AS_STAT,
+                        libc::STATX_BASIC_STATS | libc::STATX_BTIME,
+                    ) } {
+                        return ret.map(fs::Metadata::from_inner);
+                    }
+                }
+
+                let mut stat: stat64 = unsafe { mem::zeroed() };
+                cvt(unsafe { fstatat64(fd, name.as_ptr(), &mut stat, flags) })?;
+                Ok(fs::Metadata::from_inner(FileAttr::from_stat64(stat)))
+            })
+        }
+
+        /// Returns whether both handles refer to the same directory.
+        pub fn is_same_dir(&self, other: &WalkDirHandle) -> bool {
+            self.dev == other.dev && self.ino == other.ino
+        }
+    }
+}
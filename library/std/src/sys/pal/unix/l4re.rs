@@ -525,6 +525,190 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         }
     }
 
+    pub struct TcpSocket {
+        inner: Socket,
+    }
+
+    impl TcpSocket {
+        pub fn new(_: &SocketAddr) -> io::Result<TcpSocket> {
+            unimpl!();
+        }
+
+        pub fn bind(&self, _: &SocketAddr) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn connect(self, _: &SocketAddr) -> io::Result<TcpStream> {
+            unimpl!();
+        }
+
+        pub fn connect_timeout(self, _: &SocketAddr, _: Duration) -> io::Result<TcpStream> {
+            unimpl!();
+        }
+
+        pub fn listen(self, _: u32) -> io::Result<TcpListener> {
+            unimpl!();
+        }
+
+        pub fn socket_addr(&self) -> io::Result<SocketAddr> {
+            unimpl!();
+        }
+
+        pub fn set_reuseaddr(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn reuseaddr(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_reuseport(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn reuseport(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_only_v6(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn only_v6(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_send_buffer_size(&self, _: usize) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn send_buffer_size(&self) -> io::Result<usize> {
+            unimpl!();
+        }
+
+        pub fn set_recv_buffer_size(&self, _: usize) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn recv_buffer_size(&self) -> io::Result<usize> {
+            unimpl!();
+        }
+
+        pub fn set_nodelay(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn nodelay(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_keepalive(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn keepalive(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_keepalive_time(&self, _: Duration) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn keepalive_time(&self) -> io::Result<Duration> {
+            unimpl!();
+        }
+
+        pub fn set_keepalive_interval(&self, _: Duration) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn keepalive_interval(&self) -> io::Result<Duration> {
+            unimpl!();
+        }
+
+        pub fn set_keepalive_retries(&self, _: u32) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn keepalive_retries(&self) -> io::Result<u32> {
+            unimpl!();
+        }
+    }
+
+    impl fmt::Debug for TcpSocket {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "No networking support available on L4Re.")
+        }
+    }
+
+    pub struct UdpSocketBuilder {
+        inner: Socket,
+    }
+
+    impl UdpSocketBuilder {
+        pub fn new(_: &SocketAddr) -> io::Result<UdpSocketBuilder> {
+            unimpl!();
+        }
+
+        pub fn bind(self, _: &SocketAddr) -> io::Result<UdpSocket> {
+            unimpl!();
+        }
+
+        pub fn set_reuseaddr(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn reuseaddr(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_reuseport(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn reuseport(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_only_v6(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn only_v6(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+
+        pub fn set_send_buffer_size(&self, _: usize) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn send_buffer_size(&self) -> io::Result<usize> {
+            unimpl!();
+        }
+
+        pub fn set_recv_buffer_size(&self, _: usize) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn recv_buffer_size(&self) -> io::Result<usize> {
+            unimpl!();
+        }
+
+        pub fn set_broadcast(&self, _: bool) -> io::Result<()> {
+            unimpl!();
+        }
+
+        pub fn broadcast(&self) -> io::Result<bool> {
+            unimpl!();
+        }
+    }
+
+    impl fmt::Debug for UdpSocketBuilder {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "No networking support available on L4Re.")
+        }
+    }
+
     pub struct LookupHost {
         original: *mut libc::addrinfo,
         cur: *mut libc::addrinfo,
@@ -4,6 +4,7 @@
 use crate::sys::pal::unix::fd::FileDesc;
 use crate::sys::process::ExitStatus;
 use crate::sys_common::{AsInner, FromInner, IntoInner};
+use crate::time::{Duration, Instant};
 
 #[cfg(test)]
 mod tests;
@@ -12,12 +13,26 @@
 pub(crate) struct PidFd(FileDesc);
 
 impl PidFd {
+    /// Opens a pidfd referring to the process `pid`.
+    ///
+    /// The caller must make sure that `pid` can't have been recycled, e.g. because it belongs to
+    /// a child that hasn't been reaped yet.
+    pub fn open(pid: libc::pid_t) -> io::Result<PidFd> {
+        // pidfd_open sets CLOEXEC by default
+        let fd = cvt(unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) } as libc::c_int)?;
+        Ok(unsafe { PidFd::from_raw_fd(fd) })
+    }
+
     pub fn kill(&self) -> io::Result<()> {
+        self.send_signal(libc::SIGKILL)
+    }
+
+    pub fn send_signal(&self, signal: i32) -> io::Result<()> {
         cvt(unsafe {
             libc::syscall(
                 libc::SYS_pidfd_send_signal,
                 self.0.as_raw_fd(),
-                libc::SIGKILL,
+                signal,
                 crate::ptr::null::<()>(),
                 0,
             )
@@ -25,6 +40,34 @@ pub fn kill(&self) -> io::Result<()> {
         .map(drop)
     }
 
+    /// Waits for at most `timeout` for the process to exit, without reaping it.
+    ///
+    /// Returns whether the process has exited.
+    pub fn poll_exit(&self, timeout: Duration) -> io::Result<bool> {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            // Round up, so that the deadline has passed once `poll` times out.
+            let millis = remaining.as_nanos().div_ceil(1_000_000).min(libc::c_int::MAX as u128);
+            let mut pollfd =
+                libc::pollfd { fd: self.0.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+            match cvt(unsafe { libc::poll(&mut pollfd, 1, millis as libc::c_int) }) {
+                Ok(0) => {
+                    // `poll` may have been given a shorter timeout than what's left.
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        return Ok(false);
+                    }
+                }
+                Ok(_) => return Ok(true),
+                Err(e) if e.is_interrupted() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     pub fn wait(&self) -> io::Result<ExitStatus> {
         let mut siginfo: libc::siginfo_t = unsafe { crate::mem::zeroed() };
         cvt(unsafe {
@@ -50,6 +93,10 @@ pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::from_waitid_siginfo(siginfo)))
         }
     }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        if self.poll_exit(timeout)? { self.try_wait() } else { Ok(None) }
+    }
 }
 
 impl AsInner<FileDesc> for PidFd {
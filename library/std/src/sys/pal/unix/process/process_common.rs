@@ -14,7 +14,8 @@
 use crate::sys::pipe::{self, AnonPipe};
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
 use crate::sys_common::{FromInner, IntoInner};
-use crate::{fmt, io, ptr};
+use crate::time::{Duration, Instant};
+use crate::{fmt, io, ptr, thread};
 
 cfg_if::cfg_if! {
     if #[cfg(target_os = "fuchsia")] {
@@ -104,6 +105,7 @@ pub struct Command {
     #[cfg(target_os = "linux")]
     create_pidfd: bool,
     pgroup: Option<pid_t>,
+    setsid: bool,
 }
 
 // Create a new type for argv, so that we can make it `Send` and `Sync`
@@ -196,6 +198,7 @@ pub fn new(program: &OsStr) -> Command {
             stdout: None,
             stderr: None,
             pgroup: None,
+            setsid: false,
         }
     }
 
@@ -221,6 +224,7 @@ pub fn new(program: &OsStr) -> Command {
             stderr: None,
             create_pidfd: false,
             pgroup: None,
+            setsid: false,
         }
     }
 
@@ -259,6 +263,9 @@ pub fn groups(&mut self, groups: &[gid_t]) {
     pub fn pgroup(&mut self, pgroup: pid_t) {
         self.pgroup = Some(pgroup);
     }
+    pub fn setsid(&mut self, setsid: bool) {
+        self.setsid = setsid;
+    }
 
     #[cfg(target_os = "linux")]
     pub fn create_pidfd(&mut self, val: bool) {
@@ -331,6 +338,10 @@ pub fn get_groups(&self) -> Option<&[gid_t]> {
     pub fn get_pgroup(&self) -> Option<pid_t> {
         self.pgroup
     }
+    #[allow(dead_code)]
+    pub fn get_setsid(&self) -> bool {
+        self.setsid
+    }
 
     pub fn get_closures(&mut self) -> &mut Vec<Box<dyn FnMut() -> io::Result<()> + Send + Sync>> {
         &mut self.closures
@@ -442,6 +453,36 @@ fn construct_envp(env: BTreeMap<OsString, OsString>, saw_nul: &mut bool) -> CStr
     result
 }
 
+/// Waits for a child to exit by calling `try_wait` with increasing sleeps in between, until
+/// `timeout` elapses.
+///
+/// This is used where there's nothing to block on until a child exits. Relying on `SIGCHLD`
+/// instead would interfere with the signal handlers of the application.
+#[allow(dead_code)]
+pub fn wait_timeout_polling<S>(
+    timeout: Duration,
+    mut try_wait: impl FnMut() -> io::Result<Option<S>>,
+) -> io::Result<Option<S>> {
+    let deadline = Instant::now().checked_add(timeout);
+    let mut sleep = Duration::from_millis(1);
+    loop {
+        if let Some(status) = try_wait()? {
+            return Ok(Some(status));
+        }
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(None);
+                }
+                thread::sleep(sleep.min(deadline - now));
+            }
+            None => thread::sleep(sleep),
+        }
+        sleep = (sleep * 2).min(Duration::from_millis(50));
+    }
+}
+
 impl Stdio {
     pub fn to_child_stdio(&self, readable: bool) -> io::Result<(ChildStdio, Option<AnonPipe>)> {
         match *self {
@@ -572,6 +613,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             if self.pgroup.is_some() {
                 debug_command.field("pgroup", &self.pgroup);
             }
+            if self.setsid {
+                debug_command.field("setsid", &self.setsid);
+            }
 
             #[cfg(target_os = "linux")]
             {
@@ -134,6 +134,32 @@ fn test_process_group_no_posix_spawn() {
     }
 }
 
+#[test]
+#[cfg_attr(
+    any(
+        // See test_process_mask
+        target_os = "macos",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+    ),
+    ignore
+)]
+fn test_setsid() {
+    // Hang-y cat again, in a new session, which means it leads a new process group too.
+    let mut cmd = Command::new(OsStr::new("cat"));
+    cmd.setsid(true);
+    cmd.stdin(Stdio::MakePipe);
+    cmd.stdout(Stdio::MakePipe);
+    let (mut cat, _pipes) = t!(cmd.spawn(Stdio::Null, true));
+
+    let pid = cat.id() as libc::pid_t;
+    assert_eq!(unsafe { libc::getsid(pid) }, pid);
+    t!(cat.send_signal_group(libc::SIGINT));
+
+    assert_eq!(t!(cat.wait()).signal(), Some(libc::SIGINT));
+}
+
 #[test]
 fn test_program_kind() {
     let vectors = &[
@@ -3,6 +3,7 @@
 use crate::num::NonZero;
 use crate::sys::process::process_common::*;
 use crate::sys::process::zircon::{Handle, zx_handle_t};
+use crate::time::Duration;
 use crate::{fmt, io, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -161,6 +162,14 @@ pub fn kill(&mut self) -> io::Result<()> {
         Ok(())
     }
 
+    pub fn send_signal(&self, _signal: i32) -> io::Result<()> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "signals are not supported on Fuchsia"))
+    }
+
+    pub fn send_signal_group(&self, signal: i32) -> io::Result<()> {
+        self.send_signal(signal)
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use crate::sys::process::zircon::*;
 
@@ -229,6 +238,10 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
         Ok(Some(ExitStatus(proc_info.return_code)))
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        wait_timeout_polling(timeout, || self.try_wait())
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
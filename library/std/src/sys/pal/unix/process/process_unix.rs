@@ -16,6 +16,7 @@
 #[cfg(target_os = "linux")]
 use crate::sys::pal::unix::linux::pidfd::PidFd;
 use crate::sys::process::process_common::*;
+use crate::time::Duration;
 use crate::{fmt, mem, sys};
 
 cfg_if::cfg_if! {
@@ -23,7 +24,6 @@
     if #[cfg(any(target_env = "nto70", target_env = "nto71"))] {
         use crate::thread;
         use libc::{c_char, posix_spawn_file_actions_t, posix_spawnattr_t};
-        use crate::time::Duration;
         use crate::sync::LazyLock;
         // Get smallest amount of time we can sleep.
         // Return a common value if it cannot be determined.
@@ -339,6 +339,10 @@ unsafe fn do_exec(
             cvt(libc::chdir(cwd.as_ptr()))?;
         }
 
+        if self.get_setsid() {
+            cvt(libc::setsid())?;
+        }
+
         if let Some(pgroup) = self.get_pgroup() {
             cvt(libc::setpgid(0, pgroup))?;
         }
@@ -456,6 +460,7 @@ fn posix_spawn(
             || (self.env_saw_path() && !self.program_is_path())
             || !self.get_closures().is_empty()
             || self.get_groups().is_some()
+            || self.get_setsid()
         {
             return Ok(None);
         }
@@ -913,6 +918,10 @@ pub fn id(&self) -> u32 {
     }
 
     pub fn kill(&mut self) -> io::Result<()> {
+        self.send_signal(libc::SIGKILL)
+    }
+
+    pub fn send_signal(&self, signal: i32) -> io::Result<()> {
         // If we've already waited on this process then the pid can be recycled
         // and used for another process, and we probably shouldn't be killing
         // random processes, so return Ok because the process has exited already.
@@ -922,9 +931,15 @@ pub fn kill(&mut self) -> io::Result<()> {
         #[cfg(target_os = "linux")]
         if let Some(pid_fd) = self.pidfd.as_ref() {
             // pidfd_send_signal predates pidfd_open. so if we were able to get an fd then sending signals will work too
-            return pid_fd.kill();
+            return pid_fd.send_signal(signal);
         }
-        cvt(unsafe { libc::kill(self.pid, libc::SIGKILL) }).map(drop)
+        cvt(unsafe { libc::kill(self.pid, signal) }).map(drop)
+    }
+
+    pub fn send_signal_group(&self, signal: i32) -> io::Result<()> {
+        // Unlike the process itself, its group may outlive it. The group ID can't be reused
+        // while any process is left in the group, so this is fine even once it has been waited on.
+        cvt(unsafe { libc::killpg(self.pid, signal) }).map(drop)
     }
 
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
@@ -965,6 +980,33 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            // Without a pidfd from spawning the child, one can still be opened now: the child
+            // hasn't been reaped, so its pid can't have been recycled.
+            let opened;
+            let pid_fd = match self.pidfd.as_ref() {
+                Some(pid_fd) => Some(pid_fd),
+                None => {
+                    opened = PidFd::open(self.pid).ok();
+                    opened.as_ref()
+                }
+            };
+            // Fall back to polling if pidfds aren't supported.
+            if let Some(pid_fd) = pid_fd {
+                if !pid_fd.poll_exit(timeout)? {
+                    return Ok(None);
+                }
+                return self.try_wait();
+            }
+        }
+        wait_timeout_polling(timeout, || self.try_wait())
+    }
 }
 
 /// Unix exit statuses
@@ -6,6 +6,7 @@
 use crate::sys::cvt;
 use crate::sys::pal::unix::thread;
 use crate::sys::process::process_common::*;
+use crate::time::Duration;
 use crate::{fmt, sys};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -146,16 +147,27 @@ pub fn id(&self) -> u32 {
     }
 
     pub fn kill(&mut self) -> io::Result<()> {
+        self.send_signal(libc::SIGKILL)
+    }
+
+    pub fn send_signal(&self, signal: i32) -> io::Result<()> {
         // If we've already waited on this process then the pid can be recycled
         // and used for another process, and we probably shouldn't be killing
         // random processes, so return Ok because the process has exited already.
         if self.status.is_some() {
             Ok(())
         } else {
-            cvt(unsafe { libc::kill(self.pid, libc::SIGKILL) }).map(drop)
+            cvt(unsafe { libc::kill(self.pid, signal) }).map(drop)
         }
     }
 
+    pub fn send_signal_group(&self, _signal: i32) -> io::Result<()> {
+        Err(io::const_io_error!(
+            ErrorKind::Unsupported,
+            "process groups are not supported on this target",
+        ))
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use crate::sys::cvt_r;
         if let Some(status) = self.status {
@@ -180,6 +192,10 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        wait_timeout_polling(timeout, || self.try_wait())
+    }
 }
 
 /// Unix exit statuses
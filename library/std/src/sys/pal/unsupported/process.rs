@@ -6,6 +6,7 @@
 use crate::sys::pipe::AnonPipe;
 use crate::sys::unsupported;
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
+use crate::time::Duration;
 use crate::{fmt, io};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -290,6 +291,10 @@ pub fn wait(&mut self) -> io::Result<ExitStatus> {
     pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         self.0
     }
+
+    pub fn wait_timeout(&mut self, _timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        self.0
+    }
 }
 
 pub struct CommandArgs<'a> {
@@ -24,6 +24,7 @@
 use crate::sys::{cvt, path, stdio};
 use crate::sys_common::IntoInner;
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
+use crate::time::Duration;
 use crate::{cmp, env, fmt, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -709,6 +710,21 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
     }
 
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        unsafe {
+            match c::WaitForSingleObject(self.handle.as_raw_handle(), super::dur2timeout(timeout)) {
+                c::WAIT_OBJECT_0 => {}
+                c::WAIT_TIMEOUT => {
+                    return Ok(None);
+                }
+                _ => return Err(io::Error::last_os_error()),
+            }
+            let mut status = 0;
+            cvt(c::GetExitCodeProcess(self.handle.as_raw_handle(), &mut status))?;
+            Ok(Some(ExitStatus(status)))
+        }
+    }
+
     pub fn handle(&self) -> &Handle {
         &self.handle
     }
@@ -9,6 +9,9 @@
 mod udp;
 pub use udp::*;
 
+mod socket;
+pub use socket::*;
+
 // this structure needs to be synchronized with what's in net/src/api.rs
 #[repr(C)]
 #[derive(Debug)]
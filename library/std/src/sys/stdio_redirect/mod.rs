@@ -0,0 +1,16 @@
+//! Redirection of the standard output and error streams of the process.
+
+#![forbid(unsafe_op_in_unsafe_fn)]
+
+cfg_if::cfg_if! {
+    if #[cfg(unix)] {
+        mod unix;
+        pub use unix::{Redirect, pipe};
+    } else if #[cfg(windows)] {
+        mod windows;
+        pub use windows::{Redirect, pipe};
+    } else {
+        mod unsupported;
+        pub use unsupported::{Redirect, pipe};
+    }
+}
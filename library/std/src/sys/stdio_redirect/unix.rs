@@ -0,0 +1,32 @@
+use crate::fs::File;
+use crate::io;
+use crate::os::fd::{AsRawFd, BorrowedFd, OwnedFd};
+use crate::pipe::PipeReader;
+use crate::sys::cvt_r;
+
+/// A standard stream whose file descriptor points somewhere else, until it is
+/// restored.
+pub struct Redirect {
+    fd: libc::c_int,
+    saved: OwnedFd,
+}
+
+impl Redirect {
+    pub fn new(stderr: bool, target: &File) -> io::Result<Redirect> {
+        let fd = if stderr { libc::STDERR_FILENO } else { libc::STDOUT_FILENO };
+        let saved = unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?;
+        cvt_r(|| unsafe { libc::dup2(target.as_raw_fd(), fd) })?;
+        Ok(Redirect { fd, saved })
+    }
+
+    pub fn restore(self) -> io::Result<()> {
+        cvt_r(|| unsafe { libc::dup2(self.saved.as_raw_fd(), self.fd) })?;
+        Ok(())
+    }
+}
+
+/// Creates a pipe whose write end can be the target of a redirection.
+pub fn pipe() -> io::Result<(PipeReader, File)> {
+    let (reader, writer) = crate::pipe::pipe()?;
+    Ok((reader, File::from(OwnedFd::from(writer))))
+}
@@ -0,0 +1,19 @@
+use crate::fs::File;
+use crate::io;
+use crate::pipe::PipeReader;
+
+pub struct Redirect(!);
+
+impl Redirect {
+    pub fn new(_stderr: bool, _target: &File) -> io::Result<Redirect> {
+        Err(io::Error::UNSUPPORTED_PLATFORM)
+    }
+
+    pub fn restore(self) -> io::Result<()> {
+        self.0
+    }
+}
+
+pub fn pipe() -> io::Result<(PipeReader, File)> {
+    Err(io::Error::UNSUPPORTED_PLATFORM)
+}
@@ -0,0 +1,50 @@
+use crate::fs::File;
+use crate::os::windows::io::{AsHandle, AsRawHandle, OwnedHandle};
+use crate::pipe::PipeReader;
+use crate::sys::{c, cvt};
+use crate::{io, mem};
+
+/// A standard stream whose handle points somewhere else, until it is
+/// restored.
+///
+/// Only the handle returned by `GetStdHandle` is replaced: the file
+/// descriptors of the C runtime keep pointing at the original stream.
+pub struct Redirect {
+    id: c::STD_HANDLE,
+    saved: c::HANDLE,
+    /// The handle the stream points at: a duplicate of the target's, so that
+    /// the target can be closed before the stream is restored.
+    target: OwnedHandle,
+}
+
+// SAFETY: the saved handle is only ever passed back to `SetStdHandle`.
+unsafe impl Send for Redirect {}
+unsafe impl Sync for Redirect {}
+
+impl Redirect {
+    pub fn new(stderr: bool, target: &File) -> io::Result<Redirect> {
+        let id = if stderr { c::STD_ERROR_HANDLE } else { c::STD_OUTPUT_HANDLE };
+        let saved = unsafe { c::GetStdHandle(id) };
+        if saved == c::INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        let target = target.as_handle().try_clone_to_owned()?;
+        cvt(unsafe { c::SetStdHandle(id, target.as_raw_handle()) })?;
+        Ok(Redirect { id, saved, target })
+    }
+
+    pub fn restore(self) -> io::Result<()> {
+        if let Err(err) = cvt(unsafe { c::SetStdHandle(self.id, self.saved) }) {
+            // The stream still points at the duplicate, so it must stay open.
+            mem::forget(self.target);
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+/// Creates a pipe whose write end can be the target of a redirection.
+pub fn pipe() -> io::Result<(PipeReader, File)> {
+    let (reader, writer) = crate::pipe::pipe()?;
+    Ok((reader, File::from(OwnedHandle::from(writer))))
+}
@@ -3,6 +3,7 @@
 mod once;
 mod once_box;
 mod rwlock;
+mod semaphore;
 mod thread_parking;
 
 pub use condvar::Condvar;
@@ -11,4 +12,34 @@
 #[allow(unused)] // Only used on some platforms.
 use once_box::OnceBox;
 pub use rwlock::RwLock;
+pub use semaphore::{MAX_PERMITS as SEMAPHORE_MAX_PERMITS, Semaphore};
 pub use thread_parking::Parker;
+
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+/// Calls `try_lock` until it succeeds or `timeout` elapses, sleeping in between.
+///
+/// This is used to lock with a timeout on the platforms where the lock can't block with a
+/// timeout itself.
+#[allow(unused)] // Only used on some platforms.
+fn poll_lock(timeout: Duration, mut try_lock: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now().checked_add(timeout);
+    let mut sleep = Duration::from_micros(50);
+    loop {
+        if try_lock() {
+            return true;
+        }
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return false;
+                }
+                thread::sleep(sleep.min(deadline - now));
+            }
+            None => thread::sleep(sleep),
+        }
+        sleep = (sleep * 2).min(Duration::from_millis(10));
+    }
+}
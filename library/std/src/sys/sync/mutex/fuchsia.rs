@@ -44,6 +44,8 @@
     ZX_OK, ZX_TIME_INFINITE, zx_futex_wait, zx_futex_wake_single_owner, zx_handle_t,
     zx_thread_self,
 };
+use crate::sys::sync::poll_lock;
+use crate::time::Duration;
 
 // The lowest two bits of a `zx_handle_t` are always set, so the lowest bit is used to mark the
 // mutex as contested by clearing it.
@@ -87,6 +89,10 @@ pub fn try_lock(&self) -> bool {
         self.futex.compare_exchange(UNLOCKED, to_state(thread_self), Acquire, Relaxed).is_ok()
     }
 
+    pub fn try_lock_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_lock())
+    }
+
     #[inline]
     pub fn lock(&self) {
         let thread_self = unsafe { zx_thread_self() };
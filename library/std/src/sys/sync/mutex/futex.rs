@@ -1,5 +1,6 @@
 use crate::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 use crate::sys::futex::{self, futex_wait, futex_wake};
+use crate::time::{Duration, Instant};
 
 type Futex = futex::SmallFutex;
 type State = futex::SmallPrimitive;
@@ -30,8 +31,21 @@ pub fn lock(&self) {
         }
     }
 
+    #[inline]
+    pub fn try_lock_for(&self, timeout: Duration) -> bool {
+        self.try_lock() || self.lock_contended_until(Instant::now().checked_add(timeout))
+    }
+
     #[cold]
     fn lock_contended(&self) {
+        self.lock_contended_until(None);
+    }
+
+    /// Locks the mutex, unless `deadline` is reached first.
+    ///
+    /// Returns whether the mutex was locked.
+    #[cold]
+    fn lock_contended_until(&self, deadline: Option<Instant>) -> bool {
         // Spin first to speed things up if the lock is released quickly.
         let mut state = self.spin();
 
@@ -39,7 +53,7 @@ fn lock_contended(&self) {
         // without marking it as contended.
         if state == UNLOCKED {
             match self.futex.compare_exchange(UNLOCKED, LOCKED, Acquire, Relaxed) {
-                Ok(_) => return, // Locked!
+                Ok(_) => return true, // Locked!
                 Err(s) => state = s,
             }
         }
@@ -50,11 +64,18 @@ fn lock_contended(&self) {
             // to be friendlier for the caches.
             if state != CONTENDED && self.futex.swap(CONTENDED, Acquire) == UNLOCKED {
                 // We changed it from UNLOCKED to CONTENDED, so we just successfully locked it.
-                return;
+                return true;
+            }
+
+            // Give up if the deadline has been reached. Leaving the mutex marked as CONTENDED
+            // only causes an unnecessary wake up when it's unlocked.
+            let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if timeout == Some(Duration::ZERO) {
+                return false;
             }
 
             // Wait for the futex to change state, assuming it is still CONTENDED.
-            futex_wait(&self.futex, CONTENDED, None);
+            futex_wait(&self.futex, CONTENDED, timeout);
 
             // Spin again after waking up.
             state = self.spin();
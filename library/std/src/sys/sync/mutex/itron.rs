@@ -5,6 +5,8 @@
 use crate::sys::pal::itron::abi;
 use crate::sys::pal::itron::error::{ItronError, expect_success, expect_success_aborting, fail};
 use crate::sys::pal::itron::spin::SpinIdOnceCell;
+use crate::sys::sync::poll_lock;
+use crate::time::Duration;
 
 pub struct Mutex {
     /// The ID of the underlying mutex object
@@ -57,6 +59,10 @@ pub fn try_lock(&self) -> bool {
             }
         }
     }
+
+    pub fn try_lock_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_lock())
+    }
 }
 
 impl Drop for Mutex {
@@ -1,4 +1,5 @@
 use crate::cell::Cell;
+use crate::time::Duration;
 
 pub struct Mutex {
     // This platform has no threads, so we can use a Cell here.
@@ -29,4 +30,10 @@ pub unsafe fn unlock(&self) {
     pub fn try_lock(&self) -> bool {
         self.locked.replace(true) == false
     }
+
+    #[inline]
+    pub fn try_lock_for(&self, _timeout: Duration) -> bool {
+        // There is no other thread to unlock it in the meantime.
+        self.try_lock()
+    }
 }
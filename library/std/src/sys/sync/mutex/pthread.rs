@@ -2,7 +2,8 @@
 use crate::io::Error;
 use crate::mem::{MaybeUninit, forget};
 use crate::sys::cvt_nz;
-use crate::sys::sync::OnceBox;
+use crate::sys::sync::{OnceBox, poll_lock};
+use crate::time::Duration;
 
 struct AllocatedMutex(UnsafeCell<libc::pthread_mutex_t>);
 
@@ -133,6 +134,10 @@ pub unsafe fn unlock(&self) {
     pub fn try_lock(&self) -> bool {
         unsafe { libc::pthread_mutex_trylock(self.get()) == 0 }
     }
+
+    pub fn try_lock_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_lock())
+    }
 }
 
 impl Drop for Mutex {
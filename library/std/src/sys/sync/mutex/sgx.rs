@@ -1,5 +1,6 @@
 use crate::sys::pal::waitqueue::{SpinMutex, WaitQueue, WaitVariable, try_lock_or_false};
-use crate::sys::sync::OnceBox;
+use crate::sys::sync::{OnceBox, poll_lock};
+use crate::time::Duration;
 
 pub struct Mutex {
     // FIXME: `UnsafeList` is not movable.
@@ -54,4 +55,8 @@ pub fn try_lock(&self) -> bool {
             true
         }
     }
+
+    pub fn try_lock_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_lock())
+    }
 }
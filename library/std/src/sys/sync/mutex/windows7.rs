@@ -16,6 +16,8 @@
 
 use crate::cell::UnsafeCell;
 use crate::sys::c;
+use crate::sys::sync::poll_lock;
+use crate::time::Duration;
 
 pub struct Mutex {
     srwlock: UnsafeCell<c::SRWLOCK>,
@@ -47,6 +49,10 @@ pub fn try_lock(&self) -> bool {
         unsafe { c::TryAcquireSRWLockExclusive(raw(self)) != 0 }
     }
 
+    pub fn try_lock_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_lock())
+    }
+
     #[inline]
     pub unsafe fn unlock(&self) {
         c::ReleaseSRWLockExclusive(raw(self));
@@ -2,6 +2,8 @@
 use crate::os::xous::services::{TicktimerScalar, ticktimer_server};
 use crate::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 use crate::sync::atomic::{AtomicBool, AtomicUsize};
+use crate::sys::sync::poll_lock;
+use crate::time::Duration;
 
 pub struct Mutex {
     /// The "locked" value indicates how many threads are waiting on this
@@ -93,6 +95,10 @@ pub unsafe fn try_lock(&self) -> bool {
         self.locked.compare_exchange(0, 1, Acquire, Relaxed).is_ok()
     }
 
+    pub unsafe fn try_lock_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || unsafe { self.try_lock() })
+    }
+
     #[inline]
     pub unsafe fn try_lock_or_poison(&self) -> bool {
         self.locked.fetch_add(1, Acquire) == 0
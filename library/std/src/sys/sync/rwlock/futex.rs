@@ -1,5 +1,6 @@
 use crate::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 use crate::sys::futex::{Futex, Primitive, futex_wait, futex_wake, futex_wake_all};
+use crate::time::{Duration, Instant};
 
 pub struct RwLock {
     // The state consists of a 30-bit reader counter, a 'readers waiting' flag, and a 'writers waiting' flag.
@@ -84,6 +85,11 @@ pub fn read(&self) {
         }
     }
 
+    #[inline]
+    pub fn try_read_for(&self, timeout: Duration) -> bool {
+        self.try_read() || self.read_contended_until(Instant::now().checked_add(timeout))
+    }
+
     #[inline]
     pub unsafe fn read_unlock(&self) {
         let state = self.state.fetch_sub(READ_LOCKED, Release) - READ_LOCKED;
@@ -100,6 +106,14 @@ pub unsafe fn read_unlock(&self) {
 
     #[cold]
     fn read_contended(&self) {
+        self.read_contended_until(None);
+    }
+
+    /// Read locks the lock, unless `deadline` is reached first.
+    ///
+    /// Returns whether the lock was locked.
+    #[cold]
+    fn read_contended_until(&self, deadline: Option<Instant>) -> bool {
         let mut state = self.spin_read();
 
         loop {
@@ -107,7 +121,7 @@ fn read_contended(&self) {
             if is_read_lockable(state) {
                 match self.state.compare_exchange_weak(state, state + READ_LOCKED, Acquire, Relaxed)
                 {
-                    Ok(_) => return, // Locked!
+                    Ok(_) => return true, // Locked!
                     Err(s) => {
                         state = s;
                         continue;
@@ -130,8 +144,15 @@ fn read_contended(&self) {
                 }
             }
 
+            // Give up if the deadline has been reached. The readers waiting bit is cleared by
+            // whichever thread unlocks the lock next.
+            let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if timeout == Some(Duration::ZERO) {
+                return false;
+            }
+
             // Wait for the state to change.
-            futex_wait(&self.state, state | READERS_WAITING, None);
+            futex_wait(&self.state, state | READERS_WAITING, timeout);
 
             // Spin again after waking up.
             state = self.spin_read();
@@ -152,6 +173,11 @@ pub fn write(&self) {
         }
     }
 
+    #[inline]
+    pub fn try_write_for(&self, timeout: Duration) -> bool {
+        self.try_write() || self.write_contended_until(Instant::now().checked_add(timeout))
+    }
+
     #[inline]
     pub unsafe fn write_unlock(&self) {
         let state = self.state.fetch_sub(WRITE_LOCKED, Release) - WRITE_LOCKED;
@@ -165,6 +191,14 @@ pub unsafe fn write_unlock(&self) {
 
     #[cold]
     fn write_contended(&self) {
+        self.write_contended_until(None);
+    }
+
+    /// Write locks the lock, unless `deadline` is reached first.
+    ///
+    /// Returns whether the lock was locked.
+    #[cold]
+    fn write_contended_until(&self, deadline: Option<Instant>) -> bool {
         let mut state = self.spin_write();
 
         let mut other_writers_waiting = 0;
@@ -178,7 +212,7 @@ fn write_contended(&self) {
                     Acquire,
                     Relaxed,
                 ) {
-                    Ok(_) => return, // Locked!
+                    Ok(_) => return true, // Locked!
                     Err(s) => {
                         state = s;
                         continue;
@@ -211,8 +245,15 @@ fn write_contended(&self) {
                 continue;
             }
 
+            // Give up if the deadline has been reached. If the writers waiting bit is left set
+            // without any writer waiting, the next unlock wakes up the readers instead.
+            let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if timeout == Some(Duration::ZERO) {
+                return false;
+            }
+
             // Wait for the state to change.
-            futex_wait(&self.writer_notify, seq, None);
+            futex_wait(&self.writer_notify, seq, timeout);
 
             // Spin again after waking up.
             state = self.spin_write();
@@ -1,4 +1,5 @@
 use crate::cell::Cell;
+use crate::time::Duration;
 
 pub struct RwLock {
     // This platform has no threads, so we can use a Cell here.
@@ -36,6 +37,12 @@ pub fn try_read(&self) -> bool {
         }
     }
 
+    #[inline]
+    pub fn try_read_for(&self, _timeout: Duration) -> bool {
+        // There is no other thread to unlock it in the meantime.
+        self.try_read()
+    }
+
     #[inline]
     pub fn write(&self) {
         if self.mode.replace(-1) != 0 {
@@ -53,6 +60,12 @@ pub fn try_write(&self) -> bool {
         }
     }
 
+    #[inline]
+    pub fn try_write_for(&self, _timeout: Duration) -> bool {
+        // There is no other thread to unlock it in the meantime.
+        self.try_write()
+    }
+
     #[inline]
     pub unsafe fn read_unlock(&self) {
         self.mode.set(self.mode.get() - 1);
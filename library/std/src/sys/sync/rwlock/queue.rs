@@ -113,7 +113,9 @@
 use crate::ptr::{self, NonNull, null_mut, without_provenance_mut};
 use crate::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
 use crate::sync::atomic::{AtomicBool, AtomicPtr};
+use crate::sys::sync::poll_lock;
 use crate::thread::{self, Thread, ThreadId};
+use crate::time::Duration;
 
 // Locking uses exponential backoff. `SPIN_COUNT` indicates how many times the
 // locking operation will be retried.
@@ -287,6 +289,10 @@ pub fn try_read(&self) -> bool {
         self.state.fetch_update(Acquire, Relaxed, read_lock).is_ok()
     }
 
+    pub fn try_read_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_read())
+    }
+
     #[inline]
     pub fn read(&self) {
         if !self.try_read() {
@@ -304,6 +310,10 @@ pub fn try_write(&self) -> bool {
         self.state.fetch_or(LOCKED, Acquire).addr() & LOCKED == 0
     }
 
+    pub fn try_write_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_write())
+    }
+
     #[inline]
     pub fn write(&self) {
         if !self.try_write() {
@@ -4,6 +4,8 @@
 use crate::sys::pal::abi;
 use crate::sys::pal::itron::error::{ItronError, expect_success, expect_success_aborting, fail};
 use crate::sys::pal::itron::spin::SpinIdOnceCell;
+use crate::sys::sync::poll_lock;
+use crate::time::Duration;
 
 pub struct RwLock {
     /// The ID of the underlying mutex object
@@ -50,6 +52,10 @@ pub fn try_read(&self) -> bool {
         }
     }
 
+    pub fn try_read_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_read())
+    }
+
     #[inline]
     pub fn write(&self) {
         let rwl = self.raw();
@@ -68,6 +74,10 @@ pub fn try_write(&self) -> bool {
         }
     }
 
+    pub fn try_write_for(&self, timeout: Duration) -> bool {
+        poll_lock(timeout, || self.try_write())
+    }
+
     #[inline]
     pub unsafe fn read_unlock(&self) {
         let rwl = self.raw();
@@ -1,4 +1,5 @@
 use crate::sys::sync::mutex::Mutex;
+use crate::time::Duration;
 
 /// we do not supported rwlock, so use mutex to simulate rwlock.
 /// it's useful because so many code in std will use rwlock.
@@ -22,6 +23,11 @@ pub fn try_read(&self) -> bool {
         self.inner.try_lock()
     }
 
+    #[inline]
+    pub fn try_read_for(&self, timeout: Duration) -> bool {
+        self.inner.try_lock_for(timeout)
+    }
+
     #[inline]
     pub fn write(&self) {
         self.inner.lock()
@@ -32,6 +38,11 @@ pub unsafe fn try_write(&self) -> bool {
         self.inner.try_lock()
     }
 
+    #[inline]
+    pub fn try_write_for(&self, timeout: Duration) -> bool {
+        self.inner.try_lock_for(timeout)
+    }
+
     #[inline]
     pub unsafe fn read_unlock(&self) {
         unsafe { self.inner.unlock() };
@@ -0,0 +1,73 @@
+use crate::sync::{Condvar, Mutex, MutexGuard};
+use crate::time::{Duration, Instant};
+
+pub struct Semaphore {
+    permits: Mutex<usize>,
+    cvar: Condvar,
+}
+
+pub const MAX_PERMITS: usize = (u32::MAX >> 1) as usize;
+
+impl Semaphore {
+    #[inline]
+    pub const fn new(permits: usize) -> Self {
+        assert!(permits <= MAX_PERMITS, "too many permits for a semaphore");
+        Self { permits: Mutex::new(permits), cvar: Condvar::new() }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        // The counter is always consistent, so poisoning can be ignored.
+        self.permits.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn available_permits(&self) -> usize {
+        *self.lock()
+    }
+
+    pub fn try_acquire(&self) -> bool {
+        let mut permits = self.lock();
+        if *permits == 0 {
+            return false;
+        }
+        *permits -= 1;
+        true
+    }
+
+    pub fn acquire(&self) {
+        let mut permits = self.lock();
+        while *permits == 0 {
+            permits = self.cvar.wait(permits).unwrap_or_else(|e| e.into_inner());
+        }
+        *permits -= 1;
+    }
+
+    pub fn acquire_timeout(&self, timeout: Duration) -> bool {
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            self.acquire();
+            return true;
+        };
+
+        let mut permits = self.lock();
+        while *permits == 0 {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout == Duration::ZERO {
+                return false;
+            }
+            permits = self.cvar.wait_timeout(permits, timeout).unwrap_or_else(|e| e.into_inner()).0;
+        }
+        *permits -= 1;
+        true
+    }
+
+    pub fn release(&self, permits: usize) {
+        let mut available = self.lock();
+        assert!(permits <= MAX_PERMITS - *available, "too many permits for a semaphore");
+        *available += permits;
+        drop(available);
+        if permits == 1 {
+            self.cvar.notify_one();
+        } else if permits != 0 {
+            self.cvar.notify_all();
+        }
+    }
+}
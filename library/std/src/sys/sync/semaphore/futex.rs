@@ -0,0 +1,108 @@
+use crate::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::sys::futex::{Futex, Primitive, futex_wait, futex_wake_all};
+use crate::time::{Duration, Instant};
+
+pub struct Semaphore {
+    // The state consists of a 31-bit permit counter and a 'waiting' flag.
+    // Bits 0..31: The number of available permits.
+    // Bit 31: Threads are waiting on this futex for a permit.
+    state: Futex,
+}
+
+const PERMITS: Primitive = (1 << 31) - 1;
+const WAITING: Primitive = 1 << 31;
+
+pub const MAX_PERMITS: usize = PERMITS as usize;
+
+impl Semaphore {
+    #[inline]
+    pub const fn new(permits: usize) -> Self {
+        assert!(permits <= MAX_PERMITS, "too many permits for a semaphore");
+        Self { state: Futex::new(permits as Primitive) }
+    }
+
+    #[inline]
+    pub fn available_permits(&self) -> usize {
+        (self.state.load(Relaxed) & PERMITS) as usize
+    }
+
+    #[inline]
+    pub fn try_acquire(&self) -> bool {
+        self.state.fetch_update(Acquire, Relaxed, |s| (s & PERMITS != 0).then(|| s - 1)).is_ok()
+    }
+
+    #[inline]
+    pub fn acquire(&self) {
+        if !self.try_acquire() {
+            self.acquire_contended(None);
+        }
+    }
+
+    #[inline]
+    pub fn acquire_timeout(&self, timeout: Duration) -> bool {
+        self.try_acquire() || self.acquire_contended(Instant::now().checked_add(timeout))
+    }
+
+    /// Takes a permit, unless `deadline` is reached first.
+    ///
+    /// Returns whether a permit was taken.
+    #[cold]
+    fn acquire_contended(&self, deadline: Option<Instant>) -> bool {
+        let mut state = self.state.load(Relaxed);
+        loop {
+            // If there's a permit available, take it.
+            if state & PERMITS != 0 {
+                match self.state.compare_exchange_weak(state, state - 1, Acquire, Relaxed) {
+                    Ok(_) => return true,
+                    Err(s) => {
+                        state = s;
+                        continue;
+                    }
+                }
+            }
+
+            // Make sure the waiting bit is set before we go to sleep.
+            if state != WAITING {
+                if let Err(s) = self.state.compare_exchange(state, WAITING, Relaxed, Relaxed) {
+                    state = s;
+                    continue;
+                }
+            }
+
+            // Give up if the deadline has been reached. The waiting bit is cleared by the next
+            // call to `release`.
+            let timeout = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if timeout == Some(Duration::ZERO) {
+                return false;
+            }
+
+            // Wait for a permit to be released.
+            futex_wait(&self.state, WAITING, timeout);
+            state = self.state.load(Relaxed);
+        }
+    }
+
+    #[inline]
+    pub fn release(&self, permits: usize) {
+        let state = self
+            .state
+            .fetch_update(Release, Relaxed, |s| {
+                let available = (s & PERMITS) as usize;
+                (permits <= MAX_PERMITS - available).then(|| (available + permits) as Primitive)
+            })
+            .expect("too many permits for a semaphore");
+
+        // Clearing the waiting bit above means all waiting threads have to be woken up: the bit
+        // no longer records that any of them is asleep, so a later `release` wouldn't wake the
+        // ones left sleeping. Each woken thread either takes a permit, or finds them all taken
+        // and sets the waiting bit again before going back to sleep.
+        if state & WAITING != 0 {
+            self.wake();
+        }
+    }
+
+    #[cold]
+    fn wake(&self) {
+        futex_wake_all(&self.state);
+    }
+}
@@ -1,8 +1,9 @@
 #![allow(dead_code)] // not used on all platforms
 
+use crate::ffi::{OsStr, OsString};
 use crate::fs;
 use crate::io::{self, Error, ErrorKind};
-use crate::path::Path;
+use crate::path::{Path, PathBuf};
 use crate::sys_common::ignore_notfound;
 
 pub(crate) const NOT_FILE_ERROR: Error = io::const_io_error!(
@@ -51,6 +52,24 @@ fn remove_dir_all_recursive(path: &Path) -> io::Result<()> {
     ignore_notfound(fs::remove_dir(path))
 }
 
+pub fn rename_noreplace(old: &Path, new: &Path) -> io::Result<()> {
+    // Creating a hard link fails if the destination exists.
+    fs::hard_link(old, new)?;
+    fs::remove_file(old)
+}
+
+pub fn rename_exchange(_old: &Path, _new: &Path) -> io::Result<()> {
+    Err(io::const_io_error!(
+        ErrorKind::Unsupported,
+        "exchanging files is not supported on this platform",
+    ))
+}
+
+pub fn sync_dir(_path: &Path) -> io::Result<()> {
+    // Renames are either durable once they return, or there is no way to flush them.
+    Ok(())
+}
+
 pub fn exists(path: &Path) -> io::Result<bool> {
     match fs::metadata(path) {
         Ok(_) => Ok(true),
@@ -58,3 +77,49 @@ pub fn exists(path: &Path) -> io::Result<bool> {
         Err(error) => Err(error),
     }
 }
+
+/// An open directory visited by `fs::walk_dir`, on platforms without a handle-based
+/// implementation. Entries are looked up by path, so the walk can be redirected by concurrent
+/// changes to the directory tree.
+pub struct WalkDirHandle {
+    path: PathBuf,
+    /// The canonical path of the directory, which identifies it when following symlinks.
+    key: Option<PathBuf>,
+}
+
+impl WalkDirHandle {
+    pub fn open_root(path: &Path, follow_links: bool) -> io::Result<WalkDirHandle> {
+        WalkDirHandle::open(path.to_path_buf(), true, follow_links)
+    }
+
+    pub fn open_child(&self, name: &OsStr, follow_links: bool) -> io::Result<WalkDirHandle> {
+        WalkDirHandle::open(self.path.join(name), follow_links, follow_links)
+    }
+
+    fn open(path: PathBuf, follow: bool, follow_links: bool) -> io::Result<WalkDirHandle> {
+        let metadata = if follow { fs::metadata(&path)? } else { fs::symlink_metadata(&path)? };
+        if !metadata.is_dir() {
+            return Err(io::const_io_error!(ErrorKind::NotADirectory, "not a directory"));
+        }
+        let key = if follow_links { Some(fs::canonicalize(&path)?) } else { None };
+        Ok(WalkDirHandle { path, key })
+    }
+
+    pub fn entries(&self) -> io::Result<Vec<(OsString, Option<fs::FileType>)>> {
+        fs::read_dir(&self.path)?
+            .map(|entry| {
+                let entry = entry?;
+                Ok((entry.file_name(), entry.file_type().ok()))
+            })
+            .collect()
+    }
+
+    pub fn child_metadata(&self, name: &OsStr, follow_links: bool) -> io::Result<fs::Metadata> {
+        let path = self.path.join(name);
+        if follow_links { fs::metadata(path) } else { fs::symlink_metadata(path) }
+    }
+
+    pub fn is_same_dir(&self, other: &WalkDirHandle) -> bool {
+        self.key.is_some() && self.key == other.key
+    }
+}
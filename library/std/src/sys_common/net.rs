@@ -59,6 +59,52 @@
     }
 }
 
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "linux", target_os = "android",
+        target_os = "fuchsia",
+        target_os = "dragonfly", target_os = "freebsd",
+        target_os = "openbsd", target_os = "netbsd",
+        target_vendor = "apple"))] {
+        const SO_REUSEPORT: Option<c_int> = Some(c::SO_REUSEPORT);
+    } else {
+        const SO_REUSEPORT: Option<c_int> = None;
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(unix, windows))] {
+        const SO_KEEPALIVE: Option<c_int> = Some(c::SO_KEEPALIVE);
+        const SO_SNDBUF: Option<c_int> = Some(c::SO_SNDBUF);
+        const SO_RCVBUF: Option<c_int> = Some(c::SO_RCVBUF);
+    } else {
+        const SO_KEEPALIVE: Option<c_int> = None;
+        const SO_SNDBUF: Option<c_int> = None;
+        const SO_RCVBUF: Option<c_int> = None;
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "linux", target_os = "android",
+        target_os = "fuchsia",
+        target_os = "dragonfly", target_os = "freebsd",
+        target_os = "netbsd",
+        windows))] {
+        const TCP_KEEPIDLE: Option<c_int> = Some(c::TCP_KEEPIDLE);
+        const TCP_KEEPINTVL: Option<c_int> = Some(c::TCP_KEEPINTVL);
+        const TCP_KEEPCNT: Option<c_int> = Some(c::TCP_KEEPCNT);
+    } else if #[cfg(target_vendor = "apple")] {
+        const TCP_KEEPIDLE: Option<c_int> = Some(c::TCP_KEEPALIVE);
+        const TCP_KEEPINTVL: Option<c_int> = Some(c::TCP_KEEPINTVL);
+        const TCP_KEEPCNT: Option<c_int> = Some(c::TCP_KEEPCNT);
+    } else {
+        const TCP_KEEPIDLE: Option<c_int> = None;
+        const TCP_KEEPINTVL: Option<c_int> = None;
+        const TCP_KEEPCNT: Option<c_int> = None;
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // sockaddr and misc bindings
 ////////////////////////////////////////////////////////////////////////////////
@@ -96,6 +142,41 @@ pub fn getsockopt<T: Copy>(sock: &Socket, level: c_int, option_name: c_int) -> i
     }
 }
 
+/// Like `setsockopt`, for an option which isn't available on every platform.
+fn setsockopt_if_supported(
+    sock: &Socket,
+    level: c_int,
+    option_name: Option<c_int>,
+    option_value: c_int,
+) -> io::Result<()> {
+    match option_name {
+        Some(option_name) => setsockopt(sock, level, option_name, option_value),
+        None => Err(unsupported_option()),
+    }
+}
+
+/// Like `getsockopt`, for an option which isn't available on every platform.
+fn getsockopt_if_supported(
+    sock: &Socket,
+    level: c_int,
+    option_name: Option<c_int>,
+) -> io::Result<c_int> {
+    match option_name {
+        Some(option_name) => getsockopt(sock, level, option_name),
+        None => Err(unsupported_option()),
+    }
+}
+
+fn unsupported_option() -> io::Error {
+    io::const_io_error!(ErrorKind::Unsupported, "socket option not supported on this platform")
+}
+
+fn bind(sock: &Socket, addr: &SocketAddr) -> io::Result<()> {
+    let (addr, len) = addr.into_inner();
+    cvt(unsafe { c::bind(sock.as_raw(), addr.as_ptr(), len as _) })?;
+    Ok(())
+}
+
 fn sockname<F>(f: F) -> io::Result<SocketAddr>
 where
     F: FnOnce(*mut c::sockaddr, *mut c::socklen_t) -> c_int,
@@ -412,8 +493,7 @@ pub fn bind(addr: io::Result<&SocketAddr>) -> io::Result<TcpListener> {
         setsockopt(&sock, c::SOL_SOCKET, c::SO_REUSEADDR, 1 as c_int)?;
 
         // Bind our new socket
-        let (addr, len) = addr.into_inner();
-        cvt(unsafe { c::bind(sock.as_raw(), addr.as_ptr(), len as _) })?;
+        bind(&sock, addr)?;
 
         cfg_if::cfg_if! {
             if #[cfg(target_os = "horizon")] {
@@ -522,8 +602,7 @@ pub fn bind(addr: io::Result<&SocketAddr>) -> io::Result<UdpSocket> {
         init();
 
         let sock = Socket::new(addr, c::SOCK_DGRAM)?;
-        let (addr, len) = addr.into_inner();
-        cvt(unsafe { c::bind(sock.as_raw(), addr.as_ptr(), len as _) })?;
+        bind(&sock, addr)?;
         Ok(UdpSocket { inner: sock })
     }
 
@@ -724,6 +803,248 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Unbound sockets
+////////////////////////////////////////////////////////////////////////////////
+
+pub struct TcpSocket {
+    inner: Socket,
+}
+
+impl TcpSocket {
+    /// Creates a socket of the same address family as `addr`.
+    pub fn new(addr: &SocketAddr) -> io::Result<TcpSocket> {
+        init();
+
+        let sock = Socket::new(addr, c::SOCK_STREAM)?;
+        Ok(TcpSocket { inner: sock })
+    }
+
+    pub fn bind(&self, addr: &SocketAddr) -> io::Result<()> {
+        bind(&self.inner, addr)
+    }
+
+    pub fn connect(self, addr: &SocketAddr) -> io::Result<TcpStream> {
+        self.inner.connect(addr)?;
+        Ok(TcpStream { inner: self.inner })
+    }
+
+    pub fn connect_timeout(self, addr: &SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+        self.inner.connect_timeout(addr, timeout)?;
+        Ok(TcpStream { inner: self.inner })
+    }
+
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        let backlog = cmp::min(backlog, c_int::MAX as u32) as c_int;
+        cvt(unsafe { c::listen(self.inner.as_raw(), backlog) })?;
+        Ok(TcpListener { inner: self.inner })
+    }
+
+    pub fn socket_addr(&self) -> io::Result<SocketAddr> {
+        sockname(|buf, len| unsafe { c::getsockname(self.inner.as_raw(), buf, len) })
+    }
+
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        setsockopt(&self.inner, c::SOL_SOCKET, c::SO_REUSEADDR, reuseaddr as c_int)
+    }
+
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        let raw: c_int = getsockopt(&self.inner, c::SOL_SOCKET, c::SO_REUSEADDR)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_REUSEPORT, reuseport as c_int)
+    }
+
+    pub fn reuseport(&self) -> io::Result<bool> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_REUSEPORT)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        setsockopt(&self.inner, c::IPPROTO_IPV6, c::IPV6_V6ONLY, only_v6 as c_int)
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        let raw: c_int = getsockopt(&self.inner, c::IPPROTO_IPV6, c::IPV6_V6ONLY)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_SNDBUF, buffer_size(size))
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_SNDBUF)?;
+        Ok(raw as usize)
+    }
+
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_RCVBUF, buffer_size(size))
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_RCVBUF)?;
+        Ok(raw as usize)
+    }
+
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.inner.set_nodelay(nodelay)
+    }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.nodelay()
+    }
+
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_KEEPALIVE, keepalive as c_int)
+    }
+
+    pub fn keepalive(&self) -> io::Result<bool> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_KEEPALIVE)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_keepalive_time(&self, time: Duration) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::IPPROTO_TCP, TCP_KEEPIDLE, keepalive_secs(time)?)
+    }
+
+    pub fn keepalive_time(&self) -> io::Result<Duration> {
+        let raw = getsockopt_if_supported(&self.inner, c::IPPROTO_TCP, TCP_KEEPIDLE)?;
+        Ok(Duration::from_secs(raw as u64))
+    }
+
+    pub fn set_keepalive_interval(&self, interval: Duration) -> io::Result<()> {
+        let secs = keepalive_secs(interval)?;
+        setsockopt_if_supported(&self.inner, c::IPPROTO_TCP, TCP_KEEPINTVL, secs)
+    }
+
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        let raw = getsockopt_if_supported(&self.inner, c::IPPROTO_TCP, TCP_KEEPINTVL)?;
+        Ok(Duration::from_secs(raw as u64))
+    }
+
+    pub fn set_keepalive_retries(&self, retries: u32) -> io::Result<()> {
+        let retries = cmp::min(retries, c_int::MAX as u32) as c_int;
+        setsockopt_if_supported(&self.inner, c::IPPROTO_TCP, TCP_KEEPCNT, retries)
+    }
+
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        let raw = getsockopt_if_supported(&self.inner, c::IPPROTO_TCP, TCP_KEEPCNT)?;
+        Ok(raw as u32)
+    }
+}
+
+impl fmt::Debug for TcpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut res = f.debug_struct("TcpSocket");
+
+        if let Ok(addr) = self.socket_addr() {
+            res.field("addr", &addr);
+        }
+
+        let name = if cfg!(windows) { "socket" } else { "fd" };
+        res.field(name, &self.inner.as_raw()).finish()
+    }
+}
+
+pub struct UdpSocketBuilder {
+    inner: Socket,
+}
+
+impl UdpSocketBuilder {
+    /// Creates a socket of the same address family as `addr`.
+    pub fn new(addr: &SocketAddr) -> io::Result<UdpSocketBuilder> {
+        init();
+
+        let sock = Socket::new(addr, c::SOCK_DGRAM)?;
+        Ok(UdpSocketBuilder { inner: sock })
+    }
+
+    pub fn bind(self, addr: &SocketAddr) -> io::Result<UdpSocket> {
+        bind(&self.inner, addr)?;
+        Ok(UdpSocket { inner: self.inner })
+    }
+
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        setsockopt(&self.inner, c::SOL_SOCKET, c::SO_REUSEADDR, reuseaddr as c_int)
+    }
+
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        let raw: c_int = getsockopt(&self.inner, c::SOL_SOCKET, c::SO_REUSEADDR)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_REUSEPORT, reuseport as c_int)
+    }
+
+    pub fn reuseport(&self) -> io::Result<bool> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_REUSEPORT)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        setsockopt(&self.inner, c::IPPROTO_IPV6, c::IPV6_V6ONLY, only_v6 as c_int)
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        let raw: c_int = getsockopt(&self.inner, c::IPPROTO_IPV6, c::IPV6_V6ONLY)?;
+        Ok(raw != 0)
+    }
+
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_SNDBUF, buffer_size(size))
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_SNDBUF)?;
+        Ok(raw as usize)
+    }
+
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        setsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_RCVBUF, buffer_size(size))
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        let raw = getsockopt_if_supported(&self.inner, c::SOL_SOCKET, SO_RCVBUF)?;
+        Ok(raw as usize)
+    }
+
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        setsockopt(&self.inner, c::SOL_SOCKET, c::SO_BROADCAST, broadcast as c_int)
+    }
+
+    pub fn broadcast(&self) -> io::Result<bool> {
+        let raw: c_int = getsockopt(&self.inner, c::SOL_SOCKET, c::SO_BROADCAST)?;
+        Ok(raw != 0)
+    }
+}
+
+impl fmt::Debug for UdpSocketBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if cfg!(windows) { "socket" } else { "fd" };
+        f.debug_struct("UdpSocketBuilder").field(name, &self.inner.as_raw()).finish()
+    }
+}
+
+fn buffer_size(size: usize) -> c_int {
+    cmp::min(size, c_int::MAX as usize) as c_int
+}
+
+/// Converts a keepalive timing to whole seconds, rounding up.
+fn keepalive_secs(dur: Duration) -> io::Result<c_int> {
+    if dur.is_zero() {
+        return Err(io::const_io_error!(
+            ErrorKind::InvalidInput,
+            "cannot set a 0 duration keepalive timing",
+        ));
+    }
+    let secs = dur.as_secs().saturating_add((dur.subsec_nanos() > 0) as u64);
+    Ok(cmp::min(secs, c_int::MAX as u64) as c_int)
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Converting SocketAddr to libc representation
 ////////////////////////////////////////////////////////////////////////////////
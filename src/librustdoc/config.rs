@@ -87,6 +87,9 @@ pub(crate) struct Options {
     pub(crate) cfgs: Vec<String>,
     /// List of check cfg flags to hand to the compiler.
     pub(crate) check_cfgs: Vec<String>,
+    /// Configurations given with `--cfg-variant`. The crate is documented in the first one, and
+    /// loaded in the others to find out which configurations its items are available in.
+    pub(crate) cfg_variants: Vec<CfgVariant>,
     /// Codegen options to hand to the compiler.
     pub(crate) codegen_options: CodegenOptions,
     /// Codegen options strings to hand to the compiler.
@@ -143,6 +146,8 @@ pub(crate) struct Options {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     pub(crate) show_coverage: bool,
+    /// Extra settings for the `calculate-doc-coverage` pass, only used with `show_coverage`.
+    pub(crate) coverage_options: CoverageOptions,
 
     // Options that alter generated documentation pages
     /// Crate version to note on the sidebar of generated docs.
@@ -193,6 +198,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("externs", &FmtExterns(&self.externs))
             .field("cfgs", &self.cfgs)
             .field("check-cfgs", &self.check_cfgs)
+            .field("cfg_variants", &self.cfg_variants)
             .field("codegen_options", &"...")
             .field("unstable_options", &"...")
             .field("target", &self.target)
@@ -206,6 +212,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("test_run_directory", &self.test_run_directory)
             .field("persist_doctests", &self.persist_doctests)
             .field("show_coverage", &self.show_coverage)
+            .field("coverage_options", &self.coverage_options)
             .field("crate_version", &self.crate_version)
             .field("runtool", &self.runtool)
             .field("runtool_args", &self.runtool_args)
@@ -221,6 +228,92 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// A configuration given with `--cfg-variant`, on top of the options of the command line.
+#[derive(Clone, Debug)]
+pub(crate) struct CfgVariant {
+    /// The cfgs set in this configuration, as `(name, value)`.
+    pub(crate) cfgs: Vec<(String, Option<String>)>,
+    /// The target of this configuration, when it differs from `--target`.
+    pub(crate) target: Option<TargetTriple>,
+}
+
+impl CfgVariant {
+    /// Parses a comma-separated list of `name`, `name="value"` or `target=TRIPLE`.
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut variant = CfgVariant { cfgs: Vec::new(), target: None };
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (name, value) = match part.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (part, None),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(format!("invalid cfg `{part}` in `--cfg-variant {spec}`"));
+            }
+            match (name, value) {
+                ("target", Some(target)) if variant.target.is_none() => {
+                    variant.target = Some(if target.ends_with(".json") {
+                        TargetTriple::from_path(Path::new(target))
+                            .map_err(|_| format!("target file {target:?} does not exist"))?
+                    } else {
+                        TargetTriple::TargetTriple(target.to_string())
+                    });
+                }
+                ("target", _) => {
+                    return Err(format!("expected a single `target=TRIPLE` in `{spec}`"));
+                }
+                (name, value) => {
+                    variant.cfgs.push((name.to_string(), value.map(str::to_string)));
+                }
+            }
+        }
+        Ok(variant)
+    }
+
+    /// Applies this configuration to the options given on the command line.
+    pub(crate) fn apply(&self, options: &mut Options) {
+        options.cfgs.extend(self.cfgs.iter().map(|(name, value)| match value {
+            Some(value) => format!("{name}=\"{value}\""),
+            None => name.clone(),
+        }));
+        if let Some(target) = &self.target {
+            options.target = target.clone();
+        }
+    }
+}
+
+/// Configuration for the `--show-coverage` report.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CoverageOptions {
+    /// Minimum percentage of documented items required for the run to succeed
+    /// (`--coverage-min`).
+    pub(crate) min_percentage: Option<f64>,
+    /// Whether to list undocumented items, items without examples and per-module totals
+    /// (`--coverage-details`).
+    pub(crate) show_details: bool,
+}
+
+impl CoverageOptions {
+    fn from_matches(
+        matches: &getopts::Matches,
+        show_coverage: bool,
+        dcx: DiagCtxtHandle<'_>,
+    ) -> Self {
+        let show_details = matches.opt_present("coverage-details");
+        let min_percentage = matches.opt_str("coverage-min").map(|value| {
+            match value.trim_end_matches('%').parse::<f64>() {
+                Ok(min) if (0.0..=100.0).contains(&min) => min,
+                _ => dcx.fatal(format!(
+                    "`--coverage-min` expects a percentage between 0 and 100, found `{value}`"
+                )),
+            }
+        });
+        if !show_coverage && (show_details || min_percentage.is_some()) {
+            dcx.fatal("`--coverage-min` and `--coverage-details` require `--show-coverage`");
+        }
+        CoverageOptions { min_percentage, show_details }
+    }
+}
+
 /// Configuration options for the HTML page-creation process.
 #[derive(Clone, Debug)]
 pub(crate) struct RenderOptions {
@@ -300,6 +393,14 @@ pub(crate) struct RenderOptions {
     pub(crate) include_parts_dir: Vec<PathToParts>,
     /// Where to write crate-info
     pub(crate) parts_out_dir: Option<PathToParts>,
+    /// Name of the version being documented, set with `--doc-version`. When present, `output`
+    /// is a sub-directory of the `--out-dir` that holds every documented version.
+    pub(crate) doc_version: Option<String>,
+    /// Which panics the `missing_panics_doc` and `missing_errors_doc` lints look for, and on
+    /// which items. Set with `--doc-sections-strictness`.
+    pub(crate) doc_sections_strictness: DocSectionsStrictness,
+    /// If `true`, the JSON output lists the items used by the body of each local function.
+    pub(crate) json_call_graph: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -308,6 +409,30 @@ pub(crate) enum ModuleSorting {
     Alphabetical,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum DocSectionsStrictness {
+    /// Only `panic!` and the `assert!` family count as panicking.
+    Lenient,
+    /// Also count `unwrap` and `expect` on `Option` and `Result`.
+    Normal,
+    /// Also count indexing, and check private items as well as public ones.
+    Strict,
+}
+
+impl FromStr for DocSectionsStrictness {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use DocSectionsStrictness::*;
+        match s {
+            "lenient" => Ok(Lenient),
+            "normal" => Ok(Normal),
+            "strict" => Ok(Strict),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(crate) enum EmitType {
     Unversioned,
@@ -588,7 +713,24 @@ fn println_condition(condition: Condition) {
             (None, None) => PathBuf::from("doc"),
         };
 
+        let doc_version = matches.opt_str("doc-version");
+        let output = match &doc_version {
+            Some(version) if !is_valid_doc_version(version) => dcx.fatal(format!(
+                "`--doc-version` must be a non-empty name usable as a directory, found `{version}`"
+            )),
+            Some(_) if output_to_stdout => {
+                dcx.fatal("`--doc-version` cannot be used when writing to stdout")
+            }
+            Some(version) => output.join(version),
+            None => output,
+        };
+
         let cfgs = matches.opt_strs("cfg");
+        let cfg_variants = matches
+            .opt_strs("cfg-variant")
+            .iter()
+            .map(|spec| CfgVariant::parse(spec).unwrap_or_else(|err| dcx.fatal(err)))
+            .collect();
         let check_cfgs = matches.opt_strs("check-cfg");
 
         let extension_css = matches.opt_str("e").map(|s| PathBuf::from(&s));
@@ -700,6 +842,7 @@ fn println_condition(condition: Condition) {
             .collect();
 
         let show_coverage = matches.opt_present("show-coverage");
+        let coverage_options = CoverageOptions::from_matches(matches, show_coverage, dcx);
 
         let crate_types = match parse_crate_types_from_list(matches.opt_strs("crate-type")) {
             Ok(types) => types,
@@ -750,6 +893,15 @@ fn println_condition(condition: Condition) {
         let enable_per_target_ignores = matches.opt_present("enable-per-target-ignores");
         let document_private = matches.opt_present("document-private-items");
         let document_hidden = matches.opt_present("document-hidden-items");
+        let doc_sections_strictness = match matches.opt_str("doc-sections-strictness") {
+            None => DocSectionsStrictness::Normal,
+            Some(s) => s.parse().unwrap_or_else(|()| {
+                dcx.fatal(format!(
+                    "unknown `--doc-sections-strictness` value `{s}`, expected one of \
+                    `lenient`, `normal` or `strict`"
+                ))
+            }),
+        };
         let run_check = matches.opt_present("check");
         let generate_redirect_map = matches.opt_present("generate-redirect-map");
         let show_type_layout = matches.opt_present("show-type-layout");
@@ -771,6 +923,14 @@ fn println_condition(condition: Condition) {
             .emit();
         }
 
+        let mut json_call_graph = matches.opt_present("json-call-graph");
+        if json_call_graph && (show_coverage || output_format != OutputFormat::Json) {
+            dcx.struct_warn("`--json-call-graph` option can only be used with JSON output format")
+                .with_note("`--json-call-graph` option will be ignored")
+                .emit();
+            json_call_graph = false;
+        }
+
         let scrape_examples_options = ScrapeExamplesOptions::new(matches, dcx);
         let with_examples = matches.opt_strs("with-examples");
         let call_locations = crate::scrape_examples::load_call_locations(with_examples, dcx);
@@ -788,6 +948,7 @@ fn println_condition(condition: Condition) {
             extern_strs,
             cfgs,
             check_cfgs,
+            cfg_variants,
             codegen_options,
             codegen_options_strs,
             unstable_opts,
@@ -801,6 +962,7 @@ fn println_condition(condition: Condition) {
             should_test,
             test_args,
             show_coverage,
+            coverage_options,
             crate_version,
             test_run_directory,
             persist_doctests,
@@ -852,11 +1014,23 @@ fn println_condition(condition: Condition) {
             should_merge,
             include_parts_dir,
             parts_out_dir,
+            doc_version,
+            doc_sections_strictness,
+            json_call_graph,
         };
         Some((input, options, render_options))
     }
 }
 
+/// Versions are written to sibling directories, so their name must be a single path component.
+fn is_valid_doc_version(version: &str) -> bool {
+    !version.is_empty()
+        && version != "."
+        && version != ".."
+        && version != "static.files"
+        && !version.contains(['/', '\\'])
+}
+
 /// Returns `true` if the file given as `self.input` is a Markdown file.
 pub(crate) fn markdown_input(input: &Input) -> Option<&Path> {
     input.opt_path().filter(|p| matches!(p.extension(), Some(e) if e == "md" || e == "markdown"))
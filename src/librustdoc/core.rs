@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, LazyLock};
 use std::{io, mem};
@@ -8,27 +9,32 @@
 use rustc_errors::codes::*;
 use rustc_errors::emitter::{DynEmitter, HumanEmitter, stderr_destination};
 use rustc_errors::json::JsonEmitter;
-use rustc_errors::{DiagCtxtHandle, ErrorGuaranteed, TerminalUrl};
+use rustc_errors::{DiagCtxtHandle, ErrorGuaranteed, FatalError, TerminalUrl};
 use rustc_feature::UnstableFeatures;
-use rustc_hir::def::Res;
+use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::{DefId, DefIdMap, DefIdSet, LocalDefId};
 use rustc_hir::intravisit::{self, Visitor};
 use rustc_hir::{HirId, Path};
 use rustc_interface::interface;
 use rustc_lint::{MissingDoc, late_lint_mod};
 use rustc_middle::hir::nested_filter;
-use rustc_middle::ty::{ParamEnv, Ty, TyCtxt};
+use rustc_middle::ty::print::{with_no_trimmed_paths, with_no_visible_paths};
+use rustc_middle::ty::{self, ParamEnv, Ty, TyCtxt};
 use rustc_session::config::{self, CrateType, ErrorOutputType, Input, ResolveDocLinks};
 pub(crate) use rustc_session::config::{Options, UnstableOptions};
 use rustc_session::{Session, lint};
-use rustc_span::symbol::sym;
+use rustc_span::symbol::{Symbol, sym};
 use rustc_span::{Span, source_map};
 use tracing::{debug, info};
 
+use crate::clean::cfg::Cfg;
 use crate::clean::inline::build_external_trait;
 use crate::clean::{self, ItemId};
-use crate::config::{Options as RustdocOptions, OutputFormat, RenderOptions};
+use crate::config::{
+    CfgVariant, CoverageOptions, Options as RustdocOptions, OutputFormat, RenderOptions,
+};
 use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
 use crate::passes;
 use crate::passes::Condition::*;
 use crate::passes::collect_intra_doc_links::LinkCollector;
@@ -68,6 +74,8 @@ pub(crate) struct DocContext<'tcx> {
     pub(crate) output_format: OutputFormat,
     /// Used by `strip_private`.
     pub(crate) show_coverage: bool,
+    /// Used by `calculate_doc_coverage`.
+    pub(crate) coverage_options: CoverageOptions,
 }
 
 impl<'tcx> DocContext<'tcx> {
@@ -300,9 +308,254 @@ pub(crate) fn create_config(
     }
 }
 
+/// The `--cfg-variant` configurations of the documented crate.
+#[derive(Clone)]
+pub(crate) struct CfgVariants {
+    /// The items of the crate in each configuration.
+    pub(crate) variants: Arc<[CfgVariantItems]>,
+    /// The index of the configuration the crate is documented in.
+    pub(crate) current: usize,
+}
+
+/// The items of the crate in one of the `--cfg-variant` configurations.
+pub(crate) struct CfgVariantItems {
+    /// The cfgs that make up this configuration: the ones of its `--cfg-variant`, and the
+    /// `target_os`, `target_arch` and `target_env` of its target.
+    pub(crate) cfgs: Vec<(String, Option<String>)>,
+    /// The items defined in this configuration, by the path given by [`cfg_variant_path`].
+    pub(crate) items: FxHashMap<String, CfgVariantItem>,
+}
+
+/// An item defined in one of the `--cfg-variant` configurations.
+pub(crate) struct CfgVariantItem {
+    /// The path of the module listing this item, if it is documented in a module page.
+    pub(crate) module: Option<String>,
+    /// The path of the type or trait listing this item, if it is an associated item or a trait
+    /// impl documented in the page of that type or trait.
+    pub(crate) owner: Option<String>,
+    pub(crate) item_type: ItemType,
+    pub(crate) name: String,
+    /// The source of the item's signature, with its whitespace collapsed.
+    pub(crate) signature: String,
+    pub(crate) doc: String,
+}
+
+impl CfgVariants {
+    /// The configurations the item at `path` is available in, unless it is available in all of
+    /// them.
+    pub(crate) fn availability(&self, path: &str) -> Option<Cfg> {
+        if self.variants.iter().all(|variant| variant.items.contains_key(path)) {
+            return None;
+        }
+        // A configuration is only described by the cfgs that set it apart from the others.
+        let common = |cfg| self.variants.iter().all(|variant| variant.cfgs.contains(cfg));
+        let available_in = self.variants.iter().filter(|variant| variant.items.contains_key(path));
+        Some(available_in.fold(Cfg::False, |any, variant| {
+            any | variant
+                .cfgs
+                .iter()
+                .filter(|cfg| !common(cfg))
+                .map(|(name, value)| {
+                    Cfg::Cfg(Symbol::intern(name), value.as_deref().map(Symbol::intern))
+                })
+                .fold(Cfg::True, |all, cfg| all & cfg)
+        }))
+    }
+
+    /// The items listed in the module at `module_path` that are only defined in other
+    /// configurations than the documented one, with the configurations they are available in.
+    pub(crate) fn items_from_other_variants(
+        &self,
+        module_path: &str,
+    ) -> Vec<(&CfgVariantItem, Cfg)> {
+        self.other_variant_items(|item| item.module.as_deref() == Some(module_path))
+            .map(|(_, item, cfg)| (item, cfg))
+            .collect()
+    }
+
+    /// The associated items and trait impls listed in the page of the type or trait at
+    /// `owner_path` that are only defined in other configurations than the documented one, with
+    /// the configurations they are available in.
+    pub(crate) fn assoc_items_from_other_variants(
+        &self,
+        owner_path: &str,
+    ) -> Vec<(&CfgVariantItem, Cfg)> {
+        self.other_variant_items(|item| item.owner.as_deref() == Some(owner_path))
+            .map(|(_, item, cfg)| (item, cfg))
+            .collect()
+    }
+
+    /// The documented items that are only defined in other configurations than the documented
+    /// one, by path, with the configurations they are available in.
+    pub(crate) fn other_variant_items(
+        &self,
+        filter: impl Fn(&CfgVariantItem) -> bool,
+    ) -> impl Iterator<Item = (&str, &CfgVariantItem, Cfg)> {
+        let current = &self.variants[self.current];
+        let mut items = BTreeMap::new();
+        for variant in self.variants.iter() {
+            for (path, item) in &variant.items {
+                if (item.module.is_some() || item.owner.is_some())
+                    && filter(item)
+                    && !current.items.contains_key(path)
+                {
+                    items.entry(path.as_str()).or_insert(item);
+                }
+            }
+        }
+        items.into_iter().filter_map(|(path, item)| Some((path, item, self.availability(path)?)))
+    }
+}
+
+/// Whether items of this kind are compared across the `--cfg-variant` configurations.
+pub(crate) fn is_cfg_variant_item(def_kind: DefKind) -> bool {
+    matches!(
+        def_kind,
+        DefKind::Mod
+            | DefKind::Struct
+            | DefKind::Union
+            | DefKind::Enum
+            | DefKind::Variant
+            | DefKind::Trait
+            | DefKind::TraitAlias
+            | DefKind::TyAlias
+            | DefKind::ForeignTy
+            | DefKind::Fn
+            | DefKind::Const
+            | DefKind::Static { .. }
+            | DefKind::Macro(_)
+            | DefKind::AssocFn
+            | DefKind::AssocConst
+            | DefKind::AssocTy
+            | DefKind::Field
+    )
+}
+
+/// The path identifying an item across the `--cfg-variant` configurations.
+pub(crate) fn cfg_variant_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    with_no_visible_paths!(with_no_trimmed_paths!(tcx.def_path_str(def_id)))
+}
+
+/// Loads the crate in one of the `--cfg-variant` configurations, to collect the items defined
+/// there.
+pub(crate) fn load_cfg_variant(
+    input: Input,
+    mut options: RustdocOptions,
+    render_options: &RenderOptions,
+    variant: &CfgVariant,
+    using_internal_features: Arc<AtomicBool>,
+) -> CfgVariantItems {
+    variant.apply(&mut options);
+    let config = create_config(input, options, render_options, using_internal_features);
+    let (document_private, document_hidden) =
+        (render_options.document_private, render_options.document_hidden);
+    interface::run_compiler(config, |compiler| {
+        let sess = &compiler.sess;
+        compiler.enter(|queries| {
+            let Ok(mut gcx) = queries.global_ctxt() else { FatalError.raise() };
+            if sess.dcx().has_errors().is_some() {
+                sess.dcx().fatal("Compilation failed in a `--cfg-variant`, aborting rustdoc");
+            }
+            gcx.enter(|tcx| {
+                let target = &tcx.sess.target;
+                let target_cfgs = [
+                    (sym::target_os, &target.os),
+                    (sym::target_arch, &target.arch),
+                    (sym::target_env, &target.env),
+                ];
+                let cfgs = variant
+                    .cfgs
+                    .iter()
+                    .cloned()
+                    .chain(
+                        target_cfgs
+                            .into_iter()
+                            .filter(|(_, value)| !value.is_empty())
+                            .map(|(name, value)| (name.to_string(), Some(value.to_string()))),
+                    )
+                    .collect();
+
+                let effective_visibilities = tcx.effective_visibilities(());
+                let documented = |def_id: LocalDefId| {
+                    (document_private || effective_visibilities.is_exported(def_id))
+                        && (document_hidden || !tcx.is_doc_hidden(def_id))
+                };
+                // The type whose page lists the items of an impl.
+                let self_ty_def_id = |impl_def_id: LocalDefId| match *tcx
+                    .type_of(impl_def_id)
+                    .instantiate_identity()
+                    .kind()
+                {
+                    ty::Adt(adt, _) => Some(adt.did()),
+                    ty::Foreign(def_id) => Some(def_id),
+                    _ => None,
+                };
+                let items = tcx
+                    .iter_local_def_id()
+                    .filter(|&def_id| {
+                        let def_kind = tcx.def_kind(def_id);
+                        is_cfg_variant_item(def_kind)
+                            || matches!(def_kind, DefKind::Impl { of_trait: true })
+                    })
+                    .map(|def_id| {
+                        let def_kind = tcx.def_kind(def_id);
+                        let parent = tcx.opt_local_parent(def_id);
+                        let parent_kind = parent.map(|p| tcx.def_kind(p));
+                        // Module-level items that are documented in this configuration.
+                        let listed = parent_kind == Some(DefKind::Mod) && documented(def_id);
+                        // Associated items and trait impls, with the type or trait whose page
+                        // documents them. The items of trait impls are documented with the impl.
+                        let owner = match (def_kind, parent_kind) {
+                            (DefKind::Impl { .. }, _) => self_ty_def_id(def_id),
+                            (
+                                DefKind::AssocFn | DefKind::AssocConst | DefKind::AssocTy,
+                                Some(DefKind::Impl { of_trait: false }),
+                            ) => parent.and_then(self_ty_def_id),
+                            (
+                                DefKind::AssocFn | DefKind::AssocConst | DefKind::AssocTy,
+                                Some(DefKind::Trait),
+                            ) => parent.map(LocalDefId::to_def_id),
+                            _ => None,
+                        }
+                        .filter(|_| documented(def_id));
+                        let def_id = def_id.to_def_id();
+                        let name = tcx
+                            .opt_item_name(def_id)
+                            .map_or_else(String::new, |name| name.to_string());
+                        let signature = tcx
+                            .sess
+                            .source_map()
+                            .span_to_snippet(tcx.def_span(def_id))
+                            .map_or_else(
+                                |_| name.clone(),
+                                |snippet| snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+                            );
+                        let item = CfgVariantItem {
+                            module: listed.then(|| cfg_variant_path(tcx, tcx.parent(def_id))),
+                            owner: owner.map(|owner| cfg_variant_path(tcx, owner)),
+                            item_type: match def_kind {
+                                DefKind::Impl { .. } => ItemType::Impl,
+                                _ => ItemType::from_def_kind(def_kind, parent_kind),
+                            },
+                            name,
+                            signature,
+                            doc: clean::Attributes::from_ast(tcx.get_attrs_unchecked(def_id))
+                                .doc_value(),
+                        };
+                        (cfg_variant_path(tcx, def_id), item)
+                    })
+                    .collect();
+                CfgVariantItems { cfgs, items }
+            })
+        })
+    })
+}
+
 pub(crate) fn run_global_ctxt(
     tcx: TyCtxt<'_>,
     show_coverage: bool,
+    coverage_options: CoverageOptions,
+    cfg_variants: Option<CfgVariants>,
     render_options: RenderOptions,
     output_format: OutputFormat,
 ) -> Result<(clean::Crate, RenderOptions, Cache), ErrorGuaranteed> {
@@ -347,7 +600,9 @@ pub(crate) fn run_global_ctxt(
         output_format,
         render_options,
         show_coverage,
+        coverage_options,
     };
+    ctxt.cache.cfg_variants = cfg_variants;
 
     for cnum in tcx.crates(()) {
         crate::visit_lib::lib_embargo_visit_item(&mut ctxt, cnum.as_def_id());
@@ -8,7 +8,7 @@
 
 use crate::clean::types::ExternalLocation;
 use crate::clean::{self, ExternalCrate, ItemId, PrimitiveType};
-use crate::core::DocContext;
+use crate::core::{CfgVariants, DocContext, cfg_variant_path};
 use crate::fold::DocFolder;
 use crate::formats::Impl;
 use crate::formats::item_type::ItemType;
@@ -131,6 +131,10 @@ pub(crate) struct Cache {
     /// to check if a stripped item should get its file generated or not: if it's inside a
     /// `#[doc(hidden)]` item or a private one and not inlined, it shouldn't get a file.
     pub(crate) inlined_items: DefIdSet,
+
+    /// The `--cfg-variant` configurations, used to infer which configurations items are
+    /// available in, and to list the items of the other configurations in module pages.
+    pub(crate) cfg_variants: Option<CfgVariants>,
 }
 
 /// This struct is used to wrap the `cache` and `tcx` in order to run `DocFolder`.
@@ -199,6 +203,8 @@ pub(crate) fn populate(cx: &mut DocContext<'_>, mut krate: clean::Crate) -> clea
             }
         }
 
+        add_cfg_variant_items_to_search_index(tcx, &mut cx.cache);
+
         krate
     }
 }
@@ -569,6 +575,45 @@ fn add_item_to_search_index(tcx: TyCtxt<'_>, cache: &mut Cache, item: &clean::It
     cache.search_index.push(index_item);
 }
 
+/// Adds the items that are only defined in other `--cfg-variant` configurations than the
+/// documented one to the search index, under the module, type or trait documenting them in this
+/// configuration.
+fn add_cfg_variant_items_to_search_index(tcx: TyCtxt<'_>, cache: &mut Cache) {
+    let Some(variants) = cache.cfg_variants.clone() else { return };
+    let local_paths: FxHashMap<String, DefId> = cache
+        .paths
+        .keys()
+        .filter(|def_id| def_id.is_local())
+        .map(|&def_id| (cfg_variant_path(tcx, def_id), def_id))
+        .collect();
+    for (_, item, _) in variants.other_variant_items(|item| item.item_type != ItemType::Impl) {
+        let (parent, path) = if let Some(owner) = &item.owner {
+            let Some(&owner_did) = local_paths.get(owner) else { continue };
+            let fqp = &cache.paths[&owner_did].0;
+            (Some(owner_did), join_with_double_colon(&fqp[..fqp.len() - 1]))
+        } else if let Some(module) = &item.module {
+            let Some(module_did) = local_paths.get(module) else { continue };
+            (None, join_with_double_colon(&cache.paths[module_did].0))
+        } else {
+            continue;
+        };
+        cache.search_index.push(IndexItem {
+            ty: item.item_type,
+            defid: None,
+            name: Symbol::intern(&item.name),
+            path,
+            desc: short_markdown_summary(&item.doc, &[]),
+            parent,
+            parent_idx: None,
+            exact_path: None,
+            impl_id: None,
+            search_type: None,
+            aliases: Box::new([]),
+            deprecation: None,
+        });
+    }
+}
+
 /// We have a parent, but we don't know where they're
 /// defined yet. Wait for later to index this item.
 /// See [`Cache::orphan_impl_items`].
@@ -21,6 +21,8 @@ pub(crate) struct Layout {
     pub(crate) css_file_extension: Option<PathBuf>,
     /// If true, then scrape-examples.js will be included in the output HTML file
     pub(crate) scrape_examples_extension: bool,
+    /// The `--doc-version` these pages belong to, used by the version switcher.
+    pub(crate) doc_version: Option<String>,
 }
 
 pub(crate) struct Page<'a> {
@@ -34,9 +36,11 @@ pub(crate) struct Page<'a> {
 }
 
 impl<'a> Page<'a> {
-    pub(crate) fn get_static_root_path(&self) -> String {
+    pub(crate) fn get_static_root_path(&self, layout: &Layout) -> String {
         match self.static_root_path {
             Some(s) => s.to_string(),
+            // Static files are shared between all versions, one level above the version root.
+            None if layout.doc_version.is_some() => format!("{}../static.files/", self.root_path),
             None => format!("{}static.files/", self.root_path),
         }
     }
@@ -89,7 +93,7 @@ pub(crate) fn render<T: Print, S: Print>(
                 ensure_trailing_slash(&layout.krate).to_string();
             (&layout.krate[..], &layout.krate_version[..], display_krate_with_trailing_slash)
         };
-    let static_root_path = page.get_static_root_path();
+    let static_root_path = page.get_static_root_path(layout);
 
     // bootstrap passes in parts of the version separated by tabs, but other stuff might use spaces
     let (display_krate_version_number, display_krate_version_extra) =
@@ -21,6 +21,7 @@
 use crate::clean::utils::has_doc_flag;
 use crate::clean::{self, ExternalCrate};
 use crate::config::{ModuleSorting, RenderOptions, ShouldMerge};
+use crate::core::cfg_variant_path;
 use crate::docfs::{DocFS, PathError};
 use crate::error::Error;
 use crate::formats::FormatRenderer;
@@ -279,7 +280,11 @@ fn render_item(&mut self, it: &clean::Item, is_module: bool) -> String {
     }
 
     /// Construct a map of items shown in the sidebar to a plain-text summary of their docs.
-    fn build_sidebar_items(&self, m: &clean::Module) -> BTreeMap<String, Vec<String>> {
+    fn build_sidebar_items(
+        &self,
+        item: &clean::Item,
+        m: &clean::Module,
+    ) -> BTreeMap<String, Vec<String>> {
         // BTreeMap instead of HashMap to get a sorted output
         let mut map: BTreeMap<_, Vec<_>> = BTreeMap::new();
         let mut inserted: FxHashMap<ItemType, FxHashSet<Symbol>> = FxHashMap::default();
@@ -300,6 +305,17 @@ fn build_sidebar_items(&self, m: &clean::Module) -> BTreeMap<String, Vec<String>
                 map.entry(short).or_default().push(myname);
             }
         }
+        // Items that are only defined in other configurations have their pages written by
+        // those configurations.
+        if let Some(variants) = &self.shared.cache.cfg_variants {
+            let module_path = cfg_variant_path(self.tcx(), item.item_id.expect_def_id());
+            for (other, _) in variants.items_from_other_variants(&module_path) {
+                if inserted.entry(other.item_type).or_default().insert(Symbol::intern(&other.name))
+                {
+                    map.entry(other.item_type.to_string()).or_default().push(other.name.clone());
+                }
+            }
+        }
 
         match self.shared.module_sorting {
             ModuleSorting::Alphabetical => {
@@ -468,6 +484,7 @@ fn init(
             call_locations,
             no_emit_shared,
             html_no_source,
+            doc_version,
             ..
         } = options;
 
@@ -493,6 +510,7 @@ fn init(
             krate_version: krate_version.to_string(),
             css_file_extension: extension_css,
             scrape_examples_extension: !call_locations.is_empty(),
+            doc_version,
         };
         let mut issue_tracker_base_url = None;
         let mut include_sources = !html_no_source;
@@ -673,7 +691,7 @@ fn after_krate(&mut self) -> Result<(), Error> {
                             </section>\
                          </noscript>\
                          <script defer src=\"{static_root_path}{settings_js}\"></script>",
-                        static_root_path = page.get_static_root_path(),
+                        static_root_path = page.get_static_root_path(&shared.layout),
                         settings_js = static_files::STATIC_FILES.settings_js,
                     );
                     // Pre-load all theme CSS files, so that switching feels seamless.
@@ -811,7 +829,7 @@ fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
             else {
                 unreachable!()
             };
-            let items = self.build_sidebar_items(module);
+            let items = self.build_sidebar_items(item, module);
             let js_dst = self.dst.join(&format!("sidebar-items{}.js", self.shared.resource_suffix));
             let v = format!("window.SIDEBAR_ITEMS = {};", serde_json::to_string(&items).unwrap());
             self.shared.fs.write(js_dst, v)?;
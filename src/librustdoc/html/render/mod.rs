@@ -63,6 +63,7 @@
 pub(crate) use self::span_map::{LinkFromSrc, collect_spans_and_sources};
 pub(crate) use self::write_shared::*;
 use crate::clean::{self, ItemId, RenderedLink};
+use crate::core::CfgVariantItem;
 use crate::error::Error;
 use crate::formats::Impl;
 use crate::formats::cache::Cache;
@@ -2086,6 +2087,15 @@ pub(crate) fn render_impl_summary(
     w.write_str("</section>");
 }
 
+/// The id of the section documenting an associated item or trait implementation that is only
+/// defined in other `--cfg-variant` configurations than the documented one.
+fn cfg_variant_item_id(item: &CfgVariantItem) -> String {
+    match item.item_type {
+        ItemType::Impl => format!("other-impl-{}", small_url_encode(item.signature.clone())),
+        item_type => format!("{item_type}.{}", item.name),
+    }
+}
+
 pub(crate) fn small_url_encode(s: String) -> String {
     // These characters don't need to be escaped in a URI.
     // See https://url.spec.whatwg.org/#query-percent-encode-set
@@ -19,15 +19,16 @@
 
 use super::type_layout::document_type_layout;
 use super::{
-    AssocItemLink, AssocItemRender, Context, ImplRenderingParameters, RenderMode,
-    collect_paths_for_type, document, ensure_trailing_slash, get_filtered_impls_for_reference,
-    item_ty_to_section, notable_traits_button, notable_traits_json, render_all_impls,
-    render_assoc_item, render_assoc_items, render_attributes_in_code, render_attributes_in_pre,
-    render_impl, render_rightside, render_stability_since_raw,
-    render_stability_since_raw_with_extra, write_section_heading,
+    AssocItemLink, AssocItemRender, Context, ImplRenderingParameters, ItemSection, RenderMode,
+    cfg_variant_item_id, collect_paths_for_type, document, ensure_trailing_slash,
+    get_filtered_impls_for_reference, item_ty_to_section, notable_traits_button,
+    notable_traits_json, render_all_impls, render_assoc_item, render_assoc_items,
+    render_attributes_in_code, render_attributes_in_pre, render_impl, render_rightside,
+    render_stability_since_raw, render_stability_since_raw_with_extra, write_section_heading,
 };
 use crate::clean;
 use crate::config::ModuleSorting;
+use crate::core::cfg_variant_path;
 use crate::formats::Impl;
 use crate::formats::item_type::ItemType;
 use crate::html::escape::{Escape, EscapeBodyTextWithWbr};
@@ -277,6 +278,17 @@ pub(super) fn print_item(cx: &mut Context<'_>, item: &clean::Item, buf: &mut Buf
         }
     }
 
+    if matches!(
+        item.kind,
+        clean::StructItem(..)
+            | clean::UnionItem(..)
+            | clean::EnumItem(..)
+            | clean::TraitItem(..)
+            | clean::ForeignTypeItem
+    ) {
+        render_cfg_variant_assoc_items(buf, cx, item);
+    }
+
     // Render notable-traits.js used for all methods in this module.
     if !cx.types_with_notable_traits.is_empty() {
         write!(
@@ -394,11 +406,13 @@ fn cmp(i1: &clean::Item, i2: &clean::Item, tcx: TyCtxt<'_>) -> Ordering {
 
     debug!("{not_stripped_items:?}");
     let mut last_section = None;
+    let mut other_variant_rows = other_variant_item_rows(cx, item);
 
     for (_, myitem) in &not_stripped_items {
         let my_section = item_ty_to_section(myitem.type_());
         if Some(my_section) != last_section {
-            if last_section.is_some() {
+            if let Some(last_section) = last_section {
+                write_other_variant_rows(w, last_section, &mut other_variant_rows);
                 w.write_str(ITEM_TABLE_CLOSE);
             }
             last_section = Some(my_section);
@@ -528,11 +542,112 @@ fn cmp(i1: &clean::Item, i2: &clean::Item, tcx: TyCtxt<'_>) -> Ordering {
         }
     }
 
-    if last_section.is_some() {
+    if let Some(last_section) = last_section {
+        write_other_variant_rows(w, last_section, &mut other_variant_rows);
+        w.write_str(ITEM_TABLE_CLOSE);
+    }
+    // Sections that only have items from other configurations come last.
+    while let Some(&(section, _)) = other_variant_rows.first() {
+        write_section_heading(
+            w,
+            section.name(),
+            &cx.derive_id(section.id()),
+            None,
+            ITEM_TABLE_OPEN,
+        );
+        write_other_variant_rows(w, section, &mut other_variant_rows);
         w.write_str(ITEM_TABLE_CLOSE);
     }
 }
 
+/// Renders the rows listing the items of `module` that are only defined in other `--cfg-variant`
+/// configurations than the documented one. They link to the pages written for those
+/// configurations.
+fn other_variant_item_rows(cx: &Context<'_>, module: &clean::Item) -> Vec<(ItemSection, String)> {
+    let Some(variants) = &cx.shared.cache.cfg_variants else { return Vec::new() };
+    let module_path = cfg_variant_path(cx.tcx(), module.item_id.expect_def_id());
+    variants
+        .items_from_other_variants(&module_path)
+        .into_iter()
+        .map(|(item, cfg)| {
+            let docs = MarkdownSummaryLine(&item.doc, &[]).into_string();
+            let (docs_before, docs_after) = if docs.is_empty() {
+                ("", "")
+            } else {
+                ("<div class=\"desc docblock-short\">", "</div>")
+            };
+            let row = format!(
+                "{ITEM_TABLE_ROW_OPEN}\
+                 <div class=\"item-name\">\
+                    <a class=\"{class}\" href=\"{href}\" title=\"{class} {name}\">{name_wbr}</a>\
+                    <wbr><span class=\"stab portability\" title=\"{cfg_long}\">{cfg_short}</span>\
+                 </div>\
+                 {docs_before}{docs}{docs_after}\
+                 {ITEM_TABLE_ROW_CLOSE}",
+                class = item.item_type,
+                href = item_path(item.item_type, &item.name),
+                name = Escape(&item.name),
+                name_wbr = EscapeBodyTextWithWbr(&item.name),
+                cfg_long = Escape(&cfg.render_long_plain()),
+                cfg_short = cfg.render_short_html(),
+            );
+            (item_ty_to_section(item.item_type), row)
+        })
+        .collect()
+}
+
+/// Renders the associated items and trait implementations of `it` that are only defined in other
+/// `--cfg-variant` configurations than the documented one, so that they can be found from the page
+/// of every configuration.
+fn render_cfg_variant_assoc_items(w: &mut Buffer, cx: &Context<'_>, it: &clean::Item) {
+    let Some(variants) = &cx.shared.cache.cfg_variants else { return };
+    let owner_path = cfg_variant_path(cx.tcx(), it.item_id.expect_def_id());
+    let items = variants.assoc_items_from_other_variants(&owner_path);
+    if items.is_empty() {
+        return;
+    }
+    write_section_heading(
+        w,
+        "Other Configurations",
+        "other-configurations",
+        None,
+        "<div id=\"other-configurations-list\">",
+    );
+    for (item, cfg) in items {
+        let docs = MarkdownSummaryLine(&item.doc, &[]).into_string();
+        write!(
+            w,
+            "<section id=\"{id}\" class=\"{class}\">\
+                <a href=\"#{id}\" class=\"anchor\">§</a>\
+                <h4 class=\"code-header\">{signature}</h4>\
+             </section>\
+             <span class=\"item-info\"><div class=\"stab portability\">{cfg}</div></span>",
+            id = cfg_variant_item_id(item),
+            class = item.item_type,
+            signature = Escape(&item.signature),
+            cfg = cfg.render_long_html(),
+        );
+        if !docs.is_empty() {
+            write!(w, "<div class=\"docblock\">{docs}</div>");
+        }
+    }
+    w.write_str("</div>");
+}
+
+fn write_other_variant_rows(
+    w: &mut Buffer,
+    section: ItemSection,
+    rows: &mut Vec<(ItemSection, String)>,
+) {
+    rows.retain(|(row_section, row)| {
+        if *row_section != section {
+            return true;
+        }
+        w.write_str(row);
+        false
+    });
+}
+
 /// Render the stability, deprecation and portability tags that are displayed in the item's summary
 /// at the module level.
 fn extra_info_tags<'a, 'tcx: 'a>(
@@ -8,8 +8,9 @@
 use rustc_middle::ty::{self, TyCtxt};
 use tracing::debug;
 
-use super::{Context, ItemSection, item_ty_to_section};
+use super::{Context, ItemSection, cfg_variant_item_id, item_ty_to_section};
 use crate::clean;
+use crate::core::cfg_variant_path;
 use crate::formats::Impl;
 use crate::formats::item_type::ItemType;
 use crate::html::format::Buffer;
@@ -127,7 +128,7 @@ pub(super) fn print_sidebar(cx: &Context<'_>, it: &clean::Item, buffer: &mut Buf
         clean::EnumItem(ref e) => sidebar_enum(cx, it, e, &mut blocks),
         clean::TypeAliasItem(ref t) => sidebar_type_alias(cx, it, t, &mut blocks),
         clean::ModuleItem(ref m) => {
-            blocks.push(sidebar_module(&m.items, &mut ids, ModuleLike::from(it)))
+            blocks.push(sidebar_module(cx, it, &m.items, &mut ids, ModuleLike::from(it)))
         }
         clean::ForeignTypeItem => sidebar_foreign_type(cx, it, &mut blocks),
         _ => {}
@@ -453,6 +454,32 @@ fn sidebar_assoc_items<'a>(
 
         links.append(&mut blocks);
     }
+    sidebar_cfg_variant_items(cx, it, links);
+}
+
+/// Adds the associated items and trait implementations of this type or trait that are only
+/// defined in other `--cfg-variant` configurations than the documented one.
+fn sidebar_cfg_variant_items<'a>(
+    cx: &'a Context<'_>,
+    it: &'a clean::Item,
+    links: &mut Vec<LinkBlock<'a>>,
+) {
+    let Some(variants) = &cx.cache().cfg_variants else { return };
+    let owner_path = cfg_variant_path(cx.tcx(), it.item_id.expect_def_id());
+    let mut items: Vec<_> = variants
+        .assoc_items_from_other_variants(&owner_path)
+        .into_iter()
+        .map(|(item, _)| {
+            let name = if item.item_type == ItemType::Impl { &item.signature } else { &item.name };
+            Link::new(cfg_variant_item_id(item), name.clone())
+        })
+        .collect();
+    items.sort();
+    links.push(LinkBlock::new(
+        Link::new("other-configurations", "Other Configurations"),
+        "",
+        items,
+    ));
 }
 
 fn sidebar_deref_methods<'a>(
@@ -576,11 +603,13 @@ pub(crate) fn sidebar_module_like(
 }
 
 fn sidebar_module(
+    cx: &Context<'_>,
+    it: &clean::Item,
     items: &[clean::Item],
     ids: &mut IdMap,
     module_like: ModuleLike,
 ) -> LinkBlock<'static> {
-    let item_sections_in_use: FxHashSet<_> = items
+    let mut item_sections_in_use: FxHashSet<_> = items
         .iter()
         .filter(|it| {
             !it.is_stripped()
@@ -599,6 +628,16 @@ fn sidebar_module(
         })
         .map(|it| item_ty_to_section(it.type_()))
         .collect();
+    // The module page also lists the items that are only defined in other configurations.
+    if let Some(variants) = &cx.cache().cfg_variants {
+        let module_path = cfg_variant_path(cx.tcx(), it.item_id.expect_def_id());
+        item_sections_in_use.extend(
+            variants
+                .items_from_other_variants(&module_path)
+                .into_iter()
+                .map(|(item, _)| item_ty_to_section(item.item_type)),
+        );
+    }
 
     sidebar_module_like(item_sections_in_use, ids, module_like)
 }
@@ -76,13 +76,15 @@ pub(crate) fn write_shared(
     let crate_name_json = OrderedJson::serialize(crate_name).unwrap(); // "rand"
     let external_crates = hack_get_external_crate_names(&cx.dst, &cx.shared.resource_suffix)?;
     let info = CrateInfo {
-        version: CrateInfoVersion::V1,
+        version: CrateInfoVersion::V2,
         src_files_js: SourcesPart::get(cx, &crate_name_json)?,
         search_index_js: SearchIndexPart::get(index, &cx.shared.resource_suffix)?,
         all_crates: AllCratesPart::get(crate_name_json.clone(), &cx.shared.resource_suffix)?,
         crates_index: CratesIndexPart::get(&crate_name, &external_crates)?,
         trait_impl: TraitAliasPart::get(cx, &crate_name_json)?,
         type_impl: TypeAliasPart::get(cx, krate, &crate_name_json)?,
+        versions: VersionsPart::get(opt.doc_version.as_deref(), &cx.shared.resource_suffix)?,
+        item_paths: ItemPathsPart::get(cx, opt.doc_version.as_deref())?,
     };
 
     if let Some(parts_out_dir) = &opt.parts_out_dir {
@@ -165,6 +167,15 @@ fn write_rendered_cross_crate_info(
     }
     write_rendered_cci::<TraitAliasPart, _>(TraitAliasPart::blank, dst, &crates, m)?;
     write_rendered_cci::<TypeAliasPart, _>(TypeAliasPart::blank, dst, &crates, m)?;
+    if let Some(doc_version) = &opt.doc_version {
+        write_rendered_cci::<VersionsPart, _>(VersionsPart::blank, dst, &crates, m)?;
+        write_rendered_cci::<ItemPathsPart, _>(
+            || ItemPathsPart::blank(doc_version),
+            dst,
+            &crates,
+            m,
+        )?;
+    }
     Ok(())
 }
 
@@ -177,7 +188,12 @@ fn write_static_files(
     css_file_extension: Option<&Path>,
     resource_suffix: &str,
 ) -> Result<(), Error> {
-    let static_dir = dst.join("static.files");
+    // With `--doc-version`, `dst` is the directory of one version and the static files are
+    // shared by all of them.
+    let static_dir = match opt.doc_version {
+        Some(_) => dst.join("..").join("static.files"),
+        None => dst.join("static.files"),
+    };
     try_err!(fs::create_dir_all(&static_dir), &static_dir);
 
     // Handle added third-party themes
@@ -252,6 +268,11 @@ pub(crate) struct CrateInfo {
     crates_index: PartsAndLocations<CratesIndexPart>,
     trait_impl: PartsAndLocations<TraitAliasPart>,
     type_impl: PartsAndLocations<TypeAliasPart>,
+    /// Missing from `V1` files, which predate `--doc-version`.
+    #[serde(default)]
+    versions: PartsAndLocations<VersionsPart>,
+    #[serde(default)]
+    item_paths: PartsAndLocations<ItemPathsPart>,
 }
 
 impl CrateInfo {
@@ -271,14 +292,18 @@ pub(crate) fn read_many(parts_paths: &[PathToParts]) -> Result<Vec<Self>, Error>
 
 /// Version for the format of the crate-info file.
 ///
-/// This enum should only ever have one variant, representing the current version.
+/// This enum should only have variants for the versions that can still be read, the last one
+/// representing the current version.
 /// Gives pretty good error message about expecting the current version on deserialize.
 ///
 /// Must be incremented (V2, V3, etc.) upon any changes to the search index or CrateInfo,
 /// to provide better diagnostics about including an invalid file.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 enum CrateInfoVersion {
+    /// Before `--doc-version`. Its parts only lack the versions and item paths, which are
+    /// empty for crates documented without `--doc-version` anyway.
     V1,
+    V2,
 }
 
 /// Paths (relative to the doc root) and their pre-merge contents
@@ -413,6 +438,80 @@ fn hack_get_external_crate_names(
     Ok(content)
 }
 
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+struct Versions;
+type VersionsPart = Part<Versions, OrderedJson>;
+impl CciPart for VersionsPart {
+    type FileFormat = sorted_template::Js;
+    fn from_crate_info(crate_info: &CrateInfo) -> &PartsAndLocations<Self> {
+        &crate_info.versions
+    }
+}
+
+impl VersionsPart {
+    fn blank() -> SortedTemplate<<Self as CciPart>::FileFormat> {
+        SortedTemplate::from_before_after("window.DOC_VERSIONS = [", "];")
+    }
+
+    /// The list of versions lives next to the version directories, so that every version
+    /// documented into the same `--out-dir` shows up in the version switcher.
+    fn get(
+        doc_version: Option<&str>,
+        resource_suffix: &str,
+    ) -> Result<PartsAndLocations<Self>, Error> {
+        let Some(doc_version) = doc_version else {
+            return Ok(PartsAndLocations::default());
+        };
+        let path = Path::new("..").join(suffix_path("versions.js", resource_suffix));
+        Ok(PartsAndLocations::with(path, OrderedJson::serialize(doc_version).unwrap()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+struct ItemPaths;
+type ItemPathsPart = Part<ItemPaths, OrderedJson>;
+impl CciPart for ItemPathsPart {
+    type FileFormat = sorted_template::Js;
+    fn from_crate_info(crate_info: &CrateInfo) -> &PartsAndLocations<Self> {
+        &crate_info.item_paths
+    }
+}
+
+impl ItemPathsPart {
+    /// Loaded by the version switcher of *other* versions, to check whether the page they are
+    /// showing also exists in this one.
+    fn blank(doc_version: &str) -> SortedTemplate<<Self as CciPart>::FileFormat> {
+        let doc_version = OrderedJson::serialize(doc_version).unwrap();
+        SortedTemplate::from_before_after(
+            format!("window.loadedVersionItemPaths({doc_version}, ["),
+            "]);",
+        )
+    }
+
+    /// Pages of the local crate, relative to the root of the version.
+    fn get(cx: &Context<'_>, doc_version: Option<&str>) -> Result<PartsAndLocations<Self>, Error> {
+        if doc_version.is_none() {
+            return Ok(PartsAndLocations::default());
+        }
+        let cache = &cx.shared.cache;
+        let mut paths: Vec<String> = cache
+            .paths
+            .iter()
+            .filter(|(def_id, _)| def_id.is_local())
+            .map(|(_, (fqp, item_type))| match item_type {
+                ItemType::Module => format!("{}/index.html", fqp.iter().join("/")),
+                _ => {
+                    let (name, module) = fqp.split_last().expect("empty path");
+                    format!("{}/{item_type}.{name}.html", module.iter().join("/"))
+                }
+            })
+            .collect();
+        paths.sort_unstable();
+        let path = suffix_path("item-paths.js", &cx.shared.resource_suffix);
+        Ok(PartsAndLocations::with(path, OrderedJson::serialize(paths).unwrap()))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
 struct CratesIndex;
 type CratesIndexPart = Part<CratesIndex, String>;
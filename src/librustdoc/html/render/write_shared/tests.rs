@@ -66,6 +66,50 @@ fn all_crates_parts() {
     assert_eq!(&parts.parts[0].1.to_string(), r#""crate""#);
 }
 
+#[test]
+fn versions_template() {
+    let mut template = VersionsPart::blank();
+    assert_eq!(but_last_line(&template.to_string()), r"window.DOC_VERSIONS = [];");
+    template.append(OrderedJson::serialize("1.1").unwrap().to_string());
+    template.append(OrderedJson::serialize("1.0").unwrap().to_string());
+    assert_eq!(but_last_line(&template.to_string()), r#"window.DOC_VERSIONS = ["1.0","1.1"];"#);
+}
+
+#[test]
+fn versions_parts() {
+    let parts = VersionsPart::get(None, "").unwrap();
+    assert!(parts.parts.is_empty());
+    let parts = VersionsPart::get(Some("1.0"), "").unwrap();
+    assert_eq!(&parts.parts[0].0, Path::new("../versions.js"));
+    assert_eq!(&parts.parts[0].1.to_string(), r#""1.0""#);
+}
+
+#[test]
+fn v1_crate_info() {
+    // Parts written by a rustdoc without `--doc-version` can still be merged.
+    let info: CrateInfo = serde_json::from_str(
+        r#"{"version":"V1","src_files_js":[],"search_index_js":[],"all_crates":[["crates.js","a"]],"crates_index":[],"trait_impl":[],"type_impl":[]}"#,
+    )
+    .unwrap();
+    assert_eq!(&info.all_crates.parts[0].1.to_string(), r#""a""#);
+    assert!(info.versions.parts.is_empty());
+    assert!(info.item_paths.parts.is_empty());
+}
+
+#[test]
+fn item_paths_template() {
+    let mut template = ItemPathsPart::blank("1.0");
+    assert_eq!(
+        but_last_line(&template.to_string()),
+        r#"window.loadedVersionItemPaths("1.0", []);"#
+    );
+    template.append(OrderedJson::serialize(["a/index.html"]).unwrap().to_string());
+    assert_eq!(
+        but_last_line(&template.to_string()),
+        r#"window.loadedVersionItemPaths("1.0", [["a/index.html"]]);"#
+    );
+}
+
 #[test]
 fn search_index_template() {
     let mut template = SearchIndexPart::blank();
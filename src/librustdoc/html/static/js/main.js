@@ -930,6 +930,101 @@ function preLoadCss(cssUrl) {
         sidebarElems.appendChild(ul);
     }
 
+    // When documenting with `--doc-version`, add a `<select>` to jump to the current page in
+    // another version of the documentation. Versions live in sibling directories, and each of
+    // them lists its pages in `item-paths.js` so we can tell when the target page is missing.
+    function addVersionSwitcher() {
+        const currentVersion = getVar("doc-version");
+        if (!currentVersion || !window.DOC_VERSIONS) {
+            return;
+        }
+        const sidebarCrate = document.querySelector(".sidebar-crate");
+        if (!sidebarCrate) {
+            return;
+        }
+        const versionsRoot = new URL(window.rootPath + "../", window.location.href);
+        const page = new URL(window.location.href);
+        page.hash = "";
+        page.search = "";
+        const currentRoot = new URL(currentVersion + "/", versionsRoot).href;
+        const relativePath = page.href.startsWith(currentRoot) ?
+            page.href.slice(currentRoot.length) : "";
+
+        const select = document.createElement("select");
+        select.id = "version-switcher";
+        select.title = "Documentation version";
+        // `versions.js` is merged in lexicographic order, which puts "1.10" before "1.9".
+        const versions = [...window.DOC_VERSIONS].sort(
+            (a, b) => a.localeCompare(b, undefined, {numeric: true}),
+        );
+        for (const version of versions) {
+            const option = document.createElement("option");
+            option.value = version;
+            option.textContent = version;
+            option.selected = version === currentVersion;
+            select.appendChild(option);
+        }
+
+        window.loadedVersionItemPaths = (version, crates) => {
+            if (version !== select.value) {
+                return;
+            }
+            const versionRoot = new URL(version + "/", versionsRoot);
+            // Pages not generated for a specific item (`all.html`, `settings.html`, ...) are
+            // always available.
+            const isItemPage = relativePath.split("/").length > 1 &&
+                !relativePath.endsWith("/all.html");
+            if (!isItemPage || crates.some(paths => paths.includes(relativePath))) {
+                window.location.href = new URL(relativePath, versionRoot).href;
+                return;
+            }
+            let missing = document.getElementById("version-missing");
+            if (!missing) {
+                missing = document.createElement("div");
+                missing.id = "version-missing";
+                missing.className = "warning";
+                document.getElementById(MAIN_ID).prepend(missing);
+            }
+            const crateRoot = new URL(window.currentCrate + "/index.html", versionRoot);
+            // The path comes from the URL of the page, so it must not be parsed as HTML.
+            const code = text => {
+                const elem = document.createElement("code");
+                elem.textContent = text;
+                return elem;
+            };
+            const strong = document.createElement("strong");
+            strong.textContent = version;
+            const link = document.createElement("a");
+            link.href = crateRoot.href;
+            link.append("the ", code(window.currentCrate), " documentation of this version");
+            missing.replaceChildren(
+                code(relativePath),
+                " does not exist in version ",
+                strong,
+                ". Go to ",
+                link,
+                " instead.",
+            );
+            select.value = currentVersion;
+        };
+        select.onchange = () => {
+            if (select.value === currentVersion) {
+                return;
+            }
+            const version = select.value;
+            const versionRoot = new URL(version + "/", versionsRoot);
+            loadScript(
+                new URL("item-paths" + getVar("resource-suffix") + ".js", versionRoot).href,
+                () => {
+                    // The other version has no list of pages, try the page anyway.
+                    window.location.href = new URL(relativePath, versionRoot).href;
+                },
+            );
+        };
+
+        sidebarCrate.appendChild(select);
+    }
+
     function expandAllDocs() {
         const innerToggle = document.getElementById(toggleAllDocsId);
         removeClass(innerToggle, "will-expand");
@@ -1525,6 +1620,7 @@ href="https://doc.rust-lang.org/${channel}/rustdoc/read-documentation/search.htm
     setMobileTopbar();
     addSidebarItems();
     addSidebarCrates();
+    addVersionSwitcher();
     onHashChange(null);
     window.addEventListener("hashchange", onHashChange);
     searchState.setup();
@@ -0,0 +1,131 @@
+//! Collects the items used by function bodies, for the `--json-call-graph` flag.
+//!
+//! Rustdoc doesn't type-check function bodies on its own, so this is only done on request: the
+//! callee of a method call or of an overloaded operator is only known after type-checking.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_hir::def::{CtorOf, DefKind, Res};
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{self as hir, HirId};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::ty::{TyCtxt, TypeckResults};
+
+/// The items used by a function body, each listed once in the order they are first used.
+pub(crate) struct BodyRefs {
+    pub(crate) calls: FxIndexSet<DefId>,
+    pub(crate) references: FxIndexSet<DefId>,
+}
+
+/// Returns the items used by the body of `def_id`, or `None` if it doesn't have one.
+pub(crate) fn collect_body_refs(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Option<BodyRefs> {
+    let body = tcx.hir().maybe_body_owned_by(def_id)?;
+    let mut collector = BodyRefsCollector {
+        tcx,
+        typeck_results: tcx.typeck(def_id),
+        calls: FxIndexSet::default(),
+        references: FxIndexSet::default(),
+    };
+    collector.visit_body(body);
+    let BodyRefsCollector { calls, mut references, .. } = collector;
+    // Paths to called functions are visited as well; only list them as calls.
+    references.retain(|def_id| !calls.contains(def_id));
+    Some(BodyRefs { calls, references })
+}
+
+struct BodyRefsCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    calls: FxIndexSet<DefId>,
+    references: FxIndexSet<DefId>,
+}
+
+impl<'tcx> BodyRefsCollector<'tcx> {
+    /// Records a use of `res`, if it is an item which can be documented.
+    fn add_res(&mut self, res: Res) {
+        let Res::Def(kind, def_id) = res else { return };
+        let def_id = match kind {
+            // Point to the struct or the variant rather than to its constructor.
+            DefKind::Ctor(..) => self.tcx.parent(def_id),
+            DefKind::Fn
+            | DefKind::AssocFn
+            | DefKind::Const
+            | DefKind::AssocConst
+            | DefKind::Static { .. }
+            | DefKind::Struct
+            | DefKind::Enum
+            | DefKind::Union
+            | DefKind::Variant
+            | DefKind::Trait
+            | DefKind::TraitAlias
+            | DefKind::TyAlias
+            | DefKind::AssocTy
+            | DefKind::ForeignTy => def_id,
+            _ => return,
+        };
+        self.references.insert(def_id);
+    }
+
+    fn add_qpath(&mut self, qpath: &hir::QPath<'tcx>, hir_id: HirId) {
+        let res = self.typeck_results.qpath_res(qpath, hir_id);
+        self.add_res(res);
+    }
+
+    fn add_call(&mut self, def_id: DefId) {
+        match self.tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn => {
+                self.calls.insert(def_id);
+            }
+            // Calling a tuple struct or a tuple variant only constructs it.
+            DefKind::Ctor(CtorOf::Struct | CtorOf::Variant, _) => {
+                self.references.insert(self.tcx.parent(def_id));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for BodyRefsCollector<'tcx> {
+    // Closures are part of the body, and share its typeck results.
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        // Method calls and overloaded operators.
+        if let Some(def_id) = self.typeck_results.type_dependent_def_id(expr.hir_id) {
+            self.add_call(def_id);
+        }
+        match expr.kind {
+            hir::ExprKind::Call(callee, _) => {
+                if let hir::ExprKind::Path(ref qpath) = callee.kind
+                    && let Res::Def(_, def_id) = self.typeck_results.qpath_res(qpath, callee.hir_id)
+                {
+                    self.add_call(def_id);
+                }
+            }
+            hir::ExprKind::Path(ref qpath) => self.add_qpath(qpath, expr.hir_id),
+            hir::ExprKind::Struct(qpath, ..) => self.add_qpath(qpath, expr.hir_id),
+            _ => {}
+        }
+        intravisit::walk_expr(self, expr)
+    }
+
+    fn visit_pat(&mut self, pat: &'tcx hir::Pat<'tcx>) {
+        match pat.kind {
+            hir::PatKind::Path(ref qpath)
+            | hir::PatKind::TupleStruct(ref qpath, ..)
+            | hir::PatKind::Struct(ref qpath, ..) => self.add_qpath(qpath, pat.hir_id),
+            _ => {}
+        }
+        intravisit::walk_pat(self, pat)
+    }
+
+    fn visit_path(&mut self, path: &hir::Path<'tcx>, _id: HirId) {
+        // Resolved paths in types, e.g. in `let` statements or in turbofishes.
+        self.add_res(path.res);
+        intravisit::walk_path(self, path)
+    }
+}
@@ -19,6 +19,7 @@
 use crate::formats::FormatRenderer;
 use crate::formats::item_type::ItemType;
 use crate::json::JsonRenderer;
+use crate::json::call_graph::collect_body_refs;
 use crate::passes::collect_intra_doc_links::UrlFragment;
 
 impl JsonRenderer<'_> {
@@ -186,6 +187,30 @@ fn ids_keeping_stripped(
             .map(|i| (!i.is_stripped() && !i.is_keyword()).then(|| self.id_from_item(&i)))
             .collect()
     }
+
+    /// Returns the items used by the body of a local function, if `--json-call-graph` was passed.
+    fn body_refs(&self, item_id: ItemId) -> Option<BodyRefs> {
+        if !self.json_call_graph {
+            return None;
+        }
+        let def_id = item_id.as_def_id()?.as_local()?;
+        let refs = collect_body_refs(self.tcx, def_id)?;
+        self.body_referenced_items
+            .borrow_mut()
+            .extend(refs.calls.iter().chain(&refs.references).copied());
+        Some(BodyRefs {
+            calls: refs
+                .calls
+                .into_iter()
+                .map(|def_id| self.id_from_item_default(def_id.into()))
+                .collect(),
+            references: refs
+                .references
+                .into_iter()
+                .map(|def_id| self.id_from_item_default(def_id.into()))
+                .collect(),
+        })
+    }
 }
 
 pub(crate) trait FromClean<T> {
@@ -301,6 +326,7 @@ fn from_clean_item(item: clean::Item, renderer: &JsonRenderer<'_>) -> ItemEnum {
     let name = item.name;
     let is_crate = item.is_crate();
     let header = item.fn_header(renderer.tcx);
+    let item_id = item.item_id;
 
     match item.inner.kind {
         ModuleItem(m) => {
@@ -312,14 +338,22 @@ fn from_clean_item(item: clean::Item, renderer: &JsonRenderer<'_>) -> ItemEnum {
         StructFieldItem(f) => ItemEnum::StructField(f.into_json(renderer)),
         EnumItem(e) => ItemEnum::Enum(e.into_json(renderer)),
         VariantItem(v) => ItemEnum::Variant(v.into_json(renderer)),
-        FunctionItem(f) => ItemEnum::Function(from_function(f, true, header.unwrap(), renderer)),
+        FunctionItem(f) => {
+            let body_refs = renderer.body_refs(item_id);
+            ItemEnum::Function(from_function(f, true, body_refs, header.unwrap(), renderer))
+        }
         ForeignFunctionItem(f, _) => {
-            ItemEnum::Function(from_function(f, false, header.unwrap(), renderer))
+            ItemEnum::Function(from_function(f, false, None, header.unwrap(), renderer))
         }
         TraitItem(t) => ItemEnum::Trait((*t).into_json(renderer)),
         TraitAliasItem(t) => ItemEnum::TraitAlias(t.into_json(renderer)),
-        MethodItem(m, _) => ItemEnum::Function(from_function(m, true, header.unwrap(), renderer)),
-        TyMethodItem(m) => ItemEnum::Function(from_function(m, false, header.unwrap(), renderer)),
+        MethodItem(m, _) => {
+            let body_refs = renderer.body_refs(item_id);
+            ItemEnum::Function(from_function(m, true, body_refs, header.unwrap(), renderer))
+        }
+        TyMethodItem(m) => {
+            ItemEnum::Function(from_function(m, false, None, header.unwrap(), renderer))
+        }
         ImplItem(i) => ItemEnum::Impl((*i).into_json(renderer)),
         StaticItem(s) => ItemEnum::Static(s.into_json(renderer)),
         ForeignStaticItem(s, _) => ItemEnum::Static(s.into_json(renderer)),
@@ -732,6 +766,7 @@ fn from_clean(impl_: clean::Impl, renderer: &JsonRenderer<'_>) -> Self {
 pub(crate) fn from_function(
     function: Box<clean::Function>,
     has_body: bool,
+    body_refs: Option<BodyRefs>,
     header: rustc_hir::FnHeader,
     renderer: &JsonRenderer<'_>,
 ) -> Function {
@@ -741,6 +776,7 @@ pub(crate) fn from_function(
         generics: generics.into_json(renderer),
         header: from_fn_header(&header),
         has_body,
+        body_refs,
     }
 }
 
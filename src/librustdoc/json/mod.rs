@@ -4,15 +4,18 @@
 //! output. See [the RFC](https://github.com/rust-lang/rfcs/pull/2963) and the [`types`] module
 //! docs for usage and details.
 
+mod call_graph;
 mod conversions;
 mod import_finder;
 
 use std::cell::RefCell;
 use std::fs::{File, create_dir_all};
 use std::io::{BufWriter, Write, stdout};
+use std::iter;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::def_id::{DefId, DefIdSet};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::Session;
@@ -26,12 +29,14 @@
 use tracing::{debug, trace};
 
 use crate::clean::ItemKind;
+use crate::clean::inline::item_relative_path;
 use crate::clean::types::{ExternalCrate, ExternalLocation};
 use crate::config::RenderOptions;
 use crate::docfs::PathError;
 use crate::error::Error;
 use crate::formats::FormatRenderer;
 use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
 use crate::json::conversions::IntoJson;
 use crate::{clean, try_err};
 
@@ -56,6 +61,10 @@ pub(crate) struct JsonRenderer<'tcx> {
     cache: Rc<Cache>,
     imported_items: DefIdSet,
     id_interner: Rc<RefCell<FxHashMap<(FullItemId, Option<FullItemId>), types::Id>>>,
+    /// Whether to list the items used by function bodies, set with `--json-call-graph`.
+    json_call_graph: bool,
+    /// All the items used by the function bodies rendered so far.
+    body_referenced_items: Rc<RefCell<DefIdSet>>,
 }
 
 impl<'tcx> JsonRenderer<'tcx> {
@@ -114,6 +123,46 @@ fn get_impls(&mut self, id: DefId) -> Vec<types::Id> {
             .unwrap_or_default()
     }
 
+    /// Adds the external items used by function bodies to `paths`, and removes the local items
+    /// which aren't documented from the function bodies' references, since they can't be found
+    /// in the output.
+    fn resolve_body_refs(
+        &self,
+        index: &mut FxHashMap<types::Id, types::Item>,
+        paths: &mut FxHashMap<types::Id, types::ItemSummary>,
+    ) {
+        let tcx = self.tcx;
+        let mut known = FxHashSet::default();
+        for &def_id in self.body_referenced_items.borrow().iter() {
+            let id = self.id_from_item_default(def_id.into());
+            if !def_id.is_local() {
+                paths.entry(id).or_insert_with(|| {
+                    let parent_kind = tcx.opt_parent(def_id).map(|parent| tcx.def_kind(parent));
+                    let kind = ItemType::from_def_kind(tcx.def_kind(def_id), parent_kind);
+                    types::ItemSummary {
+                        crate_id: def_id.krate.as_u32(),
+                        path: iter::once(tcx.crate_name(def_id.krate))
+                            .chain(item_relative_path(tcx, def_id))
+                            .map(|s| s.to_string())
+                            .collect(),
+                        kind: kind.into_json(self),
+                    }
+                });
+            }
+            if index.contains_key(&id) || paths.contains_key(&id) {
+                known.insert(id);
+            }
+        }
+        for item in index.values_mut() {
+            if let types::ItemEnum::Function(types::Function { body_refs: Some(refs), .. }) =
+                &mut item.inner
+            {
+                refs.calls.retain(|id| known.contains(id));
+                refs.references.retain(|id| known.contains(id));
+            }
+        }
+    }
+
     fn serialize_and_write<T: Write>(
         &self,
         output_crate: types::Crate,
@@ -156,6 +205,8 @@ fn init(
                 cache: Rc::new(cache),
                 imported_items,
                 id_interner: Default::default(),
+                json_call_graph: options.json_call_graph,
+                body_referenced_items: Default::default(),
             },
             krate,
         ))
@@ -250,7 +301,23 @@ fn after_krate(&mut self) -> Result<(), Error> {
         debug!("Done with crate");
 
         let e = ExternalCrate { crate_num: LOCAL_CRATE };
-        let index = (*self.index).clone().into_inner();
+        let mut index = (*self.index).clone().into_inner();
+        let mut paths = self
+            .cache
+            .paths
+            .iter()
+            .chain(&self.cache.external_paths)
+            .map(|(&k, &(ref path, kind))| {
+                (self.id_from_item_default(k.into()), types::ItemSummary {
+                    crate_id: k.krate.as_u32(),
+                    path: path.iter().map(|s| s.to_string()).collect(),
+                    kind: kind.into_json(self),
+                })
+            })
+            .collect();
+        if self.json_call_graph {
+            self.resolve_body_refs(&mut index, &mut paths);
+        }
 
         debug!("Constructing Output");
         let output_crate = types::Crate {
@@ -258,19 +325,7 @@ fn after_krate(&mut self) -> Result<(), Error> {
             crate_version: self.cache.crate_version.clone(),
             includes_private: self.cache.document_private,
             index,
-            paths: self
-                .cache
-                .paths
-                .iter()
-                .chain(&self.cache.external_paths)
-                .map(|(&k, &(ref path, kind))| {
-                    (self.id_from_item_default(k.into()), types::ItemSummary {
-                        crate_id: k.krate.as_u32(),
-                        path: path.iter().map(|s| s.to_string()).collect(),
-                        kind: kind.into_json(self),
-                    })
-                })
-                .collect(),
+            paths,
             external_crates: self
                 .cache
                 .extern_locations
@@ -73,14 +73,14 @@
 
 use std::env::{self, VarError};
 use std::io::{self, IsTerminal};
-use std::process;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::{mem, process};
 
 use rustc_errors::{DiagCtxtHandle, ErrorGuaranteed, FatalError};
 use rustc_interface::interface;
 use rustc_middle::ty::TyCtxt;
-use rustc_session::config::{ErrorOutputType, RustcOptGroup, make_crate_type_option};
+use rustc_session::config::{ErrorOutputType, Input, RustcOptGroup, make_crate_type_option};
 use rustc_session::{EarlyDiagCtxt, getopts};
 use tracing::info;
 
@@ -503,6 +503,21 @@ fn opts() -> Vec<RustcOptGroup> {
                 "calculate percentage of public items with documentation",
             )
         }),
+        unstable("coverage-min", |o| {
+            o.optopt(
+                "",
+                "coverage-min",
+                "with --show-coverage, fail if less than this percentage of items is documented",
+                "PERCENT",
+            )
+        }),
+        unstable("coverage-details", |o| {
+            o.optflagmulti(
+                "",
+                "coverage-details",
+                "with --show-coverage, list undocumented items and per-module totals",
+            )
+        }),
         unstable("enable-per-target-ignores", |o| {
             o.optflagmulti(
                 "",
@@ -631,6 +646,42 @@ fn opts() -> Vec<RustcOptGroup> {
                 "path/to/doc.parts/<crate-name>",
             )
         }),
+        unstable("doc-version", |o| {
+            o.optopt(
+                "",
+                "doc-version",
+                "Documents into a sub-directory of --out-dir named after this version, sharing \
+                static files and a version switcher with the other versions found there",
+                "NAME",
+            )
+        }),
+        unstable("cfg-variant", |o| {
+            o.optmulti(
+                "",
+                "cfg-variant",
+                "Adds a configuration in which to load the crate, to infer which configurations \
+                its items are available in; the crate is rendered in the first one. SPEC is a \
+                comma-separated list of cfgs and an optional target=TRIPLE",
+                "SPEC",
+            )
+        }),
+        unstable("doc-sections-strictness", |o| {
+            o.optopt(
+                "",
+                "doc-sections-strictness",
+                "Which panics the missing_panics_doc and missing_errors_doc lints look for, and \
+                whether they check private items",
+                "[lenient|normal|strict]",
+            )
+        }),
+        unstable("json-call-graph", |o| {
+            o.optflag(
+                "",
+                "json-call-graph",
+                "List the items called or referenced by the body of each local function in the \
+                JSON output",
+            )
+        }),
         // deprecated / removed options
         unstable("disable-minification", |o| o.optflagmulti("", "disable-minification", "removed")),
         stable("plugin-path", |o| {
@@ -824,9 +875,60 @@ fn main_args(
         (false, None) => {}
     }
 
+    // With `--cfg-variant`, the crate is loaded in every configuration to find out which items
+    // each of them contains. The other configurations are documented first, so that the pages
+    // shared by all of them are written by the first one.
+    let mut options = options;
+    let specs = mem::take(&mut options.cfg_variants);
+    if specs.is_empty() {
+        return document(input, options, render_options, None, using_internal_features);
+    }
+    let variants: Arc<[_]> = specs
+        .iter()
+        .map(|variant| {
+            core::load_cfg_variant(
+                input.clone(),
+                options.clone(),
+                &render_options,
+                variant,
+                using_internal_features.clone(),
+            )
+        })
+        .collect();
+    let document_others = options.output_format == config::OutputFormat::Html
+        && !options.show_coverage
+        && !options.run_check
+        && options.scrape_examples_options.is_none();
+    if document_others {
+        for current in (1..specs.len()).rev() {
+            let mut options = options.clone();
+            specs[current].apply(&mut options);
+            let cfg_variants = core::CfgVariants { variants: variants.clone(), current };
+            document(
+                input.clone(),
+                options,
+                render_options.clone(),
+                Some(cfg_variants),
+                using_internal_features.clone(),
+            )?;
+        }
+    }
+    specs[0].apply(&mut options);
+    let cfg_variants = core::CfgVariants { variants, current: 0 };
+    document(input, options, render_options, Some(cfg_variants), using_internal_features)
+}
+
+fn document(
+    input: Input,
+    options: config::Options,
+    render_options: config::RenderOptions,
+    cfg_variants: Option<core::CfgVariants>,
+    using_internal_features: Arc<AtomicBool>,
+) -> MainResult {
     // need to move these items separately because we lose them by the time the closure is called,
     // but we can't create the dcx ahead of time because it's not Send
     let show_coverage = options.show_coverage;
+    let coverage_options = options.coverage_options.clone();
     let run_check = options.run_check;
 
     // First, parse the crate and extract all relevant information.
@@ -860,7 +962,14 @@ fn main_args(
 
             gcx.enter(|tcx| {
                 let (krate, render_opts, mut cache) = sess.time("run_global_ctxt", || {
-                    core::run_global_ctxt(tcx, show_coverage, render_options, output_format)
+                    core::run_global_ctxt(
+                        tcx,
+                        show_coverage,
+                        coverage_options,
+                        cfg_variants,
+                        render_options,
+                        output_format,
+                    )
                 })?;
                 info!("finished with rustc");
 
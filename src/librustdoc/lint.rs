@@ -204,6 +204,51 @@ macro_rules! declare_rustdoc_lint {
     "detects markdown that is interpreted differently in different parser"
 }
 
+declare_rustdoc_lint! {
+    /// The `missing_panics_doc` lint detects documented functions whose body can panic but
+    /// whose documentation has no `# Panics` section. This is a `rustdoc` only lint, see the
+    /// documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#missing_panics_doc
+    MISSING_PANICS_DOC,
+    Allow,
+    "detects functions that can panic without a `# Panics` section in their documentation"
+}
+
+declare_rustdoc_lint! {
+    /// The `missing_errors_doc` lint detects documented public functions returning a `Result`
+    /// whose documentation has no `# Errors` section. This is a `rustdoc` only lint, see the
+    /// documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#missing_errors_doc
+    MISSING_ERRORS_DOC,
+    Allow,
+    "detects functions returning `Result` without an `# Errors` section in their documentation"
+}
+
+declare_rustdoc_lint! {
+    /// The `outdated_doc_sections` lint detects `# Panics` sections in the documentation of
+    /// functions which can't panic, and `# Errors` sections in the documentation of functions
+    /// not returning a `Result`. This is a `rustdoc` only lint, see the documentation in the
+    /// [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#outdated_doc_sections
+    OUTDATED_DOC_SECTIONS,
+    Allow,
+    "detects `# Panics` and `# Errors` sections that don't apply to their function"
+}
+
+declare_rustdoc_lint! {
+    /// The `broken_ignored_examples` lint detects calls to the documented function with the
+    /// wrong number of arguments in `ignore` code blocks, which aren't compiled as doctests. This
+    /// is a `rustdoc` only lint, see the documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#broken_ignored_examples
+    BROKEN_IGNORED_EXAMPLES,
+    Allow,
+    "detects examples that aren't compiled and call the documented function wrongly"
+}
+
 pub(crate) static RUSTDOC_LINTS: Lazy<Vec<&'static Lint>> = Lazy::new(|| {
     vec![
         BROKEN_INTRA_DOC_LINKS,
@@ -218,6 +263,10 @@ macro_rules! declare_rustdoc_lint {
         UNESCAPED_BACKTICKS,
         REDUNDANT_EXPLICIT_LINKS,
         UNPORTABLE_MARKDOWN,
+        MISSING_PANICS_DOC,
+        MISSING_ERRORS_DOC,
+        OUTDATED_DOC_SECTIONS,
+        BROKEN_IGNORED_EXAMPLES,
     ]
 });
 
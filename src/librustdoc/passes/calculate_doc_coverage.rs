@@ -4,8 +4,11 @@
 use std::ops;
 
 use rustc_hir as hir;
+use rustc_hir::def_id::{CRATE_DEF_ID, DefId, LOCAL_CRATE, LocalDefId};
 use rustc_lint::builtin::MISSING_DOCS;
 use rustc_middle::lint::LintLevelSource;
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::print::{with_no_trimmed_paths, with_no_visible_paths};
 use rustc_session::lint;
 use rustc_span::FileName;
 use serde::Serialize;
@@ -25,10 +28,22 @@
 };
 
 fn calculate_doc_coverage(krate: clean::Crate, ctx: &mut DocContext<'_>) -> clean::Crate {
-    let mut calc = CoverageCalculator { items: Default::default(), ctx };
+    let mut calc = CoverageCalculator {
+        items: Default::default(),
+        modules: Default::default(),
+        undocumented: Vec::new(),
+        missing_examples: Vec::new(),
+        ctx,
+    };
     calc.visit_crate(&krate);
 
+    // Items are visited module by module, list them in source order instead.
+    for entries in [&mut calc.undocumented, &mut calc.missing_examples] {
+        entries.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    }
+
     calc.print_results();
+    calc.check_threshold();
 
     krate
 }
@@ -103,11 +118,34 @@ fn add_assign(&mut self, rhs: Self) {
     }
 }
 
+/// An item reported by `--coverage-details`, either because it has no documentation or because
+/// it is missing a code example.
+#[derive(Clone, Serialize, Debug)]
+struct ItemEntry {
+    path: String,
+    kind: &'static str,
+    file: String,
+    line: usize,
+}
+
 struct CoverageCalculator<'a, 'b> {
     items: BTreeMap<FileName, ItemCount>,
+    /// Counts rolled up per module, only used with `--coverage-details`.
+    modules: BTreeMap<String, ItemCount>,
+    undocumented: Vec<ItemEntry>,
+    missing_examples: Vec<ItemEntry>,
     ctx: &'a mut DocContext<'b>,
 }
 
+/// The JSON document emitted with `--coverage-details`.
+#[derive(Serialize)]
+struct DetailedReport<'a> {
+    files: BTreeMap<String, &'a ItemCount>,
+    modules: &'a BTreeMap<String, ItemCount>,
+    undocumented: &'a [ItemEntry],
+    missing_examples: &'a [ItemEntry],
+}
+
 fn limit_filename_len(filename: String) -> String {
     let nb_chars = filename.chars().count();
     if nb_chars > 35 {
@@ -118,16 +156,122 @@ fn limit_filename_len(filename: String) -> String {
     }
 }
 
+/// The path of `def_id` where it is defined, ignoring re-exports.
+fn def_path_str(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    with_no_visible_paths!(with_no_trimmed_paths!(tcx.def_path_str(def_id)))
+}
+
+fn print_table_line() {
+    println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
+}
+
+fn print_table_record(name: &str, count: ItemCount, percentage: f64, examples_percentage: f64) {
+    println!(
+        "| {name:<35} | {with_docs:>10} | {percentage:>9.1}% | {with_examples:>10} | \
+        {examples_percentage:>9.1}% |",
+        with_docs = count.with_docs,
+        with_examples = count.with_examples,
+    );
+}
+
 impl<'a, 'b> CoverageCalculator<'a, 'b> {
     fn to_json(&self) -> String {
-        serde_json::to_string(
-            &self
-                .items
-                .iter()
-                .map(|(k, v)| (k.prefer_local().to_string(), v))
-                .collect::<BTreeMap<String, &ItemCount>>(),
-        )
-        .expect("failed to convert JSON data to string")
+        let files = self
+            .items
+            .iter()
+            .map(|(k, v)| (k.prefer_local().to_string(), v))
+            .collect::<BTreeMap<String, &ItemCount>>();
+        let json = if self.ctx.coverage_options.show_details {
+            serde_json::to_string(&DetailedReport {
+                files,
+                modules: &self.modules,
+                undocumented: &self.undocumented,
+                missing_examples: &self.missing_examples,
+            })
+        } else {
+            serde_json::to_string(&files)
+        };
+        json.expect("failed to convert JSON data to string")
+    }
+
+    fn total(&self) -> ItemCount {
+        let mut total = ItemCount::default();
+        for &count in self.items.values() {
+            total += count;
+        }
+        total
+    }
+
+    /// Emits an error if `--coverage-min` was passed and the crate is documented below it.
+    fn check_threshold(&self) {
+        let Some(min) = self.ctx.coverage_options.min_percentage else { return };
+        let percentage = self.total().percentage().unwrap_or(100.0);
+        if percentage < min {
+            self.ctx.tcx.dcx().err(format!(
+                "documentation coverage of {percentage:.1}% is below the required minimum of {min}%"
+            ));
+        }
+    }
+
+    /// Returns the path of the module that contains `def_id`, or the crate name for the crate
+    /// root.
+    fn module_path(&self, def_id: LocalDefId) -> String {
+        let tcx = self.ctx.tcx;
+        let module = if def_id == CRATE_DEF_ID {
+            CRATE_DEF_ID
+        } else {
+            tcx.parent_module_from_def_id(def_id).to_local_def_id()
+        };
+        if module == CRATE_DEF_ID {
+            tcx.crate_name(LOCAL_CRATE).to_string()
+        } else {
+            format!("{}::{}", tcx.crate_name(LOCAL_CRATE), def_path_str(tcx, module.to_def_id()))
+        }
+    }
+
+    fn item_path(&self, i: &clean::Item) -> String {
+        let tcx = self.ctx.tcx;
+        match i.item_id.as_def_id() {
+            Some(def_id) if def_id.is_crate_root() => tcx.crate_name(LOCAL_CRATE).to_string(),
+            Some(def_id) => {
+                format!("{}::{}", tcx.crate_name(LOCAL_CRATE), def_path_str(tcx, def_id))
+            }
+            None => i.name.map(|name| name.to_string()).unwrap_or_default(),
+        }
+    }
+
+    fn print_details(&self) {
+        fn print_items(title: &str, items: &[ItemEntry]) {
+            if items.is_empty() {
+                return;
+            }
+            println!("\n{title}:");
+            for item in items {
+                println!("    {} {} ({}:{})", item.kind, item.path, item.file, item.line);
+            }
+        }
+
+        println!();
+        print_table_line();
+        println!(
+            "| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
+            "Module", "Documented", "Percentage", "Examples", "Percentage",
+        );
+        print_table_line();
+        for (module, &count) in &self.modules {
+            if let Some(percentage) = count.percentage() {
+                print_table_record(
+                    &limit_filename_len(module.clone()),
+                    count,
+                    percentage,
+                    count.examples_percentage().unwrap_or(0.),
+                );
+            }
+        }
+        print_table_line();
+
+        print_items("Undocumented items", &self.undocumented);
+        print_items("Items missing examples", &self.missing_examples);
     }
 
     fn print_results(&self) {
@@ -138,24 +282,6 @@ fn print_results(&self) {
         }
         let mut total = ItemCount::default();
 
-        fn print_table_line() {
-            println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
-        }
-
-        fn print_table_record(
-            name: &str,
-            count: ItemCount,
-            percentage: f64,
-            examples_percentage: f64,
-        ) {
-            println!(
-                "| {name:<35} | {with_docs:>10} | {percentage:>9.1}% | {with_examples:>10} | \
-                {examples_percentage:>9.1}% |",
-                with_docs = count.with_docs,
-                with_examples = count.with_examples,
-            );
-        }
-
         print_table_line();
         println!(
             "| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
@@ -184,6 +310,10 @@ fn print_table_record(
             total.examples_percentage().unwrap_or(0.0),
         );
         print_table_line();
+
+        if self.ctx.coverage_options.show_details {
+            self.print_details();
+        }
     }
 }
 
@@ -254,13 +384,37 @@ fn visit_item(&mut self, i: &clean::Item) {
 
                 if let Some(span) = i.span(self.ctx.tcx) {
                     let filename = span.filename(self.ctx.sess());
+                    let should_have_doc_example = should_have_doc_example(self.ctx, i);
                     debug!("counting {:?} {:?} in {filename:?}", i.type_(), i.name);
-                    self.items.entry(filename).or_default().count_item(
+                    self.items.entry(filename.clone()).or_default().count_item(
                         has_docs,
                         has_doc_example,
-                        should_have_doc_example(self.ctx, i),
+                        should_have_doc_example,
                         should_have_docs,
                     );
+
+                    if self.ctx.coverage_options.show_details {
+                        let module = self.module_path(hir_id.owner.def_id);
+                        self.modules.entry(module).or_default().count_item(
+                            has_docs,
+                            has_doc_example,
+                            should_have_doc_example,
+                            should_have_docs,
+                        );
+
+                        let entry = ItemEntry {
+                            path: self.item_path(i),
+                            kind: i.type_().as_str(),
+                            file: filename.prefer_local().to_string(),
+                            line: span.lo(self.ctx.sess()).line,
+                        };
+                        if should_have_docs && !has_docs {
+                            self.undocumented.push(entry.clone());
+                        }
+                        if should_have_doc_example && !has_doc_example {
+                            self.missing_examples.push(entry);
+                        }
+                    }
                 }
             }
         }
@@ -2,8 +2,10 @@
 //! efficiency and simplicity.
 
 mod bare_urls;
+mod broken_ignored_examples;
 mod check_code_block_syntax;
 mod html_tags;
+mod missing_doc_sections;
 mod redundant_explicit_links;
 mod unescaped_backticks;
 mod unportable_markdown;
@@ -22,6 +24,7 @@ struct Linter<'a, 'tcx> {
 
 pub(crate) fn run_lints(krate: Crate, cx: &mut DocContext<'_>) -> Crate {
     Linter { cx }.visit_crate(&krate);
+    missing_doc_sections::visit_private_fns(cx);
     krate
 }
 
@@ -46,6 +49,7 @@ fn visit_item(&mut self, item: &Item) {
             if may_have_code {
                 check_code_block_syntax::visit_item(self.cx, item, &dox);
                 unescaped_backticks::visit_item(self.cx, item, hir_id, &dox);
+                broken_ignored_examples::visit_item(self.cx, item, hir_id, &dox);
             }
             if may_have_block_comment_or_html {
                 html_tags::visit_item(self.cx, item, hir_id, &dox);
@@ -54,6 +58,8 @@ fn visit_item(&mut self, item: &Item) {
                 unportable_markdown::visit_item(self.cx, item, hir_id, &dox);
             }
         }
+        // Undocumented functions are missing their sections too.
+        missing_doc_sections::visit_item(self.cx, item, hir_id, &dox);
 
         self.visit_item_recur(item)
     }
@@ -0,0 +1,185 @@
+//! Detects `ignore` code blocks calling the documented function with the wrong number of
+//! arguments. Unlike other examples, they aren't compiled as doctests, so nothing else notices
+//! when the function changes under them.
+
+use rustc_ast::token::{BinOpToken, Delimiter, Token, TokenKind};
+use rustc_ast::tokenstream::{TokenStream, TokenTree};
+use rustc_hir::HirId;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_resolve::rustdoc::source_span_for_markdown_range;
+use rustc_session::parse::ParseSess;
+use rustc_span::FileName;
+use rustc_span::symbol::{Symbol, kw};
+
+use crate::clean::{self, Item};
+use crate::core::DocContext;
+use crate::html::markdown::{self, Ignore};
+
+pub(crate) fn visit_item(cx: &DocContext<'_>, item: &Item, hir_id: HirId, dox: &str) {
+    let tcx = cx.tcx;
+    let Some(def_id) = item.item_id.as_local_def_id() else { return };
+    if !matches!(item.kind, clean::FunctionItem(_) | clean::MethodItem(..) | clean::TyMethodItem(_))
+    {
+        return;
+    }
+    let Some(name) = item.name else { return };
+    let sig = tcx.fn_sig(def_id).instantiate_identity().skip_binder();
+    if sig.c_variadic {
+        return;
+    }
+    let has_self = tcx.def_kind(def_id) == DefKind::AssocFn
+        && tcx.associated_item(def_id).fn_has_self_parameter;
+    let callee = Callee { name, owner: owner_name(tcx, def_id.to_def_id()), has_self };
+    let inputs = sig.inputs().len();
+
+    let extra = markdown::ExtraInfo::new(tcx, def_id, item.attr_span(tcx));
+    for code_block in markdown::rust_code_blocks(dox, &extra) {
+        if code_block.lang_string.ignore == Ignore::None {
+            continue;
+        }
+        let psess = ParseSess::with_silent_emitter(
+            vec![rustc_parse::DEFAULT_LOCALE_RESOURCE],
+            String::new(),
+            false,
+        );
+        let stream = match rustc_parse::source_str_to_stream(
+            &psess,
+            FileName::Custom(String::from("doctest")),
+            dox[code_block.code.clone()].to_owned(),
+            None,
+        ) {
+            Ok(stream) => stream,
+            // Invalid code blocks are reported by `invalid_rust_codeblocks`.
+            Err(errors) => {
+                errors.into_iter().for_each(|err| err.cancel());
+                continue;
+            }
+        };
+        let Some((call, args)) = callee.find_wrong_call(&stream, inputs) else { continue };
+        let span =
+            source_span_for_markdown_range(tcx, dox, &code_block.range, &item.attrs.doc_strings)
+                .unwrap_or_else(|| item.attr_span(tcx));
+        tcx.node_span_lint(crate::lint::BROKEN_IGNORED_EXAMPLES, hir_id, span, |lint| {
+            lint.primary_message(format!("example calls `{name}` with {args} argument(s)"));
+            lint.span_label(tcx.def_span(def_id), format!("this takes {call} argument(s)"));
+            lint.help("`ignore` code blocks aren't compiled, so they must be updated by hand");
+        });
+    }
+}
+
+/// How the documented function is called in examples.
+struct Callee {
+    name: Symbol,
+    /// The name of the type or trait the function is associated with, if any.
+    owner: Option<Symbol>,
+    has_self: bool,
+}
+
+impl Callee {
+    /// Looks for a call to the function whose number of arguments is not `inputs`. Returns the
+    /// expected and the actual number of arguments of that call.
+    fn find_wrong_call(&self, stream: &TokenStream, inputs: usize) -> Option<(usize, usize)> {
+        let trees = stream.trees().collect::<Vec<_>>();
+        for (i, tree) in trees.iter().enumerate() {
+            match tree {
+                TokenTree::Token(Token { kind: TokenKind::Ident(name, _), .. }, _)
+                    if *name == self.name =>
+                {
+                    let Some(TokenTree::Delimited(.., Delimiter::Parenthesis, args)) =
+                        trees.get(i + 1)
+                    else {
+                        continue;
+                    };
+                    let prev = |n: usize| match i.checked_sub(n).map(|i| trees[i]) {
+                        Some(TokenTree::Token(token, _)) => Some(&token.kind),
+                        _ => None,
+                    };
+                    let expected = match prev(1) {
+                        // A method call.
+                        Some(TokenKind::Dot) if self.has_self => inputs - 1,
+                        Some(TokenKind::Dot) => continue,
+                        // A path to an associated function, e.g. `Type::name(...)`.
+                        Some(TokenKind::PathSep) if self.owner.is_some() => match prev(2) {
+                            Some(TokenKind::Ident(owner, _)) if self.owner == Some(*owner) => {
+                                inputs
+                            }
+                            _ => continue,
+                        },
+                        // The definition of another function of the same name.
+                        Some(TokenKind::Ident(kw::Fn, _)) => continue,
+                        // A free function, e.g. `name(...)` or `module::name(...)`.
+                        _ if self.owner.is_none() => inputs,
+                        _ => continue,
+                    };
+                    let actual = count_args(args);
+                    if actual != expected {
+                        return Some((expected, actual));
+                    }
+                }
+                TokenTree::Token(..) => {}
+                TokenTree::Delimited(.., inner) => {
+                    if let Some(wrong) = self.find_wrong_call(inner, inputs) {
+                        return Some(wrong);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Counts the comma-separated arguments in the parentheses of a call.
+fn count_args(args: &TokenStream) -> usize {
+    let mut count = 0;
+    let mut arg_started = false;
+    // Closure parameters are separated by commas too.
+    let mut in_closure_params = false;
+    for tree in args.trees() {
+        match tree {
+            TokenTree::Token(Token { kind: TokenKind::Comma, .. }, _) if !in_closure_params => {
+                arg_started = false;
+            }
+            TokenTree::Token(Token { kind: TokenKind::BinOp(BinOpToken::Or), .. }, _)
+                if in_closure_params =>
+            {
+                in_closure_params = false;
+            }
+            TokenTree::Token(Token { kind: TokenKind::BinOp(BinOpToken::Or), .. }, _)
+                if !arg_started =>
+            {
+                in_closure_params = true;
+                arg_started = true;
+                count += 1;
+            }
+            TokenTree::Token(Token { kind: TokenKind::Ident(kw::Move, _), .. }, _)
+                if !arg_started =>
+            {
+                // The closure parameters may still follow.
+            }
+            _ if !arg_started => {
+                arg_started = true;
+                count += 1;
+            }
+            _ => {}
+        }
+    }
+    count
+}
+
+/// The name of the type or trait an associated function belongs to.
+fn owner_name(tcx: TyCtxt<'_>, def_id: DefId) -> Option<Symbol> {
+    if tcx.def_kind(def_id) != DefKind::AssocFn {
+        return None;
+    }
+    let parent = tcx.parent(def_id);
+    match tcx.def_kind(parent) {
+        DefKind::Trait => Some(tcx.item_name(parent)),
+        DefKind::Impl { .. } => match tcx.type_of(parent).instantiate_identity().kind() {
+            ty::Adt(adt, _) => Some(tcx.item_name(adt.did())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
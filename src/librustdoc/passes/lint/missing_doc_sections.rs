@@ -0,0 +1,289 @@
+//! Detects functions whose documentation is missing a `# Panics` or an `# Errors` section, or
+//! has one that doesn't apply to the function anymore.
+
+use std::ops::{ControlFlow, Range};
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{self as hir, HirId};
+use rustc_lint_defs::Level;
+use rustc_middle::ty::{self, TyCtxt, TypeckResults};
+use rustc_resolve::rustdoc::{DocFragment, source_span_for_markdown_range, span_of_fragments};
+use rustc_span::{Span, sym};
+
+use crate::clean::{self, Item};
+use crate::config::DocSectionsStrictness;
+use crate::core::DocContext;
+use crate::html::markdown::{main_body_opts, plain_text_from_events};
+
+pub(crate) fn visit_item(cx: &DocContext<'_>, item: &Item, hir_id: HirId, dox: &str) {
+    let Some(def_id) = item.item_id.as_def_id().and_then(|def_id| def_id.as_local()) else {
+        return;
+    };
+    if !matches!(item.kind, clean::FunctionItem(_) | clean::MethodItem(..) | clean::TyMethodItem(_))
+    {
+        return;
+    }
+    // Other functions are checked by `visit_private_fns`, since they may have been stripped.
+    if !cx.cache.effective_visibilities.is_directly_public(cx.tcx, def_id.to_def_id()) {
+        return;
+    }
+    check_fn(cx, def_id, hir_id, dox, &item.attrs.doc_strings);
+}
+
+/// Checks the functions that aren't directly public with `--doc-sections-strictness=strict`.
+///
+/// This works on the HIR rather than on the cleaned crate, whose private items have already been
+/// stripped unless `--document-private-items` is passed.
+pub(crate) fn visit_private_fns(cx: &DocContext<'_>) {
+    if cx.render_options.doc_sections_strictness < DocSectionsStrictness::Strict {
+        return;
+    }
+    let tcx = cx.tcx;
+    for def_id in tcx.hir_crate_items(()).definitions() {
+        if !matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            || cx.cache.effective_visibilities.is_directly_public(tcx, def_id.to_def_id())
+        {
+            continue;
+        }
+        let attrs = clean::Attributes::from_ast(tcx.get_attrs_unchecked(def_id.to_def_id()));
+        let hir_id = tcx.local_def_id_to_hir_id(def_id);
+        check_fn(cx, def_id, hir_id, &attrs.doc_value(), &attrs.doc_strings);
+    }
+}
+
+fn check_fn(
+    cx: &DocContext<'_>,
+    def_id: LocalDefId,
+    hir_id: HirId,
+    dox: &str,
+    doc_strings: &[DocFragment],
+) {
+    let tcx = cx.tcx;
+    // Implementations of trait methods are documented by the trait.
+    if let Some(parent) = tcx.opt_local_parent(def_id)
+        && let hir::Node::Item(hir::Item {
+            kind: hir::ItemKind::Impl(hir::Impl { of_trait: Some(_), .. }),
+            ..
+        }) = tcx.hir_node_by_def_id(parent)
+    {
+        return;
+    }
+    let is_enabled = |lint| tcx.lint_level_at_node(lint, hir_id).0 != Level::Allow;
+    let check_panics = is_enabled(crate::lint::MISSING_PANICS_DOC);
+    let check_errors = is_enabled(crate::lint::MISSING_ERRORS_DOC);
+    let check_outdated = is_enabled(crate::lint::OUTDATED_DOC_SECTIONS);
+    if !check_panics && !check_errors && !check_outdated {
+        return;
+    }
+
+    let sections = DocSections::new(dox);
+    let span = span_of_fragments(doc_strings).unwrap_or_else(|| tcx.def_span(def_id));
+    let undocumented = dox.trim().is_empty();
+    let returns_result = returns_result(tcx, def_id.to_def_id());
+
+    if check_errors && sections.errors.is_none() && returns_result {
+        tcx.node_span_lint(crate::lint::MISSING_ERRORS_DOC, hir_id, span, |lint| {
+            lint.primary_message(if undocumented {
+                "undocumented function returning `Result`"
+            } else {
+                "docs for function returning `Result` missing `# Errors` section"
+            });
+            if undocumented {
+                lint.help("document when it fails in an `# Errors` section");
+            } else {
+                lint.span_label(tcx.def_span(def_id), "this function returns a `Result`");
+            }
+        });
+    }
+    // Outdated sections are pointed at precisely, since they may be anywhere in the docs.
+    let section_span = |range: &Range<usize>| {
+        source_span_for_markdown_range(tcx, dox, range, doc_strings).unwrap_or(span)
+    };
+    if check_outdated
+        && let Some(range) = &sections.errors
+        && !returns_result
+    {
+        let span = section_span(range);
+        tcx.node_span_lint(crate::lint::OUTDATED_DOC_SECTIONS, hir_id, span, |lint| {
+            lint.primary_message("`# Errors` section in docs of function not returning `Result`");
+            lint.span_label(tcx.def_span(def_id), "this function doesn't return a `Result`");
+        });
+    }
+
+    // Rustdoc doesn't type-check function bodies on its own, so only do it when a lint would
+    // actually be emitted.
+    let Some(body) = tcx.hir().maybe_body_owned_by(def_id) else { return };
+    let strictness = cx.render_options.doc_sections_strictness;
+    if check_panics && sections.panics.is_none() {
+        let mut finder = PanicFinder { tcx, def_id, strictness, typeck_results: None };
+        if let ControlFlow::Break(panic) = finder.visit_body(body) {
+            tcx.node_span_lint(crate::lint::MISSING_PANICS_DOC, hir_id, span, |lint| {
+                lint.primary_message(if undocumented {
+                    "undocumented function which may panic"
+                } else {
+                    "docs for function which may panic missing `# Panics` section"
+                });
+                lint.span_label(panic.span, format!("{} may panic", panic.kind));
+                if undocumented {
+                    lint.help("document when it panics in a `# Panics` section");
+                }
+            });
+        }
+    }
+    // A `# Panics` section is only outdated if the function neither panics itself, even by
+    // indexing, nor calls anything that might panic on its behalf.
+    if check_outdated && let Some(range) = &sections.panics {
+        let strictness = DocSectionsStrictness::Strict;
+        let mut finder = PanicFinder { tcx, def_id, strictness, typeck_results: None };
+        if finder.visit_body(body).is_continue() && !calls_functions(body) {
+            let span = section_span(range);
+            tcx.node_span_lint(crate::lint::OUTDATED_DOC_SECTIONS, hir_id, span, |lint| {
+                lint.primary_message("`# Panics` section in docs of function which can't panic");
+                lint.span_label(tcx.def_span(def_id), "this function can't panic");
+            });
+        }
+    }
+}
+
+/// The sections of interest found among the headings of an item's documentation, with the
+/// range of their heading.
+struct DocSections {
+    panics: Option<Range<usize>>,
+    errors: Option<Range<usize>>,
+}
+
+impl DocSections {
+    fn new(dox: &str) -> Self {
+        let mut sections = DocSections { panics: None, errors: None };
+        let mut parser = Parser::new_ext(dox, main_body_opts()).into_offset_iter();
+        while let Some((event, range)) = parser.next() {
+            if !matches!(event, Event::Start(Tag::Heading { .. })) {
+                continue;
+            }
+            let mut heading = String::new();
+            plain_text_from_events(
+                parser
+                    .by_ref()
+                    .map(|(event, _)| event)
+                    .take_while(|event| !matches!(event, Event::End(TagEnd::Heading(_)))),
+                &mut heading,
+            );
+            let range = range.start..range.start + dox[range].trim_end().len();
+            match heading.trim() {
+                h if h.eq_ignore_ascii_case("panics") => sections.panics = Some(range),
+                h if h.eq_ignore_ascii_case("errors") => sections.errors = Some(range),
+                _ => {}
+            }
+        }
+        sections
+    }
+}
+
+fn returns_result(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    let output = tcx.fn_sig(def_id).skip_binder().output().skip_binder();
+    matches!(output.kind(), ty::Adt(adt, _) if tcx.is_diagnostic_item(sym::Result, adt.did()))
+}
+
+struct Panic {
+    span: Span,
+    kind: &'static str,
+}
+
+/// Looks for the first expression of a body that may panic. Closures and nested items have
+/// bodies of their own, which aren't visited.
+struct PanicFinder<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    def_id: LocalDefId,
+    strictness: DocSectionsStrictness,
+    typeck_results: Option<&'tcx TypeckResults<'tcx>>,
+}
+
+impl<'tcx> PanicFinder<'tcx> {
+    fn typeck_results(&mut self) -> &'tcx TypeckResults<'tcx> {
+        *self.typeck_results.get_or_insert_with(|| self.tcx.typeck(self.def_id))
+    }
+
+    fn is_panic_macro(&self, span: Span) -> bool {
+        span.macro_backtrace().any(|expn| {
+            expn.macro_def_id.and_then(|def_id| self.tcx.get_diagnostic_name(def_id)).is_some_and(
+                |name| {
+                    matches!(
+                        name,
+                        sym::core_panic_macro
+                            | sym::std_panic_macro
+                            | sym::core_panic_2015_macro
+                            | sym::std_panic_2015_macro
+                            | sym::core_panic_2021_macro
+                            | sym::assert_macro
+                            | sym::assert_eq_macro
+                            | sym::assert_ne_macro
+                            | sym::unreachable_macro
+                            | sym::unreachable_2015_macro
+                            | sym::todo_macro
+                            | sym::unimplemented_macro
+                    )
+                },
+            )
+        })
+    }
+
+    /// Whether `expr` is a call to `unwrap` or `expect` on an `Option` or a `Result`.
+    fn is_unwrap(&mut self, expr: &'tcx hir::Expr<'tcx>) -> bool {
+        let hir::ExprKind::MethodCall(segment, ..) = expr.kind else { return false };
+        if !matches!(segment.ident.name, sym::unwrap | sym::expect) {
+            return false;
+        }
+        let tcx = self.tcx;
+        let Some(method) = self.typeck_results().type_dependent_def_id(expr.hir_id) else {
+            return false;
+        };
+        let Some(impl_) = tcx.impl_of_method(method) else { return false };
+        matches!(
+            tcx.type_of(impl_).instantiate_identity().kind(),
+            ty::Adt(adt, _) if tcx.is_diagnostic_item(sym::Option, adt.did())
+                || tcx.is_diagnostic_item(sym::Result, adt.did())
+        )
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for PanicFinder<'tcx> {
+    type Result = ControlFlow<Panic>;
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) -> ControlFlow<Panic> {
+        // Report the panic where it is written in the function, not inside a macro.
+        let span = expr.span.source_callsite();
+        if expr.span.from_expansion() && self.is_panic_macro(expr.span) {
+            return ControlFlow::Break(Panic { span, kind: "this macro" });
+        }
+        if self.strictness >= DocSectionsStrictness::Normal && self.is_unwrap(expr) {
+            return ControlFlow::Break(Panic { span, kind: "this call" });
+        }
+        if self.strictness >= DocSectionsStrictness::Strict
+            && let hir::ExprKind::Index(..) = expr.kind
+        {
+            return ControlFlow::Break(Panic { span, kind: "this indexing" });
+        }
+        intravisit::walk_expr(self, expr)
+    }
+}
+
+/// Whether `body` calls any function, which may then panic on its behalf.
+fn calls_functions(body: &hir::Body<'_>) -> bool {
+    struct CallFinder;
+
+    impl<'tcx> Visitor<'tcx> for CallFinder {
+        type Result = ControlFlow<()>;
+
+        fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) -> ControlFlow<()> {
+            if let hir::ExprKind::Call(..) | hir::ExprKind::MethodCall(..) = expr.kind {
+                return ControlFlow::Break(());
+            }
+            intravisit::walk_expr(self, expr)
+        }
+    }
+
+    CallFinder.visit_body(body).is_break()
+}
@@ -1,4 +1,7 @@
 //! Propagates [`#[doc(cfg(...))]`](https://github.com/rust-lang/rust/issues/43781) to child items.
+//!
+//! With `--cfg-variant`, this also infers the configurations an item is available in from the
+//! configurations that define it.
 
 use std::sync::Arc;
 
@@ -7,7 +10,7 @@
 use crate::clean::cfg::Cfg;
 use crate::clean::inline::{load_attrs, merge_attrs};
 use crate::clean::{Crate, Item, ItemKind};
-use crate::core::DocContext;
+use crate::core::{DocContext, cfg_variant_path, is_cfg_variant_item};
 use crate::fold::DocFolder;
 use crate::passes::Pass;
 
@@ -63,6 +66,18 @@ fn merge_with_parent_attributes(&mut self, item: &mut Item) {
             merge_attrs(self.cx, item.attrs.other_attrs.as_slice(), Some((&attrs, None)));
         item.cfg = cfg;
     }
+
+    /// With `--cfg-variant`, returns the configurations `item` is available in, unless it is
+    /// available in all of them.
+    fn cfg_variants_availability(&self, item: &Item) -> Option<Cfg> {
+        let variants = self.cx.cache.cfg_variants.as_ref()?;
+        let tcx = self.cx.tcx;
+        let def_id = item.item_id.as_def_id().filter(|def_id| def_id.is_local())?;
+        if !is_cfg_variant_item(tcx.def_kind(def_id)) {
+            return None;
+        }
+        variants.availability(&cfg_variant_path(tcx, def_id))
+    }
 }
 
 impl<'a, 'tcx> DocFolder for CfgPropagator<'a, 'tcx> {
@@ -70,6 +85,12 @@ fn fold_item(&mut self, mut item: Item) -> Option<Item> {
         let old_parent_cfg = self.parent_cfg.clone();
 
         self.merge_with_parent_attributes(&mut item);
+        if let Some(available_in) = self.cfg_variants_availability(&item) {
+            item.cfg = Some(Arc::new(match item.cfg.take() {
+                Some(cfg) => Arc::unwrap_or_clone(cfg) & available_in,
+                None => available_in,
+            }));
+        }
 
         let new_cfg = match (self.parent_cfg.take(), item.cfg.take()) {
             (None, None) => None,
@@ -30,7 +30,7 @@
 /// This integer is incremented with every breaking change to the API,
 /// and is returned along with the JSON blob as [`Crate::format_version`].
 /// Consuming code should assert that this value matches the format version(s) that it supports.
-pub const FORMAT_VERSION: u32 = 36;
+pub const FORMAT_VERSION: u32 = 37;
 
 /// The root of the emitted JSON blob.
 ///
@@ -729,6 +729,25 @@ pub struct Function {
     pub header: FunctionHeader,
     /// Whether the function has a body, i.e. an implementation.
     pub has_body: bool,
+    /// The items used by the function's body.
+    ///
+    /// Only present for local functions with a body, when rustdoc was run with the unstable
+    /// `--json-call-graph` flag.
+    pub body_refs: Option<BodyRefs>,
+}
+
+/// The items used by the body of a [`Function`], including the bodies of the closures it defines.
+///
+/// Only items that can be found in [`Crate::index`] or [`Crate::paths`] are listed, each of them
+/// once, in the order in which they are first used.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BodyRefs {
+    /// The functions and methods called by the body, including the trait methods called through
+    /// operators.
+    pub calls: Vec<Id>,
+    /// The other items used by the body: types, constants, statics, enum variants, and functions
+    /// that are referred to without being called.
+    pub references: Vec<Id>,
 }
 
 /// Generic parameters accepted by an item and `where` clauses imposed on it and the parameters.
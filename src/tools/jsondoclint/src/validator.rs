@@ -197,6 +197,10 @@ fn check_variant(&mut self, x: &'a Variant, id: &'a Id) {
     fn check_function(&mut self, x: &'a Function) {
         self.check_generics(&x.generics);
         self.check_function_signature(&x.sig);
+        if let Some(body_refs) = &x.body_refs {
+            body_refs.calls.iter().for_each(|i| self.add_any_id(i));
+            body_refs.references.iter().for_each(|i| self.add_any_id(i));
+        }
     }
 
     fn check_trait(&mut self, x: &'a Trait, id: &Id) {
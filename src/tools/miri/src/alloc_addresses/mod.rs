@@ -303,6 +303,16 @@ fn expose_ptr(&mut self, alloc_id: AllocId, tag: BorTag) -> InterpResult<'tcx> {
         interp_ok(())
     }
 
+    /// Returns the exposed allocation that `addr` points into, if there is one.
+    fn exposed_alloc_id(&self, addr: u64) -> Option<AllocId> {
+        let ecx = self.eval_context_ref();
+        if ecx.machine.alloc_addresses.borrow().provenance_mode == ProvenanceMode::Strict {
+            // Nothing gets exposed in strict mode.
+            return None;
+        }
+        ecx.alloc_id_from_addr(addr, 0)
+    }
+
     fn ptr_from_addr_cast(&self, addr: u64) -> InterpResult<'tcx, Pointer> {
         trace!("Casting {:#x} to a pointer", addr);
 
@@ -5,6 +5,9 @@
 use rustc_middle::mir::interpret::AllocBytes;
 use rustc_target::abi::{Align, Size};
 
+#[cfg(unix)]
+use crate::isolated_alloc;
+
 /// Allocation bytes that explicitly handle the layout of the data they're storing.
 /// This is necessary to interface with native code that accesses the program store in Miri.
 #[derive(Debug)]
@@ -35,6 +38,11 @@ fn drop(&mut self) {
         } else {
             self.layout
         };
+        // SAFETY: `self.ptr` is not used any more.
+        #[cfg(unix)]
+        if unsafe { isolated_alloc::dealloc(self.ptr, alloc_layout) } {
+            return;
+        }
         // SAFETY: Invariant, `self.ptr` points to memory allocated with `self.layout`.
         unsafe { alloc::dealloc(self.ptr, alloc_layout) }
     }
@@ -73,6 +81,10 @@ fn alloc_with(
         // When size is 0 we allocate 1 byte anyway, to ensure each allocation has a unique address.
         let alloc_layout =
             if size == 0 { Layout::from_size_align(1, align).unwrap() } else { layout };
+        // With `-Zmiri-native-lib`, the memory goes on pages of its own.
+        #[cfg(unix)]
+        let ptr = isolated_alloc::alloc(alloc_layout).unwrap_or_else(|| alloc_fn(alloc_layout));
+        #[cfg(not(unix))]
         let ptr = alloc_fn(alloc_layout);
         if ptr.is_null() {
             Err(())
@@ -527,6 +527,10 @@ fn main() {
                         "-Zmiri-isolation-error must be `abort`, `hide`, `warn`, or `warn-nobacktrace`"
                     ),
             };
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-vfs=") {
+            miri_config.vfs = Some(PathBuf::from(param));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-vfs-dump=") {
+            miri_config.vfs_dump = Some(PathBuf::from(param));
         } else if arg == "-Zmiri-ignore-leaks" {
             miri_config.ignore_leaks = true;
             miri_config.collect_leak_backtraces = false;
@@ -595,6 +599,19 @@ fn main() {
         } else if let Some(param) = arg.strip_prefix("-Zmiri-preemption-rate=") {
             miri_config.preemption_rate =
                 parse_rate(param).unwrap_or_else(|err| show_error!("-Zmiri-preemption-rate {err}"));
+        } else if arg == "-Zmiri-explore-schedules" {
+            miri_config.explore_schedules = Some(2);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-explore-schedules=") {
+            let bound = param.parse::<u32>().unwrap_or_else(|err| {
+                show_error!("-Zmiri-explore-schedules requires a `u32`: {}", err)
+            });
+            miri_config.explore_schedules = Some(bound);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-record-schedule=") {
+            miri_config.record_schedule = Some(PathBuf::from(param));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay-schedule=") {
+            miri_config.replay_schedule = Some(PathBuf::from(param));
+        } else if arg == "-Zmiri-minimize-schedule" {
+            miri_config.minimize_schedule = true;
         } else if arg == "-Zmiri-report-progress" {
             // This makes it take a few seconds between progress reports on my laptop.
             miri_config.report_progress = Some(1_000_000);
@@ -608,6 +625,8 @@ fn main() {
                 show_error!("-Zmiri-provenance-gc requires a `u32`: {}", err)
             });
             miri_config.gc_interval = interval;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-coverage=") {
+            miri_config.coverage = Some(PathBuf::from(param));
         } else if let Some(param) = arg.strip_prefix("-Zmiri-measureme=") {
             miri_config.measureme_out = Some(param.to_string());
         } else if let Some(param) = arg.strip_prefix("-Zmiri-backtrace=") {
@@ -659,6 +678,35 @@ fn main() {
             "-Zmiri-unique-is-unique only has an effect when -Zmiri-tree-borrows is also used"
         );
     }
+    // Exploring executions makes its own decisions, so they cannot be recorded or replayed.
+    if miri_config.explore_schedules.is_some()
+        && (miri_config.record_schedule.is_some() || miri_config.replay_schedule.is_some())
+    {
+        show_error!(
+            "-Zmiri-explore-schedules cannot be combined with -Zmiri-record-schedule or -Zmiri-replay-schedule"
+        );
+    }
+    // Every execution must see the same environment, which the host does not guarantee.
+    if miri_config.explore_schedules.is_some()
+        && matches!(miri_config.isolated_op, miri::IsolatedOp::Allow)
+    {
+        show_error!("-Zmiri-explore-schedules cannot be used along with -Zmiri-disable-isolation");
+    }
+    // The minimized schedule is written to the file given by `-Zmiri-record-schedule`.
+    if miri_config.minimize_schedule
+        && (miri_config.record_schedule.is_none() || miri_config.replay_schedule.is_none())
+    {
+        show_error!(
+            "-Zmiri-minimize-schedule requires both -Zmiri-replay-schedule and -Zmiri-record-schedule"
+        );
+    }
+    // The virtual file system replaces host file system access, which requires isolation.
+    if miri_config.vfs.is_some() && matches!(miri_config.isolated_op, miri::IsolatedOp::Allow) {
+        show_error!("-Zmiri-vfs cannot be used along with -Zmiri-disable-isolation");
+    }
+    if miri_config.vfs_dump.is_some() && miri_config.vfs.is_none() {
+        show_error!("-Zmiri-vfs-dump requires -Zmiri-vfs");
+    }
     // Tree Borrows + permissive provenance does not work.
     if miri_config.provenance_mode == ProvenanceMode::Permissive
         && matches!(miri_config.borrow_tracker, Some(BorrowTrackerMethod::TreeBorrows))
@@ -668,6 +716,14 @@ fn main() {
         );
     }
 
+    // The coverage report is based on the counters that rustc adds with `-Cinstrument-coverage`.
+    // Miri counts them itself, so the profiler runtime that would do that in a binary is not
+    // needed (and not part of the Miri sysroot).
+    if miri_config.coverage.is_some() {
+        rustc_args.push("-Cinstrument-coverage".to_string());
+        rustc_args.push("-Zno-profiler-runtime".to_string());
+    }
+
     debug!("rustc arguments: {:?}", rustc_args);
     debug!("crate arguments: {:?}", miri_config.args);
     run_compiler(
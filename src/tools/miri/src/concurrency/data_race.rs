@@ -52,6 +52,7 @@
 use rustc_span::Span;
 use rustc_target::abi::{Align, HasDataLayout, Size};
 
+use super::exploration::DecisionKind;
 use super::vector_clock::{VClock, VTimestamp, VectorIdx};
 use super::weak_memory::EvalContextExt as _;
 use crate::diagnostics::RacingOp;
@@ -751,10 +752,15 @@ fn atomic_compare_exchange_scalar(
         let eq = this.binary_op(mir::BinOp::Eq, &old, expect_old)?;
         // If the operation would succeed, but is "weak", fail some portion
         // of the time, based on `success_rate`.
+        // When recording or replaying decisions, they determine the spurious failures instead.
         let success_rate = 1.0 - this.machine.cmpxchg_weak_failure_rate;
         let cmpxchg_success = eq.to_scalar().to_bool()?
             && if can_fail_spuriously {
-                this.machine.rng.get_mut().gen_bool(success_rate)
+                match &this.machine.decisions {
+                    Some(decisions) if this.machine.cmpxchg_weak_failure_rate > 0.0 =>
+                        decisions.borrow_mut().decide(DecisionKind::CompareExchange, 2)? == 0,
+                    _ => this.machine.rng.get_mut().gen_bool(success_rate),
+                }
             } else {
                 true
             };
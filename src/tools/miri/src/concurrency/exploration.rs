@@ -0,0 +1,373 @@
+//! Systematic exploration of the executions of a concurrent program.
+//!
+//! Normally, Miri explores a single execution per run: the scheduler preempts threads at random
+//! and weak memory loads read from a random store. With `-Zmiri-explore-schedules`, Miri instead
+//! runs the program again and again, making different decisions each time at the points where
+//! executions can differ:
+//! - after atomic operations, thread creation and synchronization operations, which thread runs
+//!   next;
+//! - at atomic loads, which store from the store buffer the load reads from.
+//!
+//! The first alternative of every decision is the default one: keep running the active thread, or
+//! read from the latest store. To keep the number of executions finite, we use delay bounding: we
+//! only explore the executions that take a non-default alternative at most a given number of
+//! times. Every preemption and every outdated load hence counts against the bound.
+//!
+//! Given all decisions, the program behaves deterministically, so the executions can be explored
+//! by a depth-first search over the tree of decisions, where each execution replays a prefix of
+//! the previous one and then takes a different alternative. The search stops at the first
+//! execution that ends in an error or with a non-zero exit code.
+//!
+//! The same decisions also let us reproduce a single execution: with `-Zmiri-record-schedule`,
+//! Miri makes them randomly (like it otherwise does with `rng`) and writes them to a schedule
+//! trace file, which `-Zmiri-replay-schedule` then follows exactly. A replayed trace that ends in
+//! an error can be minimized by replaying shorter traces and keeping those that lead to the same
+//! error.
+
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::{fs, io};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecisionKind {
+    /// Which thread runs next.
+    Thread,
+    /// Which store an atomic load reads from.
+    Load,
+    /// Whether a weak compare-exchange operation fails spuriously.
+    CompareExchange,
+}
+
+impl DecisionKind {
+    /// The name of the decision kind in schedule trace files.
+    fn name(self) -> &'static str {
+        match self {
+            DecisionKind::Thread => "thread",
+            DecisionKind::Load => "load",
+            DecisionKind::CompareExchange => "cmpxchg",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "thread" => Some(DecisionKind::Thread),
+            "load" => Some(DecisionKind::Load),
+            "cmpxchg" => Some(DecisionKind::CompareExchange),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DecisionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecisionKind::Thread => "thread switch",
+            DecisionKind::Load => "atomic load",
+            DecisionKind::CompareExchange => "weak compare-exchange",
+        })
+    }
+}
+
+/// A decision that was made during an execution.
+#[derive(Clone, Copy, Debug)]
+pub struct Decision {
+    pub kind: DecisionKind,
+    /// The index of the alternative that was taken. The default alternative is 0.
+    pub chosen: usize,
+    /// The number of alternatives there were.
+    pub alternatives: usize,
+}
+
+/// How to make the decisions that come after the prefix.
+#[derive(Debug)]
+enum Fallback {
+    /// Take the default alternative.
+    Default,
+    /// Decide randomly, with the same probabilities Miri uses when it does not record decisions.
+    Random { rng: StdRng, preemption_rate: f64, cmpxchg_weak_failure_rate: f64 },
+}
+
+/// Makes the decisions of a single execution. The first decisions are taken from a prefix, the
+/// later ones are made by the fallback.
+#[derive(Debug)]
+pub struct Decisions {
+    prefix: Vec<Decision>,
+    /// If set, the execution stops following the prefix once it no longer fits, instead of
+    /// reporting the divergence as an error.
+    lenient: bool,
+    fallback: Fallback,
+    /// The decisions made so far.
+    trace: Vec<Decision>,
+}
+
+impl Decisions {
+    /// Follows `prefix` and takes the default alternative afterwards.
+    pub fn new(prefix: Vec<Decision>) -> Self {
+        Decisions { prefix, lenient: false, fallback: Fallback::Default, trace: Vec::new() }
+    }
+
+    /// Like `new`, but an execution that diverges from `prefix` continues with the default
+    /// alternatives.
+    pub fn new_lenient(prefix: Vec<Decision>) -> Self {
+        Decisions { lenient: true, ..Decisions::new(prefix) }
+    }
+
+    /// Makes all decisions randomly.
+    pub fn new_random(seed: u64, preemption_rate: f64, cmpxchg_weak_failure_rate: f64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        Decisions {
+            fallback: Fallback::Random { rng, preemption_rate, cmpxchg_weak_failure_rate },
+            ..Decisions::new(Vec::new())
+        }
+    }
+
+    /// Returns which of the `alternatives` to take.
+    pub fn decide<'tcx>(
+        &mut self,
+        kind: DecisionKind,
+        alternatives: usize,
+    ) -> InterpResult<'tcx, usize> {
+        assert!(alternatives > 0, "a decision needs at least one alternative");
+        if alternatives == 1 {
+            // Nothing to decide.
+            return interp_ok(0);
+        }
+        let index = self.trace.len();
+        let chosen = match self.prefix.get(index) {
+            Some(decision) if decision.kind == kind && decision.alternatives == alternatives =>
+                decision.chosen,
+            Some(decision) if !self.lenient => {
+                throw_unsup_format!(
+                    "the execution diverged from the decisions it was replaying: decision #{index} \
+                     used to be a {} with {} alternatives, but now is a {kind} with {alternatives} \
+                     alternatives; this happens when the program is not deterministic or was changed",
+                    decision.kind,
+                    decision.alternatives,
+                );
+            }
+            Some(_) => {
+                self.prefix.truncate(index);
+                self.fallback(kind, alternatives)
+            }
+            None => self.fallback(kind, alternatives),
+        };
+        self.trace.push(Decision { kind, chosen, alternatives });
+        interp_ok(chosen)
+    }
+
+    fn fallback(&mut self, kind: DecisionKind, alternatives: usize) -> usize {
+        let Fallback::Random { rng, preemption_rate, cmpxchg_weak_failure_rate } =
+            &mut self.fallback
+        else {
+            return 0;
+        };
+        match kind {
+            DecisionKind::Thread =>
+                if rng.gen_bool(*preemption_rate) {
+                    rng.gen_range(1..alternatives)
+                } else {
+                    0
+                },
+            DecisionKind::Load => rng.gen_range(0..alternatives),
+            DecisionKind::CompareExchange => usize::from(rng.gen_bool(*cmpxchg_weak_failure_rate)),
+        }
+    }
+
+    pub fn into_trace(self) -> Vec<Decision> {
+        self.trace
+    }
+}
+
+/// The state of the depth-first search over the executions of the program.
+#[derive(Debug)]
+pub struct Exploration {
+    /// The maximal number of non-default decisions in an execution.
+    bound: u32,
+    /// The prefix of the next execution, or `None` if all executions have been explored.
+    next: Option<Vec<Decision>>,
+    /// The number of executions that were started.
+    executions: u64,
+}
+
+impl Exploration {
+    pub fn new(bound: u32) -> Self {
+        Exploration { bound, next: Some(Vec::new()), executions: 0 }
+    }
+
+    pub fn executions(&self) -> u64 {
+        self.executions
+    }
+
+    /// Returns the decisions for the next execution, or `None` if there is none left.
+    pub fn next_execution(&mut self) -> Option<Decisions> {
+        let prefix = self.next.take()?;
+        self.executions = self.executions.strict_add(1);
+        Some(Decisions::new(prefix))
+    }
+
+    /// Determines the next execution from the decisions of the execution that just finished: it
+    /// takes the next alternative of the last decision where that is possible within the bound.
+    pub fn finish_execution(&mut self, mut trace: Vec<Decision>) {
+        let mut deviations = 0u32;
+        let deviations_before: Vec<u32> = trace
+            .iter()
+            .map(|decision| {
+                let before = deviations;
+                if decision.chosen != 0 {
+                    deviations = deviations.strict_add(1);
+                }
+                before
+            })
+            .collect();
+        while let Some(decision) = trace.pop() {
+            let next = decision.chosen.strict_add(1);
+            if next < decision.alternatives && deviations_before[trace.len()] < self.bound {
+                trace.push(Decision { chosen: next, ..decision });
+                self.next = Some(trace);
+                return;
+            }
+        }
+    }
+}
+
+/// Displays the non-default decisions of an execution.
+pub struct Deviations<'a>(pub &'a [Decision]);
+
+impl fmt::Display for Deviations<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut deviations =
+            self.0.iter().enumerate().filter(|(_, decision)| decision.chosen != 0).peekable();
+        if deviations.peek().is_none() {
+            return write!(f, "none");
+        }
+        for (i, (index, decision)) in deviations.enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(
+                f,
+                "decision #{index} ({}) took alternative {} of {}",
+                decision.kind,
+                decision.chosen.strict_add(1),
+                decision.alternatives
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The decisions of an execution, as stored in a schedule trace file. The seed determines the
+/// remaining non-determinism of the execution, such as the addresses of allocations.
+#[derive(Debug)]
+pub struct ScheduleTrace {
+    pub seed: u64,
+    pub decisions: Vec<Decision>,
+}
+
+impl ScheduleTrace {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let invalid = |line: usize, expected: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected {expected}", line.strict_add(1)),
+            )
+        };
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.starts_with('#') && !line.trim().is_empty());
+        let (line, seed) = lines.next().ok_or_else(|| invalid(0, "`seed <u64>`"))?;
+        let seed = seed
+            .strip_prefix("seed ")
+            .and_then(|seed| seed.parse().ok())
+            .ok_or_else(|| invalid(line, "`seed <u64>`"))?;
+        let decisions = lines
+            .map(|(line, decision)| {
+                let parse = || {
+                    let (kind, alternatives) = decision.split_once(' ')?;
+                    let (chosen, alternatives) = alternatives.split_once('/')?;
+                    let decision = Decision {
+                        kind: DecisionKind::from_name(kind)?,
+                        chosen: chosen.parse().ok()?,
+                        alternatives: alternatives.parse().ok()?,
+                    };
+                    (decision.chosen < decision.alternatives).then_some(decision)
+                };
+                parse().ok_or_else(|| invalid(line, "`<kind> <chosen>/<alternatives>`"))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(ScheduleTrace { seed, decisions })
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::from("# Miri schedule trace\n");
+        writeln!(contents, "seed {}", self.seed).unwrap();
+        for decision in &self.decisions {
+            writeln!(
+                contents,
+                "{} {}/{}",
+                decision.kind.name(),
+                decision.chosen,
+                decision.alternatives
+            )
+            .unwrap();
+        }
+        fs::write(path, contents)
+    }
+}
+
+/// Minimizes a trace that leads to an error. `reproduces` replays a candidate trace leniently and,
+/// if that leads to the same error, returns the decisions that were made.
+///
+/// First, we look for the shortest prefix of the trace that still reproduces the error, where all
+/// later decisions take the default alternative. Then, we try to make the remaining non-default
+/// decisions take the default alternative, starting from the last one.
+pub fn minimize(
+    trace: Vec<Decision>,
+    mut reproduces: impl FnMut(&[Decision]) -> Option<Vec<Decision>>,
+) -> Vec<Decision> {
+    // Trailing default decisions are implied.
+    let trim = |mut trace: Vec<Decision>| {
+        while trace.last().is_some_and(|decision| decision.chosen == 0) {
+            trace.pop();
+        }
+        trace
+    };
+    let mut best = trim(trace);
+
+    let (mut low, mut high) = (0, best.len());
+    let mut shortest = None;
+    while low < high {
+        let mid = low.strict_add(high.strict_sub(low) / 2);
+        match reproduces(&best[..mid]) {
+            Some(trace) => {
+                high = mid;
+                shortest = Some(trace);
+            }
+            None => low = mid.strict_add(1),
+        }
+    }
+    if let Some(trace) = shortest {
+        best = trim(trace);
+    }
+
+    let mut index = best.len();
+    while index > 0 {
+        index = index.strict_sub(1);
+        if index >= best.len() || best[index].chosen == 0 {
+            continue;
+        }
+        let mut candidate = best.clone();
+        candidate[index].chosen = 0;
+        if let Some(trace) = reproduces(&candidate) {
+            best = trim(trace);
+        }
+    }
+    best
+}
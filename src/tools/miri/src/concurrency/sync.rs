@@ -357,6 +357,8 @@ fn mutex_unlock(&mut self, id: MutexId) -> InterpResult<'tcx, Option<usize>> {
                 if let Some(thread) = this.machine.sync.mutexes[id].queue.pop_front() {
                     this.unblock_thread(thread, BlockReason::Mutex(id))?;
                 }
+                // Another thread might acquire the mutex before this one runs again.
+                this.scheduling_point();
             }
             Some(old_lock_count)
         } else {
@@ -477,6 +479,7 @@ fn rwlock_reader_unlock(&mut self, id: RwLockId) -> InterpResult<'tcx, bool> {
             if let Some(writer) = rwlock.writer_queue.pop_front() {
                 this.unblock_thread(writer, BlockReason::RwLock(id))?;
             }
+            this.scheduling_point();
         }
         interp_ok(true)
     }
@@ -560,6 +563,7 @@ fn rwlock_writer_unlock(&mut self, id: RwLockId) -> InterpResult<'tcx, bool> {
                     this.unblock_thread(reader, BlockReason::RwLock(id))?;
                 }
             }
+            this.scheduling_point();
             true
         } else {
             false
@@ -1,5 +1,6 @@
 //! Implements threads.
 
+use std::cell::RefCell;
 use std::mem;
 use std::num::TryFromIntError;
 use std::sync::atomic::Ordering::Relaxed;
@@ -17,6 +18,7 @@
 use rustc_target::spec::abi::Abi;
 
 use crate::concurrency::data_race;
+use crate::concurrency::exploration::{DecisionKind, Decisions};
 use crate::shims::tls;
 use crate::*;
 
@@ -173,6 +175,8 @@ pub enum BlockReason {
     InitOnce(InitOnceId),
     /// Blocked on epoll.
     Epoll,
+    /// Blocked on a socket operation.
+    Socket,
 }
 
 /// The state of a thread.
@@ -461,6 +465,9 @@ pub struct ThreadManager<'tcx> {
     thread_local_allocs: FxHashMap<(DefId, ThreadId), StrictPointer>,
     /// A flag that indicates that we should change the active thread.
     yield_active_thread: bool,
+    /// A flag that indicates that we should decide which thread runs next, even if the active
+    /// thread can keep running. This is only set when there are `decisions`.
+    scheduling_point: bool,
 }
 
 impl VisitProvenance for ThreadManager<'_> {
@@ -470,6 +477,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             thread_local_allocs,
             active_thread: _,
             yield_active_thread: _,
+            scheduling_point: _,
         } = self;
 
         for thread in threads {
@@ -491,6 +499,7 @@ fn default() -> Self {
             threads,
             thread_local_allocs: Default::default(),
             yield_active_thread: false,
+            scheduling_point: false,
         }
     }
 }
@@ -575,7 +584,7 @@ pub fn get_live_thread_count(&self) -> usize {
     }
 
     /// Has the given thread terminated?
-    fn has_terminated(&self, thread_id: ThreadId) -> bool {
+    pub fn has_terminated(&self, thread_id: ThreadId) -> bool {
         self.threads[thread_id].state.is_terminated()
     }
 
@@ -750,9 +759,19 @@ fn next_callback_wait_time(&self, clock: &Clock) -> Option<Duration> {
     /// used in stateless model checkers such as Loom: run the active thread as
     /// long as we can and switch only when we have to (the active thread was
     /// blocked, terminated, or has explicitly asked to be preempted).
-    fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
+    ///
+    /// When exploring, recording or replaying executions, the thread to run next is instead
+    /// decided by `decisions` at every scheduling point.
+    fn schedule(
+        &mut self,
+        clock: &Clock,
+        decisions: Option<&RefCell<Decisions>>,
+    ) -> InterpResult<'tcx, SchedulingAction> {
         // This thread and the program can keep going.
-        if self.threads[self.active_thread].state.is_enabled() && !self.yield_active_thread {
+        if self.threads[self.active_thread].state.is_enabled()
+            && !self.yield_active_thread
+            && !self.scheduling_point
+        {
             // The currently active thread is still enabled, just continue with it.
             return interp_ok(SchedulingAction::ExecuteStep);
         }
@@ -779,16 +798,33 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
             .iter_enumerated()
             .skip(self.active_thread.index() + 1)
             .chain(self.threads.iter_enumerated().take(self.active_thread.index()));
-        for (id, thread) in threads {
-            debug_assert_ne!(self.active_thread, id);
-            if thread.state.is_enabled() {
-                info!(
-                    "---------- Now executing on thread `{}` (previous: `{}`) ----------------------------------------",
-                    self.get_thread_display_name(id),
-                    self.get_thread_display_name(self.active_thread)
-                );
-                self.active_thread = id;
-                break;
+        if let Some(decisions) = decisions {
+            // Unless the active thread yielded, continuing it is the default. The other enabled
+            // threads are considered in the same order as above.
+            let mut candidates = Vec::new();
+            if self.threads[self.active_thread].state.is_enabled() && !self.yield_active_thread {
+                candidates.push(self.active_thread);
+            }
+            candidates
+                .extend(threads.filter(|(_, thread)| thread.state.is_enabled()).map(|(id, _)| id));
+            if !candidates.is_empty() {
+                let chosen =
+                    decisions.borrow_mut().decide(DecisionKind::Thread, candidates.len())?;
+                self.active_thread = candidates[chosen];
+            }
+            self.scheduling_point = false;
+        } else {
+            for (id, thread) in threads {
+                debug_assert_ne!(self.active_thread, id);
+                if thread.state.is_enabled() {
+                    info!(
+                        "---------- Now executing on thread `{}` (previous: `{}`) ----------------------------------------",
+                        self.get_thread_display_name(id),
+                        self.get_thread_display_name(self.active_thread)
+                    );
+                    self.active_thread = id;
+                    break;
+                }
             }
         }
         self.yield_active_thread = false;
@@ -944,6 +980,8 @@ fn start_regular_thread(
         if let Some(cpuset) = this.machine.thread_cpu_affinity.get(&old_thread_id).cloned() {
             this.machine.thread_cpu_affinity.insert(new_thread_id, cpuset);
         }
+        // ... and its signal mask.
+        this.machine.signals.thread_created(old_thread_id, new_thread_id);
 
         // Perform the function pointer load in the new thread frame.
         let instance = this.get_ptr_fn(start_routine)?.as_instance()?;
@@ -963,6 +1001,8 @@ fn start_regular_thread(
 
         // Restore the old active thread frame.
         this.machine.threads.set_active_thread_id(old_thread_id);
+        // The new thread might run first.
+        this.scheduling_point();
 
         interp_ok(new_thread_id)
     }
@@ -980,8 +1020,10 @@ fn terminate_active_thread(&mut self, tls_alloc_action: TlsAllocAction) -> Inter
         if let Some(ref mut data_race) = this.machine.data_race {
             data_race.thread_terminated(&this.machine.threads);
         }
-        // Deallocate TLS.
         let gone_thread = this.active_thread();
+        // Forget about the signal state of the thread.
+        this.machine.signals.thread_terminated(gone_thread);
+        // Deallocate TLS.
         {
             let mut free_tls_statics = Vec::new();
             this.machine.threads.thread_local_allocs.retain(|&(_def_id, thread), &mut alloc_id| {
@@ -1078,6 +1120,8 @@ fn unblock_thread(&mut self, thread: ThreadId, reason: BlockReason) -> InterpRes
         let old_thread = this.machine.threads.set_active_thread_id(thread);
         callback.unblock(this)?;
         this.machine.threads.set_active_thread_id(old_thread);
+        // The unblocked thread might run first.
+        this.scheduling_point();
         interp_ok(())
     }
 
@@ -1177,6 +1221,17 @@ fn yield_active_thread(&mut self) {
         self.eval_context_mut().machine.threads.yield_active_thread();
     }
 
+    /// When exploring, recording or replaying executions, lets the scheduler decide which thread
+    /// runs after the current step. This should be called after operations that other threads can
+    /// observe.
+    #[inline]
+    fn scheduling_point(&mut self) {
+        let this = self.eval_context_mut();
+        if this.machine.decisions.is_some() {
+            this.machine.threads.scheduling_point = true;
+        }
+    }
+
     #[inline]
     fn maybe_preempt_active_thread(&mut self) {
         use rand::Rng as _;
@@ -1196,8 +1251,16 @@ fn run_threads(&mut self) -> InterpResult<'tcx, !> {
                 this.machine.handle_abnormal_termination();
                 std::process::exit(1);
             }
-            match this.machine.threads.schedule(&this.machine.clock)? {
+            match this
+                .machine
+                .threads
+                .schedule(&this.machine.clock, this.machine.decisions.as_ref())?
+            {
                 SchedulingAction::ExecuteStep => {
+                    if this.machine.signals.has_pending() {
+                        // This might push the frame of a signal handler.
+                        this.deliver_pending_signal()?;
+                    }
                     if !this.step()? {
                         // See if this thread can do something else.
                         match this.run_on_stack_empty()? {
@@ -82,6 +82,7 @@
 use rustc_data_structures::fx::FxHashMap;
 
 use super::data_race::{GlobalState as DataRaceState, ThreadClockSet};
+use super::exploration::{DecisionKind, Decisions};
 use super::range_object_map::{AccessType, RangeObjectMap};
 use super::vector_clock::{VClock, VTimestamp, VectorIdx};
 use crate::*;
@@ -263,6 +264,7 @@ fn buffered_read(
         thread_mgr: &ThreadManager<'_>,
         is_seqcst: bool,
         rng: &mut (impl rand::Rng + ?Sized),
+        decisions: Option<&mut Decisions>,
         validate: impl FnOnce() -> InterpResult<'tcx>,
     ) -> InterpResult<'tcx, (Option<Scalar>, LoadRecency)> {
         // Having a live borrow to store_buffer while calling validate_atomic_load is fine
@@ -273,7 +275,7 @@ fn buffered_read(
             // as the race detector will update it
             let (.., clocks) = global.active_thread_state(thread_mgr);
             // Load from a valid entry in the store buffer
-            self.fetch_store(is_seqcst, &clocks, &mut *rng)
+            self.fetch_store(is_seqcst, &clocks, &mut *rng, decisions)?
         };
 
         // Unlike in buffered_atomic_write, thread clock updates have to be done
@@ -302,12 +304,15 @@ fn buffered_write(
 
     #[allow(clippy::if_same_then_else, clippy::needless_bool)]
     /// Selects a valid store element in the buffer.
+    /// When exploring, recording or replaying executions, the store is picked by `decisions`
+    /// instead of `rng`.
     fn fetch_store<R: rand::Rng + ?Sized>(
         &self,
         is_seqcst: bool,
         clocks: &ThreadClockSet,
         rng: &mut R,
-    ) -> (&StoreElement, LoadRecency) {
+        decisions: Option<&mut Decisions>,
+    ) -> InterpResult<'tcx, (&StoreElement, LoadRecency)> {
         use rand::seq::IteratorRandom;
         let mut found_sc = false;
         // FIXME: we want an inclusive take_while (stops after a false predicate, but
@@ -379,12 +384,21 @@ fn fetch_store<R: rand::Rng + ?Sized>(
                 }
             });
 
-        let chosen = candidates.choose(rng).expect("store buffer cannot be empty");
-        if std::ptr::eq(chosen, self.buffer.back().expect("store buffer cannot be empty")) {
-            (chosen, LoadRecency::Latest)
-        } else {
-            (chosen, LoadRecency::Outdated)
-        }
+        let chosen = match decisions {
+            Some(decisions) => {
+                // The candidates start with the latest store, so that is the default.
+                let candidates: Vec<_> = candidates.collect();
+                candidates[decisions.decide(DecisionKind::Load, candidates.len())?]
+            }
+            None => candidates.choose(rng).expect("store buffer cannot be empty"),
+        };
+        let recency =
+            if std::ptr::eq(chosen, self.buffer.back().expect("store buffer cannot be empty")) {
+                LoadRecency::Latest
+            } else {
+                LoadRecency::Outdated
+            };
+        interp_ok((chosen, recency))
     }
 
     /// ATOMIC STORE IMPL in the paper (except we don't need the location's vector clock)
@@ -489,6 +503,8 @@ fn buffered_atomic_read(
                         global.sc_read(&this.machine.threads);
                     }
                     let mut rng = this.machine.rng.borrow_mut();
+                    let mut decisions =
+                        this.machine.decisions.as_ref().map(|decisions| decisions.borrow_mut());
                     let Some(buffer) = alloc_buffers
                         .get_store_buffer(alloc_range(base_offset, place.layout.size))?
                     else {
@@ -500,6 +516,7 @@ fn buffered_atomic_read(
                         &this.machine.threads,
                         atomic == AtomicReadOrd::SeqCst,
                         &mut *rng,
+                        decisions.as_deref_mut(),
                         validate,
                     )?;
                     if global.track_outdated_loads && recency == LoadRecency::Outdated {
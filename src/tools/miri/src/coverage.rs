@@ -0,0 +1,202 @@
+//! Code coverage of the interpreted program.
+//!
+//! With `-Zmiri-coverage=<file>`, the program is compiled with `-Cinstrument-coverage`, so rustc
+//! adds counter statements to the MIR of each function and records which source regions each
+//! counter (or sum or difference of counters) stands for. Miri increments a counter every time it
+//! executes a basic block containing that counter's statement, and at the end of interpretation
+//! turns the counts into an lcov report.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_index::IndexVec;
+use rustc_middle::mir::coverage::{
+    CounterId, CovTerm, CoverageKind, FunctionCoverageInfo, MappingKind, Op, SourceRegion,
+};
+use rustc_middle::mir::{self, StatementKind};
+use rustc_middle::ty::TyCtxt;
+
+/// The counts of an instrumented function.
+#[derive(Debug)]
+struct FunctionCounts<'tcx> {
+    info: &'tcx FunctionCoverageInfo,
+    /// How often the function was called.
+    calls: u64,
+    counters: IndexVec<CounterId, u64>,
+}
+
+impl<'tcx> FunctionCounts<'tcx> {
+    fn new(info: &'tcx FunctionCoverageInfo) -> Self {
+        FunctionCounts { info, calls: 0, counters: IndexVec::from_elem_n(0, info.num_counters) }
+    }
+
+    /// The value of a counter or expression.
+    fn eval(&self, term: CovTerm) -> u64 {
+        match term {
+            CovTerm::Zero => 0,
+            CovTerm::Counter(id) => self.counters[id],
+            CovTerm::Expression(id) => {
+                let expression = &self.info.expressions[id];
+                let lhs = self.eval(expression.lhs);
+                let rhs = self.eval(expression.rhs);
+                match expression.op {
+                    Op::Add => lhs.saturating_add(rhs),
+                    Op::Subtract => lhs.saturating_sub(rhs),
+                }
+            }
+        }
+    }
+}
+
+/// The coverage data of one source file in the lcov report.
+#[derive(Default)]
+struct FileRecord {
+    /// The name, first line and call count of each function.
+    functions: Vec<(String, u32, u64)>,
+    /// The execution count of each line that belongs to a code region.
+    lines: BTreeMap<u32, u64>,
+    /// The line, block, branch and count of each branch outcome.
+    branches: Vec<(u32, usize, usize, u64)>,
+}
+
+/// The execution counts of all instrumented functions.
+#[derive(Debug, Default)]
+pub struct Coverage<'tcx> {
+    functions: FxHashMap<DefId, FunctionCounts<'tcx>>,
+}
+
+impl<'tcx> Coverage<'tcx> {
+    /// Records that a new frame for `body` was pushed.
+    pub fn record_call(&mut self, body: &'tcx mir::Body<'tcx>) {
+        if let Some(counts) = self.counts(body) {
+            counts.calls = counts.calls.strict_add(1);
+        }
+    }
+
+    /// Records that `block` of `body` was executed.
+    pub fn record_block(&mut self, body: &'tcx mir::Body<'tcx>, block: mir::BasicBlock) {
+        let Some(counts) = self.counts(body) else { return };
+        for statement in &body.basic_blocks[block].statements {
+            if let StatementKind::Coverage(CoverageKind::CounterIncrement { id }) = statement.kind {
+                counts.counters[id] = counts.counters[id].strict_add(1);
+            }
+        }
+    }
+
+    /// Adds the execution counts of a child process.
+    pub fn merge(&mut self, other: Coverage<'tcx>) {
+        for (def_id, other) in other.functions {
+            let counts =
+                self.functions.entry(def_id).or_insert_with(|| FunctionCounts::new(other.info));
+            counts.calls = counts.calls.strict_add(other.calls);
+            for (count, other) in counts.counters.iter_mut().zip(other.counters) {
+                *count = count.strict_add(other);
+            }
+        }
+    }
+
+    fn counts(&mut self, body: &'tcx mir::Body<'tcx>) -> Option<&mut FunctionCounts<'tcx>> {
+        // Promoted constants are evaluated at compile time, and share the coverage information of
+        // the function they were promoted from.
+        if body.source.promoted.is_some() {
+            return None;
+        }
+        let info = body.function_coverage_info.as_deref()?;
+        Some(
+            self.functions.entry(body.source.def_id()).or_insert_with(|| FunctionCounts::new(info)),
+        )
+    }
+
+    /// Writes the report for all instrumented functions of the local crate, and all other
+    /// instrumented functions that were executed, to `path`.
+    pub fn write_lcov(&self, tcx: TyCtxt<'tcx>, path: &Path) -> io::Result<()> {
+        // Functions that were never called still need to show up in the report, with all their
+        // lines marked as not executed.
+        let unexecuted = tcx
+            .mir_keys(())
+            .iter()
+            .map(|def_id| def_id.to_def_id())
+            .filter(|&def_id| {
+                tcx.def_kind(def_id).is_fn_like() && !self.functions.contains_key(&def_id)
+            })
+            .filter_map(|def_id| {
+                let info = tcx.optimized_mir(def_id).function_coverage_info.as_deref()?;
+                Some((def_id, FunctionCounts::new(info)))
+            })
+            .collect::<Vec<_>>();
+        let functions = self
+            .functions
+            .iter()
+            .map(|(def_id, counts)| (*def_id, counts))
+            .chain(unexecuted.iter().map(|(def_id, counts)| (*def_id, counts)));
+
+        let mut files: BTreeMap<String, FileRecord> = BTreeMap::new();
+        for (def_id, counts) in functions {
+            // All regions of a function are in the same file.
+            let Some(first_line) =
+                counts.info.mappings.iter().map(|m| m.source_region.start_line).min()
+            else {
+                continue;
+            };
+            let file_name = counts.info.mappings[0].source_region.file_name;
+            let record = files.entry(file_name.to_string()).or_default();
+            record.functions.push((tcx.def_path_str(def_id), first_line, counts.calls));
+            for (index, mapping) in counts.info.mappings.iter().enumerate() {
+                let SourceRegion { start_line, end_line, .. } = mapping.source_region;
+                match mapping.kind {
+                    MappingKind::Code(term) => {
+                        // A line that is covered by several regions counts as executed as often
+                        // as the most executed one of them.
+                        let count = counts.eval(term);
+                        for line in start_line..=end_line {
+                            let line_count = record.lines.entry(line).or_insert(0);
+                            *line_count = (*line_count).max(count);
+                        }
+                    }
+                    MappingKind::Branch { true_term, false_term }
+                    | MappingKind::MCDCBranch { true_term, false_term, .. } => {
+                        record.branches.push((start_line, index, 0, counts.eval(true_term)));
+                        record.branches.push((start_line, index, 1, counts.eval(false_term)));
+                    }
+                    MappingKind::MCDCDecision(_) => {}
+                }
+            }
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "TN:")?;
+        for (file_name, mut record) in files {
+            writeln!(out, "SF:{file_name}")?;
+            record.functions.sort_by_key(|&(_, line, _)| line);
+            for (name, line, _) in &record.functions {
+                writeln!(out, "FN:{line},{name}")?;
+            }
+            for (name, _, calls) in &record.functions {
+                writeln!(out, "FNDA:{calls},{name}")?;
+            }
+            writeln!(out, "FNF:{}", record.functions.len())?;
+            writeln!(
+                out,
+                "FNH:{}",
+                record.functions.iter().filter(|(_, _, calls)| *calls > 0).count()
+            )?;
+            record.branches.sort();
+            for (line, block, branch, count) in &record.branches {
+                writeln!(out, "BRDA:{line},{block},{branch},{count}")?;
+            }
+            writeln!(out, "BRF:{}", record.branches.len())?;
+            writeln!(out, "BRH:{}", record.branches.iter().filter(|b| b.3 > 0).count())?;
+            for (line, count) in &record.lines {
+                writeln!(out, "DA:{line},{count}")?;
+            }
+            writeln!(out, "LF:{}", record.lines.len())?;
+            writeln!(out, "LH:{}", record.lines.values().filter(|&&count| count > 0).count())?;
+            writeln!(out, "end_of_record")?;
+        }
+        out.flush()
+    }
+}
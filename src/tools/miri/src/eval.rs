@@ -1,10 +1,11 @@
 //! Main evaluator loop and setting up the initial stack frame.
 
+use std::cell::RefCell;
 use std::ffi::{OsStr, OsString};
 use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::task::Poll;
-use std::{iter, thread};
+use std::{iter, mem, thread};
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::def::Namespace;
@@ -12,8 +13,12 @@
 use rustc_middle::ty::layout::{LayoutCx, LayoutOf};
 use rustc_middle::ty::{self, Ty, TyCtxt};
 use rustc_session::config::EntryFnType;
+use rustc_span::Span;
 use rustc_target::spec::abi::Abi;
 
+use crate::concurrency::exploration::{
+    self, Decision, Decisions, Deviations, Exploration, ScheduleTrace,
+};
 use crate::concurrency::thread::TlsAllocAction;
 use crate::diagnostics::report_leaks;
 use crate::shims::tls;
@@ -157,6 +162,22 @@ pub struct MiriConfig {
     pub address_reuse_rate: f64,
     /// Probability for address reuse across threads.
     pub address_reuse_cross_thread_rate: f64,
+    /// If `Some`, systematically explore all executions that take a non-default alternative at
+    /// scheduling and weak memory decisions at most this many times.
+    pub explore_schedules: Option<u32>,
+    /// If `Some`, write the scheduling and weak memory decisions of the execution to this file.
+    pub record_schedule: Option<PathBuf>,
+    /// If `Some`, replay the scheduling and weak memory decisions recorded in this file.
+    pub replay_schedule: Option<PathBuf>,
+    /// Whether to minimize the replayed decisions while they still lead to the same error.
+    pub minimize_schedule: bool,
+    /// If `Some`, the program runs on an in-memory copy of this host directory instead of the host
+    /// file system.
+    pub vfs: Option<PathBuf>,
+    /// If `Some`, the final state of the in-memory file system is written to this host directory.
+    pub vfs_dump: Option<PathBuf>,
+    /// If `Some`, an lcov report of the code that was executed is written to this file.
+    pub coverage: Option<PathBuf>,
 }
 
 impl Default for MiriConfig {
@@ -194,6 +215,13 @@ fn default() -> MiriConfig {
             collect_leak_backtraces: true,
             address_reuse_rate: 0.5,
             address_reuse_cross_thread_rate: 0.1,
+            explore_schedules: None,
+            record_schedule: None,
+            replay_schedule: None,
+            minimize_schedule: false,
+            vfs: None,
+            vfs_dump: None,
+            coverage: None,
         }
     }
 }
@@ -279,6 +307,8 @@ pub fn create_ecx<'tcx>(
         // Cannot capture anything GC-relevant here.
         Box::new(move |m| state.on_main_stack_empty(m))
     })?;
+    // Child processes run the same entry function.
+    ecx.machine.children.set_entry(entry_id, entry_type);
 
     // Make sure we have MIR. We check MIR for some stable monomorphic function in libcore.
     let sentinel =
@@ -430,17 +460,196 @@ pub fn eval_entry<'tcx>(
     entry_type: EntryFnType,
     config: MiriConfig,
 ) -> Option<i64> {
-    // Copy setting before we move `config`.
-    let ignore_leaks = config.ignore_leaks;
+    if let Some(bound) = config.explore_schedules {
+        explore_executions(tcx, entry_id, entry_type, config, bound)
+    } else if config.record_schedule.is_some() || config.replay_schedule.is_some() {
+        eval_with_schedule_trace(tcx, entry_id, entry_type, config)
+    } else {
+        eval_execution(tcx, entry_id, entry_type, &config, None, true).return_code
+    }
+}
+
+/// Evaluates the entry function once for every execution we explore, until one fails.
+fn explore_executions<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    mut config: MiriConfig,
+    bound: u32,
+) -> Option<i64> {
+    // All non-determinism in scheduling is decided by the exploration. We do not explore spurious
+    // failures of weak compare-exchange operations, since retry loops would make the number of
+    // executions infinite.
+    config.preemption_rate = 0.0;
+    config.cmpxchg_weak_failure_rate = 0.0;
+    let mut exploration = Exploration::new(bound);
+    while let Some(decisions) = exploration.next_execution() {
+        let execution = eval_execution(tcx, entry_id, entry_type, &config, Some(decisions), true);
+        if execution.return_code != Some(0) {
+            tcx.dcx().note(format!(
+                "this happened in execution {} of the exploration, with non-default decisions: {}",
+                exploration.executions(),
+                Deviations(&execution.trace),
+            ));
+            return execution.return_code;
+        }
+        exploration.finish_execution(execution.trace);
+    }
+    info!("explored {} executions", exploration.executions());
+    Some(0)
+}
+
+/// Evaluates the entry function once, replaying the decisions from a schedule trace or making them
+/// randomly, and writes the decisions that were made to a schedule trace if requested. A replayed
+/// execution that fails with an error can also have its decisions minimized first.
+fn eval_with_schedule_trace<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    mut config: MiriConfig,
+) -> Option<i64> {
+    let replay = config.replay_schedule.as_ref().map(|path| {
+        ScheduleTrace::read(path).unwrap_or_else(|err| {
+            tcx.dcx().fatal(format!("failed to read schedule trace `{}`: {err}", path.display()))
+        })
+    });
+    // The seed determines all other non-determinism, so it is part of the trace.
+    let seed = replay.as_ref().map_or(config.seed.unwrap_or(0), |trace| trace.seed);
+    config.seed = Some(seed);
+    let decisions = match &replay {
+        Some(trace) => Decisions::new(trace.decisions.clone()),
+        None =>
+            Decisions::new_random(seed, config.preemption_rate, config.cmpxchg_weak_failure_rate),
+    };
+    // Threads are only preempted where the decisions are made.
+    config.preemption_rate = 0.0;
+
+    let execution = eval_execution(tcx, entry_id, entry_type, &config, Some(decisions), true);
+    let mut trace = execution.trace;
+    if let Some(replay) = &replay {
+        // An execution that stopped with an error, e.g. because it diverged from the trace, has
+        // already reported why it ended early.
+        if execution.return_code.is_some() && trace.len() < replay.decisions.len() {
+            tcx.dcx().err(format!(
+                "the execution diverged from the schedule trace: it ended after {} of the {} \
+                 decisions in the trace",
+                trace.len(),
+                replay.decisions.len(),
+            ));
+            return None;
+        }
+    }
+
+    if config.minimize_schedule {
+        match &execution.error {
+            Some(error) => {
+                trace = exploration::minimize(trace, |candidate| {
+                    let decisions = Decisions::new_lenient(candidate.to_vec());
+                    let execution =
+                        eval_execution(tcx, entry_id, entry_type, &config, Some(decisions), false);
+                    (execution.error.as_ref() == Some(error)).then_some(execution.trace)
+                });
+                tcx.dcx().note(format!(
+                    "the minimized schedule trace has {} decisions, with non-default decisions: {}",
+                    trace.len(),
+                    Deviations(&trace),
+                ));
+            }
+            None =>
+                tcx.dcx().warn(
+                    "the replayed execution did not fail with an error, so there is nothing to \
+                     minimize",
+                ),
+        }
+    }
+
+    if let Some(path) = &config.record_schedule {
+        if let Err(err) = (ScheduleTrace { seed, decisions: trace }).write(path) {
+            tcx.dcx().err(format!("failed to write schedule trace `{}`: {err}", path.display()));
+            return None;
+        }
+    }
+    execution.return_code
+}
+
+/// Identifies the error an execution failed with by its kind and where it happened, but not by
+/// details such as allocation IDs that can change when the decisions change.
+#[derive(PartialEq, Eq)]
+struct ErrorSignature<'tcx> {
+    span: Span,
+    kind: mem::Discriminant<InterpErrorKind<'tcx>>,
+    termination: Option<mem::Discriminant<TerminationInfo>>,
+}
 
-    let mut ecx = match create_ecx(tcx, entry_id, entry_type, &config).report_err() {
+/// The result of evaluating the entry function once.
+struct Execution<'tcx> {
+    /// The exit code, or `None` if an evaluation error occurred.
+    return_code: Option<i64>,
+    /// The decisions that were made, if there were any.
+    trace: Vec<Decision>,
+    /// The error the execution failed with, if any.
+    error: Option<ErrorSignature<'tcx>>,
+}
+
+/// Evaluates the entry function once, making the given decisions if we are exploring, recording or
+/// replaying executions. If `report` is false, errors are not reported.
+fn eval_execution<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+    decisions: Option<Decisions>,
+    report: bool,
+) -> Execution<'tcx> {
+    let mut ecx = create_ecx_or_panic(tcx, entry_id, entry_type, config);
+    ecx.machine.decisions = decisions.map(RefCell::new);
+    run_execution(tcx, &mut ecx, config, report)
+}
+
+/// Evaluates the entry function in a fresh interpreter, as a child process of the interpreted
+/// program. `setup` is called on the new interpreter before the child starts running, and
+/// `teardown` after it has terminated. Returns the exit code, or `None` if an evaluation error
+/// occurred. Errors are reported like for the main program.
+pub fn eval_child_process<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+    setup: impl FnOnce(&mut MiriInterpCx<'tcx>),
+    teardown: impl FnOnce(&mut MiriInterpCx<'tcx>),
+) -> Option<i64> {
+    let mut ecx = create_ecx_or_panic(tcx, entry_id, entry_type, config);
+    setup(&mut ecx);
+    let execution = run_execution(tcx, &mut ecx, config, true);
+    teardown(&mut ecx);
+    execution.return_code
+}
+
+fn create_ecx_or_panic<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+) -> MiriInterpCx<'tcx> {
+    match create_ecx(tcx, entry_id, entry_type, config).report_err() {
         Ok(v) => v,
         Err(err) => {
             let (kind, backtrace) = err.into_parts();
             backtrace.print_backtrace();
             panic!("Miri initialization error: {kind:?}")
         }
-    };
+    }
+}
+
+/// Runs all threads of `ecx` until the program terminates, and reports the result if `report` is
+/// true.
+fn run_execution<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ecx: &mut MiriInterpCx<'tcx>,
+    config: &MiriConfig,
+    report: bool,
+) -> Execution<'tcx> {
+    let ignore_leaks = config.ignore_leaks;
 
     // Perform the main execution.
     let res: thread::Result<InterpResult<'_, !>> =
@@ -459,30 +668,74 @@ pub fn eval_entry<'tcx>(
         // might not have joined the main thread (https://github.com/rust-lang/miri/issues/2020,
         // https://github.com/rust-lang/miri/issues/2508).
         ecx.allow_data_races_all_threads_done();
-        EnvVars::cleanup(&mut ecx).expect("error during env var cleanup");
+        EnvVars::cleanup(ecx).expect("error during env var cleanup");
+    }
+    let trace = ecx
+        .machine
+        .decisions
+        .take()
+        .map(|decisions| decisions.into_inner().into_trace())
+        .unwrap_or_default();
+    let termination = match err.kind() {
+        InterpErrorKind::MachineStop(info) => {
+            let info = info.downcast_ref::<TerminationInfo>().expect("invalid MachineStop payload");
+            if let TerminationInfo::Exit { code, .. } = info {
+                if !report {
+                    return Execution { return_code: Some(*code), trace, error: None };
+                }
+            }
+            Some(mem::discriminant(info))
+        }
+        _ => None,
+    };
+    let error =
+        ErrorSignature { span: ecx.cur_span(), kind: mem::discriminant(err.kind()), termination };
+    if !report {
+        return Execution { return_code: None, trace, error: Some(error) };
+    }
+
+    // Write back the virtual file system. This also happens when the program failed, since the
+    // files it left behind can help to find out why.
+    if let (Some(dir), Some(vfs)) = (&config.vfs_dump, &ecx.machine.vfs) {
+        if let Err(err) = vfs.dump(dir) {
+            tcx.dcx().err(format!(
+                "failed to write the virtual file system to `{}`: {err}",
+                dir.display()
+            ));
+            return Execution { return_code: None, trace, error: Some(error) };
+        }
+    }
+    if let (Some(path), Some(coverage)) = (&config.coverage, &ecx.machine.coverage) {
+        if let Err(err) = coverage.write_lcov(tcx, path) {
+            tcx.dcx()
+                .err(format!("failed to write the coverage report to `{}`: {err}", path.display()));
+            return Execution { return_code: None, trace, error: Some(error) };
+        }
     }
 
     // Process the result.
-    let (return_code, leak_check) = report_error(&ecx, err)?;
+    let Some((return_code, leak_check)) = report_error(ecx, err) else {
+        return Execution { return_code: None, trace, error: Some(error) };
+    };
     if leak_check && !ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
             tcx.dcx().err("the main thread terminated without waiting for all remaining threads");
             tcx.dcx().note("set `MIRIFLAGS=-Zmiri-ignore-leaks` to disable this check");
-            return None;
+            return Execution { return_code: None, trace, error: None };
         }
         // Check for memory leaks.
         info!("Additional static roots: {:?}", ecx.machine.static_roots);
         let leaks = ecx.take_leaked_allocations(|ecx| &ecx.machine.static_roots);
         if !leaks.is_empty() {
-            report_leaks(&ecx, leaks);
+            report_leaks(ecx, leaks);
             tcx.dcx().note("set `MIRIFLAGS=-Zmiri-ignore-leaks` to disable this check");
             // Ignore the provided return code - let the reported error
             // determine the return code.
-            return None;
+            return Execution { return_code: None, trace, error: None };
         }
     }
-    Some(return_code)
+    Execution { return_code: Some(return_code), trace, error: None }
 }
 
 /// Turns an array of arguments into a Windows command line string.
@@ -117,6 +117,8 @@ fn fence_ord(ord: &str) -> AtomicFenceOrd {
 
             _ => return interp_ok(EmulateItemResult::NotSupported),
         }
+        // Other threads can observe atomic operations, so they might run in between.
+        this.scheduling_point();
         interp_ok(EmulateItemResult::NeedsReturn)
     }
 }
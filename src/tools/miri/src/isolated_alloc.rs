@@ -0,0 +1,185 @@
+//! An allocator that places the memory of the interpreted program on pages of its own.
+//!
+//! With `-Zmiri-native-lib`, native code works directly on the bytes of the interpreted program.
+//! To notice native code writing to memory it was not given, all pages handed out by this
+//! allocator are write-protected for the duration of a native call (see `shims/native_lib`).
+//! That only works if no memory of Miri itself shares a page with them.
+
+use std::alloc::Layout;
+use std::collections::BTreeMap;
+use std::ptr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The granularity in which small allocations are placed on a page.
+const CHUNK_SIZE: usize = 16;
+
+struct IsolatedAlloc {
+    page_size: usize,
+    /// The address of each page that small allocations are placed on, with one bit for each
+    /// chunk of the page that is in use.
+    pages: BTreeMap<usize, Vec<u64>>,
+    /// The address of each allocation that got pages of its own, with the address and length of
+    /// its mapping.
+    huge: BTreeMap<usize, (usize, usize)>,
+}
+
+/// The allocator, once the isolation of the program's memory has been enabled.
+static ALLOC: Mutex<Option<IsolatedAlloc>> = Mutex::new(None);
+/// Whether `ALLOC` has been initialized, so that we do not have to take the lock otherwise.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+impl IsolatedAlloc {
+    fn chunks_per_page(&self) -> usize {
+        self.page_size / CHUNK_SIZE
+    }
+
+    fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        if layout.size() <= self.page_size / 2 && layout.align() <= self.page_size {
+            self.alloc_small(layout)
+        } else {
+            self.alloc_huge(layout)
+        }
+    }
+
+    fn alloc_small(&mut self, layout: Layout) -> *mut u8 {
+        let chunks = layout.size().div_ceil(CHUNK_SIZE).max(1);
+        let step = layout.align().div_ceil(CHUNK_SIZE);
+        let chunks_per_page = self.chunks_per_page();
+        for (&page, used) in &mut self.pages {
+            let mut start = 0;
+            while start + chunks <= chunks_per_page {
+                if (start..start + chunks).all(|chunk| used[chunk / 64] & (1 << (chunk % 64)) == 0)
+                {
+                    for chunk in start..start + chunks {
+                        used[chunk / 64] |= 1 << (chunk % 64);
+                    }
+                    let ptr = ptr::with_exposed_provenance_mut::<u8>(page + start * CHUNK_SIZE);
+                    // SAFETY: the chunks belong to a mapped page and were not in use.
+                    unsafe { ptr.write_bytes(0, chunks * CHUNK_SIZE) };
+                    return ptr;
+                }
+                start += step;
+            }
+        }
+        let page = map(self.page_size);
+        if page.is_null() {
+            return page;
+        }
+        let mut used = vec![0; chunks_per_page.div_ceil(64)];
+        for chunk in 0..chunks {
+            used[chunk / 64] |= 1 << (chunk % 64);
+        }
+        self.pages.insert(page.expose_provenance(), used);
+        page
+    }
+
+    fn alloc_huge(&mut self, layout: Layout) -> *mut u8 {
+        let size = layout.size().next_multiple_of(self.page_size);
+        // Mappings are only aligned to the page size, so larger alignments need some slack.
+        let len = if layout.align() > self.page_size { size + layout.align() } else { size };
+        let mapping = map(len);
+        if mapping.is_null() {
+            return mapping;
+        }
+        let ptr = mapping.wrapping_add(mapping.align_offset(layout.align()));
+        self.huge.insert(ptr.expose_provenance(), (mapping.expose_provenance(), len));
+        ptr
+    }
+
+    /// Frees the allocation at `ptr`, returning whether it was allocated by this allocator.
+    fn dealloc(&mut self, ptr: *mut u8, layout: Layout) -> bool {
+        let addr = ptr.expose_provenance();
+        let page = addr & !(self.page_size - 1);
+        if let Some(used) = self.pages.get_mut(&page) {
+            let start = (addr - page) / CHUNK_SIZE;
+            let chunks = layout.size().div_ceil(CHUNK_SIZE).max(1);
+            for chunk in start..start + chunks {
+                used[chunk / 64] &= !(1 << (chunk % 64));
+            }
+            if used.iter().all(|&bits| bits == 0) {
+                self.pages.remove(&page);
+                unmap(page, self.page_size);
+            }
+            true
+        } else if let Some((mapping, len)) = self.huge.remove(&addr) {
+            unmap(mapping, len);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Maps `len` bytes of fresh, zeroed memory, returning null on failure.
+fn map(len: usize) -> *mut u8 {
+    // SAFETY: creating a new anonymous mapping does not affect any existing memory.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED { ptr::null_mut() } else { ptr.cast() }
+}
+
+fn unmap(addr: usize, len: usize) {
+    // SAFETY: the mapping was created by `map`, and nothing in it is in use any more.
+    let res = unsafe { libc::munmap(ptr::with_exposed_provenance_mut(addr), len) };
+    assert_eq!(res, 0, "failed to unmap memory: {}", std::io::Error::last_os_error());
+}
+
+/// From now on, place all `MiriAllocBytes` on pages of their own.
+pub fn enable() {
+    let mut alloc = ALLOC.lock().unwrap();
+    if alloc.is_none() {
+        // SAFETY: `sysconf` has no preconditions.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        *alloc = Some(IsolatedAlloc {
+            page_size: page_size.try_into().unwrap(),
+            pages: BTreeMap::new(),
+            huge: BTreeMap::new(),
+        });
+        ENABLED.store(true, Ordering::Release);
+    }
+}
+
+/// Allocates zeroed memory for `layout`, which must have a non-zero size. Returns `None` if the
+/// allocator is not enabled, and a null pointer if there is no memory left.
+pub fn alloc(layout: Layout) -> Option<*mut u8> {
+    if !ENABLED.load(Ordering::Acquire) {
+        return None;
+    }
+    Some(ALLOC.lock().unwrap().as_mut()?.alloc(layout))
+}
+
+/// Frees memory that was allocated with `layout`, returning whether it was allocated by this
+/// allocator.
+///
+/// # Safety
+/// If `ptr` was allocated by this allocator, it must not be used any more.
+pub unsafe fn dealloc(ptr: *mut u8, layout: Layout) -> bool {
+    if !ENABLED.load(Ordering::Acquire) {
+        return false;
+    }
+    ALLOC.lock().unwrap().as_mut().is_some_and(|alloc| alloc.dealloc(ptr, layout))
+}
+
+/// The address and length of all memory currently handed out by this allocator, sorted by
+/// address, and the page size.
+pub fn regions() -> (Vec<(usize, usize)>, usize) {
+    let alloc = ALLOC.lock().unwrap();
+    let Some(alloc) = alloc.as_ref() else { return (Vec::new(), 0) };
+    let mut regions = alloc
+        .pages
+        .keys()
+        .map(|&page| (page, alloc.page_size))
+        .chain(alloc.huge.values().copied())
+        .collect::<Vec<_>>();
+    regions.sort_unstable();
+    (regions, alloc.page_size)
+}
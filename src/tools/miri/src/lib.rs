@@ -77,10 +77,13 @@
 mod borrow_tracker;
 mod clock;
 mod concurrency;
+mod coverage;
 mod diagnostics;
 mod eval;
 mod helpers;
 mod intrinsics;
+#[cfg(unix)]
+mod isolated_alloc;
 mod machine;
 mod mono_hash_map;
 mod operator;
@@ -150,6 +153,7 @@
 pub use crate::shims::io_error::{EvalContextExt as _, LibcError};
 pub use crate::shims::os_str::EvalContextExt as _;
 pub use crate::shims::panic::{CatchUnwindData, EvalContextExt as _};
+pub use crate::shims::signal::{EvalContextExt as _, SignalFrame, SignalState};
 pub use crate::shims::time::EvalContextExt as _;
 pub use crate::shims::tls::TlsData;
 
@@ -8,6 +8,7 @@
 use std::path::Path;
 use std::{fmt, process};
 
+use either::Either;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use rustc_attr::InlineAttr;
@@ -26,7 +27,9 @@
 
 use crate::concurrency::cpu_affinity::{self, CpuAffinityMask};
 use crate::concurrency::data_race::{self, NaReadType, NaWriteType};
+use crate::concurrency::exploration::Decisions;
 use crate::concurrency::weak_memory;
+use crate::coverage::Coverage;
 use crate::*;
 
 /// First real-time signal.
@@ -54,6 +57,10 @@ pub struct FrameExtra<'tcx> {
     /// we stop unwinding, use the `CatchUnwindData` to handle catching.
     pub catch_unwind: Option<CatchUnwindData<'tcx>>,
 
+    /// If this is Some(), then this is the frame of a signal handler. When it returns, we restore
+    /// the state of the thread from the `SignalFrame` and continue with the interrupted code.
+    pub signal_frame: Option<SignalFrame>,
+
     /// If `measureme` profiling is enabled, holds timing information
     /// for the start of this frame. When we finish executing this frame,
     /// we use this to register a completed event with `measureme`.
@@ -81,6 +88,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let FrameExtra {
             borrow_tracker,
             catch_unwind,
+            signal_frame,
             timing: _,
             is_user_relevant,
             salt,
@@ -89,6 +97,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FrameData")
             .field("borrow_tracker", borrow_tracker)
             .field("catch_unwind", catch_unwind)
+            .field("signal_frame", signal_frame)
             .field("is_user_relevant", is_user_relevant)
             .field("salt", salt)
             .field("data_race", data_race)
@@ -100,6 +109,7 @@ impl VisitProvenance for FrameExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         let FrameExtra {
             catch_unwind,
+            signal_frame,
             borrow_tracker,
             timing: _,
             is_user_relevant: _,
@@ -108,6 +118,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         } = self;
 
         catch_unwind.visit_provenance(visit);
+        signal_frame.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
     }
 }
@@ -342,6 +353,8 @@ pub struct AllocExtra<'tcx> {
     /// freed.
     /// This maps offsets to synchronization-primitive-specific data.
     pub sync: FxHashMap<Size, Box<dyn Any>>,
+    /// If this allocation is a memory mapping of a file, the state of that mapping.
+    pub file_mapping: Option<Box<shims::FileMapping>>,
 }
 
 // We need a `Clone` impl because the machine passes `Allocation` through `Cow`...
@@ -354,7 +367,14 @@ fn clone(&self) -> Self {
 
 impl VisitProvenance for AllocExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let AllocExtra { borrow_tracker, data_race, weak_memory, backtrace: _, sync: _ } = self;
+        let AllocExtra {
+            borrow_tracker,
+            data_race,
+            weak_memory,
+            backtrace: _,
+            sync: _,
+            file_mapping: _,
+        } = self;
 
         borrow_tracker.visit_provenance(visit);
         data_race.visit_provenance(visit);
@@ -478,6 +498,14 @@ pub struct MiriMachine<'tcx> {
 
     /// The list of all EpollEventInterest.
     pub(crate) epoll_interests: shims::EpollInterestTable,
+    /// The sockets bound on the virtual loopback network.
+    pub(crate) network: shims::VirtualNetwork,
+    /// The in-memory file system, if the program does not use the host file system.
+    pub(crate) vfs: Option<shims::Vfs>,
+    /// The child processes that have not been waited for yet.
+    pub(crate) children: shims::ChildProcesses,
+    /// The signal actions of the process and the signal state of each thread.
+    pub(crate) signals: SignalState,
 
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
@@ -523,6 +551,10 @@ pub struct MiriMachine<'tcx> {
     /// Needs to be queried by ptr_to_int, hence needs interior mutability.
     pub(crate) rng: RefCell<StdRng>,
 
+    /// When exploring, recording or replaying executions, this makes the decisions that are
+    /// otherwise left to `rng`.
+    pub(crate) decisions: Option<RefCell<Decisions>>,
+
     /// The allocation IDs to report when they are being allocated
     /// (helps for debugging memory leaks and use after free bugs).
     tracked_alloc_ids: FxHashSet<AllocId>,
@@ -548,6 +580,8 @@ pub struct MiriMachine<'tcx> {
     pub(crate) report_progress: Option<u32>,
     // The total number of blocks that have been executed.
     pub(crate) basic_block_count: u64,
+    /// The execution counts for the coverage report, if one was requested.
+    pub(crate) coverage: Option<Coverage<'tcx>>,
 
     /// Handle of the optional shared object file for native functions.
     #[cfg(unix)]
@@ -645,6 +679,14 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             cpu_affinity::MAX_CPUS,
             config.num_cpus
         );
+        let vfs = config.vfs.as_ref().map(|dir| {
+            shims::Vfs::snapshot(dir).unwrap_or_else(|err| {
+                tcx.dcx().fatal(format!(
+                    "failed to copy `{}` into the virtual file system: {err}",
+                    dir.display()
+                ))
+            })
+        });
         let threads = ThreadManager::default();
         let mut thread_cpu_affinity = FxHashMap::default();
         if matches!(&*tcx.sess.target.os, "linux" | "freebsd" | "android") {
@@ -667,6 +709,10 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             validation: config.validation,
             fds: shims::FdTable::init(config.mute_stdout_stderr),
             epoll_interests: shims::EpollInterestTable::new(),
+            network: Default::default(),
+            vfs,
+            children: shims::ChildProcesses::new(config),
+            signals: Default::default(),
             dirs: Default::default(),
             layouts,
             threads,
@@ -680,6 +726,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             local_crates,
             extern_statics: FxHashMap::default(),
             rng: RefCell::new(rng),
+            decisions: None,
             tracked_alloc_ids: config.tracked_alloc_ids.clone(),
             track_alloc_accesses: config.track_alloc_accesses,
             check_alignment: config.check_alignment,
@@ -689,6 +736,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
             preemption_rate: config.preemption_rate,
             report_progress: config.report_progress,
             basic_block_count: 0,
+            coverage: config.coverage.is_some().then(Coverage::default),
             clock: Clock::new(config.isolated_op == IsolatedOp::Allow),
             #[cfg(unix)]
             native_lib: config.native_lib.as_ref().map(|lib_file_path| {
@@ -701,6 +749,9 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx>) -> Self {
                         target_triple,
                     );
                 }
+                // Native code must not share pages with the memory of the interpreted program, so
+                // that native calls can detect writes to memory that was not passed to them.
+                crate::isolated_alloc::enable();
                 // Note: it is the user's responsibility to provide a correct SO file.
                 // WATCH OUT: If an invalid/incorrect SO file is specified, this can cause
                 // undefined behaviour in Miri itself!
@@ -751,6 +802,12 @@ pub(crate) fn communicate(&self) -> bool {
         self.isolated_op == IsolatedOp::Allow
     }
 
+    /// The isolation setting for file system operations. With a virtual file system, those never
+    /// reach the host, so they are always allowed.
+    pub(crate) fn fs_isolated_op(&self) -> IsolatedOp {
+        if self.vfs.is_some() { IsolatedOp::Allow } else { self.isolated_op }
+    }
+
     /// Check whether the stack frame that this `FrameInfo` refers to is part of a local crate.
     pub(crate) fn is_local(&self, frame: &FrameInfo<'_>) -> bool {
         let def_id = frame.instance.def_id();
@@ -806,6 +863,10 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             alloc_addresses,
             fds,
             epoll_interests:_,
+            network: _,
+            vfs: _,
+            children: _,
+            signals,
             tcx: _,
             isolated_op: _,
             validation: _,
@@ -818,6 +879,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             backtrace_style: _,
             local_crates: _,
             rng: _,
+            decisions: _,
             tracked_alloc_ids: _,
             track_alloc_accesses: _,
             check_alignment: _,
@@ -827,6 +889,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             preemption_rate: _,
             report_progress: _,
             basic_block_count: _,
+            coverage: _,
             native_lib: _,
             gc_interval: _,
             since_gc: _,
@@ -846,6 +909,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         env_vars.visit_provenance(visit);
         dirs.visit_provenance(visit);
         fds.visit_provenance(visit);
+        signals.visit_provenance(visit);
         data_race.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
         alloc_addresses.visit_provenance(visit);
@@ -1199,6 +1263,7 @@ fn init_alloc_extra(
             weak_memory,
             backtrace,
             sync: FxHashMap::default(),
+            file_mapping: None,
         })
     }
 
@@ -1338,6 +1403,9 @@ fn before_memory_write(
             machine
                 .emit_diagnostic(NonHaltingDiagnostic::AccessedAlloc(alloc_id, AccessKind::Write));
         }
+        if alloc_extra.file_mapping.as_ref().is_some_and(|mapping| mapping.is_read_only()) {
+            throw_ub!(WriteToReadOnly(alloc_id));
+        }
         if let Some(data_race) = &mut alloc_extra.data_race {
             data_race.write(alloc_id, range, NaWriteType::Write, None, machine)?;
         }
@@ -1454,6 +1522,7 @@ fn init_frame(
         let extra = FrameExtra {
             borrow_tracker: borrow_tracker.map(|bt| bt.borrow_mut().new_frame()),
             catch_unwind: None,
+            signal_frame: None,
             timing,
             is_user_relevant: ecx.machine.is_user_relevant(&frame),
             salt: ecx.machine.rng.borrow_mut().gen::<usize>() % ADDRS_PER_ANON_GLOBAL,
@@ -1496,6 +1565,14 @@ fn before_terminator(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
             ecx.run_provenance_gc();
         }
 
+        if ecx.machine.coverage.is_some() {
+            let frame = ecx.frame();
+            let body = frame.body();
+            if let Either::Left(loc) = frame.current_loc() {
+                ecx.machine.coverage.as_mut().unwrap().record_block(body, loc.block);
+            }
+        }
+
         // These are our preemption points.
         ecx.maybe_preempt_active_thread();
 
@@ -1507,6 +1584,10 @@ fn before_terminator(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
 
     #[inline(always)]
     fn after_stack_push(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
+        if ecx.machine.coverage.is_some() {
+            let body = ecx.frame().body();
+            ecx.machine.coverage.as_mut().unwrap().record_call(body);
+        }
         if ecx.frame().extra.is_user_relevant {
             // We just pushed a local frame, so we know that the topmost local frame is the topmost
             // frame. If we push a non-local frame, there's no need to do anything.
@@ -1549,11 +1630,19 @@ fn after_stack_pop(
             // Move `frame`` into a sub-scope so we control when it will be dropped.
             let mut frame = frame;
             let timing = frame.extra.timing.take();
+            let signal_frame = frame.extra.signal_frame.take();
             let res = ecx.handle_stack_pop_unwind(frame.extra, unwinding);
             if let Some(profiler) = ecx.machine.profiler.as_ref() {
                 profiler.finish_recording_interval_event(timing.unwrap());
             }
-            res
+            match signal_frame {
+                // Unwinding out of a signal handler is caught by its `Unreachable` unwind action.
+                Some(signal_frame) if !unwinding => {
+                    res?;
+                    ecx.return_from_signal_handler(signal_frame)
+                }
+                _ => res,
+            }
         };
         // Needs to be done after dropping frame to show up on the right nesting level.
         // (Cc https://github.com/rust-lang/miri/issues/2266)
@@ -112,6 +112,12 @@ fn get_env_var(&mut self, name: &OsStr) -> InterpResult<'tcx, Option<OsString>>
 
     fn get_pid(&self) -> u32 {
         let this = self.eval_context_ref();
-        if this.machine.communicate() { std::process::id() } else { 1000 }
+        if let Some(pid) = this.machine.children.pid() {
+            pid
+        } else if this.machine.communicate() {
+            std::process::id()
+        } else {
+            1000
+        }
     }
 }
@@ -48,6 +48,10 @@ fn emulate_foreign_item(
     ) -> InterpResult<'tcx, Option<(&'tcx mir::Body<'tcx>, ty::Instance<'tcx>)>> {
         let this = self.eval_context_mut();
 
+        if this.machine.signals.in_handler(this.active_thread()) {
+            this.check_async_signal_safe(link_name)?;
+        }
+
         // Some shims forward to other MIR bodies.
         match link_name.as_str() {
             "__rust_alloc_error_handler" => {
@@ -15,10 +15,13 @@
 pub mod io_error;
 pub mod os_str;
 pub mod panic;
+pub mod signal;
 pub mod time;
 pub mod tls;
 
-pub use self::unix::{DirTable, EpollInterestTable, FdTable};
+pub use self::unix::{
+    ChildProcesses, DirTable, EpollInterestTable, FdTable, FileMapping, Vfs, VirtualNetwork,
+};
 
 /// What needs to be done after emulating an item (a shim or an intrinsic) is done.
 pub enum EmulateItemResult {
@@ -1,14 +1,34 @@
 //! Implements calling functions from a native library.
+//!
+//! Native code can access all memory that is reachable from the pointers passed to it, so all
+//! those allocations get exposed before the call. Writes to the memory of the program are
+//! tracked during the call (see [`protect`]): writes to other memory than those allocations are
+//! reported as errors, and the allocations that were written to have unknown but initialized
+//! contents afterwards.
+mod protect;
+
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use libffi::high::call as ffi;
 use libffi::low::CodePtr;
+use rustc_data_structures::fx::FxHashSet;
 use rustc_middle::ty::{self as ty, IntTy, UintTy};
 use rustc_span::Symbol;
-use rustc_target::abi::{Abi, HasDataLayout};
+use rustc_target::abi::{Abi, HasDataLayout, Size};
 
+use self::protect::{Protection, WritableRange};
 use crate::*;
 
+/// An allocation that native code can access during a call.
+struct ExposedAlloc {
+    id: AllocId,
+    /// The address of its bytes.
+    addr: usize,
+    size: usize,
+    mutable: bool,
+}
+
 impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
 trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
     /// Call native host function and return the output as an immediate.
@@ -69,6 +89,12 @@ fn call_native_with_args<'a>(
                 let x = unsafe { ffi::call::<usize>(ptr, libffi_args.as_slice()) };
                 Scalar::from_target_usize(x.try_into().unwrap(), this)
             }
+            // Raw pointers are returned as their address here; `call_native_fn` turns them back
+            // into pointers once it is known what memory native code could access.
+            ty::RawPtr(..) => {
+                let x = unsafe { ffi::call::<*mut std::ffi::c_void>(ptr, libffi_args.as_slice()) };
+                Scalar::from_target_usize(x.addr().try_into().unwrap(), this)
+            }
             // Functions with no declared return type (i.e., the default return)
             // have the output_type `Tuple([])`.
             ty::Tuple(t_list) if t_list.len() == 0 => {
@@ -121,6 +147,72 @@ fn get_func_ptr_explicitly_from_lib(&mut self, link_name: Symbol) -> Option<Code
         // Return a pointer to the function.
         Some(CodePtr(*func.deref() as *mut _))
     }
+
+    /// Exposes all allocations that native code can reach from `ptrs`: the allocations they point
+    /// to, and transitively the allocations that the pointers stored in those point to.
+    fn expose_reachable_allocs(
+        &mut self,
+        ptrs: Vec<Pointer>,
+    ) -> InterpResult<'tcx, Vec<ExposedAlloc>> {
+        let this = self.eval_context_mut();
+        let ptr_size = this.pointer_size();
+        let mut todo = ptrs;
+        let mut done = FxHashSet::default();
+        let mut exposed = Vec::new();
+        while let Some(ptr) = todo.pop() {
+            let alloc_id = match ptr.provenance {
+                Some(Provenance::Concrete { alloc_id, tag }) => {
+                    this.expose_ptr(alloc_id, tag)?;
+                    alloc_id
+                }
+                // A wildcard pointer can only be used for allocations that are already exposed.
+                Some(Provenance::Wildcard) =>
+                    match this.exposed_alloc_id(ptr.addr().bytes()) {
+                        Some(alloc_id) => alloc_id,
+                        None => continue,
+                    },
+                None => continue,
+            };
+            if !done.insert(alloc_id) {
+                continue;
+            }
+            let (size, _align, kind) = this.get_alloc_info(alloc_id);
+            if !matches!(kind, AllocKind::LiveData) {
+                continue;
+            }
+            let alloc = this.get_alloc_raw(alloc_id)?;
+            let addr = alloc.get_bytes_unchecked_raw().addr();
+            let mut has_wildcard = false;
+            for prov in alloc.provenance().provenances() {
+                match prov {
+                    Provenance::Concrete { .. } => todo.push(Pointer::new(Some(prov), Size::ZERO)),
+                    Provenance::Wildcard => has_wildcard = true,
+                }
+            }
+            // For wildcard pointers, we need their address to know where they point. Those were
+            // written by native code, which stores pointers at pointer-aligned addresses.
+            if has_wildcard {
+                let mut offset =
+                    Size::from_bytes(addr.next_multiple_of(ptr_size.bytes_usize()) - addr);
+                while offset + ptr_size <= size {
+                    match alloc.read_scalar(this, alloc_range(offset, ptr_size), true) {
+                        Ok(Scalar::Ptr(ptr, _))
+                            if matches!(ptr.provenance, Provenance::Wildcard) =>
+                            todo.push(ptr.into()),
+                        _ => {}
+                    }
+                    offset += ptr_size;
+                }
+            }
+            exposed.push(ExposedAlloc {
+                id: alloc_id,
+                addr,
+                size: size.bytes_usize(),
+                mutable: alloc.mutability.is_mut(),
+            });
+        }
+        interp_ok(exposed)
+    }
 }
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -148,13 +240,33 @@ fn call_native_fn(
 
         // Get the function arguments, and convert them to `libffi`-compatible form.
         let mut libffi_args = Vec::<CArg>::with_capacity(args.len());
+        let mut ptrs = Vec::new();
         for arg in args.iter() {
             if !matches!(arg.layout.abi, Abi::Scalar(_)) {
                 throw_unsup_format!("only scalar argument types are support for native calls")
             }
-            libffi_args.push(imm_to_carg(this.read_immediate(arg)?, this)?);
+            let imm = this.read_immediate(arg)?;
+            if arg.layout.ty.is_unsafe_ptr() {
+                ptrs.push(imm.to_scalar().to_pointer(this)?);
+            }
+            libffi_args.push(imm_to_carg(imm, this)?);
         }
 
+        // Native code may write to all mutable memory it can reach, but nothing else.
+        let mut exposed = this.expose_reachable_allocs(ptrs)?;
+        exposed.retain(|alloc| alloc.mutable && alloc.size > 0);
+        exposed.sort_by_key(|alloc| alloc.addr);
+        let writable = exposed
+            .iter()
+            .map(|alloc| {
+                WritableRange {
+                    start: alloc.addr,
+                    end: alloc.addr + alloc.size,
+                    written: AtomicBool::new(false),
+                }
+            })
+            .collect();
+
         // Convert them to `libffi::high::Arg` type.
         let libffi_args = libffi_args
             .iter()
@@ -162,7 +274,35 @@ fn call_native_fn(
             .collect::<Vec<libffi::high::Arg<'_>>>();
 
         // Call the function and store output, depending on return type in the function signature.
-        let ret = this.call_native_with_args(link_name, dest, code_ptr, libffi_args)?;
+        let protection = Protection::start(writable);
+        let ret = this.call_native_with_args(link_name, dest, code_ptr, libffi_args);
+        let (writable, bad_write) = protection.stop();
+        let ret = ret?;
+
+        if let Some(addr) = bad_write {
+            throw_ub_format!(
+                "native code wrote to address {addr:#x}, which is not in the mutable memory that is reachable from the arguments of `{link_name}`"
+            );
+        }
+        for (alloc, range) in exposed.iter().zip(&writable) {
+            if range.written.load(Ordering::Relaxed) {
+                this.mark_native_write(alloc.id, Provenance::Wildcard)?;
+            }
+        }
+
+        let ret = if dest.layout.ty.is_unsafe_ptr() {
+            let addr = ret.to_scalar().to_target_usize(this)?;
+            // Miri can only access memory that native code got from the program.
+            if addr != 0 && this.exposed_alloc_id(addr).is_none() {
+                throw_unsup_format!(
+                    "`{link_name}` returned the pointer {addr:#x}, which does not point into memory that was exposed to native code"
+                );
+            }
+            let ptr = Pointer::new(Some(Provenance::Wildcard), Size::from_bytes(addr));
+            ImmTy::from_scalar(Scalar::from_maybe_pointer(ptr, this), dest.layout)
+        } else {
+            ret
+        };
         this.write_immediate(*ret, dest)?;
         interp_ok(true)
     }
@@ -238,18 +378,10 @@ fn imm_to_carg<'tcx>(v: ImmTy<'tcx>, cx: &impl HasDataLayout) -> InterpResult<'t
         ty::Uint(UintTy::U64) => CArg::UInt64(v.to_scalar().to_u64()?),
         ty::Uint(UintTy::Usize) =>
             CArg::USize(v.to_scalar().to_target_usize(cx)?.try_into().unwrap()),
-        ty::RawPtr(_, mutability) => {
-            // Arbitrary mutable pointer accesses are not currently supported in Miri.
-            if mutability.is_mut() {
-                throw_unsup_format!(
-                    "unsupported mutable pointer type for native call: {}",
-                    v.layout.ty
-                );
-            } else {
-                let s = v.to_scalar().to_pointer(cx)?.addr();
-                // This relies on the `expose_provenance` in `addr_from_alloc_id`.
-                CArg::RawPtr(std::ptr::with_exposed_provenance_mut(s.bytes_usize()))
-            }
+        ty::RawPtr(..) => {
+            let s = v.to_scalar().to_pointer(cx)?.addr();
+            // This relies on the `expose_provenance` in `addr_from_alloc_id`.
+            CArg::RawPtr(std::ptr::with_exposed_provenance_mut(s.bytes_usize()))
         }
         _ => throw_unsup_format!("unsupported argument type for native call: {}", v.layout.ty),
     })
@@ -0,0 +1,246 @@
+//! Detects writes of native code to the memory of the interpreted program.
+//!
+//! For the duration of a native call, all memory handed out by the isolated allocator is made
+//! read-only, and a signal handler catches the faults caused by writes to it. A write to memory
+//! that native code was allowed to write to is recorded, any other write is remembered as an
+//! error. Either way, the page is made writable again so that the write can go through. On
+//! x86_64 Linux, the faulting instruction is then single-stepped so that the page can be
+//! protected again right after it, which makes every write to it visible. Elsewhere, the page
+//! stays writable, so later writes to it are only noticed coarsely.
+//!
+//! Native code that hands memory of the program to a system call sees that call fail with
+//! `EFAULT` instead, since the kernel does not raise a signal for those accesses.
+
+use std::ffi::{c_int, c_void};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::{mem, ptr, slice};
+
+use crate::isolated_alloc;
+
+/// An address range that native code is allowed to write to.
+pub struct WritableRange {
+    pub start: usize,
+    pub end: usize,
+    /// Whether native code wrote to this range (or, without single-stepping, to its pages).
+    pub written: AtomicBool,
+}
+
+/// The state shared with the signal handlers. It is only set while a native call is running.
+static REGIONS: AtomicPtr<(usize, usize)> = AtomicPtr::new(ptr::null_mut());
+static REGIONS_LEN: AtomicUsize = AtomicUsize::new(0);
+static WRITABLE: AtomicPtr<WritableRange> = AtomicPtr::new(ptr::null_mut());
+static WRITABLE_LEN: AtomicUsize = AtomicUsize::new(0);
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+/// The first write that went to memory native code was not allowed to write to, or 0.
+static BAD_WRITE: AtomicUsize = AtomicUsize::new(0);
+/// The pages that have to be protected again once the current instruction is done.
+static STEPPED_PAGES: [AtomicUsize; 2] = [const { AtomicUsize::new(0) }; 2];
+/// The signal actions that were installed before the native call.
+static OLD_ACTIONS: AtomicPtr<[libc::sigaction; SIGNALS.len()]> = AtomicPtr::new(ptr::null_mut());
+
+/// The signals we handle: writes to protected memory raise `SIGSEGV` on Linux and `SIGBUS` on
+/// macOS, and single-stepping raises `SIGTRAP`.
+const SIGNALS: [c_int; 3] = [libc::SIGSEGV, libc::SIGBUS, libc::SIGTRAP];
+
+/// The write protection of the program's memory during a native call.
+pub struct Protection {
+    regions: Box<[(usize, usize)]>,
+    writable: Box<[WritableRange]>,
+    old_actions: Box<[libc::sigaction; SIGNALS.len()]>,
+}
+
+impl Protection {
+    /// Write-protects all memory of the program, except that native code may write to the
+    /// sorted, non-overlapping `writable` ranges.
+    pub fn start(writable: Vec<WritableRange>) -> Protection {
+        let (regions, page_size) = isolated_alloc::regions();
+        let mut protection = Protection {
+            regions: regions.into_boxed_slice(),
+            writable: writable.into_boxed_slice(),
+            // SAFETY: `sigaction` is plain old data.
+            old_actions: Box::new(unsafe { mem::zeroed() }),
+        };
+        PAGE_SIZE.store(page_size, Ordering::Relaxed);
+        BAD_WRITE.store(0, Ordering::Relaxed);
+        REGIONS_LEN.store(protection.regions.len(), Ordering::Relaxed);
+        REGIONS.store(protection.regions.as_mut_ptr(), Ordering::Relaxed);
+        WRITABLE_LEN.store(protection.writable.len(), Ordering::Relaxed);
+        WRITABLE.store(protection.writable.as_mut_ptr(), Ordering::Relaxed);
+        OLD_ACTIONS.store(&mut *protection.old_actions, Ordering::Relaxed);
+        for (sig, old_action) in SIGNALS.iter().zip(protection.old_actions.iter_mut()) {
+            // SAFETY: `sigaction` is plain old data.
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            action.sa_sigaction = if *sig == libc::SIGTRAP {
+                on_trap as extern "C" fn(c_int, *mut libc::siginfo_t, *mut c_void)
+            } else {
+                on_fault as extern "C" fn(c_int, *mut libc::siginfo_t, *mut c_void)
+            } as libc::sighandler_t;
+            action.sa_flags = libc::SA_SIGINFO;
+            // SAFETY: the handlers only use the state set up above.
+            let res = unsafe { libc::sigaction(*sig, &action, old_action) };
+            assert_eq!(res, 0, "failed to install signal handler");
+        }
+        for &(addr, len) in &protection.regions {
+            protect(addr, len, libc::PROT_READ);
+        }
+        protection
+    }
+
+    /// Lifts the write protection, and returns the writable ranges together with the first
+    /// address that native code was not allowed to write to but did, if any.
+    pub fn stop(self) -> (Box<[WritableRange]>, Option<usize>) {
+        for &(addr, len) in &self.regions {
+            protect(addr, len, libc::PROT_READ | libc::PROT_WRITE);
+        }
+        for (sig, old_action) in SIGNALS.iter().zip(self.old_actions.iter()) {
+            // SAFETY: this restores the handlers from before the native call.
+            let res = unsafe { libc::sigaction(*sig, old_action, ptr::null_mut()) };
+            assert_eq!(res, 0, "failed to restore signal handler");
+        }
+        REGIONS.store(ptr::null_mut(), Ordering::Relaxed);
+        REGIONS_LEN.store(0, Ordering::Relaxed);
+        WRITABLE.store(ptr::null_mut(), Ordering::Relaxed);
+        WRITABLE_LEN.store(0, Ordering::Relaxed);
+        OLD_ACTIONS.store(ptr::null_mut(), Ordering::Relaxed);
+        let bad_write = BAD_WRITE.swap(0, Ordering::Relaxed);
+        (self.writable, (bad_write != 0).then_some(bad_write))
+    }
+}
+
+fn protect(addr: usize, len: usize, prot: c_int) {
+    // SAFETY: the memory belongs to the isolated allocator, and only changes its permissions.
+    let res = unsafe { libc::mprotect(ptr::with_exposed_provenance_mut(addr), len, prot) };
+    // Abort rather than panic, this might run in a signal handler.
+    if res != 0 {
+        std::process::abort();
+    }
+}
+
+/// Lets the signal be handled as it would have been without us: the faulting instruction is
+/// executed again once this handler returns, and then triggers the old handler.
+fn restore_old_action(sig: c_int) {
+    let index = SIGNALS.iter().position(|&s| s == sig).unwrap();
+    // SAFETY: `OLD_ACTIONS` is set while our handlers are installed.
+    let old_action = unsafe { &(*OLD_ACTIONS.load(Ordering::Relaxed))[index] };
+    // SAFETY: this installs the handler from before the native call.
+    unsafe { libc::sigaction(sig, old_action, ptr::null_mut()) };
+}
+
+extern "C" fn on_fault(sig: c_int, info: *mut libc::siginfo_t, context: *mut c_void) {
+    // SAFETY: this is a memory fault, so `si_addr` is the address that was accessed.
+    #[cfg(target_os = "linux")]
+    let addr = unsafe { (*info).si_addr() }.addr();
+    // SAFETY: see above.
+    #[cfg(not(target_os = "linux"))]
+    let addr = unsafe { (*info).si_addr }.addr();
+    // SAFETY: these slices are set while our handlers are installed.
+    let (regions, writable) = unsafe {
+        (
+            slice::from_raw_parts(
+                REGIONS.load(Ordering::Relaxed),
+                REGIONS_LEN.load(Ordering::Relaxed),
+            ),
+            slice::from_raw_parts(
+                WRITABLE.load(Ordering::Relaxed),
+                WRITABLE_LEN.load(Ordering::Relaxed),
+            ),
+        )
+    };
+    let in_regions = match regions.binary_search_by_key(&addr, |&(start, _)| start) {
+        Ok(_) => true,
+        Err(0) => false,
+        Err(index) => {
+            let (start, len) = regions[index - 1];
+            addr < start + len
+        }
+    };
+    if !in_regions {
+        // Not a write to protected memory, so this is someone else's fault.
+        restore_old_action(sig);
+        return;
+    }
+
+    let page_size = PAGE_SIZE.load(Ordering::Relaxed);
+    let page = addr & !(page_size - 1);
+    let overlapping = |start: usize, end: usize| {
+        let first = writable.partition_point(|range| range.end <= start);
+        writable[first..].iter().take_while(move |range| range.start < end)
+    };
+    match overlapping(addr, addr + 1).next() {
+        Some(range) => range.written.store(true, Ordering::Relaxed),
+        None => {
+            let _ = BAD_WRITE.compare_exchange(0, addr, Ordering::Relaxed, Ordering::Relaxed);
+        }
+    }
+    protect(page, page_size, libc::PROT_READ | libc::PROT_WRITE);
+
+    // A page that only holds writable memory can stay writable. Otherwise, protect it again
+    // after this instruction, if we can.
+    let mut covered = page;
+    for range in overlapping(page, page + page_size) {
+        if range.start > covered {
+            break;
+        }
+        covered = covered.max(range.end);
+    }
+    if covered >= page + page_size || !single_step(context, page) {
+        // Every write to this page goes unnoticed from now on.
+        for range in overlapping(page, page + page_size) {
+            range.written.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The trap flag in `EFLAGS`.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const TRAP_FLAG: libc::greg_t = 0x100;
+
+/// Makes the thread trap after the current instruction, so that `page` can be protected again.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn single_step(context: *mut c_void, page: usize) -> bool {
+    let has_slot = STEPPED_PAGES
+        .iter()
+        .any(|slot| slot.compare_exchange(0, page, Ordering::Relaxed, Ordering::Relaxed).is_ok());
+    if !has_slot {
+        return false;
+    }
+    let context = context.cast::<libc::ucontext_t>();
+    // SAFETY: the kernel passes the interrupted context to `SA_SIGINFO` handlers.
+    unsafe { (*context).uc_mcontext.gregs[libc::REG_EFL as usize] |= TRAP_FLAG };
+    true
+}
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+fn single_step(_context: *mut c_void, _page: usize) -> bool {
+    false
+}
+
+extern "C" fn on_trap(sig: c_int, _info: *mut libc::siginfo_t, context: *mut c_void) {
+    let page_size = PAGE_SIZE.load(Ordering::Relaxed);
+    let mut stepped = false;
+    for slot in &STEPPED_PAGES {
+        let page = slot.swap(0, Ordering::Relaxed);
+        if page != 0 {
+            protect(page, page_size, libc::PROT_READ);
+            stepped = true;
+        }
+    }
+    if !stepped {
+        // Not a trap we asked for.
+        restore_old_action(sig);
+        // SAFETY: raising a signal has no preconditions.
+        unsafe { libc::raise(sig) };
+        return;
+    }
+    clear_trap_flag(context);
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn clear_trap_flag(context: *mut c_void) {
+    let context = context.cast::<libc::ucontext_t>();
+    // SAFETY: the kernel passes the interrupted context to `SA_SIGINFO` handlers.
+    unsafe { (*context).uc_mcontext.gregs[libc::REG_EFL as usize] &= !TRAP_FLAG };
+}
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+fn clear_trap_flag(_context: *mut c_void) {}
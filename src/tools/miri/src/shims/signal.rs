@@ -0,0 +1,883 @@
+//! Implements POSIX signals that the program sends to itself.
+//!
+//! Signals are generated with `kill`, `raise` and `pthread_kill`, and become pending either on the
+//! whole process or on a single thread. A pending signal is delivered when the scheduler is about
+//! to execute a step of a thread that does not block it: process-directed signals go to whichever
+//! such thread runs first. If the action for the signal is a handler, we push a stack frame for the
+//! handler on top of the code it interrupts, which continues once the handler returns.
+//!
+//! Blocking operations are never interrupted (so `SA_RESTART` makes no difference): a thread that
+//! is blocked only handles its signals once it gets unblocked.
+//!
+//! While a signal handler is running, calling a function that is not async-signal-safe is reported
+//! as undefined behavior.
+
+use std::iter;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::mir;
+use rustc_middle::ty::layout::TyAndLayout;
+use rustc_span::Symbol;
+use rustc_target::abi::Size;
+use rustc_target::spec::abi::Abi;
+
+use crate::shims::unix::*;
+use crate::*;
+
+/// Names of the standard signals, used to make messages more readable.
+const SIGNAL_NAMES: &[&str] = &[
+    "SIGHUP",
+    "SIGINT",
+    "SIGQUIT",
+    "SIGILL",
+    "SIGTRAP",
+    "SIGABRT",
+    "SIGBUS",
+    "SIGFPE",
+    "SIGKILL",
+    "SIGUSR1",
+    "SIGSEGV",
+    "SIGUSR2",
+    "SIGPIPE",
+    "SIGALRM",
+    "SIGTERM",
+    "SIGCHLD",
+    "SIGCONT",
+    "SIGSTOP",
+    "SIGTSTP",
+    "SIGTTIN",
+    "SIGTTOU",
+    "SIGURG",
+    "SIGXCPU",
+    "SIGXFSZ",
+    "SIGVTALRM",
+    "SIGPROF",
+    "SIGWINCH",
+    "SIGSYS",
+];
+
+/// A set of signals. We support the signal numbers from 1 up to 64.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct SigSet(u64);
+
+impl SigSet {
+    const EMPTY: SigSet = SigSet(0);
+
+    /// The set of all signals up to and including `max`.
+    fn full(max: i32) -> Self {
+        SigSet(u64::MAX.checked_shr(64u32.strict_sub(u32::try_from(max).unwrap())).unwrap())
+    }
+
+    fn bit(sig: i32) -> u64 {
+        1u64.checked_shl(u32::try_from(sig.strict_sub(1)).unwrap()).unwrap()
+    }
+
+    fn contains(self, sig: i32) -> bool {
+        self.0 & Self::bit(sig) != 0
+    }
+
+    fn insert(&mut self, sig: i32) {
+        self.0 |= Self::bit(sig);
+    }
+
+    fn remove(&mut self, sig: i32) {
+        self.0 &= !Self::bit(sig);
+    }
+
+    fn union(self, other: SigSet) -> SigSet {
+        SigSet(self.0 | other.0)
+    }
+
+    fn difference(self, other: SigSet) -> SigSet {
+        SigSet(self.0 & !other.0)
+    }
+
+    fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The lowest signal in the set.
+    fn first(self) -> Option<i32> {
+        (!self.is_empty()).then(|| i32::try_from(self.0.trailing_zeros()).unwrap().strict_add(1))
+    }
+}
+
+/// What happens when a signal is delivered.
+#[derive(Clone, Copy, Debug)]
+enum SigHandler {
+    /// `SIG_DFL`
+    Default,
+    /// `SIG_IGN`
+    Ignore,
+    /// A function installed by the program.
+    Function(Pointer),
+}
+
+/// The action installed for a signal, like `struct sigaction`.
+#[derive(Clone, Copy, Debug)]
+struct SigAction {
+    handler: SigHandler,
+    /// Additional signals to block while the handler runs.
+    mask: SigSet,
+    flags: i32,
+}
+
+impl Default for SigAction {
+    fn default() -> Self {
+        SigAction { handler: SigHandler::Default, mask: SigSet::EMPTY, flags: 0 }
+    }
+}
+
+/// The effect of the default action of a signal.
+enum DefaultAction {
+    Terminate,
+    Ignore,
+    Stop,
+}
+
+/// The signal state of a single thread.
+#[derive(Debug, Default)]
+struct ThreadSignals {
+    /// The signals this thread blocks.
+    mask: SigSet,
+    /// The signals sent to this thread that have not been delivered yet.
+    pending: SigSet,
+    /// How many signal handlers are currently running on this thread.
+    running_handlers: usize,
+}
+
+/// The signal state of the process.
+#[derive(Debug, Default)]
+pub struct SignalState {
+    /// The actions of all signals that do not have the default action.
+    actions: FxHashMap<i32, SigAction>,
+    /// The signals sent to the process as a whole that have not been delivered yet.
+    pending: SigSet,
+    /// Per-thread state. Threads that are not in here have not touched their signal state yet.
+    threads: FxHashMap<ThreadId, ThreadSignals>,
+    /// Whether there is any pending signal, on the process or on a thread. This lets the scheduler
+    /// skip signal delivery quickly in the common case.
+    any_pending: bool,
+    /// How many signal handlers are currently running, on all threads.
+    running_handlers: usize,
+}
+
+impl VisitProvenance for SignalState {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        for action in self.actions.values() {
+            if let SigHandler::Function(handler) = action.handler {
+                handler.visit_provenance(visit);
+            }
+        }
+    }
+}
+
+impl SignalState {
+    /// Whether some signal is waiting to be delivered. It might be blocked, though.
+    pub fn has_pending(&self) -> bool {
+        self.any_pending
+    }
+
+    /// Whether the given thread is running a signal handler.
+    pub fn in_handler(&self, thread: ThreadId) -> bool {
+        self.running_handlers > 0
+            && self.threads.get(&thread).is_some_and(|signals| signals.running_handlers > 0)
+    }
+
+    /// A new thread inherits the signal mask of the thread that created it.
+    pub fn thread_created(&mut self, parent: ThreadId, child: ThreadId) {
+        let mask = self.mask(parent);
+        if !mask.is_empty() {
+            self.thread_mut(child).mask = mask;
+        }
+    }
+
+    /// Signals that were sent to a thread that terminates are lost.
+    pub fn thread_terminated(&mut self, thread: ThreadId) {
+        self.threads.remove(&thread);
+        self.update_any_pending();
+    }
+
+    fn action(&self, sig: i32) -> SigAction {
+        self.actions.get(&sig).copied().unwrap_or_default()
+    }
+
+    fn mask(&self, thread: ThreadId) -> SigSet {
+        self.threads.get(&thread).map_or(SigSet::EMPTY, |signals| signals.mask)
+    }
+
+    fn thread_mut(&mut self, thread: ThreadId) -> &mut ThreadSignals {
+        self.threads.entry(thread).or_default()
+    }
+
+    fn update_any_pending(&mut self) {
+        self.any_pending = !self.pending.is_empty()
+            || self.threads.values().any(|signals| !signals.pending.is_empty());
+    }
+
+    /// Discard all pending instances of `sig`.
+    fn discard(&mut self, sig: i32) {
+        self.pending.remove(sig);
+        for signals in self.threads.values_mut() {
+            signals.pending.remove(sig);
+        }
+        self.update_any_pending();
+    }
+
+    /// Remove the signal that should be delivered next to `thread` from the pending signals.
+    /// Signals sent to the thread itself come first, then the lowest signal wins.
+    fn take_deliverable(&mut self, thread: ThreadId) -> Option<i32> {
+        let signals = self.threads.get(&thread);
+        let mask = signals.map_or(SigSet::EMPTY, |signals| signals.mask);
+        let thread_pending = signals.map_or(SigSet::EMPTY, |signals| signals.pending);
+        let sig = if let Some(sig) = thread_pending.difference(mask).first() {
+            self.thread_mut(thread).pending.remove(sig);
+            sig
+        } else {
+            let sig = self.pending.difference(mask).first()?;
+            self.pending.remove(sig);
+            sig
+        };
+        self.update_any_pending();
+        Some(sig)
+    }
+}
+
+/// Stored in the frame of a running signal handler, to restore the state of the interrupted code
+/// when the handler returns.
+#[derive(Debug)]
+pub struct SignalFrame {
+    /// The signal mask of the thread before the handler started.
+    mask: SigSet,
+    /// The `siginfo_t` that was passed to the handler, if any.
+    info: Option<Pointer>,
+}
+
+impl VisitProvenance for SignalFrame {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        let SignalFrame { mask: _, info } = self;
+        info.visit_provenance(visit);
+    }
+}
+
+/// The functions that may be called from a signal handler, as far as Miri implements them. This
+/// follows the list in POSIX, plus the platform-specific ways to get at `errno`.
+fn is_async_signal_safe(name: &str) -> bool {
+    // Miri's own extern functions and LLVM intrinsics do not touch any shared state.
+    if name.starts_with("miri_") || name.starts_with("llvm.") {
+        return true;
+    }
+    matches!(
+        name,
+        "__errno_location"
+            | "__error"
+            | "___errno"
+            | "__errno"
+            | "_exit"
+            | "_Exit"
+            | "abort"
+            | "accept"
+            | "access"
+            | "bind"
+            | "clock_gettime"
+            | "close"
+            | "connect"
+            | "dup"
+            | "dup2"
+            | "fcntl"
+            | "fstat"
+            | "fstat64"
+            | "fsync"
+            | "ftruncate"
+            | "ftruncate64"
+            | "getegid"
+            | "geteuid"
+            | "getgid"
+            | "getpeername"
+            | "getpid"
+            | "getppid"
+            | "getsockname"
+            | "getsockopt"
+            | "getuid"
+            | "kill"
+            | "link"
+            | "listen"
+            | "lseek"
+            | "lseek64"
+            | "lstat"
+            | "lstat64"
+            | "memchr"
+            | "memcmp"
+            | "memcpy"
+            | "memmove"
+            | "memset"
+            | "mkdir"
+            | "open"
+            | "open64"
+            | "pipe"
+            | "pthread_kill"
+            | "pthread_self"
+            | "pthread_sigmask"
+            | "raise"
+            | "read"
+            | "readlink"
+            | "recv"
+            | "recvfrom"
+            | "rename"
+            | "rmdir"
+            | "send"
+            | "sendto"
+            | "setsockopt"
+            | "shutdown"
+            | "sigaction"
+            | "sigaddset"
+            | "sigdelset"
+            | "sigemptyset"
+            | "sigfillset"
+            | "sigismember"
+            | "signal"
+            | "sigpending"
+            | "sigprocmask"
+            | "sleep"
+            | "socket"
+            | "socketpair"
+            | "stat"
+            | "stat64"
+            | "strlen"
+            | "symlink"
+            | "time"
+            | "unlink"
+            | "write"
+    )
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The highest signal number we support.
+    fn max_signal(&self) -> i32 {
+        let this = self.eval_context_ref();
+        // macOS only has 31 signals, other targets also have real-time signals.
+        if this.tcx.sess.target.os.as_ref() == "macos" { 31 } else { 64 }
+    }
+
+    fn is_valid_signal(&self, sig: i32) -> bool {
+        (1..=self.max_signal()).contains(&sig)
+    }
+
+    /// Whether the program can change the action for `sig`, and block it.
+    fn is_catchable(&self, sig: i32) -> bool {
+        let this = self.eval_context_ref();
+        this.is_valid_signal(sig)
+            && sig != this.eval_libc_i32("SIGKILL")
+            && sig != this.eval_libc_i32("SIGSTOP")
+    }
+
+    fn signal_name(&self, sig: i32) -> String {
+        let this = self.eval_context_ref();
+        match SIGNAL_NAMES.iter().find(|&&name| this.eval_libc_i32(name) == sig) {
+            Some(name) => format!("`{name}`"),
+            None => format!("{sig}"),
+        }
+    }
+
+    fn default_action(&self, sig: i32) -> DefaultAction {
+        let this = self.eval_context_ref();
+        let is_any_of = |names: &[&str]| names.iter().any(|name| this.eval_libc_i32(name) == sig);
+        if is_any_of(&["SIGCHLD", "SIGCONT", "SIGURG", "SIGWINCH"]) {
+            DefaultAction::Ignore
+        } else if is_any_of(&["SIGSTOP", "SIGTSTP", "SIGTTIN", "SIGTTOU"]) {
+            DefaultAction::Stop
+        } else {
+            DefaultAction::Terminate
+        }
+    }
+
+    /// Whether `sig` is discarded right away when it is generated.
+    fn is_ignored(&self, sig: i32) -> bool {
+        let this = self.eval_context_ref();
+        match this.machine.signals.action(sig).handler {
+            SigHandler::Ignore => true,
+            SigHandler::Default => matches!(this.default_action(sig), DefaultAction::Ignore),
+            SigHandler::Function(_) => false,
+        }
+    }
+
+    /// Make `sig` pending on the given thread, or on the whole process.
+    fn generate_signal(&mut self, sig: i32, thread: Option<ThreadId>) {
+        let this = self.eval_context_mut();
+        if this.is_ignored(sig) {
+            return;
+        }
+        let signals = &mut this.machine.signals;
+        match thread {
+            Some(thread) => signals.thread_mut(thread).pending.insert(sig),
+            None => signals.pending.insert(sig),
+        }
+        signals.any_pending = true;
+    }
+
+    fn set_signal_action(&mut self, sig: i32, action: SigAction) {
+        let this = self.eval_context_mut();
+        this.machine.signals.actions.insert(sig, action);
+        // Pending signals whose action becomes "ignore" are discarded.
+        if this.is_ignored(sig) {
+            this.machine.signals.discard(sig);
+        }
+    }
+
+    fn sig_handler_from_pointer(&self, ptr: Pointer) -> InterpResult<'tcx, SigHandler> {
+        let this = self.eval_context_ref();
+        let addr = ptr.addr().bytes();
+        interp_ok(if addr == this.eval_libc("SIG_DFL").to_target_usize(this)? {
+            SigHandler::Default
+        } else if addr == this.eval_libc("SIG_IGN").to_target_usize(this)? {
+            SigHandler::Ignore
+        } else if ptr.provenance.is_none() {
+            // Handlers are usually passed as a `sighandler_t` integer, so this acts like an
+            // int-to-pointer cast.
+            SigHandler::Function(this.ptr_from_addr_cast(addr)?)
+        } else {
+            SigHandler::Function(ptr)
+        })
+    }
+
+    fn sig_handler_to_scalar(&self, handler: SigHandler) -> Scalar {
+        let this = self.eval_context_ref();
+        match handler {
+            SigHandler::Default => this.eval_libc("SIG_DFL"),
+            SigHandler::Ignore => this.eval_libc("SIG_IGN"),
+            SigHandler::Function(ptr) => Scalar::from_maybe_null_pointer(ptr, this),
+        }
+    }
+
+    /// `sigset_t` is an array of words with one bit per signal, starting with the lowest bit of the
+    /// first word. The words are `unsigned long` on Linux and 32 bits wide everywhere else.
+    fn sigset_word_layout(&self) -> TyAndLayout<'tcx> {
+        let this = self.eval_context_ref();
+        if matches!(&*this.tcx.sess.target.os, "linux" | "android") {
+            this.machine.layouts.usize
+        } else {
+            this.machine.layouts.u32
+        }
+    }
+
+    fn read_sigset(&self, place: &MPlaceTy<'tcx>) -> InterpResult<'tcx, SigSet> {
+        let this = self.eval_context_ref();
+        let word = this.sigset_word_layout();
+        let mut set = 0u64;
+        let mut offset = Size::ZERO;
+        while offset < place.layout.size && offset.bits() < 64 {
+            let bits = this.read_scalar(&place.offset(offset, word, this)?)?.to_bits(word.size)?;
+            let shift = u32::try_from(offset.bits()).unwrap();
+            set |= u64::try_from(bits).unwrap().checked_shl(shift).unwrap();
+            offset += word.size;
+        }
+        interp_ok(SigSet(set))
+    }
+
+    fn write_sigset(&mut self, set: SigSet, place: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let word = this.sigset_word_layout();
+        // Signals we do not support are never in the set.
+        this.write_bytes_ptr(place.ptr(), iter::repeat_n(0, place.layout.size.bytes_usize()))?;
+        let mut offset = Size::ZERO;
+        while offset < place.layout.size && offset.bits() < 64 {
+            let shift = u32::try_from(offset.bits()).unwrap();
+            let bits = set.0.checked_shr(shift).unwrap()
+                & u64::try_from(word.size.unsigned_int_max()).unwrap();
+            this.write_scalar(
+                Scalar::from_uint(bits, word.size),
+                &place.offset(offset, word, this)?,
+            )?;
+            offset += word.size;
+        }
+        interp_ok(())
+    }
+
+    fn read_sigaction(&self, act: &OpTy<'tcx>) -> InterpResult<'tcx, SigAction> {
+        let this = self.eval_context_ref();
+        let act = this.deref_pointer_as(act, this.libc_ty_layout("sigaction"))?;
+        let handler = this.read_pointer(&this.project_field_named(&act, "sa_sigaction")?)?;
+        let mask = this.read_sigset(&this.project_field_named(&act, "sa_mask")?)?;
+        let flags = this.read_scalar(&this.project_field_named(&act, "sa_flags")?)?.to_i32()?;
+
+        // `SA_ONSTACK` is accepted, but handlers always run on the stack of the interrupted thread.
+        // `SA_NOCLDSTOP` and `SA_NOCLDWAIT` do not matter since there are no child processes.
+        let supported_flags = this.eval_libc_i32("SA_SIGINFO")
+            | this.eval_libc_i32("SA_ONSTACK")
+            | this.eval_libc_i32("SA_RESTART")
+            | this.eval_libc_i32("SA_NODEFER")
+            | this.eval_libc_i32("SA_RESETHAND")
+            | this.eval_libc_i32("SA_NOCLDSTOP")
+            | this.eval_libc_i32("SA_NOCLDWAIT");
+        if flags & !supported_flags != 0 {
+            throw_unsup_format!("`sigaction`: flag {:#x} is unsupported", flags & !supported_flags);
+        }
+        interp_ok(SigAction { handler: this.sig_handler_from_pointer(handler)?, mask, flags })
+    }
+
+    fn write_sigaction(&mut self, action: SigAction, oldact: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let oldact = this.deref_pointer_as(oldact, this.libc_ty_layout("sigaction"))?;
+        // Zero everything first, this also takes care of platform-specific fields.
+        this.write_bytes_ptr(oldact.ptr(), iter::repeat_n(0, oldact.layout.size.bytes_usize()))?;
+        let handler = this.sig_handler_to_scalar(action.handler);
+        this.write_scalar(handler, &this.project_field_named(&oldact, "sa_sigaction")?)?;
+        this.write_sigset(action.mask, &this.project_field_named(&oldact, "sa_mask")?)?;
+        this.write_int_fields_named(&[("sa_flags", action.flags.into())], &oldact)?;
+        interp_ok(())
+    }
+
+    /// Implements `pthread_sigmask` and `sigprocmask`. Returns `false` if `how` is invalid.
+    fn change_signal_mask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+
+        let how = this.read_scalar(how)?.to_i32()?;
+        let sigset_layout = this.libc_ty_layout("sigset_t");
+        let thread = this.active_thread();
+        let old_mask = this.machine.signals.mask(thread);
+
+        // Read the new set before writing the old one, they might be the same.
+        let new_mask = if this.ptr_is_null(this.read_pointer(set)?)? {
+            None
+        } else {
+            let set = this.read_sigset(&this.deref_pointer_as(set, sigset_layout)?)?;
+            Some(if how == this.eval_libc_i32("SIG_BLOCK") {
+                old_mask.union(set)
+            } else if how == this.eval_libc_i32("SIG_UNBLOCK") {
+                old_mask.difference(set)
+            } else if how == this.eval_libc_i32("SIG_SETMASK") {
+                set
+            } else {
+                return interp_ok(false);
+            })
+        };
+        if !this.ptr_is_null(this.read_pointer(oldset)?)? {
+            this.write_sigset(old_mask, &this.deref_pointer_as(oldset, sigset_layout)?)?;
+        }
+        if let Some(mut mask) = new_mask {
+            // `SIGKILL` and `SIGSTOP` cannot be blocked, attempts to do so are silently ignored.
+            mask.remove(this.eval_libc_i32("SIGKILL"));
+            mask.remove(this.eval_libc_i32("SIGSTOP"));
+            this.machine.signals.thread_mut(thread).mask = mask;
+        }
+        interp_ok(true)
+    }
+
+    /// Reads a signal number that may be 0, which is used to check that the target of a signal
+    /// exists without sending anything.
+    fn read_signal_or_zero(&self, sig: &OpTy<'tcx>) -> InterpResult<'tcx, Option<i32>> {
+        let this = self.eval_context_ref();
+        let sig = this.read_scalar(sig)?.to_i32()?;
+        interp_ok((sig == 0 || this.is_valid_signal(sig)).then_some(sig))
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn sigaction(
+        &mut self,
+        sig: &OpTy<'tcx>,
+        act: &OpTy<'tcx>,
+        oldact: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let sig = this.read_scalar(sig)?.to_i32()?;
+        let changes_action = !this.ptr_is_null(this.read_pointer(act)?)?;
+        if !this.is_valid_signal(sig) || (changes_action && !this.is_catchable(sig)) {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        let old_action = this.machine.signals.action(sig);
+        if changes_action {
+            let action = this.read_sigaction(act)?;
+            this.set_signal_action(sig, action);
+        }
+        if !this.ptr_is_null(this.read_pointer(oldact)?)? {
+            this.write_sigaction(old_action, oldact)?;
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn signal(&mut self, sig: &OpTy<'tcx>, handler: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let sig = this.read_scalar(sig)?.to_i32()?;
+        let handler = this.read_pointer(handler)?;
+        if !this.is_catchable(sig) {
+            this.set_last_error(LibcError("EINVAL"))?;
+            return interp_ok(this.eval_libc("SIG_ERR"));
+        }
+
+        let old_action = this.machine.signals.action(sig);
+        // Like glibc and the BSDs, we implement the BSD semantics: the handler stays installed
+        // while it runs and interrupted calls are restarted.
+        let action = SigAction {
+            handler: this.sig_handler_from_pointer(handler)?,
+            mask: SigSet::EMPTY,
+            flags: this.eval_libc_i32("SA_RESTART"),
+        };
+        this.set_signal_action(sig, action);
+        interp_ok(this.sig_handler_to_scalar(old_action.handler))
+    }
+
+    fn sigprocmask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        // We treat this like `pthread_sigmask`, which is also what the common implementations do
+        // in multi-threaded programs.
+        if this.change_signal_mask(how, set, oldset)? {
+            interp_ok(Scalar::from_i32(0))
+        } else {
+            this.set_last_error_and_return_i32(LibcError("EINVAL"))
+        }
+    }
+
+    fn pthread_sigmask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        if this.change_signal_mask(how, set, oldset)? {
+            interp_ok(Scalar::from_i32(0))
+        } else {
+            interp_ok(this.eval_libc("EINVAL"))
+        }
+    }
+
+    fn sigpending(&mut self, set: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let signals = &this.machine.signals;
+        let thread_pending =
+            signals.threads.get(&this.active_thread()).map_or(SigSet::EMPTY, |s| s.pending);
+        let pending = thread_pending.union(signals.pending);
+        this.write_sigset(pending, &set)?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Implements `sigemptyset` and `sigfillset`.
+    fn sigset_fill(&mut self, set: &OpTy<'tcx>, full: bool) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let value = if full { SigSet::full(this.max_signal()) } else { SigSet::EMPTY };
+        this.write_sigset(value, &set)?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Implements `sigaddset` and `sigdelset`.
+    fn sigset_update(
+        &mut self,
+        set: &OpTy<'tcx>,
+        sig: &OpTy<'tcx>,
+        insert: bool,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let sig = this.read_scalar(sig)?.to_i32()?;
+        if !this.is_valid_signal(sig) {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let mut value = this.read_sigset(&set)?;
+        if insert {
+            value.insert(sig);
+        } else {
+            value.remove(sig);
+        }
+        this.write_sigset(value, &set)?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn sigismember(&mut self, set: &OpTy<'tcx>, sig: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let sig = this.read_scalar(sig)?.to_i32()?;
+        if !this.is_valid_signal(sig) {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        interp_ok(Scalar::from_i32(this.read_sigset(&set)?.contains(sig).into()))
+    }
+
+    fn kill(&mut self, pid: &OpTy<'tcx>, sig: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let pid = this.read_scalar(pid)?.to_i32()?;
+        let Some(sig) = this.read_signal_or_zero(sig)? else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+        if let Some(result) = this.kill_child_process(pid, sig)? {
+            return interp_ok(result);
+        }
+        // `0` is our process group, and as far as we are concerned we are alone in there.
+        if pid != 0 && u32::try_from(pid) != Ok(this.get_pid()) {
+            throw_unsup_format!("`kill`: sending signals to other processes is not supported");
+        }
+        if sig != 0 {
+            this.generate_signal(sig, None);
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn raise(&mut self, sig: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let sig = this.read_scalar(sig)?.to_i32()?;
+        if !this.is_valid_signal(sig) {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        // If the signal is not blocked, it is delivered before the next step of this thread, so
+        // the handler runs before `raise` returns as far as the program can tell.
+        let thread = this.active_thread();
+        this.generate_signal(sig, Some(thread));
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn pthread_kill(
+        &mut self,
+        thread: &OpTy<'tcx>,
+        sig: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let thread = this.read_scalar(thread)?.to_int(this.libc_ty_layout("pthread_t").size)?;
+        let Some(sig) = this.read_signal_or_zero(sig)? else {
+            return interp_ok(this.eval_libc("EINVAL"));
+        };
+        let thread = match ThreadId::try_from(thread) {
+            Ok(thread)
+                if thread.to_u32() < u32::try_from(this.get_total_thread_count()).unwrap()
+                    && !this.machine.threads.has_terminated(thread) =>
+                thread,
+            _ => return interp_ok(this.eval_libc("ESRCH")),
+        };
+        if sig != 0 {
+            this.generate_signal(sig, Some(thread));
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Delivers a pending signal to the active thread, if there is one that the thread does not
+    /// block. This is called by the scheduler before each step.
+    fn deliver_pending_signal(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        // A signal can only interrupt a thread that is executing some function; the handler then
+        // runs before the next statement of that function.
+        if !this.active_thread_stack().last().is_some_and(|frame| frame.current_loc().is_left()) {
+            return interp_ok(());
+        }
+        let thread = this.active_thread();
+        let Some(sig) = this.machine.signals.take_deliverable(thread) else {
+            return interp_ok(());
+        };
+
+        let action = this.machine.signals.action(sig);
+        let handler = match action.handler {
+            SigHandler::Ignore => return interp_ok(()),
+            SigHandler::Default =>
+                match this.default_action(sig) {
+                    DefaultAction::Ignore => return interp_ok(()),
+                    DefaultAction::Terminate =>
+                        throw_machine_stop!(TerminationInfo::Abort(format!(
+                            "the program was terminated by signal {}",
+                            this.signal_name(sig)
+                        ))),
+                    DefaultAction::Stop =>
+                        throw_unsup_format!(
+                            "stopping the program with signal {} is not supported",
+                            this.signal_name(sig)
+                        ),
+                },
+            SigHandler::Function(handler) => handler,
+        };
+        if action.flags & this.eval_libc_i32("SA_RESETHAND") != 0 {
+            this.machine.signals.actions.remove(&sig);
+        }
+
+        // While the handler runs, the signal itself and the signals in the mask of the action are
+        // blocked in addition to the signals that were blocked before.
+        let old_mask = this.machine.signals.mask(thread);
+        let mut mask = old_mask.union(action.mask);
+        if action.flags & this.eval_libc_i32("SA_NODEFER") == 0 {
+            mask.insert(sig);
+        }
+        let signals = this.machine.signals.thread_mut(thread);
+        signals.mask = mask;
+        signals.running_handlers = signals.running_handlers.strict_add(1);
+        this.machine.signals.running_handlers = this.machine.signals.running_handlers.strict_add(1);
+
+        let mut args = vec![ImmTy::from_int(sig, this.machine.layouts.i32)];
+        let info = if action.flags & this.eval_libc_i32("SA_SIGINFO") != 0 {
+            // Only `si_signo` is filled in. We do not have a `ucontext_t` to offer.
+            let info =
+                this.allocate(this.libc_ty_layout("siginfo_t"), MiriMemoryKind::Machine.into())?;
+            this.write_bytes_ptr(info.ptr(), iter::repeat_n(0, info.layout.size.bytes_usize()))?;
+            this.write_int_fields_named(&[("si_signo", sig.into())], &info)?;
+            args.push(ImmTy::from_scalar(
+                Scalar::from_maybe_null_pointer(info.ptr(), this),
+                this.machine.layouts.mut_raw_ptr,
+            ));
+            args.push(ImmTy::from_scalar(Scalar::null_ptr(this), this.machine.layouts.mut_raw_ptr));
+            Some(info.ptr())
+        } else {
+            None
+        };
+
+        let instance = this.get_ptr_fn(handler)?.as_instance()?;
+        this.call_function(
+            instance,
+            Abi::C { unwind: false },
+            &args,
+            None,
+            // `return_from_signal_handler` takes care of getting back to the interrupted code.
+            StackPopCleanup::Goto { ret: None, unwind: mir::UnwindAction::Unreachable },
+        )?;
+        this.frame_mut().extra.signal_frame = Some(SignalFrame { mask: old_mask, info });
+        interp_ok(())
+    }
+
+    /// Called when the frame of a signal handler returns.
+    fn return_from_signal_handler(
+        &mut self,
+        signal_frame: SignalFrame,
+    ) -> InterpResult<'tcx, ReturnAction> {
+        let this = self.eval_context_mut();
+
+        let thread = this.active_thread();
+        let signals = this.machine.signals.thread_mut(thread);
+        signals.mask = signal_frame.mask;
+        signals.running_handlers = signals.running_handlers.strict_sub(1);
+        this.machine.signals.running_handlers = this.machine.signals.running_handlers.strict_sub(1);
+        if let Some(info) = signal_frame.info {
+            this.deallocate_ptr(info, None, MiriMemoryKind::Machine.into())?;
+        }
+        // The interrupted frame continues where it left off.
+        interp_ok(ReturnAction::NoJump)
+    }
+
+    /// Called for every foreign function that is invoked while a signal handler is running.
+    fn check_async_signal_safe(&self, link_name: Symbol) -> InterpResult<'tcx> {
+        if !is_async_signal_safe(link_name.as_str()) {
+            throw_ub_format!(
+                "calling `{link_name}` in a signal handler, but it is not async-signal-safe"
+            );
+        }
+        interp_ok(())
+    }
+}
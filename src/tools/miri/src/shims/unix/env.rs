@@ -212,14 +212,18 @@ fn getcwd(&mut self, buf_op: &OpTy<'tcx>, size_op: &OpTy<'tcx>) -> InterpResult<
         let buf = this.read_pointer(buf_op)?;
         let size = this.read_target_usize(size_op)?;
 
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`getcwd`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Pointer::null());
         }
 
+        let cwd = match &this.machine.vfs {
+            Some(vfs) => Ok(vfs.cwd().to_path_buf()),
+            None => env::current_dir(),
+        };
         // If we cannot get the current directory, we return null
-        match env::current_dir() {
+        match cwd {
             Ok(cwd) => {
                 if this.write_path_to_c_str(&cwd, buf, size)?.0 {
                     return interp_ok(buf);
@@ -238,11 +242,18 @@ fn chdir(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
 
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`chdir`", reject_with)?;
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
+        if let Some(vfs) = &mut this.machine.vfs {
+            return match vfs.chdir(&path) {
+                Ok(()) => interp_ok(Scalar::from_i32(0)),
+                Err(e) => this.set_last_error_and_return_i32(e),
+            };
+        }
+
         let result = env::set_current_dir(path).map(|()| 0);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
@@ -248,6 +248,41 @@ fn write<'tcx>(
     }
 }
 
+/// The `/dev/null` device, which can be opened even with isolation enabled.
+#[derive(Debug)]
+pub struct DevNull;
+
+impl FileDescription for DevNull {
+    fn name(&self) -> &'static str {
+        "/dev/null"
+    }
+
+    fn read<'tcx>(
+        &self,
+        _self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        _len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        // Reading always reaches the end of the file immediately.
+        ecx.return_read_success(ptr, &[], 0, dest)
+    }
+
+    fn write<'tcx>(
+        &self,
+        _self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        _ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        ecx.return_write_success(len, dest)
+    }
+}
+
 /// Structure contains both the file description and its unique identifier.
 #[derive(Clone, Debug)]
 pub struct FileDescWithId<T: FileDescription + ?Sized> {
@@ -528,7 +563,7 @@ fn fcntl(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
                 && cmd == this.eval_libc_i32("F_FULLFSYNC") =>
             {
                 // Reject if isolation is enabled.
-                if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
                     this.reject_in_isolation("`fcntl`", reject_with)?;
                     return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
                 }
@@ -541,6 +576,109 @@ fn fcntl(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
         }
     }
 
+    fn ioctl(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let [fd_num, request] = check_min_arg_count("ioctl", args)?;
+
+        let fd_num = this.read_scalar(fd_num)?.to_i32()?;
+        let request = this.read_scalar(request)?;
+        let request = request.to_uint(request.size())?;
+        let libc_request = |name| {
+            let value = this.eval_libc(name);
+            value.to_uint(value.size())
+        };
+
+        if request == libc_request("FIOCLEX")? || request == libc_request("FIONCLEX")? {
+            // As usual we ignore CLOEXEC, but we still need to check that the file is open.
+            interp_ok(Scalar::from_i32(if this.machine.fds.is_fd_num(fd_num) {
+                0
+            } else {
+                this.fd_not_found()?
+            }))
+        } else if request == libc_request("FIONBIO")? {
+            let [_, _, value] = check_min_arg_count("ioctl(fd, FIONBIO, ...)", args)?;
+            if let Some(result) = this.anonsocket_set_nonblocking(fd_num, value)? {
+                return interp_ok(result);
+            }
+            this.socket_set_nonblocking(fd_num, value)
+        } else {
+            throw_unsup_format!("ioctl: unsupported request {request:#x}");
+        }
+    }
+
+    fn poll(
+        &mut self,
+        fds_op: &OpTy<'tcx>,
+        nfds_op: &OpTy<'tcx>,
+        timeout_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let nfds = this.read_scalar(nfds_op)?;
+        let nfds = u64::try_from(nfds.to_uint(nfds.size())?).unwrap();
+        let timeout = this.read_scalar(timeout_op)?.to_i32()?;
+        let fds = this.deref_pointer_as(fds_op, this.libc_array_ty_layout("pollfd", nfds))?;
+
+        let pollin = this.eval_libc("POLLIN").to_i16()?;
+        let pollout = this.eval_libc("POLLOUT").to_i16()?;
+        let pollhup = this.eval_libc("POLLHUP").to_i16()?;
+        let pollerr = this.eval_libc("POLLERR").to_i16()?;
+        let pollnval = this.eval_libc("POLLNVAL").to_i16()?;
+
+        let mut ran_child_processes = false;
+        loop {
+            let mut ready = 0i32;
+            for i in 0..nfds {
+                let pollfd = this.project_index(&fds, i)?;
+                let fd_num = this.project_field_named(&pollfd, "fd")?;
+                let fd_num = this.read_scalar(&fd_num)?.to_i32()?;
+                let events = this.project_field_named(&pollfd, "events")?;
+                let events = this.read_scalar(&events)?.to_i16()?;
+                // Negative file descriptors are ignored.
+                let revents = if fd_num < 0 {
+                    0
+                } else if let Some(fd) = this.machine.fds.get(fd_num) {
+                    let ready_events = fd.get_epoll_ready_events()?;
+                    let mut revents = 0;
+                    if ready_events.epollin {
+                        revents |= pollin;
+                    }
+                    if ready_events.epollout {
+                        revents |= pollout;
+                    }
+                    // Hangups and errors are reported even if they were not requested.
+                    revents &= events;
+                    if ready_events.epollhup {
+                        revents |= pollhup;
+                    }
+                    if ready_events.epollerr {
+                        revents |= pollerr;
+                    }
+                    revents
+                } else {
+                    pollnval
+                };
+                let revents_place = this.project_field_named(&pollfd, "revents")?;
+                this.write_scalar(Scalar::from_i16(revents), &revents_place)?;
+                if revents != 0 {
+                    ready = ready.strict_add(1);
+                }
+            }
+            if ready > 0 || timeout == 0 {
+                return interp_ok(Scalar::from_i32(ready));
+            }
+            if ran_child_processes {
+                throw_unsup_format!(
+                    "`poll`: blocking until a file descriptor is ready is not supported"
+                );
+            }
+            // A child process that has not run yet might make one of the file descriptors ready.
+            this.run_child_processes()?;
+            ran_child_processes = true;
+        }
+    }
+
     fn close(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let this = self.eval_context_mut();
 
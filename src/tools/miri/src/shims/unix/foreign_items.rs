@@ -147,6 +147,13 @@ fn emulate_foreign_item_inner(
                 let result = this.fcntl(args)?;
                 this.write_scalar(result, dest)?;
             }
+            "ioctl" => {
+                // `ioctl` is variadic. The argument count is checked based on the request
+                // in `this.ioctl()`, so we do not use `check_shim` here.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                let result = this.ioctl(args)?;
+                this.write_scalar(result, dest)?;
+            }
             "dup" => {
                 let [old_fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let old_fd = this.read_scalar(old_fd)?.to_i32()?;
@@ -167,6 +174,11 @@ fn emulate_foreign_item_inner(
                 let result = this.flock(fd, op)?;
                 this.write_scalar(result, dest)?;
             }
+            "poll" => {
+                let [fds, nfds, timeout] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.poll(fds, nfds, timeout)?;
+                this.write_scalar(result, dest)?;
+            }
 
             // File and file system access
             "open" | "open64" => {
@@ -305,6 +317,216 @@ fn emulate_foreign_item_inner(
                 this.write_scalar(result, dest)?;
             }
 
+            // Internet sockets
+            "socket" => {
+                let [domain, type_, protocol] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.socket(domain, type_, protocol)?;
+                this.write_scalar(result, dest)?;
+            }
+            "bind" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.bind(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "listen" => {
+                let [socket, backlog] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.listen(socket, backlog)?;
+                this.write_scalar(result, dest)?;
+            }
+            "connect" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.connect(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "accept" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(socket, address, address_len, /*flags*/ None, dest)?;
+            }
+            "accept4" => {
+                // Currently this function does not exist on all Unixes, e.g. on macOS.
+                if !matches!(&*this.tcx.sess.target.os, "linux" | "android" | "freebsd") {
+                    throw_unsup_format!(
+                        "`accept4` is not supported on {}",
+                        this.tcx.sess.target.os
+                    );
+                }
+                let [socket, address, address_len, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(socket, address, address_len, Some(flags), dest)?;
+            }
+            "send" => {
+                let [socket, buf, len, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.socket_transfer(/*is_send*/ true, socket, buf, len, flags, None, dest)?;
+            }
+            "sendto" => {
+                let [socket, buf, len, flags, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let address = Some((address, address_len));
+                this.socket_transfer(/*is_send*/ true, socket, buf, len, flags, address, dest)?;
+            }
+            "recv" => {
+                let [socket, buf, len, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.socket_transfer(/*is_send*/ false, socket, buf, len, flags, None, dest)?;
+            }
+            "recvfrom" => {
+                let [socket, buf, len, flags, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let address = Some((address, address_len));
+                this.socket_transfer(/*is_send*/ false, socket, buf, len, flags, address, dest)?;
+            }
+            "shutdown" => {
+                let [socket, how] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.shutdown(socket, how)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getsockname" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockname(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getpeername" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getpeername(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "setsockopt" => {
+                let [socket, level, option_name, option_value, option_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result =
+                    this.setsockopt(socket, level, option_name, option_value, option_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getsockopt" => {
+                let [socket, level, option_name, option_value, option_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result =
+                    this.getsockopt(socket, level, option_name, option_value, option_len)?;
+                this.write_scalar(result, dest)?;
+            }
+
+            // Signals
+            "sigaction" => {
+                let [sig, act, oldact] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigaction(sig, act, oldact)?;
+                this.write_scalar(result, dest)?;
+            }
+            "signal" => {
+                let [sig, handler] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.signal(sig, handler)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigprocmask" => {
+                let [how, set, oldset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigprocmask(how, set, oldset)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_sigmask" => {
+                let [how, set, oldset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_sigmask(how, set, oldset)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigpending" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigpending(set)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigemptyset" | "sigfillset" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigset_fill(set, link_name.as_str() == "sigfillset")?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigaddset" | "sigdelset" => {
+                let [set, sig] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigset_update(set, sig, link_name.as_str() == "sigaddset")?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigismember" => {
+                let [set, sig] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigismember(set, sig)?;
+                this.write_scalar(result, dest)?;
+            }
+            "kill" => {
+                let [pid, sig] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.kill(pid, sig)?;
+                this.write_scalar(result, dest)?;
+            }
+            "raise" => {
+                let [sig] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.raise(sig)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_kill" => {
+                let [thread, sig] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_kill(thread, sig)?;
+                this.write_scalar(result, dest)?;
+            }
+
+            // Child processes
+            "posix_spawn_file_actions_init" | "posix_spawn_file_actions_destroy" => {
+                let [file_actions] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_init(file_actions)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawn_file_actions_adddup2" => {
+                let [file_actions, fd, new_fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_adddup2(file_actions, fd, new_fd)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawn_file_actions_addclose" => {
+                let [file_actions, fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_addclose(file_actions, fd)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawnattr_init" | "posix_spawnattr_destroy" => {
+                let [attr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                // We do not store any attributes.
+                this.read_pointer(attr)?;
+                this.write_null(dest)?;
+            }
+            "posix_spawnattr_setflags" => {
+                let [attr, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawnattr_setflags(attr, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawnattr_setsigdefault" => {
+                let [attr, sigdefault] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                // A child process starts out with the default action for all signals anyway.
+                this.read_pointer(attr)?;
+                this.read_pointer(sigdefault)?;
+                this.write_null(dest)?;
+            }
+            "posix_spawn" | "posix_spawnp" => {
+                let [pid, path, file_actions, attr, argv, envp] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn(pid, path, file_actions, attr, argv, envp)?;
+                this.write_scalar(result, dest)?;
+            }
+            "waitpid" => {
+                let [pid, status, options] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.waitpid(pid, status, options)?;
+                this.write_scalar(result, dest)?;
+            }
+            "fork" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                throw_unsup_format!(
+                    "`fork` is not supported; only child processes spawned with `posix_spawn` can be run"
+                );
+            }
+
             // Time
             "gettimeofday" => {
                 let [tv, tz] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -341,6 +563,11 @@ fn emulate_foreign_item_inner(
                 let result = this.munmap(addr, length)?;
                 this.write_scalar(result, dest)?;
             }
+            "msync" => {
+                let [addr, length, flags] = this.check_shim(abi, Abi::C {unwind: false}, link_name, args)?;
+                let result = this.msync(addr, length, flags)?;
+                this.write_scalar(result, dest)?;
+            }
 
             "reallocarray" => {
                 // Currently this function does not exist on all Unixes, e.g. on macOS.
@@ -889,14 +1116,12 @@ fn emulate_foreign_item_inner(
                 this.write_null(dest)?;
             }
 
-            | "signal"
-            | "sigaltstack"
+            "sigaltstack"
             if this.frame_in_std() => {
                 let [_, _] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 this.write_null(dest)?;
             }
-            | "sigaction"
-            | "mprotect"
+            "mprotect"
             if this.frame_in_std() => {
                 let [_, _, _] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 this.write_null(dest)?;
@@ -1,6 +1,7 @@
 //! File and file system access
 
 use std::borrow::Cow;
+use std::ffi::OsString;
 use std::fs::{
     DirBuilder, File, FileType, OpenOptions, ReadDir, read_dir, remove_dir, remove_file, rename,
 };
@@ -15,14 +16,15 @@
 use self::shims::time::system_time_to_duration;
 use crate::helpers::check_min_arg_count;
 use crate::shims::os_str::bytes_to_os_str;
-use crate::shims::unix::fd::FileDescriptionRef;
+use crate::shims::unix::fd::{DevNull, FileDescriptionRef};
+use crate::shims::unix::vfs::{VfsDirEntry, VfsFileHandle, VfsMetadata, VfsOpenOptions};
 use crate::shims::unix::*;
 use crate::*;
 
 #[derive(Debug)]
-struct FileHandle {
-    file: File,
-    writable: bool,
+pub struct FileHandle {
+    pub(super) file: File,
+    pub(super) writable: bool,
 }
 
 impl FileDescription for FileHandle {
@@ -305,6 +307,56 @@ fn macos_stat_write_buf(
         interp_ok(0)
     }
 
+    /// Turns the result of an operation on the virtual file system into the return value of a
+    /// shim that returns 0 on success and -1 on error.
+    fn vfs_return_i32(&mut self, result: Result<(), IoError>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        match result {
+            Ok(()) => interp_ok(Scalar::from_i32(0)),
+            Err(e) => this.set_last_error_and_return_i32(e),
+        }
+    }
+
+    /// Advances the directory stream `dirp` and returns the name, inode number and `d_type` of
+    /// its next entry, or `None` at the end of the stream.
+    fn next_dir_entry(
+        &mut self,
+        dirp: u64,
+        shim_name: &str,
+    ) -> InterpResult<'tcx, Option<std::io::Result<(OsString, u64, i32)>>> {
+        let this = self.eval_context_mut();
+        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
+            err_unsup_format!("the DIR pointer passed to {shim_name} did not come from opendir")
+        })?;
+        let entry = match &mut open_dir.stream {
+            DirStream::Host(read_dir) =>
+                match read_dir.next() {
+                    None => return interp_ok(None),
+                    Some(Err(e)) => return interp_ok(Some(Err(e))),
+                    Some(Ok(dir_entry)) => {
+                        // If the host is a Unix system, fill in the inode number with its real
+                        // value. If not, use 0 as a fallback value.
+                        #[cfg(unix)]
+                        let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
+                        #[cfg(not(unix))]
+                        let ino = 0u64;
+
+                        let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
+                        (dir_entry.file_name(), ino, file_type)
+                    }
+                },
+            DirStream::Virtual(entries) =>
+                match entries.next() {
+                    None => return interp_ok(None),
+                    Some(VfsDirEntry { name, is_dir, ino }) => {
+                        let file_type = if is_dir { "DT_DIR" } else { "DT_REG" };
+                        (name, ino, this.eval_libc(file_type).to_u8()?.into())
+                    }
+                },
+        };
+        interp_ok(Some(Ok(entry)))
+    }
+
     fn file_type_to_d_type(
         &mut self,
         file_type: std::io::Result<FileType>,
@@ -350,19 +402,27 @@ fn file_type_to_d_type(
     }
 }
 
+/// The entries of an open directory.
+#[derive(Debug)]
+enum DirStream {
+    /// The directory reader on the host.
+    Host(ReadDir),
+    /// The entries of a directory of the virtual file system, read when it was opened.
+    Virtual(std::vec::IntoIter<VfsDirEntry>),
+}
+
 /// An open directory, tracked by DirHandler.
 #[derive(Debug)]
 struct OpenDir {
-    /// The directory reader on the host.
-    read_dir: ReadDir,
+    stream: DirStream,
     /// The most recent entry returned by readdir().
     /// Will be freed by the next call.
     entry: Option<Pointer>,
 }
 
 impl OpenDir {
-    fn new(read_dir: ReadDir) -> Self {
-        Self { read_dir, entry: None }
+    fn new(stream: DirStream) -> Self {
+        Self { stream, entry: None }
     }
 }
 
@@ -387,10 +447,10 @@ pub struct DirTable {
 
 impl DirTable {
     #[allow(clippy::arithmetic_side_effects)]
-    fn insert_new(&mut self, read_dir: ReadDir) -> u64 {
+    fn insert_new(&mut self, stream: DirStream) -> u64 {
         let id = self.next_id;
         self.next_id += 1;
-        self.streams.try_insert(id, OpenDir::new(read_dir)).unwrap();
+        self.streams.try_insert(id, OpenDir::new(stream)).unwrap();
         id
     }
 }
@@ -443,6 +503,8 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
         let flag = this.read_scalar(flag)?.to_i32()?;
 
         let mut options = OpenOptions::new();
+        // The same options, for opening a file of the virtual file system.
+        let mut vfs_options = VfsOpenOptions::default();
 
         let o_rdonly = this.eval_libc_i32("O_RDONLY");
         let o_wronly = this.eval_libc_i32("O_WRONLY");
@@ -461,10 +523,14 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
         if access_mode == o_rdonly {
             writable = false;
             options.read(true);
+            vfs_options.read = true;
         } else if access_mode == o_wronly {
             options.write(true);
+            vfs_options.write = true;
         } else if access_mode == o_rdwr {
             options.read(true).write(true);
+            vfs_options.read = true;
+            vfs_options.write = true;
         } else {
             throw_unsup_format!("unsupported access mode {:#x}", access_mode);
         }
@@ -476,11 +542,13 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
         let o_append = this.eval_libc_i32("O_APPEND");
         if flag & o_append == o_append {
             options.append(true);
+            vfs_options.append = true;
             mirror |= o_append;
         }
         let o_trunc = this.eval_libc_i32("O_TRUNC");
         if flag & o_trunc == o_trunc {
             options.truncate(true);
+            vfs_options.truncate = true;
             mirror |= o_trunc;
         }
         let o_creat = this.eval_libc_i32("O_CREAT");
@@ -514,8 +582,10 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
             if flag & o_excl == o_excl {
                 mirror |= o_excl;
                 options.create_new(true);
+                vfs_options.create_new = true;
             } else {
                 options.create(true);
+                vfs_options.create = true;
             }
         }
         let o_cloexec = this.eval_libc_i32("O_CLOEXEC");
@@ -562,13 +632,26 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
             throw_unsup_format!("unsupported flags {:#x}", flag & !mirror);
         }
 
+        // `/dev/null` does not reveal anything about the host, so it is available in isolation.
+        if !this.machine.communicate() && path == Path::new("/dev/null") {
+            return interp_ok(Scalar::from_i32(this.machine.fds.insert_new(DevNull)));
+        }
+
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`open`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Scalar::from_i32(-1));
         }
 
+        if let Some(vfs) = &mut this.machine.vfs {
+            // The virtual file system has no symbolic links, so `O_NOFOLLOW` has no effect.
+            return match vfs.open(&path, &vfs_options) {
+                Ok(handle) => interp_ok(Scalar::from_i32(this.machine.fds.insert_new(handle))),
+                Err(e) => this.set_last_error_and_return_i32(e),
+            };
+        }
+
         let fd = options
             .open(path)
             .map(|file| this.machine.fds.insert_new(FileHandle { file, writable }));
@@ -616,12 +699,17 @@ fn unlink(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`unlink`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Scalar::from_i32(-1));
         }
 
+        if let Some(vfs) = &mut this.machine.vfs {
+            let result = vfs.unlink(&path);
+            return this.vfs_return_i32(result);
+        }
+
         let result = remove_file(path).map(|_| 0);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
@@ -647,12 +735,16 @@ fn create_link(src: &Path, dst: &Path) -> std::io::Result<()> {
         let linkpath = this.read_path_from_c_str(this.read_pointer(linkpath_op)?)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`symlink`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Scalar::from_i32(-1));
         }
 
+        if this.machine.vfs.is_some() {
+            throw_unsup_format!("symbolic links are not supported by the virtual file system");
+        }
+
         let result = create_link(&target, &linkpath).map(|_| 0);
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
@@ -672,7 +764,7 @@ fn macos_fbsd_stat(
         let path = this.read_path_from_c_str(path_scalar)?.into_owned();
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`stat`", reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
@@ -704,7 +796,7 @@ fn macos_fbsd_lstat(
         let path = this.read_path_from_c_str(path_scalar)?.into_owned();
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`lstat`", reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
@@ -733,7 +825,7 @@ fn macos_fbsd_fstat(
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`fstat`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
@@ -796,7 +888,7 @@ fn linux_statx(
         }
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`statx`", reject_with)?;
             let ecode = if path.is_absolute() || dirfd == this.eval_libc_i32("AT_FDCWD") {
                 // since `path` is provided, either absolute or
@@ -948,12 +1040,17 @@ fn rename(
         let newpath = this.read_path_from_c_str(newpath_ptr)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`rename`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Scalar::from_i32(-1));
         }
 
+        if let Some(vfs) = &mut this.machine.vfs {
+            let result = vfs.rename(&oldpath, &newpath);
+            return this.vfs_return_i32(result);
+        }
+
         let result = rename(oldpath, newpath).map(|_| 0);
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
@@ -972,12 +1069,18 @@ fn mkdir(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`mkdir`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Scalar::from_i32(-1));
         }
 
+        if let Some(vfs) = &mut this.machine.vfs {
+            // The virtual file system does not track permissions, so the mode is ignored.
+            let result = vfs.mkdir(&path);
+            return this.vfs_return_i32(result);
+        }
+
         #[cfg_attr(not(unix), allow(unused_mut))]
         let mut builder = DirBuilder::new();
 
@@ -1000,12 +1103,17 @@ fn rmdir(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`rmdir`", reject_with)?;
             this.set_last_error(ErrorKind::PermissionDenied)?;
             return interp_ok(Scalar::from_i32(-1));
         }
 
+        if let Some(vfs) = &mut this.machine.vfs {
+            let result = vfs.rmdir(&path);
+            return this.vfs_return_i32(result);
+        }
+
         let result = remove_dir(path).map(|_| 0i32);
 
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
@@ -1017,18 +1125,21 @@ fn opendir(&mut self, name_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let name = this.read_path_from_c_str(this.read_pointer(name_op)?)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`opendir`", reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
             return interp_ok(Scalar::null_ptr(this));
         }
 
-        let result = read_dir(name);
+        let result = match &this.machine.vfs {
+            Some(vfs) => vfs.read_dir(&name).map(|entries| DirStream::Virtual(entries.into_iter())),
+            None => read_dir(name).map(DirStream::Host).map_err(IoError::HostError),
+        };
 
         match result {
-            Ok(dir_iter) => {
-                let id = this.machine.dirs.insert_new(dir_iter);
+            Ok(stream) => {
+                let id = this.machine.dirs.insert_new(stream);
 
                 // The libc API for opendir says that this method returns a pointer to an opaque
                 // structure, but we are returning an ID number. Thus, pass it as a scalar of
@@ -1050,19 +1161,15 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`readdir`", reject_with)?;
             let eacc = this.eval_libc("EBADF");
             this.set_last_error(eacc)?;
             return interp_ok(Scalar::null_ptr(this));
         }
 
-        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
-            err_unsup_format!("the DIR pointer passed to readdir64 did not come from opendir")
-        })?;
-
-        let entry = match open_dir.read_dir.next() {
-            Some(Ok(dir_entry)) => {
+        let entry = match this.next_dir_entry(dirp, "readdir64")? {
+            Some(Ok((mut name, ino, file_type))) => {
                 // Write the directory entry into a newly allocated buffer.
                 // The name is written with write_bytes, while the rest of the
                 // dirent64 struct is written using write_int_fields.
@@ -1076,7 +1183,7 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
                 //     pub d_name: [c_char; 256],
                 // }
 
-                let mut name = dir_entry.file_name(); // not a Path as there are no separators!
+                // `name` is not a Path as there are no separators!
                 name.push("\0"); // Add a NUL terminator
                 let name_bytes = name.as_encoded_bytes();
                 let name_len = u64::try_from(name_bytes.len()).unwrap();
@@ -1092,15 +1199,6 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
                 )?;
                 let entry: Pointer = entry.into();
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
-
-                let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
-
                 this.write_int_fields_named(
                     &[
                         ("d_ino", ino.into()),
@@ -1150,17 +1248,14 @@ fn macos_fbsd_readdir_r(
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`readdir_r`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
         }
 
-        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
-            err_unsup_format!("the DIR pointer passed to readdir_r did not come from opendir")
-        })?;
-        interp_ok(Scalar::from_i32(match open_dir.read_dir.next() {
-            Some(Ok(dir_entry)) => {
+        interp_ok(Scalar::from_i32(match this.next_dir_entry(dirp, "readdir_r")? {
+            Some(Ok((file_name, ino, file_type))) => {
                 // Write into entry, write pointer to result, return 0 on success.
                 // The name is written with write_os_str_to_c_str, while the rest of the
                 // dirent struct is written using write_int_fields.
@@ -1178,7 +1273,7 @@ fn macos_fbsd_readdir_r(
                 let entry_place = this.deref_pointer_as(entry_op, this.libc_ty_layout("dirent"))?;
                 let name_place = this.project_field_named(&entry_place, "d_name")?;
 
-                let file_name = dir_entry.file_name(); // not a Path as there are no separators!
+                // `file_name` is not a Path as there are no separators!
                 let (name_fits, file_name_buf_len) = this.write_os_str_to_c_str(
                     &file_name,
                     name_place.ptr(),
@@ -1191,15 +1286,6 @@ fn macos_fbsd_readdir_r(
                     );
                 }
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
-
-                let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
-
                 // Common fields.
                 this.write_int_fields_named(
                     &[
@@ -1265,7 +1351,7 @@ fn closedir(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
 
         // Reject if isolation is enabled.
         interp_ok(Scalar::from_i32(
-            if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
                 this.reject_in_isolation("`closedir`", reject_with)?;
                 this.fd_not_found()?
             } else if let Some(open_dir) = this.machine.dirs.streams.remove(&dirp) {
@@ -1284,7 +1370,7 @@ fn ftruncate64(&mut self, fd_num: i32, length: i128) -> InterpResult<'tcx, Scala
         let this = self.eval_context_mut();
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`ftruncate64`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
@@ -1294,6 +1380,15 @@ fn ftruncate64(&mut self, fd_num: i32, length: i128) -> InterpResult<'tcx, Scala
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
         };
 
+        if let Some(handle) = fd.downcast::<VfsFileHandle>() {
+            let result = match u64::try_from(length) {
+                Ok(length) => handle.set_len(length),
+                Err(_) => Err(LibcError("EINVAL")),
+            };
+            drop(fd);
+            return this.vfs_return_i32(result);
+        }
+
         // FIXME: Support ftruncate64 for all FDs
         let FileHandle { file, writable } = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`ftruncate64` is only supported on file-backed file descriptors")
@@ -1329,7 +1424,7 @@ fn fsync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`fsync`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
@@ -1343,6 +1438,10 @@ fn ffullsync_fd(&mut self, fd_num: i32) -> InterpResult<'tcx, Scalar> {
         let Some(fd) = this.machine.fds.get(fd_num) else {
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
         };
+        // Virtual files do not need to be written back anywhere.
+        if fd.downcast::<VfsFileHandle>().is_some() {
+            return interp_ok(Scalar::from_i32(0));
+        }
         // Only regular files support synchronization.
         let FileHandle { file, writable } = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`fsync` is only supported on file-backed file descriptors")
@@ -1358,7 +1457,7 @@ fn fdatasync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`fdatasync`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
@@ -1367,6 +1466,10 @@ fn fdatasync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let Some(fd) = this.machine.fds.get(fd) else {
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
         };
+        // Virtual files do not need to be written back anywhere.
+        if fd.downcast::<VfsFileHandle>().is_some() {
+            return interp_ok(Scalar::from_i32(0));
+        }
         // Only regular files support synchronization.
         let FileHandle { file, writable } = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`fdatasync` is only supported on file-backed file descriptors")
@@ -1403,7 +1506,7 @@ fn sync_file_range(
         }
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`sync_file_range`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
@@ -1412,6 +1515,10 @@ fn sync_file_range(
         let Some(fd) = this.machine.fds.get(fd) else {
             return interp_ok(Scalar::from_i32(this.fd_not_found()?));
         };
+        // Virtual files do not need to be written back anywhere.
+        if fd.downcast::<VfsFileHandle>().is_some() {
+            return interp_ok(Scalar::from_i32(0));
+        }
         // Only regular files support synchronization.
         let FileHandle { file, writable } = fd.downcast::<FileHandle>().ok_or_else(|| {
             err_unsup_format!("`sync_data_range` is only supported on file-backed file descriptors")
@@ -1434,13 +1541,23 @@ fn readlink(
         let bufsize = this.read_target_usize(bufsize_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`readlink`", reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
             return interp_ok(-1);
         }
 
+        if let Some(vfs) = &this.machine.vfs {
+            // There are no symbolic links in the virtual file system.
+            let e = match vfs.metadata(&pathname) {
+                Ok(_) => LibcError("EINVAL"),
+                Err(e) => e,
+            };
+            this.set_last_error(e)?;
+            return interp_ok(-1);
+        }
+
         let result = std::fs::read_link(pathname);
         match result {
             Ok(resolved) => {
@@ -1497,14 +1614,17 @@ fn realpath(
         let processed_ptr = this.read_pointer(processed_path_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`realpath`", reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
             return interp_ok(Scalar::from_target_usize(0, this));
         }
 
-        let result = std::fs::canonicalize(pathname);
+        let result = match &this.machine.vfs {
+            Some(vfs) => vfs.canonicalize(&pathname),
+            None => std::fs::canonicalize(pathname).map_err(IoError::HostError),
+        };
         match result {
             Ok(resolved) => {
                 let path_max = this
@@ -1572,7 +1692,7 @@ fn mkstemp(&mut self, template_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let template_bytes = template.as_mut_slice();
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.fs_isolated_op() {
             this.reject_in_isolation("`mkstemp`", reject_with)?;
             let eacc = this.eval_libc("EACCES");
             this.set_last_error(eacc)?;
@@ -1641,6 +1761,25 @@ fn mkstemp(&mut self, template_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             // To actually open the file, turn this into a host OsString.
             let p = bytes_to_os_str(template_bytes)?.to_os_string();
 
+            if let Some(vfs) = &mut this.machine.vfs {
+                let possibly_unique = Path::new("/tmp").join::<PathBuf>(p.into());
+                let options = VfsOpenOptions {
+                    read: true,
+                    write: true,
+                    create_new: true,
+                    ..Default::default()
+                };
+                match vfs.open(&possibly_unique, &options) {
+                    Ok(handle) => {
+                        let fd = this.machine.fds.insert_new(handle);
+                        return interp_ok(Scalar::from_i32(fd));
+                    }
+                    // If the random file already exists, keep trying.
+                    Err(LibcError("EEXIST")) => continue,
+                    Err(e) => return this.set_last_error_and_return_i32(e),
+                }
+            }
+
             let possibly_unique = std::env::temp_dir().join::<PathBuf>(p.into());
 
             let file = fopts.open(possibly_unique);
@@ -1703,6 +1842,12 @@ fn from_path<'tcx>(
         path: &Path,
         follow_symlink: bool,
     ) -> InterpResult<'tcx, Option<FileMetadata>> {
+        if let Some(vfs) = &ecx.machine.vfs {
+            // There are no symbolic links to follow in the virtual file system.
+            let metadata = vfs.metadata(path);
+            return FileMetadata::from_vfs_meta(ecx, metadata);
+        }
+
         let metadata =
             if follow_symlink { std::fs::metadata(path) } else { std::fs::symlink_metadata(path) };
 
@@ -1717,6 +1862,12 @@ fn from_fd_num<'tcx>(
             return ecx.fd_not_found().map(|_: i32| None);
         };
 
+        if let Some(handle) = fd.downcast::<VfsFileHandle>() {
+            let metadata = handle.metadata();
+            drop(fd);
+            return FileMetadata::from_vfs_meta(ecx, Ok(metadata));
+        }
+
         let file = &fd
             .downcast::<FileHandle>()
             .ok_or_else(|| {
@@ -1764,4 +1915,28 @@ fn from_meta<'tcx>(
         // FIXME: Provide more fields using platform specific methods.
         interp_ok(Some(FileMetadata { mode, size, created, accessed, modified }))
     }
+
+    fn from_vfs_meta<'tcx>(
+        ecx: &mut MiriInterpCx<'tcx>,
+        metadata: Result<VfsMetadata, IoError>,
+    ) -> InterpResult<'tcx, Option<FileMetadata>> {
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                ecx.set_last_error(e)?;
+                return interp_ok(None);
+            }
+        };
+
+        let mode = ecx.eval_libc(if metadata.is_dir { "S_IFDIR" } else { "S_IFREG" });
+
+        // The virtual file system does not track timestamps.
+        interp_ok(Some(FileMetadata {
+            mode,
+            size: metadata.size,
+            created: None,
+            accessed: None,
+            modified: None,
+        }))
+    }
 }
@@ -327,6 +327,14 @@ fn epoll_ctl(
                 );
             }
 
+            // Host sockets do not notify epoll when their readiness changes, so `epoll_wait`
+            // would never be woken up by them.
+            if is_host_socket(&fd_ref) {
+                throw_unsup_format!(
+                    "epoll_ctl: sockets backed by host sockets (with isolation disabled) are not supported"
+                );
+            }
+
             let epoll_key = (id, fd);
 
             // Check the existence of fd in the interest list.
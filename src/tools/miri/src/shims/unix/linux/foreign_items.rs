@@ -1,3 +1,5 @@
+use std::ffi::OsStr;
+
 use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
@@ -194,6 +196,14 @@ fn emulate_foreign_item_inner(
 
                 this.write_int(SIGRTMAX, dest)?;
             }
+            "gnu_get_libc_version" if this.tcx.sess.target.env == "gnu" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                // The standard library only uses `posix_spawn` with glibc 2.24 or newer.
+                let version =
+                    this.alloc_os_str_as_c_str(OsStr::new("2.40"), MiriMemoryKind::Machine.into())?;
+                this.write_pointer(version, dest)?;
+            }
 
             // Incomplete shims that we "stub out" just to get pre-main initialization code to work.
             // These shims are enabled only when the caller is in the standard library.
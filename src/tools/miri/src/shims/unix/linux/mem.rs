@@ -42,6 +42,13 @@ fn mremap(
             return interp_ok(this.eval_libc("MAP_FAILED"));
         }
 
+        if let Ok((alloc_id, _, _)) = this.ptr_try_get_alloc_id(old_address, 0)
+            && this.get_alloc_info(alloc_id).2 == AllocKind::LiveData
+            && this.get_alloc_extra(alloc_id)?.file_mapping.is_some()
+        {
+            throw_unsup_format!("Miri does not support mremap on file mappings");
+        }
+
         let align = this.machine.page_align();
         let ptr = this.reallocate_ptr(
             old_address,
@@ -1,11 +1,18 @@
 //! This is an incomplete implementation of mmap/munmap which is restricted in order to be
 //! implementable on top of the existing memory system. The point of these function as-written is
-//! to allow memory allocators written entirely in Rust to be executed by Miri. This implementation
-//! does not support other uses of mmap such as file mappings.
+//! to allow memory allocators written entirely in Rust to be executed by Miri, and to allow
+//! programs to map files.
 //!
-//! mmap/munmap behave a lot like alloc/dealloc, and for simple use they are exactly
-//! equivalent. That is the only part we support: no MAP_FIXED or MAP_SHARED or anything
-//! else that goes beyond a basic allocation API.
+//! Anonymous mmap/munmap behave a lot like alloc/dealloc, and for simple use they are exactly
+//! equivalent. That is the only part we support for anonymous mappings: no MAP_FIXED or
+//! MAP_SHARED or anything else that goes beyond a basic allocation API.
+//!
+//! File mappings are allocations that are initialized with the contents of the file. Changes to a
+//! shared file mapping are written back to the file when the mapping is synchronized with msync or
+//! unmapped, so until then they are not visible to other mappings of the same file or to reads of
+//! the file, and changes to the file are never visible in existing mappings. The part of a mapping
+//! past the end of the file is zero-initialized and never written back. Mappings that are still
+//! around when the program exits are not written back.
 //!
 //! Note that in addition to only supporting malloc-like calls to mmap, we only support free-like
 //! calls to munmap, but for a very different reason. In principle, according to the man pages, it
@@ -14,10 +21,97 @@
 //! munmap shim which would partially unmap a region of address space previously mapped by mmap will
 //! report UB.
 
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
 use rustc_target::abi::Size;
 
+use crate::shims::unix::fs::FileHandle;
 use crate::*;
 
+/// The state of a memory mapping of a file, stored in the `AllocExtra` of the mapped allocation.
+#[derive(Debug)]
+pub struct FileMapping {
+    /// The file that changes are written back to. This is only set for shared mappings. Like in the
+    /// kernel, the mapping keeps the file open even after its file descriptor has been closed.
+    file: Option<File>,
+    /// The offset in the file at which the mapping starts.
+    offset: u64,
+    /// How many bytes at the start of the mapping are backed by the file.
+    len: u64,
+    /// Whether the mapping was created with `PROT_WRITE`.
+    writable: bool,
+}
+
+impl FileMapping {
+    /// Writing to a mapping without `PROT_WRITE` is UB.
+    pub fn is_read_only(&self) -> bool {
+        !self.writable
+    }
+}
+
+/// Reads up to `len` bytes of `file` starting at `offset`.
+fn read_file_at(file: &File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    // Like the `pread` emulation, this restores the cursor position afterwards, since the file
+    // shares it with the file descriptor it came from.
+    let file = &mut &*file;
+    let cursor_pos = file.stream_position()?;
+    file.seek(SeekFrom::Start(offset))?;
+    let res = Read::by_ref(file).take(len).read_to_end(&mut bytes);
+    file.seek(SeekFrom::Start(cursor_pos))?;
+    res.map(|_| bytes)
+}
+
+/// Writes all of `bytes` to `file` starting at `offset`.
+fn write_file_at(file: &File, offset: u64, bytes: &[u8]) -> io::Result<()> {
+    let file = &mut &*file;
+    let cursor_pos = file.stream_position()?;
+    file.seek(SeekFrom::Start(offset))?;
+    let res = file.write_all(bytes);
+    file.seek(SeekFrom::Start(cursor_pos))?;
+    res
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns the file mapping that `ptr` points into, if any.
+    fn file_mapping(&self, ptr: Pointer) -> InterpResult<'tcx, Option<(&FileMapping, Size)>> {
+        let this = self.eval_context_ref();
+        let Ok((alloc_id, offset, _)) = this.ptr_try_get_alloc_id(ptr, 0) else {
+            return interp_ok(None);
+        };
+        if this.get_alloc_info(alloc_id).2 != AllocKind::LiveData {
+            return interp_ok(None);
+        }
+        let mapping = this.get_alloc_extra(alloc_id)?.file_mapping.as_deref();
+        interp_ok(mapping.map(|mapping| (mapping, offset)))
+    }
+
+    /// If `ptr` points into a shared file mapping, writes the `len` bytes starting at `ptr` back to
+    /// the file, as far as they are backed by the file.
+    fn write_back_file_mapping(
+        &self,
+        ptr: Pointer,
+        len: u64,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        let this = self.eval_context_ref();
+        let Some((mapping, offset)) = this.file_mapping(ptr)? else {
+            return interp_ok(Ok(()));
+        };
+        let Some(file) = &mapping.file else {
+            // Changes to private mappings are never written back.
+            return interp_ok(Ok(()));
+        };
+        let end = offset.bytes().saturating_add(len).min(mapping.len);
+        let Some(len) = end.checked_sub(offset.bytes()).filter(|&len| len > 0) else {
+            return interp_ok(Ok(()));
+        };
+        let bytes = this.read_bytes_ptr_strip_provenance(ptr, Size::from_bytes(len))?;
+        interp_ok(write_file_at(file, mapping.offset.strict_add(offset.bytes()), bytes))
+    }
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn mmap(
@@ -65,13 +159,6 @@ fn mmap(
             return interp_ok(this.eval_libc("MAP_FAILED"));
         }
 
-        // If a user tries to map a file, we want to loudly inform them that this is not going
-        // to work. It is possible that POSIX gives us enough leeway to return an error, but the
-        // outcome for the user (I need to add cfg(miri)) is the same, just more frustrating.
-        if fd != -1 {
-            throw_unsup_format!("Miri does not support file-backed memory mappings");
-        }
-
         // Miri doesn't support MAP_FIXED.
         if flags & map_fixed != 0 {
             throw_unsup_format!(
@@ -79,27 +166,95 @@ fn mmap(
             );
         }
 
-        // Miri doesn't support protections other than PROT_READ|PROT_WRITE.
-        if prot != prot_read | prot_write {
-            throw_unsup_format!(
-                "Miri does not support calls to mmap with protections other than \
-                 PROT_READ|PROT_WRITE",
-            );
-        }
+        let file_mapping = if flags & map_anonymous != 0 {
+            if fd != -1 {
+                throw_unsup_format!(
+                    "Miri does not support anonymous mappings with a file descriptor other than -1"
+                );
+            }
 
-        // Miri does not support shared mappings, or any of the other extensions that for example
-        // Linux has added to the flags arguments.
-        if flags != map_private | map_anonymous {
-            throw_unsup_format!(
-                "Miri only supports calls to mmap which set the flags argument to \
-                 MAP_PRIVATE|MAP_ANONYMOUS",
-            );
-        }
+            // Miri doesn't support protections other than PROT_READ|PROT_WRITE.
+            if prot != prot_read | prot_write {
+                throw_unsup_format!(
+                    "Miri does not support calls to mmap with protections other than \
+                     PROT_READ|PROT_WRITE",
+                );
+            }
 
-        // This is only used for file mappings, which we don't support anyway.
-        if offset != 0 {
-            throw_unsup_format!("Miri does not support non-zero offsets to mmap");
-        }
+            // Miri does not support shared mappings, or any of the other extensions that for example
+            // Linux has added to the flags arguments.
+            if flags != map_private | map_anonymous {
+                throw_unsup_format!(
+                    "Miri only supports calls to mmap which set the flags argument to \
+                     MAP_PRIVATE|MAP_ANONYMOUS",
+                );
+            }
+
+            // This is only used for file mappings.
+            if offset != 0 {
+                throw_unsup_format!("Miri does not support non-zero offsets to anonymous mmap");
+            }
+
+            None
+        } else {
+            // File mappings can be read-only, in which case writing to them is UB.
+            let writable = prot == prot_read | prot_write;
+            if !writable && prot != prot_read {
+                throw_unsup_format!(
+                    "Miri does not support calls to mmap with protections other than \
+                     PROT_READ or PROT_READ|PROT_WRITE",
+                );
+            }
+
+            if flags != map_private && flags != map_shared {
+                throw_unsup_format!(
+                    "Miri only supports calls to mmap for files which set the flags argument to \
+                     MAP_PRIVATE or MAP_SHARED",
+                );
+            }
+
+            // The offset must be a multiple of the page size.
+            #[allow(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+            let Some(offset) =
+                u64::try_from(offset).ok().filter(|offset| offset % this.machine.page_size == 0)
+            else {
+                this.set_last_error(this.eval_libc("EINVAL"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            };
+
+            let Some(fd) = this.machine.fds.get(fd) else {
+                this.set_last_error(this.eval_libc("EBADF"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            };
+            // Only regular files can be mapped.
+            let Some(FileHandle { file, writable: fd_writable }) = fd.downcast::<FileHandle>()
+            else {
+                this.set_last_error(this.eval_libc("ENODEV"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            };
+            // Writing to a shared mapping writes to the file, which requires it to be writable.
+            let shared = flags == map_shared;
+            if shared && writable && !fd_writable {
+                this.set_last_error(this.eval_libc("EACCES"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            }
+            let f = || {
+                let contents = read_file_at(file, offset, length)?;
+                let file = if shared { Some(file.try_clone()?) } else { None };
+                io::Result::Ok((file, contents))
+            };
+            let result = f();
+            drop(fd);
+            let (file, contents) = match result {
+                Ok(res) => res,
+                Err(e) => {
+                    this.set_last_error(e)?;
+                    return interp_ok(this.eval_libc("MAP_FAILED"));
+                }
+            };
+            let len = u64::try_from(contents.len()).unwrap();
+            Some((FileMapping { file, offset, len, writable }, contents))
+        };
 
         let align = this.machine.page_align();
         let Some(map_length) = length.checked_next_multiple_of(this.machine.page_size) else {
@@ -121,6 +276,13 @@ fn mmap(
         )
         .unwrap();
 
+        if let Some((file_mapping, contents)) = file_mapping {
+            // The access is in-bounds since we never read more than `length` bytes.
+            this.write_bytes_ptr(ptr.into(), contents).unwrap();
+            let (alloc_id, _, _) = this.ptr_get_alloc_id(ptr.into(), 0)?;
+            this.get_alloc_extra_mut(alloc_id)?.0.file_mapping = Some(Box::new(file_mapping));
+        }
+
         interp_ok(Scalar::from_pointer(ptr, this))
     }
 
@@ -147,6 +309,12 @@ fn munmap(&mut self, addr: &OpTy<'tcx>, length: &OpTy<'tcx>) -> InterpResult<'tc
             return interp_ok(this.eval_libc("MAP_FAILED"));
         }
 
+        // Changes to shared file mappings are written back before the mapping goes away. The
+        // kernel cannot report errors here, but we can, so we keep the mapping in that case.
+        if let Err(e) = this.write_back_file_mapping(addr, length)? {
+            return this.set_last_error_and_return_i32(e);
+        }
+
         let length = Size::from_bytes(length);
         this.deallocate_ptr(
             addr,
@@ -156,4 +324,52 @@ fn munmap(&mut self, addr: &OpTy<'tcx>, length: &OpTy<'tcx>) -> InterpResult<'tc
 
         interp_ok(Scalar::from_i32(0))
     }
+
+    fn msync(
+        &mut self,
+        addr: &OpTy<'tcx>,
+        length: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        let length = this.read_target_usize(length)?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        let ms_async = this.eval_libc_i32("MS_ASYNC");
+        let ms_sync = this.eval_libc_i32("MS_SYNC");
+        let ms_invalidate = this.eval_libc_i32("MS_INVALIDATE");
+
+        // addr must be a multiple of the page size, and `MS_ASYNC` and `MS_SYNC` are exclusive.
+        #[allow(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+        if addr.addr().bytes() % this.machine.page_size != 0
+            || flags & !(ms_async | ms_sync | ms_invalidate) != 0
+            || (flags & ms_async != 0 && flags & ms_sync != 0)
+        {
+            this.set_last_error(this.eval_libc("EINVAL"))?;
+            return interp_ok(Scalar::from_i32(-1));
+        }
+
+        // The whole range must be mapped.
+        let mapped =
+            length.checked_next_multiple_of(this.machine.page_size).is_some_and(|length| {
+                this.ptr_try_get_alloc_id(addr, 0).is_ok_and(|(alloc_id, offset, _)| {
+                    let (size, _, kind) = this.get_alloc_info(alloc_id);
+                    kind == AllocKind::LiveData
+                        && offset.bytes().checked_add(length).is_some_and(|end| end <= size.bytes())
+                })
+            });
+        if !mapped {
+            this.set_last_error(this.eval_libc("ENOMEM"))?;
+            return interp_ok(Scalar::from_i32(-1));
+        }
+
+        // Both `MS_ASYNC` and `MS_SYNC` write the changes back right away. `MS_INVALIDATE` has
+        // nothing to do since changes to the file are never visible in the mapping anyway.
+        match this.write_back_file_mapping(addr, length)? {
+            Ok(()) => interp_ok(Scalar::from_i32(0)),
+            Err(e) => this.set_last_error_and_return_i32(e),
+        }
+    }
 }
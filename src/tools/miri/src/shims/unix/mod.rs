@@ -4,9 +4,12 @@
 mod fd;
 mod fs;
 mod mem;
+mod process;
+mod socket;
 mod sync;
 mod thread;
 mod unnamed_socket;
+mod vfs;
 
 mod android;
 mod freebsd;
@@ -19,10 +22,13 @@
 pub use self::fd::{EvalContextExt as _, FdTable, FileDescription};
 pub use self::fs::{DirTable, EvalContextExt as _};
 pub use self::linux::epoll::EpollInterestTable;
-pub use self::mem::EvalContextExt as _;
+pub use self::mem::{EvalContextExt as _, FileMapping};
+pub use self::process::{ChildProcesses, EvalContextExt as _};
+pub use self::socket::{EvalContextExt as _, VirtualNetwork, is_host_socket};
 pub use self::sync::EvalContextExt as _;
 pub use self::thread::EvalContextExt as _;
 pub use self::unnamed_socket::EvalContextExt as _;
+pub use self::vfs::Vfs;
 
 // Make up some constants.
 const UID: u32 = 1000;
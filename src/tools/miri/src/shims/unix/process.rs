@@ -0,0 +1,438 @@
+//! Child processes.
+//!
+//! Miri can only run the program it is interpreting, so `posix_spawn` starts the entry function of
+//! that same program again in a fresh interpreter, with the arguments and environment that were
+//! passed to `posix_spawn`. The standard streams of the child are those of the parent after
+//! applying the file actions, so the parent can talk to the child through pipes. The child shares
+//! the virtual file system with its parent, but otherwise uses the same configuration as the
+//! parent.
+//!
+//! Processes do not run concurrently. A child only runs once its parent waits for it, or would
+//! block reading from a pipe or in `poll`, and then runs to completion before the parent
+//! continues. This means a child cannot wait for its parent, e.g. by reading from a pipe the parent
+//! has not written to and closed yet.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_session::config::EntryFnType;
+
+use crate::eval::eval_child_process;
+use crate::shims::unix::fd::FileDescriptionRef;
+use crate::shims::unix::unnamed_socket::{reset_anonsocket_clocks, unbound_anonsocket};
+use crate::shims::unix::*;
+use crate::*;
+
+/// A file action that `posix_spawn` performs in the child before it starts.
+#[derive(Debug, Clone, Copy)]
+enum FileAction {
+    Dup2 { fd: i32, new_fd: i32 },
+    Close { fd: i32 },
+}
+
+/// The file actions recorded in a `posix_spawn_file_actions_t`.
+#[derive(Debug, Default)]
+struct FileActions(RefCell<Vec<FileAction>>);
+
+#[derive(Debug)]
+enum ChildState {
+    /// The child has been spawned, but has not run yet.
+    Pending {
+        args: Vec<String>,
+        env: FxHashMap<String, String>,
+        /// The standard input, output and error of the child, or `None` if they are closed.
+        stdio: [Option<FileDescriptionRef>; 3],
+    },
+    /// The child has terminated with this wait status, which has not been collected yet.
+    Terminated { status: i32 },
+}
+
+/// The child processes of the interpreted program that have not been waited for yet.
+pub struct ChildProcesses {
+    /// The configuration that child processes are interpreted with, apart from their arguments and
+    /// environment.
+    config: MiriConfig,
+    /// The entry function of the interpreted program.
+    entry: Option<(DefId, EntryFnType)>,
+    /// The process ID of this process, if it is a child process itself.
+    pid: Option<u32>,
+    /// The number of child processes that have been spawned so far.
+    spawned: u32,
+    children: BTreeMap<i32, ChildState>,
+}
+
+impl VisitProvenance for ChildProcesses {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // File descriptions do not contain any tags.
+    }
+}
+
+impl ChildProcesses {
+    pub fn new(config: &MiriConfig) -> Self {
+        let mut config = config.clone();
+        // Children run inside the virtual file system and coverage counts of their parent, and
+        // only the main program writes reports.
+        config.vfs = None;
+        config.vfs_dump = None;
+        config.coverage = None;
+        config.measureme_out = None;
+        config.explore_schedules = None;
+        config.record_schedule = None;
+        config.replay_schedule = None;
+        // The environment of a child is given to `posix_spawn`.
+        config.env = Vec::new();
+        config.forwarded_env_vars = Vec::new();
+        ChildProcesses { config, entry: None, pid: None, spawned: 0, children: BTreeMap::new() }
+    }
+
+    pub fn set_entry(&mut self, entry_id: DefId, entry_type: EntryFnType) {
+        self.entry = Some((entry_id, entry_type));
+    }
+
+    /// The process ID of this process, if it is a child process.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Whether `path` refers to the interpreted program. That is the case if it has the same file
+    /// name as the program's first argument, or is what `std::env::current_exe` returns without
+    /// isolation.
+    fn is_interpreted_program(&self, path: &Path) -> bool {
+        let Some(arg0) = self.config.args.first() else {
+            return false;
+        };
+        path.file_name().is_some_and(|name| Path::new(arg0).file_name() == Some(name))
+            || std::env::current_exe().is_ok_and(|exe| exe == path)
+    }
+}
+
+/// Returns the wait status of a process that exited with `code`.
+fn exit_status(code: i64) -> i32 {
+    // Only the lowest 8 bits of the exit code are visible to the parent.
+    i32::try_from(code & 0xff).unwrap() << 8
+}
+
+impl<'tcx> EvalContextExtPrivate<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPrivate<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn file_actions(&mut self, file_actions: &OpTy<'tcx>) -> InterpResult<'tcx, &FileActions> {
+        let this = self.eval_context_mut();
+        let file_actions = this.read_pointer(file_actions)?;
+        this.get_sync_or_init(file_actions, |_| interp_ok(FileActions::default()))
+    }
+
+    /// Reads a null-terminated array of C strings, like `argv`.
+    fn read_c_str_array(&self, array: Pointer, name: &str) -> InterpResult<'tcx, Vec<String>> {
+        let this = self.eval_context_ref();
+        let ptr_layout = this.machine.layouts.mut_raw_ptr;
+        let mut strings = Vec::new();
+        loop {
+            let index = u64::try_from(strings.len()).unwrap();
+            let place = this
+                .ptr_to_mplace(array.wrapping_offset(ptr_layout.size * index, this), ptr_layout);
+            let ptr = this.read_pointer(&place)?;
+            if this.ptr_is_null(ptr)? {
+                return interp_ok(strings);
+            }
+            let Ok(string) = str::from_utf8(this.read_c_str(ptr)?) else {
+                throw_unsup_format!(
+                    "`posix_spawn`: {name} that are not valid UTF-8 are not supported"
+                );
+            };
+            strings.push(string.to_owned());
+        }
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn posix_spawn_file_actions_init(
+        &mut self,
+        file_actions: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        this.file_actions(file_actions)?.0.borrow_mut().clear();
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn posix_spawn_file_actions_adddup2(
+        &mut self,
+        file_actions: &OpTy<'tcx>,
+        fd: &OpTy<'tcx>,
+        new_fd: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let new_fd = this.read_scalar(new_fd)?.to_i32()?;
+        if fd < 0 || new_fd < 0 {
+            return interp_ok(this.eval_libc("EBADF"));
+        }
+        this.file_actions(file_actions)?.0.borrow_mut().push(FileAction::Dup2 { fd, new_fd });
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn posix_spawn_file_actions_addclose(
+        &mut self,
+        file_actions: &OpTy<'tcx>,
+        fd: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        if fd < 0 {
+            return interp_ok(this.eval_libc("EBADF"));
+        }
+        this.file_actions(file_actions)?.0.borrow_mut().push(FileAction::Close { fd });
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn posix_spawnattr_setflags(
+        &mut self,
+        attr: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        this.read_pointer(attr)?;
+        let flags = i32::from(this.read_scalar(flags)?.to_i16()?);
+        // A child process starts out with the default action for all signals anyway.
+        let posix_spawn_setsigdef = this.eval_libc_i32("POSIX_SPAWN_SETSIGDEF");
+        if flags & !posix_spawn_setsigdef != 0 {
+            throw_unsup_format!("`posix_spawnattr_setflags`: unsupported flags {flags:#x}");
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn posix_spawn(
+        &mut self,
+        pid_op: &OpTy<'tcx>,
+        path_op: &OpTy<'tcx>,
+        file_actions_op: &OpTy<'tcx>,
+        attr_op: &OpTy<'tcx>,
+        argv_op: &OpTy<'tcx>,
+        envp_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let pid_ptr = this.read_pointer(pid_op)?;
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?.into_owned();
+        let file_actions_ptr = this.read_pointer(file_actions_op)?;
+        this.read_pointer(attr_op)?;
+        let args = this.read_c_str_array(this.read_pointer(argv_op)?, "arguments")?;
+        let env = this
+            .read_c_str_array(this.read_pointer(envp_op)?, "environment variables")?
+            .into_iter()
+            .filter_map(|var| {
+                let (name, value) = var.split_once('=')?;
+                Some((name.to_owned(), value.to_owned()))
+            })
+            .collect();
+
+        if !this.machine.children.is_interpreted_program(&path) {
+            throw_unsup_format!(
+                "`posix_spawn`: Miri can only spawn the interpreted program itself, not `{}`",
+                path.display()
+            );
+        }
+
+        // The child starts out with the file descriptors of its parent, and the file actions then
+        // change what its standard streams refer to. As usual we ignore CLOEXEC, but since the
+        // child cannot know about any other file descriptors, it only inherits these three.
+        let mut stdio = [0, 1, 2].map(|fd_num| this.machine.fds.get(fd_num));
+        let mut changed: BTreeMap<i32, Option<FileDescriptionRef>> = BTreeMap::new();
+        let file_actions = if this.ptr_is_null(file_actions_ptr)? {
+            Vec::new()
+        } else {
+            this.file_actions(file_actions_op)?.0.borrow().clone()
+        };
+        for action in file_actions {
+            match action {
+                FileAction::Dup2 { fd, new_fd } => {
+                    let Some(fd) =
+                        changed.get(&fd).cloned().unwrap_or_else(|| this.machine.fds.get(fd))
+                    else {
+                        return interp_ok(this.eval_libc("EBADF"));
+                    };
+                    changed.insert(new_fd, Some(fd));
+                }
+                FileAction::Close { fd } => {
+                    changed.insert(fd, None);
+                }
+            }
+        }
+        for (fd_num, fd) in changed {
+            if let Some(slot) = usize::try_from(fd_num).ok().and_then(|i| stdio.get_mut(i)) {
+                *slot = fd;
+            }
+        }
+
+        // The child only runs once the parent would block, so neither of them may block writing
+        // to a full pipe that the other reads from.
+        for fd in stdio.iter().flatten() {
+            unbound_anonsocket(fd);
+        }
+        let children = &mut this.machine.children;
+        children.spawned = children.spawned.strict_add(1);
+        let spawned = children.spawned;
+        let pid = i32::try_from(this.get_pid().strict_add(spawned)).unwrap();
+        this.machine.children.children.insert(pid, ChildState::Pending { args, env, stdio });
+        if !this.ptr_is_null(pid_ptr)? {
+            let pid_place = this.ptr_to_mplace(pid_ptr, this.libc_ty_layout("pid_t"));
+            this.write_scalar(Scalar::from_i32(pid), &pid_place)?;
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn waitpid(
+        &mut self,
+        pid_op: &OpTy<'tcx>,
+        status_op: &OpTy<'tcx>,
+        options_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let pid = this.read_scalar(pid_op)?.to_i32()?;
+        let status_ptr = this.read_pointer(status_op)?;
+        let options = this.read_scalar(options_op)?.to_i32()?;
+
+        // `WNOHANG` makes no difference, since we can always run the child to completion.
+        let wnohang = this.eval_libc_i32("WNOHANG");
+        if options & !wnohang != 0 {
+            throw_unsup_format!("`waitpid`: unsupported options {options:#x}");
+        }
+        let pid = match pid {
+            -1 => this.machine.children.children.keys().next().copied(),
+            pid if pid > 0 => this.machine.children.children.contains_key(&pid).then_some(pid),
+            _ => throw_unsup_format!("`waitpid`: waiting for process groups is not supported"),
+        };
+        let Some(pid) = pid else {
+            return this.set_last_error_and_return_i32(LibcError("ECHILD"));
+        };
+
+        this.run_child_processes()?;
+        let Some(ChildState::Terminated { status }) = this.machine.children.children.remove(&pid)
+        else {
+            unreachable!()
+        };
+        if !this.ptr_is_null(status_ptr)? {
+            let status_place = this.ptr_to_mplace(status_ptr, this.machine.layouts.i32);
+            this.write_scalar(Scalar::from_i32(status), &status_place)?;
+        }
+        interp_ok(Scalar::from_i32(pid))
+    }
+
+    /// Sends a signal to a child process. Returns `None` if `pid` is not a child process.
+    fn kill_child_process(&mut self, pid: i32, sig: i32) -> InterpResult<'tcx, Option<Scalar>> {
+        let this = self.eval_context_mut();
+        let Some(child) = this.machine.children.children.get_mut(&pid) else {
+            return interp_ok(None);
+        };
+        // A child that has not run yet is terminated by the signal. A child that has terminated
+        // already ignores it.
+        if sig != 0 && matches!(child, ChildState::Pending { .. }) {
+            *child = ChildState::Terminated { status: sig };
+        }
+        interp_ok(Some(Scalar::from_i32(0)))
+    }
+
+    /// Runs all child processes that have not run yet, in the order they were spawned. This is
+    /// called whenever the interpreted program would block waiting for a child or for data that a
+    /// child might provide.
+    fn run_child_processes(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let pending = this
+            .machine
+            .children
+            .children
+            .iter()
+            .filter(|(_, state)| matches!(state, ChildState::Pending { .. }))
+            .map(|(pid, _)| *pid)
+            .collect::<Vec<_>>();
+        for pid in pending {
+            let ChildState::Pending { args, env, stdio } = this
+                .machine
+                .children
+                .children
+                .insert(pid, ChildState::Terminated { status: 0 })
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            let mut config = this.machine.children.config.clone();
+            config.args = args;
+            config.set_env_vars = env;
+            let (entry_id, entry_type) = this.machine.children.entry.unwrap();
+
+            // The child works on the virtual file system of its parent, but has its own working
+            // directory. Without isolation, this is the working directory of the Miri process.
+            let vfs = this.machine.vfs.take();
+            let vfs_cwd = vfs.as_ref().map(|vfs| vfs.cwd().to_owned());
+            let host_cwd = std::env::current_dir().ok();
+            let mut returned_vfs = None;
+            let coverage = this.machine.coverage.is_some();
+            let mut returned_coverage = None;
+            // The parent and the child do not share vector clocks, so neither of them synchronizes
+            // with the other when reading data from a pipe.
+            for fd in stdio.iter().flatten() {
+                reset_anonsocket_clocks(fd);
+            }
+            let code = eval_child_process(
+                *this.tcx,
+                entry_id,
+                entry_type,
+                &config,
+                |child| {
+                    child.machine.children.pid = Some(u32::try_from(pid).unwrap());
+                    child.machine.vfs = vfs;
+                    child.machine.coverage = coverage.then(Default::default);
+                    // FIXME: file descriptions are identified by an ID that is only unique within
+                    // one process, so epoll gets confused when it is used on a file description
+                    // that is shared with the parent.
+                    for (fd_num, fd) in (0..).zip(stdio) {
+                        match fd {
+                            Some(fd) => child.machine.fds.fds.insert(fd_num, fd),
+                            None => child.machine.fds.remove(fd_num),
+                        };
+                    }
+                },
+                |child| {
+                    for fd in child.machine.fds.fds.values() {
+                        reset_anonsocket_clocks(fd);
+                    }
+                    returned_vfs = child.machine.vfs.take();
+                    returned_coverage = child.machine.coverage.take();
+                },
+            );
+            this.machine.vfs = returned_vfs;
+            if let (Some(vfs), Some(cwd)) = (&mut this.machine.vfs, vfs_cwd) {
+                // The child might have removed the directory, in which case there is nothing we can
+                // do about it.
+                let _ = vfs.chdir(&cwd);
+            }
+            if let Some(cwd) = host_cwd {
+                let _ = std::env::set_current_dir(cwd);
+            }
+            if let (Some(coverage), Some(child_coverage)) =
+                (&mut this.machine.coverage, returned_coverage)
+            {
+                coverage.merge(child_coverage);
+            }
+
+            let Some(code) = code else {
+                // The error has already been reported.
+                throw_machine_stop!(TerminationInfo::Abort(format!(
+                    "child process {pid} was terminated by an error"
+                )));
+            };
+            this.machine
+                .children
+                .children
+                .insert(pid, ChildState::Terminated { status: exit_status(code) });
+        }
+        interp_ok(())
+    }
+}
@@ -0,0 +1,1757 @@
+//! This implements Internet domain (`AF_INET` and `AF_INET6`) sockets.
+//!
+//! With isolation enabled, sockets are attached to a virtual loopback network that exists entirely
+//! inside Miri: only loopback and unspecified addresses can be bound, and connections and datagrams
+//! are delivered between sockets of the interpreted program. Everything that happens on this
+//! network is deterministic. With isolation disabled, sockets are backed by host sockets instead.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{
+    self, IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, SocketAddrV4, SocketAddrV6,
+};
+use std::time::Duration;
+
+use rustc_target::abi::Size;
+
+use crate::concurrency::VClock;
+use crate::shims::unix::fd::{FileDescriptionRef, WeakFileDescriptionRef};
+use crate::shims::unix::linux::epoll::{EpollReadyEvents, EvalContextExt as _};
+use crate::shims::unix::*;
+use crate::*;
+
+/// The maximum number of bytes that can be queued for reading on a virtual socket.
+/// Like for socketpairs, this number is arbitrary as the value can always
+/// be configured in the real system.
+const MAX_SOCKET_BUFFER_CAPACITY: usize = 212992;
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// The first port handed out when binding to port 0, the start of the IANA dynamic port range.
+const FIRST_EPHEMERAL_PORT: u16 = 49152;
+
+/// How long a thread that is blocked on a host socket waits before trying again.
+const HOST_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SocketKind {
+    Stream,
+    Datagram,
+}
+
+/// The sockets of the virtual loopback network that have a local address.
+#[derive(Debug, Default)]
+pub struct VirtualNetwork {
+    bound: BTreeMap<(SocketKind, SocketAddr), WeakFileDescriptionRef>,
+    /// The next port to try when binding to port 0.
+    next_ephemeral_port: u16,
+}
+
+impl VisitProvenance for VirtualNetwork {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // Sockets do not contain any tags.
+    }
+}
+
+impl VirtualNetwork {
+    /// Returns the socket that is bound to exactly this address, if it is still open.
+    fn get(&self, kind: SocketKind, addr: SocketAddr) -> Option<FileDescriptionRef> {
+        self.bound.get(&(kind, addr)).and_then(|weak| weak.upgrade())
+    }
+
+    /// Finds the socket that receives connections or datagrams sent to `dest`: either a socket bound
+    /// to exactly that address, or one bound to the unspecified address on the same port.
+    fn lookup(&self, kind: SocketKind, dest: SocketAddr) -> Option<FileDescriptionRef> {
+        let port = dest.port();
+        let candidates = [
+            Some(dest),
+            Some(SocketAddr::new(unspecified(dest.ip()), port)),
+            // An IPv6 socket bound to `::` also receives IPv4 traffic unless it is `IPV6_V6ONLY`.
+            dest.is_ipv4().then(|| SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port)),
+        ];
+        candidates.into_iter().flatten().find_map(|addr| {
+            let fd = self.get(kind, addr)?;
+            let socket = fd.downcast::<InetSocket>().unwrap();
+            if addr.is_ipv6() && dest.is_ipv4() && socket.only_v6.get() {
+                return None;
+            }
+            let accepts = match &*socket.state.borrow() {
+                SocketState::Listening(_) => kind == SocketKind::Stream,
+                SocketState::Datagram(_) => kind == SocketKind::Datagram,
+                _ => false,
+            };
+            accepts.then_some(fd)
+        })
+    }
+
+    /// Checks whether `addr` can be bound by a socket of the given kind without clashing with an
+    /// address that is already in use.
+    fn is_available(&self, kind: SocketKind, addr: SocketAddr, only_v6: bool) -> bool {
+        !self.bound.iter().any(|(&(bound_kind, bound), weak)| {
+            if bound_kind != kind || bound.port() != addr.port() {
+                return false;
+            }
+            let Some(fd) = weak.upgrade() else { return false };
+            let bound_only_v6 = fd.downcast::<InetSocket>().unwrap().only_v6.get();
+            addresses_overlap(addr.ip(), only_v6, bound.ip(), bound_only_v6)
+        })
+    }
+
+    /// Picks an unused port for binding a socket to `ip`.
+    fn ephemeral_port(&mut self, kind: SocketKind, ip: IpAddr, only_v6: bool) -> Option<u16> {
+        let count = u16::MAX.strict_sub(FIRST_EPHEMERAL_PORT).strict_add(1);
+        for _ in 0..count {
+            let port = self.next_ephemeral_port.max(FIRST_EPHEMERAL_PORT);
+            self.next_ephemeral_port = port.checked_add(1).unwrap_or(FIRST_EPHEMERAL_PORT);
+            if self.is_available(kind, SocketAddr::new(ip, port), only_v6) {
+                return Some(port);
+            }
+        }
+        None
+    }
+
+    fn insert(&mut self, kind: SocketKind, addr: SocketAddr, fd: &FileDescriptionRef) {
+        self.bound.insert((kind, addr), fd.downgrade());
+    }
+
+    /// Forgets all sockets that have been closed.
+    fn remove_closed(&mut self) {
+        self.bound.retain(|_, weak| weak.upgrade().is_some());
+    }
+}
+
+fn unspecified(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+        IpAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+    }
+}
+
+fn loopback(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+        IpAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+    }
+}
+
+fn addresses_overlap(a: IpAddr, a_only_v6: bool, b: IpAddr, b_only_v6: bool) -> bool {
+    match (a, b) {
+        (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) =>
+            a == b || a.is_unspecified() || b.is_unspecified(),
+        (IpAddr::V6(v6), IpAddr::V4(_)) => v6.is_unspecified() && !a_only_v6,
+        (IpAddr::V4(_), IpAddr::V6(v6)) => v6.is_unspecified() && !b_only_v6,
+    }
+}
+
+/// Bytes queued for reading on a connected stream socket, together with the clock of the writers.
+#[derive(Debug, Default)]
+struct Buffer {
+    buf: VecDeque<u8>,
+    clock: VClock,
+}
+
+#[derive(Debug)]
+struct Datagram {
+    from: SocketAddr,
+    data: Vec<u8>,
+    clock: VClock,
+}
+
+#[derive(Debug)]
+struct Listener {
+    local: SocketAddr,
+    /// Connections that have been established but not yet accepted, with the clock of the thread
+    /// that connected.
+    backlog: VecDeque<(FileDescriptionRef, VClock)>,
+}
+
+#[derive(Debug)]
+struct Stream {
+    local: SocketAddr,
+    peer_addr: SocketAddr,
+    /// The other end of the connection. Writes go to its `readbuf`.
+    peer: WeakFileDescriptionRef,
+    readbuf: Buffer,
+    /// The peer has closed the connection or shut down writing.
+    eof: bool,
+    /// The peer was closed while data it had not read yet was pending.
+    peer_lost_data: bool,
+    read_shutdown: bool,
+    write_shutdown: bool,
+}
+
+#[derive(Debug)]
+struct DatagramSocket {
+    local: SocketAddr,
+    connected: Option<SocketAddr>,
+    queue: VecDeque<Datagram>,
+    queued_bytes: usize,
+}
+
+#[derive(Debug)]
+enum SocketState {
+    /// The socket is not listening, connected, or (for datagram sockets) bound yet. A stream
+    /// socket remembers the address it was bound to.
+    Idle {
+        local: Option<SocketAddr>,
+    },
+    Listening(Listener),
+    Connected(Stream),
+    Datagram(DatagramSocket),
+    HostListener(net::TcpListener),
+    HostStream(net::TcpStream),
+    HostDatagram(net::UdpSocket),
+}
+
+impl SocketState {
+    fn is_host(&self) -> bool {
+        matches!(
+            self,
+            SocketState::HostListener(_)
+                | SocketState::HostStream(_)
+                | SocketState::HostDatagram(_)
+        )
+    }
+}
+
+/// Returns whether `fd` is a socket that is, or will be once it is bound or connected, backed by a
+/// host socket.
+pub fn is_host_socket(fd: &FileDescriptionRef) -> bool {
+    fd.downcast::<InetSocket>().is_some_and(|socket| socket.host)
+}
+
+/// Checks which events a host socket is ready for, without blocking.
+#[cfg(unix)]
+fn host_ready_events<'tcx>(
+    socket: &impl std::os::fd::AsRawFd,
+) -> InterpResult<'tcx, EpollReadyEvents> {
+    let mut pollfd =
+        libc::pollfd { fd: socket.as_raw_fd(), events: libc::POLLIN | libc::POLLOUT, revents: 0 };
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        pollfd.events |= libc::POLLRDHUP;
+    }
+    // SAFETY: `pollfd` is a single valid `pollfd`, and a zero timeout makes the call return
+    // immediately.
+    if unsafe { libc::poll(&mut pollfd, 1, 0) } < 0 {
+        let err = io::Error::last_os_error();
+        throw_unsup_format!("failed to check the readiness of a host socket: {err}");
+    }
+    let mut epoll_ready_events = EpollReadyEvents::new();
+    epoll_ready_events.epollin = pollfd.revents & libc::POLLIN != 0;
+    epoll_ready_events.epollout = pollfd.revents & libc::POLLOUT != 0;
+    epoll_ready_events.epollhup = pollfd.revents & libc::POLLHUP != 0;
+    epoll_ready_events.epollerr = pollfd.revents & libc::POLLERR != 0;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        epoll_ready_events.epollrdhup = pollfd.revents & libc::POLLRDHUP != 0;
+    }
+    interp_ok(epoll_ready_events)
+}
+
+#[cfg(not(unix))]
+fn host_ready_events<'tcx, T>(_socket: &T) -> InterpResult<'tcx, EpollReadyEvents> {
+    throw_unsup_format!("checking the readiness of host sockets is not supported on this host")
+}
+
+#[derive(Debug)]
+struct InetSocket {
+    is_ipv6: bool,
+    kind: SocketKind,
+    /// Whether the socket is backed by a host socket once it is bound or connected.
+    host: bool,
+    state: RefCell<SocketState>,
+    is_nonblock: Cell<bool>,
+    only_v6: Cell<bool>,
+    read_timeout: Cell<Option<Duration>>,
+    write_timeout: Cell<Option<Duration>>,
+    /// Integer options that have no effect on the emulation, but can be read back.
+    options: RefCell<BTreeMap<(i32, i32), i32>>,
+    /// The threads that are blocked on an operation on this socket.
+    blocked_threads: RefCell<Vec<ThreadId>>,
+}
+
+impl InetSocket {
+    fn new(is_ipv6: bool, kind: SocketKind, host: bool, state: SocketState) -> Self {
+        InetSocket {
+            is_ipv6,
+            kind,
+            host,
+            state: RefCell::new(state),
+            is_nonblock: Cell::new(false),
+            only_v6: Cell::new(false),
+            read_timeout: Cell::new(None),
+            write_timeout: Cell::new(None),
+            options: RefCell::new(BTreeMap::new()),
+            blocked_threads: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn unspecified_addr(&self) -> SocketAddr {
+        if self.is_ipv6 {
+            SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0)
+        } else {
+            SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &*self.state.borrow() {
+            SocketState::Idle { local } => Ok(local.unwrap_or_else(|| self.unspecified_addr())),
+            SocketState::Listening(listener) => Ok(listener.local),
+            SocketState::Connected(stream) => Ok(stream.local),
+            SocketState::Datagram(socket) => Ok(socket.local),
+            SocketState::HostListener(listener) => listener.local_addr(),
+            SocketState::HostStream(stream) => stream.local_addr(),
+            SocketState::HostDatagram(socket) => socket.local_addr(),
+        }
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr, IoError> {
+        match &*self.state.borrow() {
+            SocketState::Connected(stream) => Ok(stream.peer_addr),
+            SocketState::Datagram(DatagramSocket { connected: Some(peer), .. }) => Ok(*peer),
+            SocketState::HostStream(stream) => Ok(stream.peer_addr()?),
+            SocketState::HostDatagram(socket) => Ok(socket.peer_addr()?),
+            _ => Err(LibcError("ENOTCONN")),
+        }
+    }
+}
+
+impl FileDescription for InetSocket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        // We only check the status of EPOLLIN, EPOLLOUT, EPOLLHUP and EPOLLRDHUP flags.
+        let mut epoll_ready_events = EpollReadyEvents::new();
+        match &*self.state.borrow() {
+            SocketState::Idle { .. } => {
+                // An unconnected stream socket reports a hangup, like on Linux. An unbound
+                // datagram socket can be written to.
+                epoll_ready_events.epollout = true;
+                epoll_ready_events.epollhup = self.kind == SocketKind::Stream;
+            }
+            SocketState::Listening(listener) => {
+                epoll_ready_events.epollin = !listener.backlog.is_empty();
+            }
+            SocketState::Connected(stream) => {
+                epoll_ready_events.epollin =
+                    !stream.readbuf.buf.is_empty() || stream.eof || stream.read_shutdown;
+                epoll_ready_events.epollrdhup = stream.eof;
+                match stream.peer.upgrade() {
+                    Some(peer) => {
+                        let peer = peer.downcast::<InetSocket>().unwrap();
+                        if let SocketState::Connected(peer_stream) = &*peer.state.borrow() {
+                            epoll_ready_events.epollout = stream.write_shutdown
+                                || peer_stream.readbuf.buf.len() < MAX_SOCKET_BUFFER_CAPACITY;
+                        }
+                    }
+                    None => {
+                        // Writes will fail with EPIPE, so they won't block.
+                        epoll_ready_events.epollout = true;
+                        epoll_ready_events.epollhup = true;
+                        epoll_ready_events.epollerr = stream.peer_lost_data;
+                    }
+                }
+            }
+            SocketState::Datagram(socket) => {
+                epoll_ready_events.epollin = !socket.queue.is_empty();
+                epoll_ready_events.epollout = true;
+            }
+            SocketState::HostListener(listener) => return host_ready_events(listener),
+            SocketState::HostStream(stream) => return host_ready_events(stream),
+            SocketState::HostDatagram(socket) => return host_ready_events(socket),
+        }
+        interp_ok(epoll_ready_events)
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        // Other threads may still be blocked on this socket; they will see that it is gone.
+        let blocked_threads = self.blocked_threads.take();
+        for thread in blocked_threads {
+            ecx.unblock_thread(thread, BlockReason::Socket)?;
+        }
+        ecx.machine.network.remove_closed();
+        match self.state.into_inner() {
+            SocketState::Listening(listener) =>
+                for (fd, _clock) in listener.backlog {
+                    // Connections that were never accepted get closed, so their peers see EOF.
+                    fd.close(ecx.machine.communicate(), ecx)?.ok();
+                },
+            SocketState::Connected(stream) =>
+                if let Some(peer) = stream.peer.upgrade() {
+                    if let SocketState::Connected(peer_stream) =
+                        &mut *peer.downcast::<InetSocket>().unwrap().state.borrow_mut()
+                    {
+                        peer_stream.eof = true;
+                        peer_stream.peer_lost_data = !stream.readbuf.buf.is_empty();
+                    }
+                    ecx.notify_socket(&peer)?;
+                },
+            _ => {}
+        }
+        interp_ok(Ok(()))
+    }
+
+    fn read<'tcx>(
+        &self,
+        self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let op = SocketOp::Recv { buf: ptr, len, flags: 0, from: None };
+        ecx.socket_op(self_ref.downgrade(), op, dest.clone())
+    }
+
+    fn write<'tcx>(
+        &self,
+        self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let op = SocketOp::Send { buf: ptr, len, flags: 0, to: None };
+        ecx.socket_op(self_ref.downgrade(), op, dest.clone())
+    }
+}
+
+/// A socket operation that may block, with everything needed to retry it once it can make
+/// progress.
+#[derive(Debug, Clone)]
+enum SocketOp {
+    Accept { addr: Pointer, addrlen: Pointer, flags: i32 },
+    Recv { buf: Pointer, len: usize, flags: i32, from: Option<(Pointer, Pointer)> },
+    Send { buf: Pointer, len: usize, flags: i32, to: Option<SocketAddr> },
+}
+
+impl VisitProvenance for SocketOp {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        match self {
+            SocketOp::Accept { addr, addrlen, .. } => {
+                addr.visit_provenance(visit);
+                addrlen.visit_provenance(visit);
+            }
+            SocketOp::Recv { buf, from, .. } => {
+                buf.visit_provenance(visit);
+                from.visit_provenance(visit);
+            }
+            SocketOp::Send { buf, .. } => buf.visit_provenance(visit),
+        }
+    }
+}
+
+/// Whether an attempted operation completed, in which case its result has been written.
+enum Progress {
+    Done,
+    WouldBlock,
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns the socket with the given file descriptor, or the error to report.
+    fn get_socket(&self, fd_num: i32) -> Result<FileDescriptionRef, IoError> {
+        let this = self.eval_context_ref();
+        let fd = this.machine.fds.get(fd_num).ok_or(LibcError("EBADF"))?;
+        if fd.downcast::<InetSocket>().is_none() {
+            return Err(LibcError("ENOTSOCK"));
+        }
+        Ok(fd)
+    }
+
+    /// Wakes up the threads blocked on `fd` and updates its epoll readiness.
+    fn notify_socket(&mut self, fd: &FileDescriptionRef) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let blocked_threads = fd.downcast::<InetSocket>().unwrap().blocked_threads.take();
+        for thread in blocked_threads {
+            this.unblock_thread(thread, BlockReason::Socket)?;
+        }
+        this.check_and_update_readiness(fd)
+    }
+
+    /// Performs `op`, blocking the current thread until it can make progress unless the socket
+    /// is non-blocking.
+    fn socket_op(
+        &mut self,
+        weak_fd: WeakFileDescriptionRef,
+        op: SocketOp,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let Some(fd) = weak_fd.upgrade() else {
+            // The socket was closed while we were blocked on it.
+            return this.set_last_error_and_return(LibcError("EBADF"), &dest);
+        };
+        let progress = match &op {
+            SocketOp::Accept { addr, addrlen, flags } =>
+                this.try_accept(&fd, *addr, *addrlen, *flags, &dest)?,
+            SocketOp::Recv { buf, len, flags, from } =>
+                this.try_recv(&fd, *buf, *len, *flags, *from, &dest)?,
+            SocketOp::Send { buf, len, flags, to } =>
+                this.try_send(&fd, *buf, *len, *flags, *to, &dest)?,
+        };
+        let Progress::WouldBlock = progress else { return interp_ok(()) };
+
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let (flags, timeout) = match op {
+            SocketOp::Accept { .. } => (0, socket.read_timeout.get()),
+            SocketOp::Recv { flags, .. } => (flags, socket.read_timeout.get()),
+            SocketOp::Send { flags, .. } => (flags, socket.write_timeout.get()),
+        };
+        if socket.is_nonblock.get() || flags & this.eval_libc_i32("MSG_DONTWAIT") != 0 {
+            return this.set_last_error_and_return(ErrorKind::WouldBlock, &dest);
+        }
+        // Host sockets cannot wake us up, so we poll them instead.
+        // FIXME: this ignores the timeouts set on host sockets.
+        let host = socket.state.borrow().is_host();
+        let timeout = if host { Some(HOST_POLL_INTERVAL) } else { timeout };
+        if !host {
+            socket.blocked_threads.borrow_mut().push(this.active_thread());
+        }
+        drop(fd);
+        this.block_thread(
+            BlockReason::Socket,
+            timeout.map(|duration| (TimeoutClock::Monotonic, TimeoutAnchor::Relative, duration)),
+            callback!(
+                @capture<'tcx> {
+                    weak_fd: WeakFileDescriptionRef,
+                    op: SocketOp,
+                    dest: MPlaceTy<'tcx>,
+                    host: bool,
+                }
+                @unblock = |this| {
+                    this.socket_op(weak_fd, op, dest)
+                }
+                @timeout = |this| {
+                    if host {
+                        return this.socket_op(weak_fd, op, dest);
+                    }
+                    if let Some(fd) = weak_fd.upgrade() {
+                        fd.downcast::<InetSocket>()
+                            .unwrap()
+                            .blocked_threads
+                            .borrow_mut()
+                            .retain(|&id| id != this.active_thread());
+                    }
+                    this.set_last_error_and_return(ErrorKind::WouldBlock, &dest)
+                }
+            ),
+        );
+        interp_ok(())
+    }
+
+    fn try_accept(
+        &mut self,
+        fd: &FileDescriptionRef,
+        addr: Pointer,
+        addrlen: Pointer,
+        flags: i32,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, Progress> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<InetSocket>().unwrap();
+
+        let (new_fd, peer_addr) = match &mut *socket.state.borrow_mut() {
+            SocketState::Listening(listener) => {
+                let Some((new_fd, clock)) = listener.backlog.pop_front() else {
+                    return interp_ok(Progress::WouldBlock);
+                };
+                // Synchronize with the thread that connected.
+                this.acquire_clock(&clock);
+                let peer_addr = new_fd.downcast::<InetSocket>().unwrap().peer_addr().unwrap();
+                (new_fd, peer_addr)
+            }
+            SocketState::HostListener(listener) =>
+                match listener.accept() {
+                    Ok((stream, peer_addr)) => {
+                        stream.set_nonblocking(true).unwrap();
+                        let new_socket = InetSocket::new(
+                            peer_addr.is_ipv6(),
+                            SocketKind::Stream,
+                            true,
+                            SocketState::HostStream(stream),
+                        );
+                        (this.machine.fds.new_ref(new_socket), peer_addr)
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock =>
+                        return interp_ok(Progress::WouldBlock),
+                    Err(e) => {
+                        this.set_last_error_and_return(e, dest)?;
+                        return interp_ok(Progress::Done);
+                    }
+                },
+            _ => {
+                this.set_last_error_and_return(LibcError("EINVAL"), dest)?;
+                return interp_ok(Progress::Done);
+            }
+        };
+
+        new_fd.downcast::<InetSocket>().unwrap().is_nonblock.set(flags & sock_nonblock(this) != 0);
+        // Accepting frees up space in the backlog.
+        this.check_and_update_readiness(fd)?;
+        let new_fd_num = this.machine.fds.insert(new_fd);
+        if !this.ptr_is_null(addr)? {
+            this.write_socket_address(peer_addr, addr, addrlen)?;
+        }
+        this.write_int(new_fd_num, dest)?;
+        interp_ok(Progress::Done)
+    }
+
+    fn try_recv(
+        &mut self,
+        fd: &FileDescriptionRef,
+        buf: Pointer,
+        len: usize,
+        flags: i32,
+        from: Option<(Pointer, Pointer)>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, Progress> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let peek = flags & this.eval_libc_i32("MSG_PEEK") != 0;
+        let mut bytes = vec![0; len];
+
+        let mut state = socket.state.borrow_mut();
+        let (read_size, from_addr, notify) = match &mut *state {
+            SocketState::Connected(stream) => {
+                if len == 0 {
+                    // Always succeed on read size 0.
+                    (0, Some(stream.peer_addr), None)
+                } else if stream.readbuf.buf.is_empty() {
+                    if !(stream.eof || stream.read_shutdown) {
+                        return interp_ok(Progress::WouldBlock);
+                    }
+                    // 0 bytes successfully read indicates end-of-file.
+                    (0, Some(stream.peer_addr), None)
+                } else {
+                    // Synchronize with all previous writes to this buffer.
+                    // FIXME: this over-synchronizes, like for socketpairs.
+                    this.acquire_clock(&stream.readbuf.clock);
+                    // Like `read` on the `VecDeque`, peeking only looks at the first contiguous part.
+                    let read_size = if peek {
+                        let (mut front, _) = stream.readbuf.buf.as_slices();
+                        front.read(&mut bytes).unwrap()
+                    } else {
+                        stream.readbuf.buf.read(&mut bytes).unwrap()
+                    };
+                    // Reading frees up space for the peer to write.
+                    let notify = if peek { None } else { stream.peer.upgrade() };
+                    (read_size, Some(stream.peer_addr), notify)
+                }
+            }
+            SocketState::Datagram(receiver) => {
+                let Some(datagram) = receiver.queue.front() else {
+                    return interp_ok(Progress::WouldBlock);
+                };
+                this.acquire_clock(&datagram.clock);
+                // Whatever does not fit into the buffer is discarded.
+                let read_size = datagram.data.len().min(len);
+                bytes[..read_size].copy_from_slice(&datagram.data[..read_size]);
+                let from_addr = datagram.from;
+                if !peek {
+                    let datagram = receiver.queue.pop_front().unwrap();
+                    receiver.queued_bytes = receiver.queued_bytes.strict_sub(datagram.data.len());
+                }
+                (read_size, Some(from_addr), None)
+            }
+            SocketState::HostStream(stream) => {
+                // The host fails to report the peer address once the peer has disconnected
+                // (usually with `ENOTCONN`). Only ask for it if the program wants to know, and
+                // before reading so that no data is lost when that happens.
+                let peer_addr = match from.map(|_| stream.peer_addr()).transpose() {
+                    Ok(peer_addr) => peer_addr,
+                    Err(e) => {
+                        this.set_last_error_and_return(e, dest)?;
+                        return interp_ok(Progress::Done);
+                    }
+                };
+                let result = if peek { stream.peek(&mut bytes) } else { stream.read(&mut bytes) };
+                match result {
+                    Ok(read_size) => (read_size, peer_addr, None),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock =>
+                        return interp_ok(Progress::WouldBlock),
+                    Err(e) => {
+                        this.set_last_error_and_return(e, dest)?;
+                        return interp_ok(Progress::Done);
+                    }
+                }
+            }
+            SocketState::HostDatagram(socket) => {
+                let result =
+                    if peek { socket.peek_from(&mut bytes) } else { socket.recv_from(&mut bytes) };
+                match result {
+                    Ok((read_size, from_addr)) => (read_size, Some(from_addr), None),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock =>
+                        return interp_ok(Progress::WouldBlock),
+                    Err(e) => {
+                        this.set_last_error_and_return(e, dest)?;
+                        return interp_ok(Progress::Done);
+                    }
+                }
+            }
+            SocketState::Idle { .. } if socket.kind == SocketKind::Datagram => {
+                // Nobody can send anything to a socket without an address.
+                return interp_ok(Progress::WouldBlock);
+            }
+            SocketState::Idle { .. } | SocketState::Listening(_) | SocketState::HostListener(_) => {
+                this.set_last_error_and_return(LibcError("ENOTCONN"), dest)?;
+                return interp_ok(Progress::Done);
+            }
+        };
+        drop(state);
+
+        if let Some(peer) = notify {
+            this.notify_socket(&peer)?;
+        }
+        if let (Some((addr, addrlen)), Some(from_addr)) = (from, from_addr) {
+            if !this.ptr_is_null(addr)? {
+                this.write_socket_address(from_addr, addr, addrlen)?;
+            }
+        }
+        this.return_read_success(buf, &bytes, read_size, dest)?;
+        interp_ok(Progress::Done)
+    }
+
+    fn try_send(
+        &mut self,
+        fd: &FileDescriptionRef,
+        buf: Pointer,
+        len: usize,
+        _flags: i32,
+        to: Option<SocketAddr>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, Progress> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?.to_vec();
+
+        if socket.kind == SocketKind::Datagram {
+            let is_idle = matches!(&*socket.state.borrow(), SocketState::Idle { .. });
+            if is_idle {
+                // Sending from an unbound datagram socket binds it to an ephemeral port.
+                if let Err(e) = this.bind_socket(fd, socket.unspecified_addr()) {
+                    this.set_last_error_and_return(e, dest)?;
+                    return interp_ok(Progress::Done);
+                }
+            }
+        }
+
+        // Never raise SIGPIPE: that is what `MSG_NOSIGNAL` asks for, and what the standard library
+        // arranges for on targets where that flag does not exist.
+        let mut state = socket.state.borrow_mut();
+        let result: Result<usize, IoError> = match &mut *state {
+            SocketState::Connected(stream) => {
+                if len == 0 {
+                    Ok(0)
+                } else if stream.write_shutdown {
+                    Err(LibcError("EPIPE"))
+                } else if let Some(peer) = stream.peer.upgrade() {
+                    let peer_socket = peer.downcast::<InetSocket>().unwrap();
+                    let mut peer_state = peer_socket.state.borrow_mut();
+                    let SocketState::Connected(peer_stream) = &mut *peer_state else {
+                        unreachable!("the peer of a connected socket is always connected")
+                    };
+                    let available_space =
+                        MAX_SOCKET_BUFFER_CAPACITY.strict_sub(peer_stream.readbuf.buf.len());
+                    if available_space == 0 {
+                        return interp_ok(Progress::WouldBlock);
+                    }
+                    // Remember this clock so `recv` can synchronize with us.
+                    this.release_clock(|clock| peer_stream.readbuf.clock.join(clock));
+                    let write_size = len.min(available_space);
+                    peer_stream.readbuf.buf.extend(&bytes[..write_size]);
+                    drop(peer_state);
+                    drop(state);
+                    this.notify_socket(&peer)?;
+                    this.write_int(u64::try_from(write_size).unwrap(), dest)?;
+                    return interp_ok(Progress::Done);
+                } else {
+                    Err(LibcError("EPIPE"))
+                }
+            }
+            SocketState::Datagram(sender) =>
+                match to.or(sender.connected) {
+                    None => Err(LibcError("EDESTADDRREQ")),
+                    Some(_) if len > MAX_DATAGRAM_SIZE => Err(LibcError("EMSGSIZE")),
+                    Some(to) => {
+                        let from = if sender.local.ip().is_unspecified() {
+                            SocketAddr::new(loopback(to.ip()), sender.local.port())
+                        } else {
+                            sender.local
+                        };
+                        drop(state);
+                        this.deliver_datagram(from, to, bytes)?;
+                        Ok(len)
+                    }
+                },
+            SocketState::HostStream(stream) =>
+                match stream.write(&bytes) {
+                    Ok(write_size) => Ok(write_size),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock =>
+                        return interp_ok(Progress::WouldBlock),
+                    Err(e) => Err(e.into()),
+                },
+            SocketState::HostDatagram(socket) => {
+                let result = match to {
+                    Some(to) => socket.send_to(&bytes, to),
+                    None => socket.send(&bytes),
+                };
+                match result {
+                    Ok(write_size) => Ok(write_size),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock =>
+                        return interp_ok(Progress::WouldBlock),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            SocketState::Idle { .. } | SocketState::Listening(_) | SocketState::HostListener(_) =>
+                Err(LibcError("ENOTCONN")),
+        };
+
+        match result {
+            Ok(write_size) => this.write_int(u64::try_from(write_size).unwrap(), dest)?,
+            Err(e) => this.set_last_error_and_return(e, dest)?,
+        }
+        interp_ok(Progress::Done)
+    }
+
+    /// Queues a datagram on the virtual socket that receives traffic for `to`. Like on a real
+    /// network, the datagram is silently dropped if there is no such socket or its queue is full.
+    fn deliver_datagram(
+        &mut self,
+        from: SocketAddr,
+        to: SocketAddr,
+        data: Vec<u8>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let Some(dest_fd) = this.machine.network.lookup(SocketKind::Datagram, to) else {
+            return interp_ok(());
+        };
+        let dest_socket = dest_fd.downcast::<InetSocket>().unwrap();
+        let mut state = dest_socket.state.borrow_mut();
+        let SocketState::Datagram(receiver) = &mut *state else { unreachable!() };
+        let Some(queued_bytes) = receiver.queued_bytes.checked_add(data.len()) else {
+            return interp_ok(());
+        };
+        if queued_bytes > MAX_SOCKET_BUFFER_CAPACITY {
+            return interp_ok(());
+        }
+        let mut clock = VClock::default();
+        this.release_clock(|release| clock.clone_from(release));
+        receiver.queued_bytes = queued_bytes;
+        receiver.queue.push_back(Datagram { from, data, clock });
+        drop(state);
+        this.notify_socket(&dest_fd)
+    }
+
+    /// Gives a virtual socket a local address, picking an ephemeral port if `addr` has port 0.
+    fn bind_socket(&mut self, fd: &FileDescriptionRef, addr: SocketAddr) -> Result<(), IoError> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let only_v6 = socket.only_v6.get();
+        if !(addr.ip().is_loopback() || addr.ip().is_unspecified()) {
+            return Err(LibcError("EADDRNOTAVAIL"));
+        }
+        let addr = if addr.port() == 0 {
+            let port = this
+                .machine
+                .network
+                .ephemeral_port(socket.kind, addr.ip(), only_v6)
+                .ok_or(LibcError("EADDRINUSE"))?;
+            SocketAddr::new(addr.ip(), port)
+        } else if this.machine.network.is_available(socket.kind, addr, only_v6) {
+            addr
+        } else {
+            return Err(LibcError("EADDRINUSE"));
+        };
+        this.machine.network.insert(socket.kind, addr, fd);
+        *socket.state.borrow_mut() = match socket.kind {
+            SocketKind::Stream => SocketState::Idle { local: Some(addr) },
+            SocketKind::Datagram =>
+                SocketState::Datagram(DatagramSocket {
+                    local: addr,
+                    connected: None,
+                    queue: VecDeque::new(),
+                    queued_bytes: 0,
+                }),
+        };
+        Ok(())
+    }
+
+    /// Connects a virtual stream socket to the listener at `dest`.
+    fn connect_stream(&mut self, fd: &FileDescriptionRef, dest: SocketAddr) -> Result<(), IoError> {
+        let this = self.eval_context_mut();
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let SocketState::Idle { local } = *socket.state.borrow() else {
+            return Err(LibcError("EISCONN"));
+        };
+        let Some(listener_fd) = this.machine.network.lookup(SocketKind::Stream, dest) else {
+            return Err(LibcError("ECONNREFUSED"));
+        };
+        let local = match local {
+            Some(local) if local.ip().is_unspecified() =>
+                SocketAddr::new(loopback(dest.ip()), local.port()),
+            Some(local) => local,
+            None => {
+                let ip = loopback(dest.ip());
+                let port = this
+                    .machine
+                    .network
+                    .ephemeral_port(SocketKind::Stream, ip, socket.only_v6.get())
+                    .ok_or(LibcError("EADDRNOTAVAIL"))?;
+                let local = SocketAddr::new(ip, port);
+                this.machine.network.insert(SocketKind::Stream, local, fd);
+                local
+            }
+        };
+
+        let listener_socket = listener_fd.downcast::<InetSocket>().unwrap();
+        let server_end = this.machine.fds.new_ref(InetSocket::new(
+            listener_socket.is_ipv6,
+            SocketKind::Stream,
+            false,
+            SocketState::Connected(Stream::new(dest, local, fd.downgrade())),
+        ));
+        *socket.state.borrow_mut() =
+            SocketState::Connected(Stream::new(local, dest, server_end.downgrade()));
+
+        let mut clock = VClock::default();
+        this.release_clock(|release| clock.clone_from(release));
+        let mut listener_state = listener_socket.state.borrow_mut();
+        let SocketState::Listening(listener) = &mut *listener_state else { unreachable!() };
+        listener.backlog.push_back((server_end, clock));
+        Ok(())
+    }
+
+    /// Reads a `sockaddr_in` or `sockaddr_in6` of `len` bytes.
+    fn read_socket_address(
+        &self,
+        addr: Pointer,
+        len: u64,
+        is_ipv6: bool,
+    ) -> InterpResult<'tcx, Result<SocketAddr, IoError>> {
+        let this = self.eval_context_ref();
+        let sockaddr = this.ptr_to_mplace(addr, this.libc_ty_layout("sockaddr"));
+        if len < sockaddr.layout.size.bytes() {
+            return interp_ok(Err(LibcError("EINVAL")));
+        }
+        let family_place = this.project_field_named(&sockaddr, "sa_family")?;
+        let family = this.read_scalar(&family_place)?.to_uint(family_place.layout.size)?;
+        let family = i32::try_from(family).unwrap();
+
+        let expected_family = if is_ipv6 { "AF_INET6" } else { "AF_INET" };
+        if family != this.eval_libc_i32(expected_family) {
+            return interp_ok(Err(LibcError("EAFNOSUPPORT")));
+        }
+
+        let addr = if is_ipv6 {
+            let place = this.ptr_to_mplace(addr, this.libc_ty_layout("sockaddr_in6"));
+            if len < place.layout.size.bytes() {
+                return interp_ok(Err(LibcError("EINVAL")));
+            }
+            let port = this.project_field_named(&place, "sin6_port")?;
+            let port = this.read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?;
+            let port = u16::from_be_bytes(port.try_into().unwrap());
+            let ip = this.project_field_named(&place, "sin6_addr")?;
+            let ip = this.read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(16))?;
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(ip).unwrap());
+            let flowinfo = this.project_field_named(&place, "sin6_flowinfo")?;
+            let flowinfo = this.read_scalar(&flowinfo)?.to_u32()?;
+            let scope_id = this.project_field_named(&place, "sin6_scope_id")?;
+            let scope_id = this.read_scalar(&scope_id)?.to_u32()?;
+            SocketAddr::V6(SocketAddrV6::new(ip, port, flowinfo, scope_id))
+        } else {
+            let place = this.ptr_to_mplace(addr, this.libc_ty_layout("sockaddr_in"));
+            if len < place.layout.size.bytes() {
+                return interp_ok(Err(LibcError("EINVAL")));
+            }
+            let port = this.project_field_named(&place, "sin_port")?;
+            let port = this.read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?;
+            let port = u16::from_be_bytes(port.try_into().unwrap());
+            let ip = this.project_field_named(&place, "sin_addr")?;
+            let ip = this.read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(4))?;
+            let ip = Ipv4Addr::from(<[u8; 4]>::try_from(ip).unwrap());
+            SocketAddr::V4(SocketAddrV4::new(ip, port))
+        };
+        if let SocketAddr::V6(v6) = addr {
+            if v6.ip().to_ipv4_mapped().is_some() {
+                throw_unsup_format!("IPv4-mapped IPv6 socket addresses are not supported");
+            }
+        }
+        interp_ok(Ok(addr))
+    }
+
+    /// Writes `addr` as a `sockaddr_in` or `sockaddr_in6` to `ptr`, and its size to `len_ptr`.
+    fn write_socket_address(
+        &mut self,
+        addr: SocketAddr,
+        ptr: Pointer,
+        len_ptr: Pointer,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let len_place = this.ptr_to_mplace(len_ptr, this.libc_ty_layout("socklen_t"));
+        let len = this.read_scalar(&len_place)?.to_u32()?;
+
+        let layout = match addr {
+            SocketAddr::V4(_) => this.libc_ty_layout("sockaddr_in"),
+            SocketAddr::V6(_) => this.libc_ty_layout("sockaddr_in6"),
+        };
+        let size = layout.size.bytes();
+        if u64::from(len) < size {
+            throw_unsup_format!("truncating socket addresses is not supported");
+        }
+        let place = this.ptr_to_mplace(ptr, layout);
+        this.write_bytes_ptr(ptr, std::iter::repeat(0u8).take(usize::try_from(size).unwrap()))?;
+
+        match addr {
+            SocketAddr::V4(v4) => {
+                // BSD-like systems have a length field.
+                if this.projectable_has_field(&place, "sin_len") {
+                    this.write_int_fields_named(&[("sin_len", size.into())], &place)?;
+                }
+                let family = this.eval_libc_i32("AF_INET");
+                this.write_int_fields_named(&[("sin_family", family.into())], &place)?;
+                let port = this.project_field_named(&place, "sin_port")?;
+                this.write_bytes_ptr(port.ptr(), v4.port().to_be_bytes())?;
+                let ip = this.project_field_named(&place, "sin_addr")?;
+                this.write_bytes_ptr(ip.ptr(), v4.ip().octets())?;
+            }
+            SocketAddr::V6(v6) => {
+                if this.projectable_has_field(&place, "sin6_len") {
+                    this.write_int_fields_named(&[("sin6_len", size.into())], &place)?;
+                }
+                let family = this.eval_libc_i32("AF_INET6");
+                this.write_int_fields_named(
+                    &[
+                        ("sin6_family", family.into()),
+                        ("sin6_flowinfo", v6.flowinfo().into()),
+                        ("sin6_scope_id", v6.scope_id().into()),
+                    ],
+                    &place,
+                )?;
+                let port = this.project_field_named(&place, "sin6_port")?;
+                this.write_bytes_ptr(port.ptr(), v6.port().to_be_bytes())?;
+                let ip = this.project_field_named(&place, "sin6_addr")?;
+                this.write_bytes_ptr(ip.ptr(), v6.ip().octets())?;
+            }
+        }
+        this.write_int(size, &len_place)
+    }
+
+    /// Reads the `struct timeval` of a timeout option. A zero timeout means "no timeout".
+    fn read_timeval_option(&mut self, value: Pointer) -> InterpResult<'tcx, Option<Duration>> {
+        let this = self.eval_context_mut();
+        let timeval = this.ptr_to_mplace(value, this.libc_ty_layout("timeval"));
+        let seconds = this.project_field_named(&timeval, "tv_sec")?;
+        let seconds = this.read_scalar(&seconds)?.to_int(seconds.layout.size)?;
+        let micros = this.project_field_named(&timeval, "tv_usec")?;
+        let micros = this.read_scalar(&micros)?.to_int(micros.layout.size)?;
+        let (Ok(seconds), Ok(micros)) = (u64::try_from(seconds), u32::try_from(micros)) else {
+            throw_unsup_format!("negative socket timeouts are not supported");
+        };
+        let duration =
+            Duration::from_secs(seconds).saturating_add(Duration::from_micros(u64::from(micros)));
+        interp_ok(if duration.is_zero() { None } else { Some(duration) })
+    }
+
+    /// The integer socket options we accept. None of them change how sockets behave.
+    fn int_socket_options(&self) -> Vec<((i32, i32), i32)> {
+        let this = self.eval_context_ref();
+        let os = this.tcx.sess.target.os.as_ref();
+        let sol_socket = this.eval_libc_i32("SOL_SOCKET");
+        let ipproto_tcp = this.eval_libc_i32("IPPROTO_TCP");
+        let ipproto_ip = this.eval_libc_i32("IPPROTO_IP");
+        let buffer_size = i32::try_from(MAX_SOCKET_BUFFER_CAPACITY).unwrap();
+        // Options with their default value.
+        let mut options = vec![
+            ((sol_socket, this.eval_libc_i32("SO_REUSEADDR")), 0),
+            ((sol_socket, this.eval_libc_i32("SO_KEEPALIVE")), 0),
+            ((sol_socket, this.eval_libc_i32("SO_BROADCAST")), 0),
+            ((sol_socket, this.eval_libc_i32("SO_SNDBUF")), buffer_size),
+            ((sol_socket, this.eval_libc_i32("SO_RCVBUF")), buffer_size),
+            ((ipproto_tcp, this.eval_libc_i32("TCP_NODELAY")), 0),
+            ((ipproto_ip, this.eval_libc_i32("IP_TTL")), 64),
+        ];
+        if matches!(os, "linux" | "android" | "macos" | "freebsd") {
+            options.push(((sol_socket, this.eval_libc_i32("SO_REUSEPORT")), 0));
+        }
+        if matches!(os, "linux" | "android") {
+            options.push(((ipproto_tcp, this.eval_libc_i32("TCP_KEEPIDLE")), 7200));
+            options.push(((ipproto_tcp, this.eval_libc_i32("TCP_KEEPINTVL")), 75));
+            options.push(((ipproto_tcp, this.eval_libc_i32("TCP_KEEPCNT")), 9));
+        }
+        if matches!(os, "macos" | "freebsd") {
+            options.push(((sol_socket, this.eval_libc_i32("SO_NOSIGPIPE")), 0));
+        }
+        options
+    }
+}
+
+impl Stream {
+    fn new(local: SocketAddr, peer_addr: SocketAddr, peer: WeakFileDescriptionRef) -> Self {
+        Stream {
+            local,
+            peer_addr,
+            peer,
+            readbuf: Buffer::default(),
+            eof: false,
+            peer_lost_data: false,
+            read_shutdown: false,
+            write_shutdown: false,
+        }
+    }
+}
+
+/// The `SOCK_NONBLOCK` flag, or 0 on targets that do not have it.
+fn sock_nonblock(ecx: &MiriInterpCx<'_>) -> i32 {
+    if matches!(&*ecx.tcx.sess.target.os, "linux" | "android" | "freebsd") {
+        ecx.eval_libc_i32("SOCK_NONBLOCK")
+    } else {
+        0
+    }
+}
+
+/// The `SOCK_CLOEXEC` flag, or 0 on targets that do not have it.
+fn sock_cloexec(ecx: &MiriInterpCx<'_>) -> i32 {
+    if matches!(&*ecx.tcx.sess.target.os, "linux" | "android" | "freebsd") {
+        ecx.eval_libc_i32("SOCK_CLOEXEC")
+    } else {
+        0
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// For more information on the arguments see the socket manpage:
+    /// <https://man7.org/linux/man-pages/man2/socket.2.html>
+    fn socket(
+        &mut self,
+        domain: &OpTy<'tcx>,
+        type_: &OpTy<'tcx>,
+        protocol: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut flags = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+
+        // Interpret the flag. Every flag we recognize is "subtracted" from `flags`, so
+        // if there is anything left at the end, that's an unsupported flag.
+        let sock_nonblock = sock_nonblock(this);
+        let sock_cloexec = sock_cloexec(this);
+        let mut is_nonblock = false;
+        if sock_nonblock != 0 && flags & sock_nonblock == sock_nonblock {
+            is_nonblock = true;
+            flags &= !sock_nonblock;
+        }
+        // As usual we ignore CLOEXEC.
+        if sock_cloexec != 0 && flags & sock_cloexec == sock_cloexec {
+            flags &= !sock_cloexec;
+        }
+
+        let is_ipv6 = if domain == this.eval_libc_i32("AF_INET") {
+            false
+        } else if domain == this.eval_libc_i32("AF_INET6") {
+            true
+        } else {
+            throw_unsup_format!(
+                "socket: domain {domain:#x} is unsupported, only AF_INET and AF_INET6 are allowed"
+            );
+        };
+        let (kind, expected_protocol) = if flags == this.eval_libc_i32("SOCK_STREAM") {
+            (SocketKind::Stream, this.eval_libc_i32("IPPROTO_TCP"))
+        } else if flags == this.eval_libc_i32("SOCK_DGRAM") {
+            (SocketKind::Datagram, this.eval_libc_i32("IPPROTO_UDP"))
+        } else {
+            throw_unsup_format!(
+                "socket: type {flags:#x} is unsupported, only SOCK_STREAM, SOCK_DGRAM, \
+                 SOCK_CLOEXEC and SOCK_NONBLOCK are allowed"
+            );
+        };
+        if protocol != 0 && protocol != expected_protocol {
+            throw_unsup_format!("socket: protocol {protocol} is unsupported for this socket type");
+        }
+
+        let host = this.machine.communicate();
+        let socket = InetSocket::new(is_ipv6, kind, host, SocketState::Idle { local: None });
+        socket.is_nonblock.set(is_nonblock);
+        interp_ok(Scalar::from_i32(this.machine.fds.insert_new(socket)))
+    }
+
+    fn bind(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_scalar(address_len)?.to_u32()?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let addr = match this.read_socket_address(address, address_len.into(), socket.is_ipv6)? {
+            Ok(addr) => addr,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        if !matches!(&*socket.state.borrow(), SocketState::Idle { local: None }) {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        let result = if socket.host {
+            match socket.kind {
+                // We only know whether this will become a listener once `listen` is called.
+                SocketKind::Stream => {
+                    *socket.state.borrow_mut() = SocketState::Idle { local: Some(addr) };
+                    Ok(())
+                }
+                SocketKind::Datagram =>
+                    net::UdpSocket::bind(addr)
+                        .map(|host_socket| {
+                            host_socket.set_nonblocking(true).unwrap();
+                            *socket.state.borrow_mut() = SocketState::HostDatagram(host_socket);
+                        })
+                        .map_err(IoError::from),
+            }
+        } else {
+            this.bind_socket(&fd, addr)
+        };
+        match result {
+            Ok(()) => interp_ok(Scalar::from_i32(0)),
+            Err(e) => this.set_last_error_and_return_i32(e),
+        }
+    }
+
+    fn listen(&mut self, socket: &OpTy<'tcx>, backlog: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        // The backlog is ignored: any number of connections can be pending on the virtual network,
+        // and host listeners use the default backlog of std.
+        let _backlog = this.read_scalar(backlog)?.to_i32()?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        if socket.kind != SocketKind::Stream {
+            return this.set_last_error_and_return_i32(LibcError("EOPNOTSUPP"));
+        }
+        let local = match &*socket.state.borrow() {
+            SocketState::Idle { local } => *local,
+            SocketState::Listening(_) | SocketState::HostListener(_) =>
+                return interp_ok(Scalar::from_i32(0)),
+            _ => return this.set_last_error_and_return_i32(LibcError("EINVAL")),
+        };
+
+        if socket.host {
+            let addr = local.unwrap_or_else(|| socket.unspecified_addr());
+            match net::TcpListener::bind(addr) {
+                Ok(listener) => {
+                    listener.set_nonblocking(true).unwrap();
+                    *socket.state.borrow_mut() = SocketState::HostListener(listener);
+                }
+                Err(e) => return this.set_last_error_and_return_i32(e),
+            }
+        } else {
+            let local = match local {
+                Some(local) => local,
+                None => {
+                    // Listening on an unbound socket binds it to an ephemeral port.
+                    if let Err(e) = this.bind_socket(&fd, socket.unspecified_addr()) {
+                        return this.set_last_error_and_return_i32(e);
+                    }
+                    socket.local_addr().unwrap()
+                }
+            };
+            *socket.state.borrow_mut() =
+                SocketState::Listening(Listener { local, backlog: VecDeque::new() });
+            this.check_and_update_readiness(&fd)?;
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn connect(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_scalar(address_len)?.to_u32()?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let addr = match this.read_socket_address(address, address_len.into(), socket.is_ipv6)? {
+            Ok(addr) => addr,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+
+        if socket.host {
+            return match connect_host_socket(socket, addr) {
+                Ok(()) => interp_ok(Scalar::from_i32(0)),
+                Err(e) => this.set_last_error_and_return_i32(e),
+            };
+        }
+
+        // Connecting to the unspecified address means connecting to the local host.
+        let addr = if addr.ip().is_unspecified() {
+            SocketAddr::new(loopback(addr.ip()), addr.port())
+        } else {
+            addr
+        };
+        if !addr.ip().is_loopback() {
+            // Only the local host is reachable.
+            return this.set_last_error_and_return_i32(LibcError("ENETUNREACH"));
+        }
+        let result = match socket.kind {
+            SocketKind::Stream => this.connect_stream(&fd, addr),
+            SocketKind::Datagram => {
+                let is_idle = matches!(&*socket.state.borrow(), SocketState::Idle { .. });
+                let result =
+                    if is_idle { this.bind_socket(&fd, socket.unspecified_addr()) } else { Ok(()) };
+                result.map(|()| {
+                    let mut state = socket.state.borrow_mut();
+                    let SocketState::Datagram(datagram) = &mut *state else { unreachable!() };
+                    datagram.connected = Some(addr);
+                })
+            }
+        };
+        if let Err(e) = result {
+            return this.set_last_error_and_return_i32(e);
+        }
+
+        this.check_and_update_readiness(&fd)?;
+        if socket.kind == SocketKind::Stream {
+            // There is a new connection to accept.
+            let listener = this.machine.network.lookup(SocketKind::Stream, addr).unwrap();
+            this.notify_socket(&listener)?;
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn accept(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+        flags: Option<&OpTy<'tcx>>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let addr = this.read_pointer(address)?;
+        let addrlen = this.read_pointer(address_len)?;
+        let mut flags = match flags {
+            Some(flags) => this.read_scalar(flags)?.to_i32()?,
+            None => 0,
+        };
+        // As usual we ignore CLOEXEC.
+        let sock_cloexec = sock_cloexec(this);
+        if sock_cloexec != 0 && flags & sock_cloexec == sock_cloexec {
+            flags &= !sock_cloexec;
+        }
+        if flags & !sock_nonblock(this) != 0 {
+            throw_unsup_format!("accept4: flag {flags:#x} is unsupported");
+        }
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return(e, dest),
+        };
+        this.socket_op(fd.downgrade(), SocketOp::Accept { addr, addrlen, flags }, dest.clone())
+    }
+
+    /// Implements `send`, `sendto`, `recv` and `recvfrom`. `address` is the destination address
+    /// when sending, and where to store the source address when receiving.
+    fn socket_transfer(
+        &mut self,
+        is_send: bool,
+        socket: &OpTy<'tcx>,
+        buf: &OpTy<'tcx>,
+        len: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        address: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        let mut supported_flags = this.eval_libc_i32("MSG_DONTWAIT");
+        if is_send {
+            if matches!(&*this.tcx.sess.target.os, "linux" | "android" | "freebsd") {
+                supported_flags |= this.eval_libc_i32("MSG_NOSIGNAL");
+            }
+        } else {
+            supported_flags |= this.eval_libc_i32("MSG_PEEK");
+        }
+        if flags & !supported_flags != 0 {
+            throw_unsup_format!("send/recv: flag {flags:#x} is unsupported");
+        }
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+        // Like `read` and `write`, cap the size so it fits into host and target `isize`.
+        let len = len
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let len = usize::try_from(len).unwrap();
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return(e, dest),
+        };
+        let op = if is_send {
+            let to = match address {
+                Some((addr, addrlen)) => {
+                    let addr = this.read_pointer(addr)?;
+                    let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+                    if this.ptr_is_null(addr)? {
+                        None
+                    } else {
+                        let is_ipv6 = fd.downcast::<InetSocket>().unwrap().is_ipv6;
+                        match this.read_socket_address(addr, addrlen.into(), is_ipv6)? {
+                            Ok(addr) => Some(addr),
+                            Err(e) => return this.set_last_error_and_return(e, dest),
+                        }
+                    }
+                }
+                None => None,
+            };
+            SocketOp::Send { buf, len, flags, to }
+        } else {
+            let from = match address {
+                Some((addr, addrlen)) =>
+                    Some((this.read_pointer(addr)?, this.read_pointer(addrlen)?)),
+                None => None,
+            };
+            SocketOp::Recv { buf, len, flags, from }
+        };
+        this.socket_op(fd.downgrade(), op, dest.clone())
+    }
+
+    fn shutdown(&mut self, socket: &OpTy<'tcx>, how: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+
+        let how = if how == this.eval_libc_i32("SHUT_RD") {
+            Shutdown::Read
+        } else if how == this.eval_libc_i32("SHUT_WR") {
+            Shutdown::Write
+        } else if how == this.eval_libc_i32("SHUT_RDWR") {
+            Shutdown::Both
+        } else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let socket = fd.downcast::<InetSocket>().unwrap();
+
+        let mut state = socket.state.borrow_mut();
+        let peer = match &mut *state {
+            SocketState::Connected(stream) => {
+                if matches!(how, Shutdown::Read | Shutdown::Both) {
+                    stream.read_shutdown = true;
+                }
+                if matches!(how, Shutdown::Write | Shutdown::Both) {
+                    stream.write_shutdown = true;
+                }
+                stream.peer.upgrade()
+            }
+            SocketState::HostStream(stream) => {
+                let result = stream.shutdown(how);
+                drop(state);
+                return match result {
+                    Ok(()) => interp_ok(Scalar::from_i32(0)),
+                    Err(e) => this.set_last_error_and_return_i32(e),
+                };
+            }
+            _ => {
+                drop(state);
+                return this.set_last_error_and_return_i32(LibcError("ENOTCONN"));
+            }
+        };
+        drop(state);
+
+        if let Some(peer) = peer {
+            if matches!(how, Shutdown::Write | Shutdown::Both) {
+                // The peer reads EOF once it has consumed the pending data.
+                if let SocketState::Connected(peer_stream) =
+                    &mut *peer.downcast::<InetSocket>().unwrap().state.borrow_mut()
+                {
+                    peer_stream.eof = true;
+                }
+                this.notify_socket(&peer)?;
+            }
+        }
+        // Blocked reads on this socket now see EOF.
+        this.notify_socket(&fd)?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn getsockname(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        match fd.downcast::<InetSocket>().unwrap().local_addr() {
+            Ok(addr) => this.write_socket_address(addr, address, address_len)?,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn getpeername(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        match fd.downcast::<InetSocket>().unwrap().peer_addr() {
+            Ok(addr) => this.write_socket_address(addr, address, address_len)?,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn setsockopt(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        level: &OpTy<'tcx>,
+        option_name: &OpTy<'tcx>,
+        option_value: &OpTy<'tcx>,
+        option_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let name = this.read_scalar(option_name)?.to_i32()?;
+        let value = this.read_pointer(option_value)?;
+        let len = this.read_scalar(option_len)?.to_u32()?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let sol_socket = this.eval_libc_i32("SOL_SOCKET");
+
+        if level == sol_socket
+            && (name == this.eval_libc_i32("SO_RCVTIMEO")
+                || name == this.eval_libc_i32("SO_SNDTIMEO"))
+        {
+            if u64::from(len) < this.libc_ty_layout("timeval").size.bytes() {
+                return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+            }
+            let timeout = this.read_timeval_option(value)?;
+            if name == this.eval_libc_i32("SO_RCVTIMEO") {
+                socket.read_timeout.set(timeout);
+            } else {
+                socket.write_timeout.set(timeout);
+            }
+            return interp_ok(Scalar::from_i32(0));
+        }
+
+        if len < 4 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let value = this.ptr_to_mplace(value, this.machine.layouts.i32);
+        let value = this.read_scalar(&value)?.to_i32()?;
+
+        if level == this.eval_libc_i32("IPPROTO_IPV6") && name == this.eval_libc_i32("IPV6_V6ONLY")
+        {
+            if !socket.is_ipv6 {
+                return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+            }
+            socket.only_v6.set(value != 0);
+            return interp_ok(Scalar::from_i32(0));
+        }
+        if !this.int_socket_options().iter().any(|&(option, _)| option == (level, name)) {
+            throw_unsup_format!("setsockopt: option {name:#x} at level {level:#x} is unsupported");
+        }
+        socket.options.borrow_mut().insert((level, name), value);
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn getsockopt(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        level: &OpTy<'tcx>,
+        option_name: &OpTy<'tcx>,
+        option_value: &OpTy<'tcx>,
+        option_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(socket)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let name = this.read_scalar(option_name)?.to_i32()?;
+        let value_ptr = this.read_pointer(option_value)?;
+        let len_place = this.deref_pointer_as(option_len, this.libc_ty_layout("socklen_t"))?;
+        let len = this.read_scalar(&len_place)?.to_u32()?;
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let socket = fd.downcast::<InetSocket>().unwrap();
+        let sol_socket = this.eval_libc_i32("SOL_SOCKET");
+
+        if level == sol_socket
+            && (name == this.eval_libc_i32("SO_RCVTIMEO")
+                || name == this.eval_libc_i32("SO_SNDTIMEO"))
+        {
+            let layout = this.libc_ty_layout("timeval");
+            if u64::from(len) < layout.size.bytes() {
+                return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+            }
+            let timeout = if name == this.eval_libc_i32("SO_RCVTIMEO") {
+                socket.read_timeout.get()
+            } else {
+                socket.write_timeout.get()
+            };
+            let timeout = timeout.unwrap_or_default();
+            let timeval = this.ptr_to_mplace(value_ptr, layout);
+            this.write_int_fields_named(
+                &[
+                    ("tv_sec", timeout.as_secs().into()),
+                    ("tv_usec", timeout.subsec_micros().into()),
+                ],
+                &timeval,
+            )?;
+            this.write_int(layout.size.bytes(), &len_place)?;
+            return interp_ok(Scalar::from_i32(0));
+        }
+
+        let value = if level == sol_socket && name == this.eval_libc_i32("SO_ERROR") {
+            // Errors are always reported directly by the operation that caused them.
+            0
+        } else if level == sol_socket && name == this.eval_libc_i32("SO_TYPE") {
+            match socket.kind {
+                SocketKind::Stream => this.eval_libc_i32("SOCK_STREAM"),
+                SocketKind::Datagram => this.eval_libc_i32("SOCK_DGRAM"),
+            }
+        } else if level == this.eval_libc_i32("IPPROTO_IPV6")
+            && name == this.eval_libc_i32("IPV6_V6ONLY")
+        {
+            socket.only_v6.get().into()
+        } else {
+            let Some(&(_, default)) =
+                this.int_socket_options().iter().find(|&&(option, _)| option == (level, name))
+            else {
+                throw_unsup_format!(
+                    "getsockopt: option {name:#x} at level {level:#x} is unsupported"
+                );
+            };
+            socket.options.borrow().get(&(level, name)).copied().unwrap_or(default)
+        };
+        if len < 4 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let value_place = this.ptr_to_mplace(value_ptr, this.machine.layouts.i32);
+        this.write_scalar(Scalar::from_i32(value), &value_place)?;
+        this.write_int(4, &len_place)?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Implements the `FIONBIO` request of `ioctl`, which is how the standard library makes
+    /// sockets non-blocking.
+    fn socket_set_nonblocking(
+        &mut self,
+        fd_num: i32,
+        value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = match this.get_socket(fd_num) {
+            Ok(fd) => fd,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        let value = this.deref_pointer_as(value, this.machine.layouts.i32)?;
+        let value = this.read_scalar(&value)?.to_i32()?;
+        fd.downcast::<InetSocket>().unwrap().is_nonblock.set(value != 0);
+        interp_ok(Scalar::from_i32(0))
+    }
+}
+
+/// Connects a socket that is backed by a host socket.
+///
+/// This blocks the interpreter until the host has established the connection.
+fn connect_host_socket(socket: &InetSocket, addr: SocketAddr) -> Result<(), IoError> {
+    let mut state = socket.state.borrow_mut();
+    match (&mut *state, socket.kind) {
+        (SocketState::Idle { local: None }, SocketKind::Stream) => {
+            let stream = net::TcpStream::connect(addr)?;
+            stream.set_nonblocking(true)?;
+            *state = SocketState::HostStream(stream);
+        }
+        (SocketState::Idle { local: None }, SocketKind::Datagram) => {
+            let host_socket = net::UdpSocket::bind(socket.unspecified_addr())?;
+            host_socket.set_nonblocking(true)?;
+            host_socket.connect(addr)?;
+            *state = SocketState::HostDatagram(host_socket);
+        }
+        (SocketState::HostDatagram(host_socket), _) => host_socket.connect(addr)?,
+        (SocketState::Idle { local: Some(_) }, _) => {
+            // FIXME: the standard library cannot connect from a given local address.
+            return Err(LibcError("EADDRNOTAVAIL"));
+        }
+        _ => return Err(LibcError("EISCONN")),
+    }
+    Ok(())
+}
@@ -34,7 +34,11 @@ struct AnonSocket {
     /// This flag is set to `true` if the peer's `readbuf` is non-empty at the time
     /// of closure.
     peer_lost_data: Cell<bool>,
-    is_nonblock: bool,
+    is_nonblock: Cell<bool>,
+    /// Whether the buffer we write to has no size limit. This is the case once the socket is
+    /// shared with another process: processes do not run concurrently, so the other side could
+    /// never drain a full buffer.
+    unbounded: Cell<bool>,
 }
 
 #[derive(Debug)]
@@ -53,6 +57,44 @@ impl AnonSocket {
     fn peer_fd(&self) -> &WeakFileDescriptionRef {
         self.peer_fd.get().unwrap()
     }
+
+    /// The number of bytes that can still be written to `writebuf`, the buffer of our peer.
+    fn available_space(&self, writebuf: &Buffer) -> usize {
+        if self.unbounded.get() {
+            usize::MAX
+        } else {
+            MAX_SOCKETPAIR_BUFFER_CAPACITY.strict_sub(writebuf.buf.len())
+        }
+    }
+}
+
+/// Forgets when the data buffered in a socketpair or pipe was written, if `fd` refers to one of
+/// them. This is needed when the data is read by another process, since the vector clocks of its
+/// threads are unrelated to ours.
+pub fn reset_anonsocket_clocks(fd: &FileDescriptionRef) {
+    let Some(socket) = fd.downcast::<AnonSocket>() else {
+        return;
+    };
+    let peer_fd = socket.peer_fd().upgrade();
+    let peer = peer_fd.as_ref().and_then(|peer_fd| peer_fd.downcast::<AnonSocket>());
+    for socket in [Some(socket), peer].into_iter().flatten() {
+        if let Some(readbuf) = &socket.readbuf {
+            readbuf.borrow_mut().clock = VClock::default();
+        }
+    }
+}
+
+/// Lifts the size limit of the buffers of a socketpair or pipe, if `fd` refers to one of them.
+/// This is needed when it is shared with another process, which can only drain a full buffer once
+/// we are done.
+pub fn unbound_anonsocket(fd: &FileDescriptionRef) {
+    let Some(socket) = fd.downcast::<AnonSocket>() else {
+        return;
+    };
+    socket.unbounded.set(true);
+    if let Some(peer_fd) = socket.peer_fd().upgrade() {
+        peer_fd.downcast::<AnonSocket>().unwrap().unbounded.set(true);
+    }
 }
 
 impl FileDescription for AnonSocket {
@@ -79,9 +121,7 @@ fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
         // Check if is writable.
         if let Some(peer_fd) = self.peer_fd().upgrade() {
             if let Some(writebuf) = &peer_fd.downcast::<AnonSocket>().unwrap().readbuf {
-                let data_size = writebuf.borrow().buf.len();
-                let available_space = MAX_SOCKETPAIR_BUFFER_CAPACITY.strict_sub(data_size);
-                if available_space != 0 {
+                if self.available_space(&writebuf.borrow()) != 0 {
                     epoll_ready_events.epollout = true;
                 }
             } else {
@@ -146,6 +186,10 @@ fn read<'tcx>(
             // corresponding ErrorKind variant.
             throw_unsup_format!("reading from the write end of a pipe");
         };
+        if readbuf.borrow().buf.is_empty() && self.peer_fd().upgrade().is_some() {
+            // A child process that has not run yet might write to this socket.
+            ecx.run_child_processes()?;
+        }
         let mut readbuf = readbuf.borrow_mut();
         if readbuf.buf.is_empty() {
             if self.peer_fd().upgrade().is_none() {
@@ -153,7 +197,7 @@ fn read<'tcx>(
                 // 0 bytes successfully read indicates end-of-file.
                 return ecx.return_read_success(ptr, &bytes, 0, dest);
             } else {
-                if self.is_nonblock {
+                if self.is_nonblock.get() {
                     // Non-blocking socketpair with writer and empty buffer.
                     // https://linux.die.net/man/2/read
                     // EAGAIN or EWOULDBLOCK can be returned for socket,
@@ -222,10 +266,9 @@ fn write<'tcx>(
             throw_unsup_format!("writing to the reading end of a pipe");
         };
         let mut writebuf = writebuf.borrow_mut();
-        let data_size = writebuf.buf.len();
-        let available_space = MAX_SOCKETPAIR_BUFFER_CAPACITY.strict_sub(data_size);
+        let available_space = self.available_space(&writebuf);
         if available_space == 0 {
-            if self.is_nonblock {
+            if self.is_nonblock.get() {
                 // Non-blocking socketpair with a full buffer.
                 return ecx.set_last_error_and_return(ErrorKind::WouldBlock, dest);
             } else {
@@ -255,6 +298,27 @@ fn write<'tcx>(
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Implements the `FIONBIO` request of `ioctl` for socketpairs and pipes. Returns `None` if
+    /// `fd_num` does not refer to one of them.
+    fn anonsocket_set_nonblocking(
+        &mut self,
+        fd_num: i32,
+        value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Option<Scalar>> {
+        let this = self.eval_context_mut();
+
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            return interp_ok(None);
+        };
+        let Some(socket) = fd.downcast::<AnonSocket>() else {
+            return interp_ok(None);
+        };
+        let value = this.deref_pointer_as(value, this.machine.layouts.i32)?;
+        let value = this.read_scalar(&value)?.to_i32()?;
+        socket.is_nonblock.set(value != 0);
+        interp_ok(Some(Scalar::from_i32(0)))
+    }
+
     /// For more information on the arguments see the socketpair manpage:
     /// <https://linux.die.net/man/2/socketpair>
     fn socketpair(
@@ -316,13 +380,15 @@ fn socketpair(
             readbuf: Some(RefCell::new(Buffer::new())),
             peer_fd: OnceCell::new(),
             peer_lost_data: Cell::new(false),
-            is_nonblock: is_sock_nonblock,
+            is_nonblock: Cell::new(is_sock_nonblock),
+            unbounded: Cell::new(false),
         });
         let fd1 = fds.new_ref(AnonSocket {
             readbuf: Some(RefCell::new(Buffer::new())),
             peer_fd: OnceCell::new(),
             peer_lost_data: Cell::new(false),
-            is_nonblock: is_sock_nonblock,
+            is_nonblock: Cell::new(is_sock_nonblock),
+            unbounded: Cell::new(false),
         });
 
         // Make the file descriptions point to each other.
@@ -380,13 +446,15 @@ fn pipe2(
             readbuf: Some(RefCell::new(Buffer::new())),
             peer_fd: OnceCell::new(),
             peer_lost_data: Cell::new(false),
-            is_nonblock,
+            is_nonblock: Cell::new(is_nonblock),
+            unbounded: Cell::new(false),
         });
         let fd1 = fds.new_ref(AnonSocket {
             readbuf: None,
             peer_fd: OnceCell::new(),
             peer_lost_data: Cell::new(false),
-            is_nonblock,
+            is_nonblock: Cell::new(is_nonblock),
+            unbounded: Cell::new(false),
         });
 
         // Make the file descriptions point to each other.
@@ -0,0 +1,31 @@
+//@ignore-target: windows # No pthreads on Windows
+//@compile-flags: -Zmiri-explore-schedules=1
+
+// The new thread only gets to write `DATA` before the main thread loads the flag if it is
+// scheduled right after it was created, so the race is only found in a later execution.
+
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static mut DATA: u32 = 0;
+static READY: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn thread_start(_arg: *mut libc::c_void) -> *mut libc::c_void {
+    unsafe { DATA = 42 };
+    READY.store(true, Ordering::Relaxed);
+    ptr::null_mut()
+}
+
+fn main() {
+    unsafe {
+        let mut thread = std::mem::zeroed();
+        assert_eq!(
+            libc::pthread_create(&mut thread, ptr::null(), thread_start, ptr::null_mut()),
+            0
+        );
+        if READY.load(Ordering::Relaxed) {
+            let _val = DATA; //~ ERROR: Data race detected
+        }
+        assert_eq!(libc::pthread_join(thread, ptr::null_mut()), 0);
+    }
+}
@@ -0,0 +1,32 @@
+//@only-target: linux # The minimized trace is written to `/dev/stderr`
+//@compile-flags: -Zmiri-replay-schedule=tests/fail-dep/concurrency/minimize_schedule.trace
+//@compile-flags: -Zmiri-minimize-schedule -Zmiri-record-schedule=/dev/stderr
+
+// The trace switches threads twice, but only the first switch is needed for the race: the new
+// thread then writes `DATA` before the main thread loads the flag.
+
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static mut DATA: u32 = 0;
+static READY: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn thread_start(_arg: *mut libc::c_void) -> *mut libc::c_void {
+    unsafe { DATA = 42 };
+    READY.store(true, Ordering::Relaxed);
+    ptr::null_mut()
+}
+
+fn main() {
+    unsafe {
+        let mut thread = std::mem::zeroed();
+        assert_eq!(
+            libc::pthread_create(&mut thread, ptr::null(), thread_start, ptr::null_mut()),
+            0
+        );
+        if READY.load(Ordering::Relaxed) {
+            let _val = DATA; //~ ERROR: Data race detected
+        }
+        assert_eq!(libc::pthread_join(thread, ptr::null_mut()), 0);
+    }
+}
@@ -0,0 +1,27 @@
+//@ignore-target: windows # No pthreads on Windows
+//@compile-flags: -Zmiri-replay-schedule=tests/fail-dep/concurrency/replay_schedule_diverged.trace
+
+// The trace has a weak compare-exchange where the program decides which thread runs after the
+// load.
+
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static READY: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn thread_start(_arg: *mut libc::c_void) -> *mut libc::c_void {
+    READY.store(true, Ordering::Relaxed);
+    ptr::null_mut()
+}
+
+fn main() {
+    unsafe {
+        let mut thread = std::mem::zeroed();
+        assert_eq!(
+            libc::pthread_create(&mut thread, ptr::null(), thread_start, ptr::null_mut()),
+            0
+        );
+        let _ready = READY.load(Ordering::Relaxed); //~ ERROR: diverged from the decisions it was replaying
+        assert_eq!(libc::pthread_join(thread, ptr::null_mut()), 0);
+    }
+}
@@ -0,0 +1,23 @@
+//@ignore-target: windows # No pthreads on Windows
+//@compile-flags: -Zmiri-replay-schedule=tests/fail-dep/concurrency/replay_schedule_ended_early.trace
+//@error-in-other-file: it ended after 1 of the 3 decisions in the trace
+
+// The program only decides which thread runs once, right after the thread was created, but the
+// trace has more decisions.
+
+use std::ptr;
+
+extern "C" fn thread_start(_arg: *mut libc::c_void) -> *mut libc::c_void {
+    ptr::null_mut()
+}
+
+fn main() {
+    unsafe {
+        let mut thread = std::mem::zeroed();
+        assert_eq!(
+            libc::pthread_create(&mut thread, ptr::null(), thread_start, ptr::null_mut()),
+            0
+        );
+        assert_eq!(libc::pthread_join(thread, ptr::null_mut()), 0);
+    }
+}
@@ -0,0 +1,13 @@
+// Only works on Unix targets
+//@ignore-target: windows wasm
+//@only-on-host
+
+extern "C" {
+    fn return_bogus_ptr() -> *mut i32;
+}
+
+fn main() {
+    unsafe {
+        let _ptr = return_bogus_ptr(); //~ ERROR: returned the pointer
+    }
+}
@@ -0,0 +1,15 @@
+// Only x86_64 Linux single-steps the writes of native code. Elsewhere, the write past the end may
+// go unnoticed since the page was already written to.
+//@only-target: x86_64-unknown-linux
+//@only-on-host
+
+extern "C" {
+    fn write_past_end(ptr: *mut i32);
+}
+
+fn main() {
+    let mut x = 0i32;
+    unsafe {
+        write_past_end(&mut x); //~ ERROR: native code wrote to address
+    }
+}
@@ -0,0 +1,15 @@
+// Only works on Unix targets
+//@ignore-target: windows wasm
+//@only-on-host
+
+extern "C" {
+    fn increment_int(ptr: *mut i32);
+}
+
+static X: i32 = 0;
+
+fn main() {
+    unsafe {
+        increment_int(&X as *const i32 as *mut i32); //~ ERROR: native code wrote to address
+    }
+}
@@ -0,0 +1,65 @@
+// Only works on Unix targets
+//@ignore-target: windows wasm
+//@only-on-host
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+fn main() {
+    test_increment_int();
+
+    test_init_array();
+
+    test_set_ptr();
+
+    test_return_ptr();
+}
+
+// Test function that writes to an integer it got a pointer to.
+fn test_increment_int() {
+    extern "C" {
+        fn increment_int(ptr: *mut i32);
+    }
+
+    let mut x = 11;
+
+    unsafe { increment_int(&mut x) };
+    assert_eq!(x, 12);
+}
+
+// Test function that initializes memory, which can be read as initialized afterwards.
+fn test_init_array() {
+    extern "C" {
+        fn init_array(array: *mut i32, len: usize, value: i32);
+    }
+
+    let mut array = [MaybeUninit::<i32>::uninit(); 4];
+
+    unsafe { init_array(array.as_mut_ptr().cast(), array.len(), 31) };
+    assert_eq!(unsafe { array.map(|x| x.assume_init()) }, [31; 4]);
+}
+
+// Test function that stores a pointer, which can be used afterwards.
+fn test_set_ptr() {
+    extern "C" {
+        fn set_ptr(out: *mut *mut i32, target: *mut i32);
+    }
+
+    let mut x = 1;
+    let mut out = ptr::null_mut();
+
+    unsafe { set_ptr(&mut out, &mut x) };
+    unsafe { *out = 2 };
+    assert_eq!(x, 2);
+}
+
+// Test function that returns a pointer into memory it got a pointer to.
+fn test_return_ptr() {
+    extern "C" {
+        fn last_elem(array: *const i32, len: usize) -> *const i32;
+    }
+
+    let array = [1, 2, 3];
+
+    assert_eq!(unsafe { *last_elem(array.as_ptr(), array.len()) }, 3);
+}
@@ -0,0 +1,47 @@
+#include <stddef.h>
+
+// See comments in build_native_lib()
+#define EXPORT __attribute__((visibility("default")))
+
+/* Test: test_increment_int */
+
+EXPORT void increment_int(int *ptr) {
+  *ptr += 1;
+}
+
+/* Test: test_init_array */
+
+EXPORT void init_array(int *array, size_t len, int value) {
+  for (size_t i = 0; i < len; i++) {
+    array[i] = value;
+  }
+}
+
+/* Test: test_set_ptr */
+
+EXPORT void set_ptr(int **out, int *target) {
+  *out = target;
+}
+
+/* Test: test_return_ptr */
+
+EXPORT const int *last_elem(const int *array, size_t len) {
+  return &array[len - 1];
+}
+
+/* Test: fail/write_past_end */
+
+EXPORT void write_past_end(int *ptr) {
+  // The first write is allowed. The page is only protected again for the second one if the
+  // first write was single-stepped.
+  ptr[0] = 1;
+  ptr[1] = 1;
+}
+
+/* Test: fail/return_bogus_ptr */
+
+static int not_exposed;
+
+EXPORT int *return_bogus_ptr(void) {
+  return &not_exposed;
+}
@@ -0,0 +1,108 @@
+//@only-target: linux
+
+use std::convert::TryInto;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::os::fd::AsRawFd;
+
+fn main() {
+    test_epoll_tcp();
+    test_epoll_udp();
+}
+
+// Using `as` cast since `EPOLLET` wraps around
+const EPOLL_IN_OUT_ET: u32 = (libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLET) as _;
+
+#[track_caller]
+fn check_epoll_wait<const N: usize>(epfd: i32, expected_notifications: &[(u32, u64)]) {
+    let epoll_event = libc::epoll_event { events: 0, u64: 0 };
+    let mut array: [libc::epoll_event; N] = [epoll_event; N];
+    let maxsize = N;
+    let array_ptr = array.as_mut_ptr();
+    let res = unsafe { libc::epoll_wait(epfd, array_ptr, maxsize.try_into().unwrap(), 0) };
+    if res < 0 {
+        panic!("epoll_wait failed: {}", std::io::Error::last_os_error());
+    }
+    assert_eq!(
+        res,
+        expected_notifications.len().try_into().unwrap(),
+        "got wrong number of notifications"
+    );
+    let slice = unsafe { std::slice::from_raw_parts(array_ptr, res.try_into().unwrap()) };
+    for (return_event, expected_event) in slice.iter().zip(expected_notifications.iter()) {
+        let event = return_event.events;
+        let data = return_event.u64;
+        assert_eq!(event, expected_event.0, "got wrong events");
+        assert_eq!(data, expected_event.1, "got wrong data");
+    }
+}
+
+#[track_caller]
+fn register(epfd: i32, fd: i32, events: u32, data: u64) {
+    let mut ev = libc::epoll_event { events, u64: data };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut ev) };
+    assert_eq!(res, 0);
+}
+
+fn test_epoll_tcp() {
+    // Create an epoll instance.
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+
+    // A listener without pending connections is not ready.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    register(epfd, listener.as_raw_fd(), (libc::EPOLLIN | libc::EPOLLET) as _, 0);
+    check_epoll_wait::<8>(epfd, &[]);
+
+    // A pending connection makes the listener readable.
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    check_epoll_wait::<8>(epfd, &[(libc::EPOLLIN as _, 0)]);
+
+    // Accepting the connection empties the backlog again.
+    let (mut server, _) = listener.accept().unwrap();
+    check_epoll_wait::<8>(epfd, &[]);
+
+    // The accepted stream is writable.
+    register(epfd, server.as_raw_fd(), EPOLL_IN_OUT_ET | libc::EPOLLRDHUP as u32, 1);
+    check_epoll_wait::<8>(epfd, &[(libc::EPOLLOUT as _, 1)]);
+
+    // Data sent by the peer makes it readable.
+    client.write_all(b"hello").unwrap();
+    check_epoll_wait::<8>(epfd, &[((libc::EPOLLIN | libc::EPOLLOUT) as _, 1)]);
+
+    // Reading the data does not notify the reader.
+    let mut buf = [0; 5];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+    check_epoll_wait::<8>(epfd, &[]);
+
+    // Closing the peer hangs up the stream.
+    drop(client);
+    let expected_event =
+        u32::try_from(libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLRDHUP | libc::EPOLLHUP).unwrap();
+    check_epoll_wait::<8>(epfd, &[(expected_event, 1)]);
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+}
+
+fn test_epoll_udp() {
+    // Create an epoll instance.
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    // A datagram socket is always writable.
+    register(epfd, receiver.as_raw_fd(), EPOLL_IN_OUT_ET, 2);
+    check_epoll_wait::<8>(epfd, &[(libc::EPOLLOUT as _, 2)]);
+
+    // A queued datagram makes it readable.
+    sender.send_to(b"hello", receiver.local_addr().unwrap()).unwrap();
+    check_epoll_wait::<8>(epfd, &[((libc::EPOLLIN | libc::EPOLLOUT) as _, 2)]);
+
+    let mut buf = [0; 5];
+    let (len, from) = receiver.recv_from(&mut buf).unwrap();
+    assert_eq!((len, &buf), (5, b"hello"));
+    assert_eq!(from, sender.local_addr().unwrap());
+    check_epoll_wait::<8>(epfd, &[]);
+}
@@ -0,0 +1,122 @@
+//@ignore-target: windows # File handling is not implemented yet
+//@compile-flags: -Zmiri-disable-isolation
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Error;
+use std::os::fd::AsRawFd;
+use std::{ptr, slice};
+
+#[path = "../../utils/mod.rs"]
+mod utils;
+
+fn main() {
+    test_private();
+    test_shared();
+    test_offset();
+    test_errors();
+}
+
+fn map(len: usize, prot: libc::c_int, flags: libc::c_int, file: &File, offset: usize) -> *mut u8 {
+    let ptr = unsafe {
+        libc::mmap(ptr::null_mut(), len, prot, flags, file.as_raw_fd(), offset.try_into().unwrap())
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    ptr.cast()
+}
+
+fn test_private() {
+    let page_size = page_size::get();
+    let path = utils::prepare_with_content("miri_test_libc_fs_mmap_private.txt", b"hello mmap");
+    let file = File::open(&path).unwrap();
+
+    let ptr = map(page_size, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_PRIVATE, &file, 0);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr, page_size) };
+    assert_eq!(&slice[..10], b"hello mmap");
+    // The rest of the page is zeroed.
+    assert!(slice[10..].iter().all(|b| *b == 0));
+
+    // Changes to private mappings do not end up in the file.
+    slice[0] = b'j';
+    assert_eq!(unsafe { libc::msync(ptr.cast(), page_size, libc::MS_SYNC) }, 0);
+    assert_eq!(unsafe { libc::munmap(ptr.cast(), page_size) }, 0);
+    assert_eq!(fs::read(&path).unwrap(), b"hello mmap");
+
+    fs::remove_file(&path).unwrap();
+}
+
+fn test_shared() {
+    let path = utils::prepare_with_content("miri_test_libc_fs_mmap_shared.txt", b"hello mmap");
+    let file = OpenOptions::new().read(true).write(true).open(&path).unwrap();
+
+    let ptr = map(10, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED, &file, 0);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr, 10) };
+    slice[..5].copy_from_slice(b"HELLO");
+    assert_eq!(unsafe { libc::msync(ptr.cast(), 10, libc::MS_SYNC) }, 0);
+    assert_eq!(fs::read(&path).unwrap(), b"HELLO mmap");
+
+    // The mapping stays usable after the file is closed, and unmapping it writes back the changes.
+    drop(file);
+    slice[6..].copy_from_slice(b"MMAP");
+    assert_eq!(unsafe { libc::munmap(ptr.cast(), 10) }, 0);
+    assert_eq!(fs::read(&path).unwrap(), b"HELLO MMAP");
+
+    fs::remove_file(&path).unwrap();
+}
+
+fn test_offset() {
+    let page_size = page_size::get();
+    let mut contents = vec![b'a'; page_size];
+    contents.extend_from_slice(b"tail");
+    let path = utils::prepare_with_content("miri_test_libc_fs_mmap_offset.txt", &contents);
+    let file = File::open(&path).unwrap();
+
+    let ptr = map(4, libc::PROT_READ, libc::MAP_SHARED, &file, page_size);
+    let slice = unsafe { slice::from_raw_parts(ptr, 4) };
+    assert_eq!(slice, b"tail");
+    assert_eq!(unsafe { libc::munmap(ptr.cast(), 4) }, 0);
+
+    fs::remove_file(&path).unwrap();
+}
+
+fn test_errors() {
+    let page_size = page_size::get();
+    let path = utils::prepare_with_content("miri_test_libc_fs_mmap_errors.txt", b"hello mmap");
+    let file = File::open(&path).unwrap();
+    let fd = file.as_raw_fd();
+    let rw = libc::PROT_READ | libc::PROT_WRITE;
+
+    let mmap = |len, prot, flags, fd, offset| unsafe {
+        let ptr = libc::mmap(ptr::null_mut(), len, prot, flags, fd, offset);
+        assert_eq!(ptr, libc::MAP_FAILED);
+        Error::last_os_error().raw_os_error().unwrap()
+    };
+    // The offset must be a multiple of the page size.
+    assert_eq!(mmap(page_size, rw, libc::MAP_PRIVATE, fd, 1), libc::EINVAL);
+    // Writable shared mappings need a writable file.
+    assert_eq!(mmap(page_size, rw, libc::MAP_SHARED, fd, 0), libc::EACCES);
+    // The file descriptor must be open...
+    assert_eq!(mmap(page_size, rw, libc::MAP_PRIVATE, -1, 0), libc::EBADF);
+    // ... and refer to a regular file.
+    let mut fds = [-1; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    assert_eq!(mmap(page_size, rw, libc::MAP_PRIVATE, fds[0], 0), libc::ENODEV);
+
+    let msync = |addr, len, flags| unsafe {
+        assert_eq!(libc::msync(addr, len, flags), -1);
+        Error::last_os_error().raw_os_error().unwrap()
+    };
+    let ptr = map(page_size, rw, libc::MAP_PRIVATE, &file, 0);
+    // The address must be a multiple of the page size.
+    assert_eq!(msync(ptr.wrapping_add(1).cast(), 1, libc::MS_SYNC), libc::EINVAL);
+    // `MS_SYNC` and `MS_ASYNC` cannot be combined.
+    assert_eq!(msync(ptr.cast(), page_size, libc::MS_SYNC | libc::MS_ASYNC), libc::EINVAL);
+    // The range must be mapped.
+    assert_eq!(unsafe { libc::munmap(ptr.cast(), page_size) }, 0);
+    assert_eq!(msync(ptr.cast(), page_size, libc::MS_SYNC), libc::ENOMEM);
+
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,220 @@
+//@ignore-target: windows # No libc signal support on Windows
+//@compile-flags: -Zmiri-permissive-provenance
+
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+use std::{ptr, thread};
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+static LAST_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn count_handler(sig: libc::c_int) {
+    LAST_SIGNAL.store(sig, Ordering::Relaxed);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+extern "C" fn siginfo_handler(sig: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+    assert_eq!(unsafe { (*info).si_signo }, sig);
+    count_handler(sig);
+}
+
+fn handled() -> usize {
+    HANDLED.load(Ordering::Relaxed)
+}
+
+fn empty_set() -> libc::sigset_t {
+    let mut set = MaybeUninit::uninit();
+    assert_eq!(unsafe { libc::sigemptyset(set.as_mut_ptr()) }, 0);
+    unsafe { set.assume_init() }
+}
+
+fn current_mask() -> libc::sigset_t {
+    let mut set = empty_set();
+    assert_eq!(unsafe { libc::pthread_sigmask(libc::SIG_SETMASK, ptr::null(), &mut set) }, 0);
+    set
+}
+
+fn install(sig: libc::c_int, handler: libc::sighandler_t, flags: libc::c_int) {
+    let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+    action.sa_sigaction = handler;
+    action.sa_mask = empty_set();
+    action.sa_flags = flags;
+    assert_eq!(unsafe { libc::sigaction(sig, &action, ptr::null_mut()) }, 0);
+}
+
+fn main() {
+    test_sigset();
+    test_sigpipe();
+    test_raise();
+    test_siginfo_and_query();
+    test_mask_in_handler();
+    test_blocked_signals();
+    test_ignored_signals();
+    test_reset_handler();
+    test_kill();
+    test_pthread_kill();
+    test_invalid();
+}
+
+fn test_sigset() {
+    unsafe {
+        let mut set = empty_set();
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigaddset(&mut set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 1);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR2), 0);
+        assert_eq!(libc::sigdelset(&mut set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigfillset(&mut set), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGTERM), 1);
+        assert_eq!(libc::sigaddset(&mut set, 0), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    }
+}
+
+fn test_sigpipe() {
+    unsafe {
+        // The runtime of std already ignores `SIGPIPE`.
+        assert_eq!(libc::signal(libc::SIGPIPE, libc::SIG_IGN), libc::SIG_IGN);
+        assert_eq!(libc::signal(libc::SIGPIPE, libc::SIG_DFL), libc::SIG_IGN);
+        assert_eq!(libc::signal(libc::SIGPIPE, libc::SIG_IGN), libc::SIG_DFL);
+    }
+}
+
+fn test_raise() {
+    let old = unsafe { libc::signal(libc::SIGUSR1, count_handler as libc::sighandler_t) };
+    assert_eq!(old, libc::SIG_DFL);
+    let before = handled();
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    // The handler has run by the time `raise` returns.
+    assert_eq!(handled(), before + 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR1);
+    // `signal` keeps the handler installed.
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(handled(), before + 2);
+}
+
+fn test_siginfo_and_query() {
+    install(libc::SIGUSR2, siginfo_handler as libc::sighandler_t, libc::SA_SIGINFO);
+    let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::sigaction(libc::SIGUSR2, ptr::null(), &mut old) }, 0);
+    assert_eq!(old.sa_sigaction, siginfo_handler as libc::sighandler_t);
+    assert_eq!(old.sa_flags & libc::SA_SIGINFO, libc::SA_SIGINFO);
+
+    let before = handled();
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(handled(), before + 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR2);
+}
+
+static BLOCKED_IN_HANDLER: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn mask_handler(sig: libc::c_int) {
+    // The signal that is being handled is blocked while the handler runs.
+    let mask = current_mask();
+    BLOCKED_IN_HANDLER.store(unsafe { libc::sigismember(&mask, sig) } == 1, Ordering::Relaxed);
+}
+
+fn test_mask_in_handler() {
+    install(libc::SIGALRM, mask_handler as libc::sighandler_t, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGALRM) }, 0);
+    assert!(BLOCKED_IN_HANDLER.load(Ordering::Relaxed));
+    // ... and unblocked again afterwards.
+    assert_eq!(unsafe { libc::sigismember(&current_mask(), libc::SIGALRM) }, 0);
+}
+
+fn test_blocked_signals() {
+    unsafe {
+        let mut set = empty_set();
+        libc::sigaddset(&mut set, libc::SIGUSR1);
+        assert_eq!(libc::pthread_sigmask(libc::SIG_BLOCK, &set, ptr::null_mut()), 0);
+
+        let before = handled();
+        assert_eq!(libc::raise(libc::SIGUSR1), 0);
+        assert_eq!(handled(), before);
+        let mut pending = empty_set();
+        assert_eq!(libc::sigpending(&mut pending), 0);
+        assert_eq!(libc::sigismember(&pending, libc::SIGUSR1), 1);
+
+        // New threads inherit the signal mask.
+        thread::spawn(|| {
+            assert_eq!(libc::sigismember(&current_mask(), libc::SIGUSR1), 1);
+        })
+        .join()
+        .unwrap();
+
+        // Unblocking delivers the pending signal.
+        assert_eq!(libc::sigprocmask(libc::SIG_UNBLOCK, &set, ptr::null_mut()), 0);
+        assert_eq!(handled(), before + 1);
+        assert_eq!(libc::sigpending(&mut pending), 0);
+        assert_eq!(libc::sigismember(&pending, libc::SIGUSR1), 0);
+    }
+}
+
+fn test_ignored_signals() {
+    let before = handled();
+    unsafe {
+        // `SIGCHLD` is ignored by default.
+        assert_eq!(libc::raise(libc::SIGCHLD), 0);
+
+        let old = libc::signal(libc::SIGUSR1, libc::SIG_IGN);
+        assert_eq!(old, count_handler as libc::sighandler_t);
+        assert_eq!(libc::raise(libc::SIGUSR1), 0);
+        libc::signal(libc::SIGUSR1, count_handler as libc::sighandler_t);
+    }
+    assert_eq!(handled(), before);
+}
+
+fn test_reset_handler() {
+    install(libc::SIGHUP, count_handler as libc::sighandler_t, libc::SA_RESETHAND);
+    let before = handled();
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+    assert_eq!(handled(), before + 1);
+    let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+    assert_eq!(unsafe { libc::sigaction(libc::SIGHUP, ptr::null(), &mut old) }, 0);
+    assert_eq!(old.sa_sigaction, libc::SIG_DFL);
+}
+
+fn test_kill() {
+    let before = handled();
+    unsafe {
+        assert_eq!(libc::kill(libc::getpid(), 0), 0);
+        assert_eq!(libc::kill(libc::getpid(), libc::SIGUSR1), 0);
+    }
+    assert_eq!(handled(), before + 1);
+}
+
+static RECEIVER: AtomicUsize = AtomicUsize::new(0);
+static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn thread_handler(_sig: libc::c_int) {
+    RECEIVER.store(unsafe { libc::pthread_self() } as usize, Ordering::Relaxed);
+    RECEIVED.store(true, Ordering::Release);
+}
+
+fn test_pthread_kill() {
+    install(libc::SIGTERM, thread_handler as libc::sighandler_t, 0);
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = thread::spawn(move || {
+        tx.send(unsafe { libc::pthread_self() } as usize).unwrap();
+        while !RECEIVED.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+    });
+    let target = rx.recv().unwrap();
+    assert_eq!(unsafe { libc::pthread_kill(target as libc::pthread_t, libc::SIGTERM) }, 0);
+    handle.join().unwrap();
+    assert_eq!(RECEIVER.load(Ordering::Relaxed), target);
+}
+
+fn test_invalid() {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = count_handler as libc::sighandler_t;
+        assert_eq!(libc::sigaction(libc::SIGKILL, &action, ptr::null_mut()), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+        assert_eq!(libc::signal(libc::SIGSTOP, libc::SIG_IGN), libc::SIG_ERR);
+        assert_eq!(libc::raise(1000), -1);
+        assert_eq!(libc::pthread_kill(libc::pthread_self(), -1), libc::EINVAL);
+    }
+}
@@ -0,0 +1,39 @@
+//@compile-flags: -Zmiri-explore-schedules=1
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn mutex_counter() {
+    let counter = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let counter = Arc::clone(&counter);
+            thread::spawn(move || *counter.lock().unwrap() += 1)
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(*counter.lock().unwrap(), 2);
+}
+
+fn message_passing() {
+    static DATA: AtomicUsize = AtomicUsize::new(0);
+    static READY: AtomicBool = AtomicBool::new(false);
+
+    let reader = thread::spawn(|| {
+        if READY.load(Ordering::Acquire) {
+            // Every execution where the flag is observed must also observe the data.
+            assert_eq!(DATA.load(Ordering::Relaxed), 42);
+        }
+    });
+    DATA.store(42, Ordering::Relaxed);
+    READY.store(true, Ordering::Release);
+    reader.join().unwrap();
+}
+
+fn main() {
+    mutex_counter();
+    message_passing();
+}
@@ -0,0 +1,14 @@
+//@compile-flags: -Zmiri-replay-schedule=tests/pass/concurrency/replay_schedule.trace
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+static FLAG: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    let handle = thread::spawn(|| FLAG.store(true, Ordering::Relaxed));
+    // The trace switches to the new thread right after it was spawned, and the load then reads the
+    // latest store.
+    assert!(FLAG.load(Ordering::Relaxed));
+    handle.join().unwrap();
+}
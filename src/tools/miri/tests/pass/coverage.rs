@@ -0,0 +1,23 @@
+//@ignore-host: windows # the report is written to `/dev/stdout`
+//@compile-flags: -Zmiri-coverage=/dev/stdout -Zcoverage-options=branch
+
+fn classify(n: u32) -> &'static str {
+    if n % 2 == 0 {
+        // Taken three times.
+        "even"
+    } else {
+        // Taken twice.
+        "odd"
+    }
+}
+
+#[allow(dead_code)]
+fn never_called() -> u32 {
+    42
+}
+
+fn main() {
+    let add = |a: u32, b: u32| a + b;
+    let kinds = [classify(0), classify(1), classify(2), classify(3), classify(4)];
+    let _ = (add(1, 2), kinds);
+}
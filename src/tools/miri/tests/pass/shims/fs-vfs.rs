@@ -0,0 +1,117 @@
+//@ignore-target: windows # File handling is not implemented yet
+//@compile-flags: -Zmiri-vfs=tests/pass/shims/fs-vfs
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+fn main() {
+    test_read_snapshot();
+    test_write();
+    test_dirs();
+    test_rename();
+    test_cwd();
+    test_errors();
+}
+
+fn test_read_snapshot() {
+    assert_eq!(fs::read_to_string("/data/hello.txt").unwrap(), "Hello, World!\n");
+    assert_eq!(fs::read_to_string("/bye.txt").unwrap(), "Goodbye\n");
+    assert!(Path::new("/tmp").is_dir());
+    assert_eq!(fs::metadata("/data/hello.txt").unwrap().len(), 14);
+}
+
+fn test_write() {
+    let path = env::temp_dir().join("miri_test_fs_vfs_write.txt");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"Hello").unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.write_all(b"J").unwrap();
+    drop(file);
+    assert_eq!(fs::read(&path).unwrap(), b"Jello");
+
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b", World!").unwrap();
+    file.set_len(8).unwrap();
+    assert_eq!(file.metadata().unwrap().len(), 8);
+    file.sync_all().unwrap();
+    drop(file);
+
+    let mut contents = String::new();
+    File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "Jello, W");
+
+    // Files stay readable after they were removed.
+    let mut file = File::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(!path.exists());
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "Jello, W");
+}
+
+fn test_dirs() {
+    let dir = Path::new("/tmp/miri_test_fs_vfs_dirs");
+    fs::create_dir_all(dir.join("a/b")).unwrap();
+    fs::write(dir.join("file.txt"), b"").unwrap();
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            (entry.file_name().into_string().unwrap(), entry.file_type().unwrap().is_dir())
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(entries, [("a".to_owned(), true), ("file.txt".to_owned(), false)]);
+
+    assert_eq!(fs::remove_dir(dir.join("a")).unwrap_err().kind(), ErrorKind::DirectoryNotEmpty);
+    fs::remove_dir_all(dir).unwrap();
+    assert!(!dir.exists());
+}
+
+fn test_rename() {
+    fs::create_dir("/data/moved").unwrap();
+    fs::rename("/data/hello.txt", "/data/moved/hello.txt").unwrap();
+    fs::rename("/data/moved", "/renamed").unwrap();
+    assert!(!Path::new("/data/hello.txt").exists());
+    assert_eq!(fs::read_to_string("/renamed/hello.txt").unwrap(), "Hello, World!\n");
+    assert_eq!(
+        fs::canonicalize("/renamed/../renamed/./hello.txt").unwrap(),
+        Path::new("/renamed/hello.txt")
+    );
+    fs::rename("/renamed/hello.txt", "/data/hello.txt").unwrap();
+    fs::remove_dir("/renamed").unwrap();
+}
+
+fn test_cwd() {
+    assert_eq!(env::current_dir().unwrap(), Path::new("/"));
+    env::set_current_dir("/data").unwrap();
+    assert_eq!(env::current_dir().unwrap(), Path::new("/data"));
+    assert_eq!(fs::read_to_string("hello.txt").unwrap(), "Hello, World!\n");
+    assert_eq!(fs::read_to_string("../bye.txt").unwrap(), "Goodbye\n");
+    env::set_current_dir("..").unwrap();
+    assert_eq!(env::current_dir().unwrap(), PathBuf::from("/"));
+}
+
+fn test_errors() {
+    assert_eq!(File::open("/missing.txt").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(File::open("/bye.txt/nested").unwrap_err().kind(), ErrorKind::NotADirectory);
+    assert_eq!(
+        OpenOptions::new().write(true).create_new(true).open("/bye.txt").unwrap_err().kind(),
+        ErrorKind::AlreadyExists
+    );
+    assert_eq!(fs::remove_file("/data").unwrap_err().kind(), ErrorKind::IsADirectory);
+    assert_eq!(fs::create_dir("/missing/dir").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(fs::read_link("/bye.txt").unwrap_err().kind(), ErrorKind::InvalidInput);
+
+    // Virtual files are kept in memory, so their size is limited.
+    let path = "/tmp/miri_test_fs_vfs_too_large.txt";
+    let mut file = File::create(path).unwrap();
+    assert_eq!(file.set_len(u64::MAX / 2).unwrap_err().kind(), ErrorKind::FileTooLarge);
+    file.seek(SeekFrom::Start(1 << 40)).unwrap();
+    assert_eq!(file.write(b"x").unwrap_err().kind(), ErrorKind::FileTooLarge);
+    assert_eq!(file.metadata().unwrap().len(), 0);
+    drop(file);
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,182 @@
+//@ignore-target: windows # No socket shims on Windows
+//@ignore-target: wasm # No socket shims on wasm
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    test_tcp_echo();
+    test_tcp_ipv6();
+    test_tcp_connection_refused();
+    test_tcp_nonblocking_accept();
+    test_tcp_read_timeout();
+    test_tcp_shutdown_and_peek();
+    test_tcp_close_gives_eof();
+    test_tcp_addr_in_use();
+    test_unreachable_in_isolation();
+    test_udp();
+}
+
+fn test_tcp_echo() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert!(addr.ip().is_loopback());
+    assert_ne!(addr.port(), 0);
+
+    let server = thread::spawn(move || {
+        // This blocks until the client connects.
+        let (mut stream, peer) = listener.accept().unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), peer);
+        assert_eq!(stream.local_addr().unwrap(), addr);
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&buf).unwrap();
+        peer
+    });
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.set_nodelay(true).unwrap();
+    assert!(stream.nodelay().unwrap());
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    stream.write_all(b"hello").unwrap();
+    let mut buf = [0; 5];
+    // This blocks until the server has echoed the data.
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+    assert_eq!(server.join().unwrap(), stream.local_addr().unwrap());
+}
+
+fn test_tcp_ipv6() {
+    let listener = TcpListener::bind((Ipv6Addr::UNSPECIFIED, 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let mut client = TcpStream::connect((Ipv6Addr::LOCALHOST, port)).unwrap();
+    let (mut server, peer) = listener.accept().unwrap();
+    assert_eq!(peer, client.local_addr().unwrap());
+    client.write_all(b"v6").unwrap();
+    let mut buf = [0; 2];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"v6");
+
+    // Unless it is restricted to IPv6, a socket bound to `::` also accepts IPv4 connections.
+    let _client = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+    let (_server, peer) = listener.accept().unwrap();
+    assert!(peer.is_ipv4());
+}
+
+fn test_tcp_connection_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let err = TcpStream::connect(addr).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+}
+
+fn test_tcp_nonblocking_accept() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    server.set_nonblocking(true).unwrap();
+    let mut buf = [0; 1];
+    assert_eq!(server.read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+fn test_tcp_read_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let _server = listener.accept().unwrap();
+    client.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+    assert_eq!(client.read_timeout().unwrap(), Some(Duration::from_millis(10)));
+    let mut buf = [0; 1];
+    assert_eq!(client.read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+fn test_tcp_shutdown_and_peek() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    client.write_all(b"abc").unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    assert_eq!(client.write(b"d").unwrap_err().kind(), ErrorKind::BrokenPipe);
+
+    let mut buf = [0; 3];
+    assert_eq!(server.peek(&mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"abc");
+    let mut data = Vec::new();
+    // Reading to the end works because the client shut down writing.
+    server.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"abc");
+}
+
+fn test_tcp_close_gives_eof() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    let reader = thread::spawn(move || {
+        let mut data = Vec::new();
+        server.read_to_end(&mut data).unwrap();
+        data
+    });
+    let mut client = client;
+    client.write_all(b"bye").unwrap();
+    drop(client);
+    assert_eq!(reader.join().unwrap(), b"bye");
+}
+
+fn test_tcp_addr_in_use() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_eq!(TcpListener::bind(addr).unwrap_err().kind(), ErrorKind::AddrInUse);
+    // The unspecified address overlaps with every address.
+    let unspecified = SocketAddr::from((Ipv4Addr::UNSPECIFIED, addr.port()));
+    assert_eq!(TcpListener::bind(unspecified).unwrap_err().kind(), ErrorKind::AddrInUse);
+    drop(listener);
+    TcpListener::bind(addr).unwrap();
+}
+
+fn test_unreachable_in_isolation() {
+    // Only the local host exists on the virtual network.
+    let err = TcpStream::connect("192.0.2.1:80").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NetworkUnreachable);
+    let err = TcpListener::bind("192.0.2.1:0").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AddrNotAvailable);
+}
+
+fn test_udp() {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+
+    a.send_to(b"ping", b_addr).unwrap();
+    a.send_to(b"pong", b_addr).unwrap();
+    let mut buf = [0; 16];
+    let (len, from) = b.peek_from(&mut buf).unwrap();
+    assert_eq!((&buf[..len], from), (&b"ping"[..], a_addr));
+    let (len, from) = b.recv_from(&mut buf).unwrap();
+    assert_eq!((&buf[..len], from), (&b"ping"[..], a_addr));
+    // Datagrams keep their boundaries, and excess bytes are discarded.
+    let mut small = [0; 2];
+    let (len, _) = b.recv_from(&mut small).unwrap();
+    assert_eq!(&small[..len], b"po");
+
+    // Datagrams to an address nobody is bound to get lost.
+    drop(b);
+    a.send_to(b"lost", b_addr).unwrap();
+
+    // Connected sockets send to and only receive from their peer.
+    let c = UdpSocket::bind("127.0.0.1:0").unwrap();
+    c.connect(a_addr).unwrap();
+    c.send(b"hi").unwrap();
+    let (len, from) = a.recv_from(&mut buf).unwrap();
+    assert_eq!((&buf[..len], from), (&b"hi"[..], c.local_addr().unwrap()));
+
+    a.set_nonblocking(true).unwrap();
+    assert_eq!(a.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
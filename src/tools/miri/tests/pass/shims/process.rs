@@ -0,0 +1,88 @@
+//@only-target: linux # spawning child processes is only supported on Linux
+
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{self, Command, Stdio};
+
+/// The interpreted program itself, which is the only program Miri can spawn.
+fn program() -> Command {
+    Command::new(env::args().next().unwrap())
+}
+
+fn main() {
+    match env::var("MIRI_TEST_CHILD").as_deref() {
+        Ok("output") => {
+            print!("to stdout");
+            eprint!("to stderr");
+        }
+        Ok("exit") => process::exit(42),
+        Ok("echo") => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input).unwrap();
+            print!("{} {input}", env::args().nth(1).unwrap());
+        }
+        Ok("wait") => {
+            // Block until the parent kills us.
+            std::io::stdin().read_to_end(&mut Vec::new()).unwrap();
+            unreachable!();
+        }
+        _ => {
+            test_output();
+            test_status();
+            test_stdin();
+            test_large_pipes();
+            test_kill();
+        }
+    }
+}
+
+fn test_output() {
+    let output = program().env("MIRI_TEST_CHILD", "output").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"to stdout");
+    assert_eq!(output.stderr, b"to stderr");
+}
+
+fn test_status() {
+    let status = program().env("MIRI_TEST_CHILD", "exit").stdout(Stdio::null()).status().unwrap();
+    assert_eq!(status.code(), Some(42));
+}
+
+fn test_stdin() {
+    let mut child = program()
+        .env("MIRI_TEST_CHILD", "echo")
+        .arg("hello")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"world").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello world");
+}
+
+fn test_large_pipes() {
+    // Both pipes hold more than the size of a pipe buffer before the child runs.
+    let input = "x".repeat(256 * 1024);
+    let mut child = program()
+        .env("MIRI_TEST_CHILD", "echo")
+        .arg("large")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, format!("large {input}").into_bytes());
+}
+
+fn test_kill() {
+    let mut child = program().env("MIRI_TEST_CHILD", "wait").stdin(Stdio::piped()).spawn().unwrap();
+    child.kill().unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), None);
+    assert_eq!(status.signal(), Some(9));
+}
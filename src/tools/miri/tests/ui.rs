@@ -8,7 +8,7 @@
 use colored::*;
 use regex::bytes::Regex;
 use ui_test::build_manager::BuildManager;
-use ui_test::color_eyre::eyre::{Context, Result};
+use ui_test::color_eyre::eyre::{Context, Result, bail};
 use ui_test::custom_flags::edition::Edition;
 use ui_test::dependencies::DependencyBuilder;
 use ui_test::per_test_config::TestConfig;
@@ -64,6 +64,7 @@ fn build_native_lib() -> PathBuf {
             // FIXME: Automate gathering of all relevant C source files in the directory.
             "tests/native-lib/scalar_arguments.c",
             "tests/native-lib/ptr_read_access.c",
+            "tests/native-lib/ptr_write_access.c",
             // Ensure we notice serious problems in the C code.
             "-Wall",
             "-Wextra",
@@ -297,6 +298,63 @@ fn get_target() -> String {
     env::var("MIRI_TEST_TARGET").ok().unwrap_or_else(get_host)
 }
 
+/// Runs `tests/vfs-dump/main.rs` with `-Zmiri-vfs-dump` and compares a listing of the dumped
+/// directory with `tests/vfs-dump/main.dump`.
+fn vfs_dump(target: &str, tmpdir: &Path) -> Result<()> {
+    let msg = format!("## Checking the file system dumped by -Zmiri-vfs-dump for {target}");
+    eprintln!("{}", msg.green().bold());
+
+    let dump_dir = tmpdir.join("vfs-dump");
+    let mut dump_flag = OsString::from("-Zmiri-vfs-dump=");
+    dump_flag.push(&dump_dir);
+    let output = Command::new(miri_path())
+        .arg(format!(
+            "--sysroot={}",
+            env::var("MIRI_SYSROOT").expect("MIRI_SYSROOT must be set to run the ui test suite")
+        ))
+        .args(["--edition", "2021", "--target", target, "tests/vfs-dump/main.rs"])
+        .arg("-Zmiri-vfs=tests/vfs-dump/input")
+        .arg(dump_flag)
+        .output()
+        .context("failed to run Miri")?;
+    if !output.status.success() {
+        bail!(
+            "running tests/vfs-dump/main.rs failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    // Directories end with a slash, files are followed by their contents.
+    fn list(dir: &Path, prefix: &str, listing: &mut String) -> Result<()> {
+        let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+            if entry.file_type()?.is_dir() {
+                listing.push_str(&format!("{name}/\n"));
+                list(&entry.path(), &format!("{name}/"), listing)?;
+            } else {
+                let contents = std::fs::read_to_string(entry.path())?;
+                listing.push_str(&format!("{name}: {contents:?}\n"));
+            }
+        }
+        Ok(())
+    }
+    let mut listing = String::new();
+    list(&dump_dir, "", &mut listing)?;
+
+    let expected_path = Path::new("tests/vfs-dump/main.dump");
+    if ui_test::Args::test()?.bless || env::var_os("RUSTC_BLESS").is_some_and(|v| v != "0") {
+        std::fs::write(expected_path, listing)?;
+    } else if listing != std::fs::read_to_string(expected_path)? {
+        bail!(
+            "the dumped file system does not match {}; run `./miri test --bless` to update it:\n{listing}",
+            expected_path.display()
+        );
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     ui_test::color_eyre::install()?;
 
@@ -321,6 +379,10 @@ fn main() -> Result<()> {
         ui(Mode::Pass, "tests/native-lib/pass", &target, WithoutDependencies, tmpdir.path())?;
         ui(Mode::Fail, "tests/native-lib/fail", &target, WithoutDependencies, tmpdir.path())?;
     }
+    // The virtual file system is not implemented for Windows targets.
+    if !target.contains("windows") {
+        vfs_dump(&target, tmpdir.path())?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,13 @@
+//! Changes the file system given by `-Zmiri-vfs=tests/vfs-dump/input`. `tests/ui.rs` runs this
+//! with `-Zmiri-vfs-dump` and compares the dumped directory with `main.dump`.
+
+use std::fs;
+
+fn main() {
+    fs::write("/hello.txt", "Hello again!\n").unwrap();
+    fs::remove_file("/remove.txt").unwrap();
+    fs::create_dir("/data/new").unwrap();
+    fs::write("/data/new/file.txt", "new\n").unwrap();
+    fs::rename("/data/keep.txt", "/data/moved.txt").unwrap();
+    fs::write("/tmp/scratch.txt", "").unwrap();
+}
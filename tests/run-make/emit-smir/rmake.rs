@@ -0,0 +1,36 @@
+// `-Zemit-smir` writes the stable MIR of the crate to `<crate>.smir.json`, either next to the other
+// outputs or in the given directory, which is created if needed. Check that the file can be
+// deserialized and describes the crate, and that failing to write it is reported as an error.
+
+use std::path::Path;
+
+use run_make_support::{rfs, rustc, serde_json};
+
+fn check_smir(path: &Path) {
+    let smir: serde_json::Value =
+        serde_json::from_str(&rfs::read_to_string(path)).expect("stable MIR should be valid json");
+    assert_eq!(smir["version"].as_u64(), Some(1));
+    assert_eq!(smir["name"].as_str(), Some("foo"));
+    let items = smir["items"].as_array().expect("stable MIR should contain the mono items");
+    let add = items
+        .iter()
+        .find(|item| item["name"].as_str().is_some_and(|name| name.ends_with("add")))
+        .expect("`add` should be a mono item");
+    assert!(add["body"].is_object());
+}
+
+fn main() {
+    rustc().input("foo.rs").crate_type("lib").arg("-Zemit-smir").run();
+    check_smir(Path::new("foo.smir.json"));
+
+    rustc().input("foo.rs").crate_type("lib").arg("-Zemit-smir=smir/nested").run();
+    check_smir(Path::new("smir/nested/foo.smir.json"));
+
+    // The directory can't be created inside of a file.
+    rustc()
+        .input("foo.rs")
+        .crate_type("lib")
+        .arg("-Zemit-smir=foo.rs")
+        .run_fail()
+        .assert_stderr_contains("failed to write stable MIR to `foo.rs");
+}
@@ -0,0 +1,43 @@
+//@ compile-flags: -Z unstable-options --json-call-graph
+
+//@ set helper = "$.index[*][?(@.name=='helper')].id"
+pub fn helper() -> u32 {
+    1
+}
+
+//@ set Point = "$.index[*][?(@.name=='Point')].id"
+pub struct Point {
+    pub x: u32,
+}
+
+//@ set LIMIT = "$.index[*][?(@.name=='LIMIT')].id"
+pub const LIMIT: u32 = 10;
+
+//@ set norm = "$.index[*][?(@.name=='norm')].id"
+impl Point {
+    pub fn norm(&self) -> u32 {
+        self.x
+    }
+}
+
+// Private items aren't part of the output, so calls to them aren't listed.
+fn private_helper() {}
+
+//@ count "$.index[*][?(@.name=='caller')].inner.function.body_refs.calls[*]" 3
+//@ has "$.index[*][?(@.name=='caller')].inner.function.body_refs.calls[*]" $helper
+//@ has "$.index[*][?(@.name=='caller')].inner.function.body_refs.calls[*]" $norm
+//@ has "$.paths[*].path" '["core", "clone", "Clone", "clone"]'
+//@ count "$.index[*][?(@.name=='caller')].inner.function.body_refs.references[*]" 2
+//@ has "$.index[*][?(@.name=='caller')].inner.function.body_refs.references[*]" $Point
+//@ has "$.index[*][?(@.name=='caller')].inner.function.body_refs.references[*]" $LIMIT
+pub fn caller() -> u32 {
+    private_helper();
+    let p = Point { x: helper() };
+    let n = p.norm();
+    n.clone() + LIMIT
+}
+
+//@ is "$.index[*][?(@.name=='signature_only')].inner.function.body_refs" null
+pub trait Trait {
+    fn signature_only();
+}
@@ -0,0 +1,8 @@
+//@ compile-flags:-Z unstable-options --show-coverage --coverage-min 80
+
+//! Crate docs
+
+/// Documented
+pub fn documented() {}
+
+pub fn undocumented() {}
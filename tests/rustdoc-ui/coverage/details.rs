@@ -0,0 +1,29 @@
+//@ compile-flags:-Z unstable-options --show-coverage --coverage-details
+//@ check-pass
+
+//! Crate docs
+//!
+//! ```
+//! let x = 1;
+//! ```
+
+/// Documented
+///
+/// ```
+/// let x = 1;
+/// ```
+pub fn documented() {}
+
+pub fn undocumented() {}
+
+/// Inner module
+///
+/// ```
+/// let x = 1;
+/// ```
+pub mod inner {
+    pub struct Undocumented;
+
+    /// Documented
+    pub struct Documented;
+}
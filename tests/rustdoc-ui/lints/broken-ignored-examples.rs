@@ -0,0 +1,51 @@
+#![deny(rustdoc::broken_ignored_examples)]
+
+/// Adds two numbers.
+///
+/// ```ignore (illustrative)
+/// let sum = add(1);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 { //~^^^^ ERROR example calls `add` with 1 argument(s)
+    a + b
+}
+
+/// Subtracts two numbers.
+///
+/// ```ignore (illustrative)
+/// assert_eq!(sub(3, 2), 1);
+/// ```
+pub fn sub(a: u32, b: u32) -> u32 {
+    a - b
+}
+
+/// Applies `f` to `x` twice.
+///
+/// ```ignore (illustrative)
+/// let four = twice(|x, _| x * 2, 1);
+/// ```
+pub fn twice(f: impl Fn(u32, u32) -> u32, x: u32) -> u32 {
+    f(f(x, 0), 0)
+}
+
+pub struct Counter(u32);
+
+impl Counter {
+    /// Adds to the counter.
+    ///
+    /// ```ignore (illustrative)
+    /// counter.add(1, 2);
+    /// ```
+    pub fn add(&mut self, n: u32) { //~^^^^ ERROR example calls `add` with 2 argument(s)
+        self.0 += n;
+    }
+
+    /// Creates a counter, unlike `Vec::new(1)`.
+    ///
+    /// ```ignore (illustrative)
+    /// let counter = Counter::new();
+    /// let v = Vec::new(1);
+    /// ```
+    pub fn new() -> Counter {
+        Counter(0)
+    }
+}
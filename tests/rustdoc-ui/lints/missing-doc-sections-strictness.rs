@@ -0,0 +1,24 @@
+//@ compile-flags: -Z unstable-options --doc-sections-strictness=strict
+
+#![deny(rustdoc::missing_panics_doc, rustdoc::missing_errors_doc)]
+
+/// Indexing counts as panicking.
+pub fn indexes(x: &[u32]) -> u32 { //~^ ERROR missing `# Panics` section
+    x[0]
+}
+
+/// Private items are checked.
+fn private() -> Result<(), ()> { //~^ ERROR missing `# Errors` section
+    Ok(())
+}
+
+/// Private functions are checked even though they are stripped.
+fn private_panics() { //~^ ERROR missing `# Panics` section
+    todo!();
+}
+
+mod private_module {
+    pub fn undocumented() -> Result<(), ()> { //~ ERROR undocumented function returning `Result`
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+#![deny(rustdoc::missing_errors_doc)]
+
+/// Parses a number.
+pub fn parse(s: &str) -> Result<u32, std::num::ParseIntError> { //~^ ERROR missing `# Errors` section
+    s.parse()
+}
+
+/// Parses a number.
+///
+/// # Errors
+///
+/// If `s` isn't a number.
+pub fn documented(s: &str) -> Result<u32, std::num::ParseIntError> {
+    s.parse()
+}
+
+/// Private items are only checked with `--doc-sections-strictness=strict`.
+fn private() -> Result<(), ()> {
+    Ok(())
+}
+
+pub trait Fallible {
+    /// Required methods are checked too.
+    fn run(&self) -> Result<(), ()>; //~^ ERROR missing `# Errors` section
+}
+
+pub struct S;
+
+impl Fallible for S {
+    /// Trait implementations are documented by the trait.
+    fn run(&self) -> Result<(), ()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+#![deny(rustdoc::missing_panics_doc)]
+
+/// Always panics.
+pub fn explicit() { //~^ ERROR missing `# Panics` section
+    panic!("boom");
+}
+
+/// Checks its argument.
+pub fn asserts(x: u32) { //~^ ERROR missing `# Panics` section
+    assert!(x > 0);
+}
+
+/// Unwraps its argument.
+pub fn unwraps(x: Option<u32>) -> u32 { //~^ ERROR missing `# Panics` section
+    x.unwrap()
+}
+
+/// Unwraps its argument.
+///
+/// # Panics
+///
+/// If `x` is `None`.
+pub fn documented(x: Option<u32>) -> u32 {
+    x.unwrap()
+}
+
+/// Indexing only counts with `--doc-sections-strictness=strict`.
+pub fn indexes(x: &[u32]) -> u32 {
+    x[0]
+}
+
+/// Closures have bodies of their own.
+pub fn closure() -> impl Fn(Option<u32>) -> u32 {
+    |x| x.unwrap()
+}
+
+/// Private items are only checked with `--doc-sections-strictness=strict`.
+fn private() {
+    panic!("boom");
+}
+
+pub struct S;
+
+impl Clone for S {
+    /// Trait implementations are documented by the trait.
+    fn clone(&self) -> Self {
+        panic!("boom");
+    }
+}
+
+impl S {
+    /// Methods are checked too.
+    pub fn method(&self) { //~^ ERROR missing `# Panics` section
+        unimplemented_yet().expect("oops");
+    }
+}
+
+fn unimplemented_yet() -> Result<(), ()> {
+    Ok(())
+}
+
+/// Marks unfinished code.
+pub fn todo() { //~^ ERROR missing `# Panics` section
+    todo!();
+}
+
+/// Marks unimplemented code.
+pub fn unimplemented() { //~^ ERROR missing `# Panics` section
+    unimplemented!();
+}
+
+/// Marks unreachable code.
+pub fn unreachable() { //~^ ERROR missing `# Panics` section
+    unreachable!();
+}
+
+pub fn undocumented() { //~ ERROR undocumented function which may panic
+    panic!("boom");
+}
@@ -0,0 +1,42 @@
+#![deny(rustdoc::outdated_doc_sections)]
+
+/// Returns its argument.
+///
+/// # Errors
+pub fn errors(x: u32) -> u32 { //~^ ERROR `# Errors` section
+    x
+}
+
+/// Returns its argument.
+///
+/// # Panics
+pub fn panics(x: u32) -> u32 { //~^ ERROR `# Panics` section
+    x
+}
+
+/// Unwraps its argument.
+///
+/// # Panics
+///
+/// If `x` is `None`.
+pub fn unwraps(x: Option<u32>) -> u32 {
+    x.unwrap()
+}
+
+/// Calls a function which may panic.
+///
+/// # Panics
+///
+/// If `x` is `None`.
+pub fn calls(x: Option<u32>) -> u32 {
+    unwraps(x)
+}
+
+/// Parses a number.
+///
+/// # Errors
+///
+/// If `s` isn't a number.
+pub fn parses(s: &str) -> Result<u32, std::num::ParseIntError> {
+    s.parse()
+}
@@ -0,0 +1,63 @@
+//@ compile-flags:-Z unstable-options --check-cfg=cfg(variant_a,variant_b,variant_c)
+//@ compile-flags:--cfg-variant variant_a --cfg-variant variant_b --cfg-variant variant_c
+#![crate_name = "foo"]
+
+//@ has foo/struct.Everywhere.html
+//@ !has - '//*[@id="main-content"]/*[@class="item-info"]/*[@class="stab portability"]' ''
+//@ has - '//*[@id="method.only_a"]' 'fn only_a()'
+//@ has - '//*[@class="stab portability"]' 'Available on variant_a only.'
+//@ has - '//*[@id="other-configurations-list"]/*[@id="method.only_b"]' 'fn only_b(&self) -> u32'
+//@ has - '//*[@id="other-configurations-list"]//*[@class="stab portability"]' \
+//  'Available on variant_b only.'
+//@ has - '//*[@id="other-configurations-list"]//*[@class="docblock"]' 'A method of variant_b.'
+//@ has - '//*[@id="other-configurations-list"]/*[@class="impl"]' 'impl Default for Everywhere'
+//@ has - '//*[@class="sidebar-elems"]//a[@href="#method.only_b"]' 'only_b'
+pub struct Everywhere;
+
+impl Everywhere {
+    #[cfg(variant_a)]
+    pub fn only_a() {}
+
+    /// A method of variant_b.
+    #[cfg(variant_b)]
+    pub fn only_b(&self) -> u32 {
+        0
+    }
+}
+
+#[cfg(variant_c)]
+impl Default for Everywhere {
+    fn default() -> Self {
+        Everywhere
+    }
+}
+
+//@ hasraw search-index.js 'OnlyB'
+//@ hasraw search-index.js 'only_b'
+//@ hasraw foo/sidebar-items.js 'OnlyB'
+
+//@ has foo/struct.OnlyA.html \
+//  '//*[@id="main-content"]/*[@class="item-info"]/*[@class="stab portability"]' \
+//  'Available on variant_a only.'
+#[cfg(variant_a)]
+pub struct OnlyA;
+
+//@ has foo/struct.AOrB.html \
+//  '//*[@id="main-content"]/*[@class="item-info"]/*[@class="stab portability"]' \
+//  'Available on variant_a or variant_b only.'
+#[cfg(any(variant_a, variant_b))]
+pub struct AOrB;
+
+//@ has foo/struct.NotB.html \
+//  '//*[@id="main-content"]/*[@class="item-info"]/*[@class="stab portability"]' \
+//  'Available on variant_a or variant_c only.'
+#[cfg(not(variant_b))]
+pub struct NotB;
+
+//@ has foo/struct.OnlyB.html \
+//  '//*[@id="main-content"]/*[@class="item-info"]/*[@class="stab portability"]' \
+//  'Available on variant_b only.'
+//@ has foo/index.html '//a[@href="struct.OnlyB.html"]' 'OnlyB'
+//@ has - '//*[@class="stab portability"]' 'variant_b'
+#[cfg(variant_b)]
+pub struct OnlyB;
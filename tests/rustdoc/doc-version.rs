@@ -0,0 +1,19 @@
+//@ compile-flags:-Z unstable-options --doc-version 1.0
+#![crate_name = "foo"]
+
+//@ has 1.0/foo/struct.SomeStruct.html
+//@ matchesraw - 'data-doc-version="1\.0"'
+//@ matchesraw - '"\.\./\.\./static\.files/main-'
+//@ matchesraw - '"\.\./\.\./versions\.js"'
+//@ matchesraw - 'data-root-path="\.\./"'
+pub struct SomeStruct;
+
+pub mod inner {
+    pub fn some_fn() {}
+}
+
+//@ hasraw versions.js '"1.0"'
+//@ hasraw 1.0/item-paths.js 'window.loadedVersionItemPaths("1.0"'
+//@ hasraw 1.0/item-paths.js '"foo/struct.SomeStruct.html"'
+//@ hasraw 1.0/item-paths.js '"foo/inner/fn.some_fn.html"'
+//@ hasraw 1.0/item-paths.js '"foo/inner/index.html"'
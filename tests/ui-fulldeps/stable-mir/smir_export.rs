@@ -0,0 +1,101 @@
+//@ run-pass
+//! Test that the stable MIR of a crate can be exported and read back without the compiler.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate stable_mir;
+extern crate serde_json;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_smir::rustc_internal::export::write_smir_json;
+use stable_mir::export::{ExportedCrate, FORMAT_VERSION};
+use stable_mir::ty::{RigidTy, TyKind};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn export(tcx: TyCtxt<'_>) -> ControlFlow<Vec<u8>> {
+    let mut json = Vec::new();
+    write_smir_json(tcx, &mut json).unwrap();
+    ControlFlow::Break(json)
+}
+
+/// Check the exported data without access to the compiler.
+fn check(json: &[u8]) {
+    let krate: ExportedCrate = serde_json::from_slice(json).unwrap();
+    assert_eq!(krate.version, FORMAT_VERSION);
+    assert_eq!(krate.name, CRATE_NAME);
+
+    let main = krate.items.iter().find(|item| item.name.as_deref() == Some("main")).unwrap();
+    let body = main.body.as_ref().unwrap();
+    assert!(krate.spans.iter().any(|(span, _)| *span == body.span));
+
+    let (_, point) = krate.adts.iter().find(|(_, adt)| adt.name == "Point").unwrap();
+    assert_eq!(point.variants.len(), 1);
+    let fields = &point.variants[0].1;
+    assert_eq!(fields.iter().map(|field| field.name.as_str()).collect::<Vec<_>>(), ["x", "y"]);
+    let (_, field_ty) = krate.types.iter().find(|(ty, _)| *ty == fields[0].ty).unwrap();
+    assert!(matches!(field_ty.kind, TyKind::RigidTy(RigidTy::Int(_))));
+    assert!(field_ty.layout.is_some());
+
+    assert!(krate.def_names.iter().any(|(_, name)| name == "sum"));
+    assert!(!krate.allocs.is_empty(), "the string literal should be exported");
+
+    // The data survives another round trip unchanged.
+    let again = serde_json::to_vec(&krate).unwrap();
+    assert_eq!(again, json);
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "export_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    let Err(stable_mir::CompilerError::Interrupted(json)) = run_with_tcx!(args, export) else {
+        panic!("expected the export to interrupt the compilation");
+    };
+    check(&json);
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub struct Point {{
+        x: i32,
+        y: i32,
+    }}
+
+    fn sum(p: Point) -> i32 {{
+        p.x + p.y
+    }}
+
+    pub fn main() {{
+        let p = Point {{ x: 1, y: 2 }};
+        println!("{{}}", sum(p));
+    }}
+    "#
+    )?;
+    Ok(())
+}